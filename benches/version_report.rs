@@ -0,0 +1,100 @@
+//! Benchmark of `status`/`verify` reads: one [`VersionManager::version_report`] pass
+//! against asking each build system for every fact separately
+//!
+//! Before timing, both approaches are run once under a trace (see
+//! [`versioneer::trace`]) and their file reads are counted and printed, so the bench
+//! shows the reads saved as well as the time.
+
+// `criterion_group!` generates an undocumented public function
+#![allow(missing_docs)]
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+use tempfile::TempDir;
+use versioneer::VersionManager;
+
+/// A two-manifest project like the one a pre-commit hook checks
+fn fixture() -> TempDir {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    fs::write(root.join("VERSION"), "1.2.3\n").expect("Failed to write VERSION");
+    fs::write(
+        root.join("Cargo.toml"),
+        "[package]\nname = \"demo\"\nversion = \"1.2.3\"\n",
+    )
+    .expect("Failed to write Cargo.toml");
+    fs::write(
+        root.join("package.json"),
+        "{\n  \"name\": \"demo\",\n  \"version\": \"1.2.3\"\n}\n",
+    )
+    .expect("Failed to write package.json");
+    temp_dir
+}
+
+/// Check every build system the way `status` and `verify` did before the report
+fn per_fact(manager: &VersionManager) -> usize {
+    let version = manager.read_version_file().expect("Failed to read VERSION");
+    manager
+        .detect_build_systems()
+        .iter()
+        .filter(|system| {
+            !manager.build_system_inherits_version(system)
+                && !manager.build_system_is_virtual_workspace(system)
+                && manager
+                    .read_build_system_version(system, None)
+                    .is_ok_and(|found| found == version)
+        })
+        .count()
+}
+
+/// Check every build system from a single report
+fn single_pass(manager: &VersionManager) -> usize {
+    manager
+        .version_report()
+        .expect("Failed to build version report")
+        .issues()
+        .len()
+}
+
+/// Number of traced reads `run` performs
+fn count_reads(trace_path: &Path, run: impl FnOnce()) -> usize {
+    let reads = || {
+        fs::read_to_string(trace_path)
+            .expect("Failed to read trace")
+            .lines()
+            .filter(|line| line.contains(r#""action":"read""#))
+            .count()
+    };
+    let before = reads();
+    run();
+    reads() - before
+}
+
+fn bench_version_report(c: &mut Criterion) {
+    let temp_dir = fixture();
+    let manager = VersionManager::new(temp_dir.path());
+
+    let trace_dir = TempDir::new().expect("Failed to create temp dir");
+    let trace_path = trace_dir.path().join("trace.ndjson");
+    versioneer::trace::start(&trace_path).expect("Failed to start trace");
+    let per_fact_reads = count_reads(&trace_path, || {
+        per_fact(&manager);
+    });
+    let single_pass_reads = count_reads(&trace_path, || {
+        single_pass(&manager);
+    });
+    eprintln!("file reads: per-fact {per_fact_reads}, single pass {single_pass_reads}");
+    assert!(single_pass_reads < per_fact_reads);
+
+    let mut group = c.benchmark_group("status");
+    group.bench_function("per_fact", |b| b.iter(|| per_fact(black_box(&manager))));
+    group.bench_function("version_report", |b| {
+        b.iter(|| single_pass(black_box(&manager)));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_version_report);
+criterion_main!(benches);
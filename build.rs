@@ -0,0 +1,55 @@
+//! Build script capturing build information for `versioneer version --verbose`
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Run a command and return its trimmed stdout, if it succeeded
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+/// Ask cargo to rerun when the checked-out commit changes
+///
+/// Only for a `.git` directory (a worktree or submodule has a `.git` file instead).
+/// Watches HEAD and the branch ref it points at; `packed-refs` covers a ref that has
+/// been packed. Paths that do not exist are not emitted, since cargo would then rerun
+/// the script on every build.
+fn watch_git_head() {
+    let git_dir = Path::new(".git");
+    if !git_dir.is_dir() {
+        return;
+    }
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    let head = fs::read_to_string(git_dir.join("HEAD")).unwrap_or_default();
+    if let Some(reference) = head.strip_prefix("ref: ") {
+        let ref_file = git_dir.join(reference.trim());
+        if ref_file.is_file() {
+            println!("cargo:rerun-if-changed={}", ref_file.display());
+        }
+    }
+    if git_dir.join("packed-refs").is_file() {
+        println!("cargo:rerun-if-changed=.git/packed-refs");
+    }
+}
+
+fn main() {
+    let target = env::var("TARGET").unwrap_or_else(|_| "unknown".to_string());
+    let commit = command_output("git", &["rev-parse", "--short=12", "HEAD"])
+        .unwrap_or_else(|| "unknown".to_string());
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version =
+        command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=VERSIONEER_BUILD_TARGET={target}");
+    println!("cargo:rustc-env=VERSIONEER_BUILD_COMMIT={commit}");
+    println!("cargo:rustc-env=VERSIONEER_BUILD_RUSTC={rustc_version}");
+    println!("cargo:rerun-if-changed=build.rs");
+    watch_git_head();
+}
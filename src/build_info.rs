@@ -0,0 +1,96 @@
+//! Build information for the `version` command.
+
+use anyhow::Result;
+use serde::Serialize;
+use versioneer::output::Reporter;
+
+/// What this binary was built from, captured at compile time by `build.rs`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    /// Package name
+    pub name: &'static str,
+    /// Package version
+    pub version: &'static str,
+    /// Target triple
+    pub target: &'static str,
+    /// Short git commit SHA, or `unknown` when built outside a git checkout
+    pub commit: &'static str,
+    /// `rustc --version` of the compiler used
+    pub rustc: &'static str,
+}
+
+impl BuildInfo {
+    /// Build information for the running binary
+    pub const fn current() -> Self {
+        Self {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            target: env!("VERSIONEER_BUILD_TARGET"),
+            commit: env!("VERSIONEER_BUILD_COMMIT"),
+            rustc: env!("VERSIONEER_BUILD_RUSTC"),
+        }
+    }
+}
+
+/// Report the version as one line, with build details under `verbose`, or as JSON
+///
+/// # Errors
+///
+/// Returns an error if JSON serialization fails.
+pub fn run_version(
+    reporter: &dyn Reporter,
+    info: &BuildInfo,
+    verbose: bool,
+    json: bool,
+) -> Result<()> {
+    if json {
+        reporter.data(&reporter.json_style().render(info)?);
+        return Ok(());
+    }
+
+    reporter.data(&format!("{} {}", info.name, info.version));
+    if verbose {
+        reporter.data(&format!("target: {}", info.target));
+        reporter.data(&format!("commit: {}", info.commit));
+        reporter.data(&format!("rustc:  {}", info.rustc));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use versioneer::output::{OutputFormatter, WriterReporter};
+
+    const INFO: BuildInfo = BuildInfo {
+        name: "versioneer",
+        version: "1.2.3",
+        target: "x86_64-unknown-linux-gnu",
+        commit: "0123456789ab",
+        rustc: "rustc 1.85.0",
+    };
+
+    fn captured(verbose: bool, json: bool) -> String {
+        let mut out = Vec::new();
+        {
+            let reporter = WriterReporter::new(OutputFormatter::plain(), &mut out, std::io::sink());
+            run_version(&reporter, &INFO, verbose, json).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_version_plain_is_one_line() {
+        assert_eq!(captured(false, false), "versioneer 1.2.3\n");
+        assert!(captured(true, false).contains("commit: 0123456789ab"));
+    }
+
+    #[test]
+    fn test_version_json_includes_build_info() {
+        let json: serde_json::Value = serde_json::from_str(&captured(false, true)).unwrap();
+        assert_eq!(json["name"], "versioneer");
+        assert_eq!(json["version"], "1.2.3");
+        assert_eq!(json["target"], "x86_64-unknown-linux-gnu");
+        assert_eq!(json["commit"], "0123456789ab");
+    }
+}
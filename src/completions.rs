@@ -0,0 +1,68 @@
+//! Shell completion scripts for the `completions` command.
+
+use clap::CommandFactory;
+use clap_complete::Shell;
+use versioneer::output::Reporter;
+
+/// Generate the completion script for `T` as a string instead of writing it to stdout
+pub fn generate_completion_string<T: CommandFactory>(shell: Shell) -> String {
+    let mut cmd = T::command();
+    let bin_name = cmd.get_name().to_string();
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut cmd, bin_name, &mut script);
+    String::from_utf8_lossy(&script).into_owned()
+}
+
+/// Comment lines explaining how to install the script for `shell`
+fn install_instructions(bin_name: &str, shell: Shell) -> String {
+    let how = match shell {
+        Shell::Bash => {
+            format!("# For bash (~/.bashrc):\n#   source <({bin_name} completions bash)")
+        }
+        Shell::Zsh => format!(
+            "# For zsh (~/.zshrc):\n#   {bin_name} completions zsh > ~/.zsh/completions/_{bin_name}\n#   # Ensure fpath includes ~/.zsh/completions"
+        ),
+        Shell::Fish => format!(
+            "# For fish (~/.config/fish/config.fish):\n#   {bin_name} completions fish | source"
+        ),
+        _ => format!(
+            "# For {shell}:\n#   {bin_name} completions {shell} > /path/to/completions/_{bin_name}"
+        ),
+    };
+    format!(
+        "# Shell completion for {bin_name}\n#\n# To enable completions, add this to your shell config:\n#\n{how}\n"
+    )
+}
+
+/// Report installation instructions followed by the completion script for `T`
+pub fn run_completions<T: CommandFactory>(reporter: &dyn Reporter, shell: Shell) {
+    let bin_name = T::command().get_name().to_string();
+    reporter.data(&install_instructions(&bin_name, shell));
+    reporter.data(generate_completion_string::<T>(shell).trim_end());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use clap::ValueEnum;
+
+    #[test]
+    fn test_completions_mention_every_subcommand() {
+        let subcommands: Vec<String> = Cli::command()
+            .get_subcommands()
+            .map(|cmd| cmd.get_name().to_string())
+            .collect();
+        assert!(subcommands.iter().any(|name| name == "completions"));
+
+        for shell in Shell::value_variants() {
+            let script = generate_completion_string::<Cli>(*shell);
+            for name in &subcommands {
+                assert!(
+                    script.contains(name.as_str()),
+                    "{shell} completions do not mention `{name}`"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,553 @@
+//! Project configuration loaded from `.versioneer.toml`
+
+use crate::{BuildSystem, trace};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Configuration filename looked up in the project root
+pub const CONFIG_FILE: &str = ".versioneer.toml";
+
+/// Project-level settings read from [`CONFIG_FILE`]
+///
+/// Every section is optional; a missing file yields the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Settings for the Terraform (`.tf`) build system
+    pub terraform: TerraformConfig,
+    /// Settings for the `pyproject.toml` build system
+    pub pyproject: PyProjectConfig,
+    /// Which build systems drop `+build` metadata on write
+    pub build_metadata: BuildMetadataConfig,
+    /// Where the canonical version is read from
+    pub version: VersionConfig,
+    /// How `verify` and `status` compare versions
+    pub verify: VerifyConfig,
+    /// Bounds on the versions `reset` may write
+    pub reset: ResetConfig,
+    /// How tagging checks a version against the latest tag
+    pub tag: TagConfig,
+    /// How JSON manifests end when rewritten
+    pub json: JsonConfig,
+    /// Python source files whose version assignment is kept in sync
+    pub python_modules: Vec<PythonModuleConfig>,
+    /// JSON files whose (possibly nested) version field is kept in sync
+    pub json_files: Vec<JsonFileConfig>,
+    /// Manifests with a nonstandard filename, each read and written as a given format
+    pub manifest_overrides: Vec<ManifestOverride>,
+    /// Build systems left alone even when their manifest is present, by name
+    /// (`package-json`, ...): never detected, read, written or verified
+    pub ignore_systems: Vec<BuildSystem>,
+}
+
+/// Where the Terraform build system finds the module version
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TerraformConfig {
+    /// The `.tf` file holding the version
+    pub file: String,
+    /// Name of the `locals` entry or `variable` whose string default is the version
+    pub name: String,
+}
+
+impl Default for TerraformConfig {
+    fn default() -> Self {
+        Self {
+            file: "main.tf".to_string(),
+            name: "module_version".to_string(),
+        }
+    }
+}
+
+/// How versions are written to `pyproject.toml`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PyProjectConfig {
+    /// Translate semver prereleases to PEP 440 (`1.2.3-rc.2` → `1.2.3rc2`) on write
+    pub pep440: bool,
+}
+
+impl Default for PyProjectConfig {
+    fn default() -> Self {
+        Self { pep440: true }
+    }
+}
+
+/// Build systems whose manifests never carry `+build` metadata
+///
+/// The VERSION file keeps the metadata; listed systems are written without it, and
+/// verification ignores build metadata differences for them.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuildMetadataConfig {
+    /// Build systems to strip, by name (`cargo`, `package-json`, ...)
+    pub strip: Vec<BuildSystem>,
+}
+
+impl Default for BuildMetadataConfig {
+    fn default() -> Self {
+        Self {
+            strip: vec![BuildSystem::Cargo, BuildSystem::PackageJson],
+        }
+    }
+}
+
+/// Where the canonical version comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionSource {
+    /// The VERSION file (the default)
+    #[default]
+    File,
+    /// An environment variable, for builds where the version is injected
+    Env,
+    /// A `KEY=value` line in a dotenv-style version file (e.g. `version.env`)
+    Dotenv,
+}
+
+/// Settings for reading the canonical version
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VersionConfig {
+    /// Where to read the version from
+    pub source: VersionSource,
+    /// Environment variable holding the version when `source = "env"`
+    pub env_var: String,
+    /// With `source = "env"`, also write computed versions to the VERSION file
+    pub write_file: bool,
+    /// Key holding the version when `source = "dotenv"`
+    pub key: String,
+    /// Write the VERSION file as `v1.2.3` (`true`) or `1.2.3` (`false`); unset keeps
+    /// whatever the file already uses
+    pub v_prefix: Option<bool>,
+    /// Reject a VERSION file that is not exactly the canonical version (stray
+    /// whitespace, blank lines), rather than trimming it
+    pub strict: bool,
+}
+
+impl Default for VersionConfig {
+    fn default() -> Self {
+        Self {
+            source: VersionSource::File,
+            env_var: "APP_VERSION".to_string(),
+            write_file: false,
+            key: "VERSION".to_string(),
+            v_prefix: None,
+            strict: false,
+        }
+    }
+}
+
+/// Comparison policy for `verify` and `status`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VerifyConfig {
+    /// Compare only `major.minor.patch`, so a manifest at `1.2.0` matches a VERSION
+    /// file at `1.2.0-rc.3`; build metadata is ignored too
+    pub ignore_prerelease: bool,
+}
+
+/// Guardrails against resetting to a version outside the release range
+///
+/// Targets are compared by semver precedence; a bound itself is allowed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResetConfig {
+    /// Lowest version `reset` may write
+    pub min_version: Option<semver::Version>,
+    /// Highest version `reset` may write
+    pub max_version: Option<semver::Version>,
+}
+
+/// Precedence policy for new git tags
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TagConfig {
+    /// Also refuse prerelease moves within the latest tag's `major.minor.patch`
+    /// (`1.2.0` → `1.2.0-rc.1`), which are allowed by default
+    pub block_prerelease_downgrade: bool,
+}
+
+/// Whether a rewritten JSON manifest ends with a newline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrailingNewline {
+    /// Keep whatever the file had before (the default)
+    #[default]
+    Preserve,
+    /// Always end the file with a newline
+    Always,
+    /// Never end the file with a newline
+    Never,
+}
+
+/// Formatting policy for JSON manifests (`package.json` and `[[json_files]]`)
+///
+/// Line endings (LF or CRLF) always follow the original file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JsonConfig {
+    /// Whether the file ends with a newline after a write
+    pub trailing_newline: TrailingNewline,
+    /// Only replace the text of the existing version string, never re-serialize the
+    /// document or adjust its trailing newline
+    ///
+    /// A manifest without a string version is then an error instead of gaining one.
+    pub exact: bool,
+}
+
+/// A Python source file declaring the version as a top-level string assignment
+///
+/// Configured as `[[python_modules]]` entries, e.g. `src/pkg/__init__.py` with
+/// `__version__`, or `docs/conf.py` with `release`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PythonModuleConfig {
+    /// Path to the file, relative to the project root
+    pub path: String,
+    /// Name of the variable holding the version
+    #[serde(default = "default_python_variable")]
+    pub variable: String,
+}
+
+fn default_python_variable() -> String {
+    "__version__".to_string()
+}
+
+/// A JSON file declaring the version as a string field, possibly nested
+///
+/// Configured as `[[json_files]]` entries, e.g. `app.json` with `key = "meta.version"`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonFileConfig {
+    /// Path to the file, relative to the project root
+    pub path: String,
+    /// Location of the version: a dotted path (`meta.version`) or a JSON pointer
+    /// (`/meta/version`) for keys containing dots
+    #[serde(default = "default_json_key")]
+    pub key: String,
+}
+
+/// A manifest whose filename no build system recognizes, treated as a given format
+///
+/// Configured as `[[manifest_overrides]]` entries, e.g. `Cargo.toml.tpl` with
+/// `format = "cargo"`. Filename-based classification is bypassed; the format's usual
+/// read and update logic applies.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestOverride {
+    /// Path to the file, relative to the project root
+    pub path: String,
+    /// Build system whose format the file has, by name (`cargo`, `package-json`, ...)
+    pub format: BuildSystem,
+}
+
+fn default_json_key() -> String {
+    "version".to_string()
+}
+
+impl JsonFileConfig {
+    /// The object keys leading to the version field
+    #[must_use]
+    pub fn segments(&self) -> Vec<String> {
+        self.key.strip_prefix('/').map_or_else(
+            || self.key.split('.').map(str::to_string).collect(),
+            |pointer| {
+                pointer
+                    .split('/')
+                    .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+                    .collect()
+            },
+        )
+    }
+
+    /// The version's location as a JSON pointer, for [`serde_json::Value::pointer`]
+    #[must_use]
+    pub fn pointer(&self) -> String {
+        self.segments()
+            .iter()
+            .fold(String::new(), |mut pointer, segment| {
+                pointer.push('/');
+                pointer.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+                pointer
+            })
+    }
+}
+
+impl Config {
+    /// Load [`CONFIG_FILE`] from `dir`, returning defaults if it does not exist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = trace::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Dotted keys (`section.key`) set explicitly in [`CONFIG_FILE`] in `dir`
+    ///
+    /// Tells configured values apart from defaults; a missing file sets nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn explicit_keys(dir: &Path) -> Result<Vec<String>> {
+        let path = dir.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = trace::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let table: toml::Table = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(table
+            .iter()
+            .filter_map(|(section, value)| Some((section, value.as_table()?)))
+            .flat_map(|(section, keys)| keys.keys().map(move |key| format!("{section}.{key}")))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_file_uses_defaults() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config = Config::load(temp_dir.path())?;
+
+        assert_eq!(config, Config::default());
+        assert_eq!(config.terraform.name, "module_version");
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_partial_terraform_section() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[terraform]\nname = \"version\"\n",
+        )?;
+
+        let config = Config::load(temp_dir.path())?;
+        assert_eq!(config.terraform.name, "version");
+        assert_eq!(config.terraform.file, "main.tf");
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_keys() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[terraform]\nnmae = \"x\"\n",
+        )?;
+
+        assert!(Config::load(temp_dir.path()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_pyproject_pep440_toggle() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        assert!(Config::load(temp_dir.path())?.pyproject.pep440);
+
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[pyproject]\npep440 = false\n",
+        )?;
+        assert!(!Config::load(temp_dir.path())?.pyproject.pep440);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_build_metadata_strip_list() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[build_metadata]\nstrip = [\"cargo\", \"nuspec\"]\n",
+        )?;
+        assert_eq!(
+            Config::load(temp_dir.path())?.build_metadata.strip,
+            vec![BuildSystem::Cargo, BuildSystem::Nuspec]
+        );
+
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[build_metadata]\nstrip = [\"maven\"]\n",
+        )?;
+        assert!(Config::load(temp_dir.path()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_json_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[[json_files]]\npath = \"app.json\"\nkey = \"meta.version\"\n\n[[json_files]]\npath = \"manifest.json\"\n\n[[json_files]]\npath = \"odd.json\"\nkey = \"/a.b/c~1d\"\n",
+        )?;
+        let files = Config::load(temp_dir.path())?.json_files;
+
+        assert_eq!(files[0].segments(), vec!["meta", "version"]);
+        assert_eq!(files[0].pointer(), "/meta/version");
+        assert_eq!(files[1].key, "version");
+        assert_eq!(files[2].segments(), vec!["a.b", "c/d"]);
+        assert_eq!(files[2].pointer(), "/a.b/c~1d");
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_manifest_overrides() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[[manifest_overrides]]\npath = \"Cargo.toml.tpl\"\nformat = \"cargo\"\n",
+        )?;
+        assert_eq!(
+            Config::load(temp_dir.path())?.manifest_overrides,
+            vec![ManifestOverride {
+                path: "Cargo.toml.tpl".to_string(),
+                format: BuildSystem::Cargo,
+            }]
+        );
+
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[[manifest_overrides]]\npath = \"package.json5\"\nformat = \"json5\"\n",
+        )?;
+        let err = format!("{:#}", Config::load(temp_dir.path()).unwrap_err());
+        assert!(err.contains("Unknown build system 'json5'"), "{err}");
+        assert!(err.contains("package-json"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_env_version_source() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        assert_eq!(
+            Config::load(temp_dir.path())?.version.source,
+            VersionSource::File
+        );
+
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[version]\nsource = \"env\"\nenv_var = \"BUILD_VERSION\"\n",
+        )?;
+        let config = Config::load(temp_dir.path())?;
+        assert_eq!(config.version.source, VersionSource::Env);
+        assert_eq!(config.version.env_var, "BUILD_VERSION");
+        assert!(!config.version.write_file);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_python_modules() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[[python_modules]]\npath = \"src/pkg/__init__.py\"\n\n[[python_modules]]\npath = \"docs/conf.py\"\nvariable = \"release\"\n",
+        )?;
+        let config = Config::load(temp_dir.path())?;
+        assert_eq!(
+            config.python_modules,
+            vec![
+                PythonModuleConfig {
+                    path: "src/pkg/__init__.py".to_string(),
+                    variable: "__version__".to_string(),
+                },
+                PythonModuleConfig {
+                    path: "docs/conf.py".to_string(),
+                    variable: "release".to_string(),
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dotenv_version_source() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        assert_eq!(Config::load(temp_dir.path())?.version.key, "VERSION");
+
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[version]\nsource = \"dotenv\"\nkey = \"APP_VERSION\"\n",
+        )?;
+        let config = Config::load(temp_dir.path())?;
+        assert_eq!(config.version.source, VersionSource::Dotenv);
+        assert_eq!(config.version.key, "APP_VERSION");
+        Ok(())
+    }
+
+    #[test]
+    fn test_explicit_keys_lists_only_keys_in_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        assert!(Config::explicit_keys(temp_dir.path())?.is_empty());
+
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[terraform]\nname = \"version\"\n\n[version]\nsource = \"env\"\n",
+        )?;
+        assert_eq!(
+            Config::explicit_keys(temp_dir.path())?,
+            vec!["terraform.name".to_string(), "version.source".to_string()]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_version_v_prefix() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        assert_eq!(Config::load(temp_dir.path())?.version.v_prefix, None);
+
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[version]\nv_prefix = true\n",
+        )?;
+        assert_eq!(Config::load(temp_dir.path())?.version.v_prefix, Some(true));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_verify_ignore_prerelease() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        assert!(!Config::load(temp_dir.path())?.verify.ignore_prerelease);
+
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[verify]\nignore_prerelease = true\n",
+        )?;
+        assert!(Config::load(temp_dir.path())?.verify.ignore_prerelease);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_reset_bounds() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[reset]\nmax_version = \"9.0.0\"\n",
+        )?;
+
+        let config = Config::load(temp_dir.path())?;
+        assert_eq!(config.reset.min_version, None);
+        assert_eq!(
+            config.reset.max_version,
+            Some(semver::Version::new(9, 0, 0))
+        );
+        Ok(())
+    }
+}
@@ -1,78 +1,253 @@
 //! Health check and diagnostics module.
 
+use anyhow::Result;
 use versioneer::VersionManager;
+use versioneer::config::VersionSource;
+use versioneer::output::{NullReporter, Reporter};
+
+/// State of the VERSION file as `doctor` found it
+#[derive(Debug, Default, PartialEq, Eq)]
+enum VersionFileState {
+    #[default]
+    Ok,
+    /// Does not exist and can be created
+    Missing,
+    /// Exists but cannot be read or parsed, or the version comes from elsewhere
+    Unreadable,
+}
+
+/// What `doctor` found, beyond the printed report
+#[derive(Debug, Default)]
+struct Findings {
+    /// Whether the VERSION file is usable
+    version_file: VersionFileState,
+    /// A build system file cannot be read, or none were found
+    manifest_issue: bool,
+    /// The versions disagree
+    out_of_sync: bool,
+    /// A manifest was committed after the VERSION file
+    stale_version_file: bool,
+}
+
+/// A remediation `doctor --fix` can apply
+enum Repair {
+    /// Create the VERSION file from the build systems, which must agree (`sync --reverse`)
+    CreateVersionFile,
+    /// Write the VERSION file's version to every build system (`sync`)
+    Sync,
+}
+
+impl Findings {
+    /// Whether anything needs attention; a stale VERSION file only counts when `strict`
+    fn has_errors(&self, strict: bool) -> bool {
+        self.version_file != VersionFileState::Ok
+            || self.manifest_issue
+            || self.out_of_sync
+            || (strict && self.stale_version_file)
+    }
+
+    /// The repair for these findings, if they are repairable
+    ///
+    /// Corrupt or versionless manifests and a corrupt VERSION file need a human.
+    const fn repair(&self) -> Option<Repair> {
+        match self.version_file {
+            VersionFileState::Missing if !self.manifest_issue => Some(Repair::CreateVersionFile),
+            VersionFileState::Ok if !self.manifest_issue && self.out_of_sync => Some(Repair::Sync),
+            _ => None,
+        }
+    }
+}
 
 /// Run doctor command to check health and configuration.
 ///
-/// Returns exit code: 0 if healthy, 1 if issues found.
-pub fn run_doctor(manager: &VersionManager) -> i32 {
-    println!("🏥 versioneer health check");
-    println!("==========================");
-    println!();
+/// Returns exit code: 0 if healthy, 1 if issues found. With `strict`, a VERSION file
+/// older than a manifest in git history is an issue rather than a warning.
+pub fn run_doctor(manager: &VersionManager, reporter: &dyn Reporter, strict: bool) -> i32 {
+    let findings = check(manager, reporter);
+    summarize(reporter, findings.has_errors(strict))
+}
+
+/// Run doctor, then repair what it can once `confirm` approves
+///
+/// `confirm` gets the question to ask. The exit code reflects the issues left
+/// afterwards: 0 if everything was repaired, 1 otherwise.
+pub fn run_doctor_fix(
+    manager: &VersionManager,
+    reporter: &dyn Reporter,
+    strict: bool,
+    confirm: &dyn Fn(&str) -> Result<bool>,
+) -> i32 {
+    let findings = check(manager, reporter);
+    if !findings.has_errors(strict) {
+        return summarize(reporter, false);
+    }
+
+    let Some(repair) = findings.repair() else {
+        reporter.data("🔧 Nothing here can be repaired automatically");
+        return summarize(reporter, true);
+    };
+    let question = match repair {
+        Repair::CreateVersionFile => format!(
+            "Create {} from the build system version?",
+            manager.version_file
+        ),
+        Repair::Sync => format!(
+            "Update every build system to the {} version?",
+            manager.version_file
+        ),
+    };
+    match confirm(&question) {
+        Ok(true) => {}
+        Ok(false) => {
+            reporter.data("🔧 Skipped repair");
+            return summarize(reporter, true);
+        }
+        Err(e) => {
+            reporter.data(&format!("  ❌ {e}"));
+            return summarize(reporter, true);
+        }
+    }
+
+    let repaired = match repair {
+        Repair::CreateVersionFile => manager.adopt_build_system_version(None),
+        Repair::Sync => manager
+            .sync_versions()
+            .and_then(|_| manager.read_version_file()),
+    };
+    match repaired {
+        Ok(version) => reporter.data(&format!("🔧 Synchronized all files to {version}")),
+        Err(e) => reporter.data(&format!("  ❌ Repair failed: {e:#}")),
+    }
+    reporter.data("");
+
+    // Re-check so the exit code reflects what is left
+    summarize(reporter, check(manager, &NullReporter).has_errors(strict))
+}
+
+/// Print the closing line and return the exit code
+fn summarize(reporter: &dyn Reporter, has_errors: bool) -> i32 {
+    if has_errors {
+        reporter.data("❌ Issues found - see above for details");
+        1
+    } else {
+        reporter.data("✨ Everything looks healthy!");
+        0
+    }
+}
 
-    let mut has_errors = false;
+/// Print the health report and return what it found
+fn check(manager: &VersionManager, reporter: &dyn Reporter) -> Findings {
+    reporter.data("🏥 versioneer health check");
+    reporter.data("==========================");
+    reporter.data("");
+
+    let mut findings = Findings::default();
 
     // Check version file
-    println!("Version Files:");
+    reporter.data("Version Files:");
     match manager.read_version_file() {
         Ok(version) => {
-            println!("  ✅ {} file: {version}", manager.version_file);
+            reporter.data(&format!("  ✅ {} file: {version}", manager.version_file));
         }
         Err(e) => {
-            println!("  ❌ {} file error: {e}", manager.version_file);
-            has_errors = true;
+            reporter.data(&format!("  ❌ {} file error: {e}", manager.version_file));
+            // A missing file can be created, unless the version comes from elsewhere
+            if manager.version_file_path().exists()
+                || manager.config.version.source != VersionSource::File
+            {
+                findings.version_file = VersionFileState::Unreadable;
+            } else {
+                findings.version_file = VersionFileState::Missing;
+            }
         }
     }
 
     // Check build system files
-    println!();
-    println!("Build Systems:");
+    reporter.data("");
+    reporter.data("Build Systems:");
     let build_systems = manager.detect_build_systems();
 
-    if build_systems.is_empty() {
-        println!("  ❌ No build system files detected");
-        println!(
-            "  ℹ️  At least one build system file (Cargo.toml, pyproject.toml, package.json) is required"
-        );
-        has_errors = true;
+    if build_systems.is_empty() && manager.allow_empty {
+        reporter.data("  ℹ️  VERSION file only (no build system files)");
+    } else if build_systems.is_empty() {
+        reporter.data("  ❌ No build system files detected");
+        reporter.data("  ℹ️  At least one build system file (Cargo.toml, pyproject.toml, package.json, CMakeLists.txt, *.nuspec, gleam.toml, Directory.Packages.props, setup.cfg, *.gemspec, Info.plist) is required");
+        findings.manifest_issue = true;
     } else {
-        for system in &build_systems {
-            match manager.read_build_system_version(system) {
+        for (path, system) in &manager.detect_manifests() {
+            let name = system.describe(path);
+            if manager.build_system_is_virtual_workspace(system) {
+                reporter.data(&format!(
+                    "  ℹ️  {name}: virtual workspace has no package version"
+                ));
+                continue;
+            }
+            match manager.read_build_system_version(system, Some(path)) {
                 Ok(version) => {
-                    println!("  ✅ {system:?}: {version}");
+                    reporter.data(&format!("  ✅ {name}: {version}"));
                 }
                 Err(e) => {
-                    println!("  ❌ {system:?}: {e}");
-                    has_errors = true;
+                    reporter.data(&format!("  ❌ {name}: {e}"));
+                    findings.manifest_issue = true;
                 }
             }
         }
     }
 
+    for (path, system) in manager.ignored_manifests() {
+        reporter.data(&format!(
+            "  ℹ️  {}: ignored (ignore_systems or --skip)",
+            system.describe(&path)
+        ));
+    }
+
     // Check version synchronization
-    println!();
-    println!("Synchronization:");
+    reporter.data("");
+    reporter.data("Synchronization:");
     match manager.verify_versions_in_sync() {
         Ok(()) => {
-            println!("  ✅ All versions are synchronized");
+            reporter.data("  ✅ All versions are synchronized");
         }
         Err(e) => {
-            println!("  ❌ Versions are out of sync");
-            println!("  ℹ️  {e}");
-            has_errors = true;
+            reporter.data("  ❌ Versions are out of sync");
+            reporter.data(&format!("  ℹ️  {e}"));
+            findings.out_of_sync = true;
         }
     }
 
-    println!();
-
-    // Summary
-    if has_errors {
-        println!("❌ Issues found - see above for details");
-        1
-    } else {
-        println!("✨ Everything looks healthy!");
-        0
+    // Check git history for manifests changed after VERSION
+    reporter.data("");
+    reporter.data("History:");
+    match manager.manifests_newer_than_version_file() {
+        Ok(newer) if newer.is_empty() => {
+            reporter.data(&format!(
+                "  ✅ No build system file was committed after {}",
+                manager.version_file
+            ));
+        }
+        Ok(newer) => {
+            for (path, _) in &newer {
+                let path = path.strip_prefix(&manager.base_path).unwrap_or(path);
+                reporter.data(&format!(
+                    "  ⚠️  {} was committed after {}",
+                    path.display(),
+                    manager.version_file
+                ));
+            }
+            reporter.data(&format!(
+                "  ℹ️  Check that the version was not changed by hand without updating {}",
+                manager.version_file
+            ));
+            findings.stale_version_file = true;
+        }
+        Err(e) => {
+            reporter.data(&format!("  ⚠️  Could not compare commit times: {e}"));
+        }
     }
+
+    reporter.data("");
+
+    findings
 }
 
 #[cfg(test)]
@@ -93,7 +268,7 @@ mod tests {
         .unwrap();
 
         let manager = VersionManager::new(temp_dir.path());
-        let exit_code = run_doctor(&manager);
+        let exit_code = run_doctor(&manager, &NullReporter, false);
 
         // Should return 0 or 1 (with warnings from update check)
         assert!(exit_code == 0 || exit_code == 1);
@@ -109,7 +284,7 @@ mod tests {
         .unwrap();
 
         let manager = VersionManager::new(temp_dir.path());
-        let exit_code = run_doctor(&manager);
+        let exit_code = run_doctor(&manager, &NullReporter, false);
 
         // Should return 1 (error) for missing VERSION file
         assert_eq!(exit_code, 1);
@@ -126,7 +301,7 @@ mod tests {
         .unwrap();
 
         let manager = VersionManager::new(temp_dir.path());
-        let exit_code = run_doctor(&manager);
+        let exit_code = run_doctor(&manager, &NullReporter, false);
 
         // Should return 1 (error) for invalid version
         assert_eq!(exit_code, 1);
@@ -138,12 +313,24 @@ mod tests {
         fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
 
         let manager = VersionManager::new(temp_dir.path());
-        let exit_code = run_doctor(&manager);
+        let exit_code = run_doctor(&manager, &NullReporter, false);
 
         // Should return 1 (error) for no build systems
         assert_eq!(exit_code, 1);
     }
 
+    #[test]
+    fn test_doctor_with_no_build_systems_allow_empty() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+
+        let manager = VersionManager::new(temp_dir.path()).with_allow_empty(true);
+        let exit_code = run_doctor(&manager, &NullReporter, false);
+
+        // VERSION-only directories are healthy when explicitly allowed
+        assert_eq!(exit_code, 0);
+    }
+
     #[test]
     fn test_doctor_with_invalid_cargo_toml() {
         let temp_dir = TempDir::new().unwrap();
@@ -155,7 +342,7 @@ mod tests {
         .unwrap();
 
         let manager = VersionManager::new(temp_dir.path());
-        let exit_code = run_doctor(&manager);
+        let exit_code = run_doctor(&manager, &NullReporter, false);
 
         // Should return 1 (error) for invalid Cargo.toml
         assert_eq!(exit_code, 1);
@@ -172,7 +359,7 @@ mod tests {
         .unwrap();
 
         let manager = VersionManager::new(temp_dir.path());
-        let exit_code = run_doctor(&manager);
+        let exit_code = run_doctor(&manager, &NullReporter, false);
 
         // Should return 1 (error) for missing version in Cargo.toml
         assert_eq!(exit_code, 1);
@@ -189,7 +376,7 @@ mod tests {
         .unwrap();
 
         let manager = VersionManager::new(temp_dir.path());
-        let exit_code = run_doctor(&manager);
+        let exit_code = run_doctor(&manager, &NullReporter, false);
 
         // Should return 1 (error) for version mismatch
         assert_eq!(exit_code, 1);
@@ -206,7 +393,7 @@ mod tests {
         .unwrap();
 
         let manager = VersionManager::new(temp_dir.path());
-        let exit_code = run_doctor(&manager);
+        let exit_code = run_doctor(&manager, &NullReporter, false);
 
         // Should return 1 (error) for invalid pyproject.toml
         assert_eq!(exit_code, 1);
@@ -219,7 +406,7 @@ mod tests {
         fs::write(temp_dir.path().join("package.json"), "invalid json {{{").unwrap();
 
         let manager = VersionManager::new(temp_dir.path());
-        let exit_code = run_doctor(&manager);
+        let exit_code = run_doctor(&manager, &NullReporter, false);
 
         // Should return 1 (error) for invalid package.json
         assert_eq!(exit_code, 1);
@@ -241,7 +428,7 @@ mod tests {
         .unwrap();
 
         let manager = VersionManager::new(temp_dir.path());
-        let exit_code = run_doctor(&manager);
+        let exit_code = run_doctor(&manager, &NullReporter, false);
 
         // Should return 0 (may have update warning)
         assert!(exit_code == 0 || exit_code == 1);
@@ -263,9 +450,111 @@ mod tests {
         .unwrap();
 
         let manager = VersionManager::new(temp_dir.path());
-        let exit_code = run_doctor(&manager);
+        let exit_code = run_doctor(&manager, &NullReporter, false);
 
         // Should return 1 (error) for out of sync
         assert_eq!(exit_code, 1);
     }
+
+    #[test]
+    fn test_doctor_output_is_captured() {
+        use versioneer::output::{OutputFormatter, WriterReporter};
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"2.0.0\"\n",
+        )
+        .unwrap();
+
+        let manager = VersionManager::new(temp_dir.path());
+        let mut out = Vec::new();
+        let exit_code = {
+            let reporter = WriterReporter::new(OutputFormatter::plain(), &mut out, std::io::sink());
+            run_doctor(&manager, &reporter, false)
+        };
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(exit_code, 1);
+        assert!(out.contains("Cargo: 2.0.0"));
+        assert!(out.contains("Versions are out of sync"));
+    }
+
+    #[test]
+    fn test_doctor_fix_syncs_out_of_sync_manifests() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"2.0.0\"\n",
+        )
+        .unwrap();
+
+        let manager = VersionManager::new(temp_dir.path());
+
+        // Declining leaves the issue in place
+        assert_eq!(
+            run_doctor_fix(&manager, &NullReporter, false, &|_| Ok(false)),
+            1
+        );
+        assert_eq!(
+            manager
+                .read_build_system_version(&versioneer::BuildSystem::Cargo, None)
+                .unwrap()
+                .to_string(),
+            "2.0.0"
+        );
+
+        assert_eq!(
+            run_doctor_fix(&manager, &NullReporter, false, &|_| Ok(true)),
+            0
+        );
+        assert_eq!(
+            manager
+                .read_build_system_version(&versioneer::BuildSystem::Cargo, None)
+                .unwrap()
+                .to_string(),
+            "1.0.0"
+        );
+    }
+
+    #[test]
+    fn test_doctor_fix_creates_missing_version_file() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"1.4.0\"\n",
+        )
+        .unwrap();
+
+        let manager = VersionManager::new(temp_dir.path());
+        assert_eq!(
+            run_doctor_fix(&manager, &NullReporter, false, &|_| Ok(true)),
+            0
+        );
+        assert_eq!(manager.read_version_file().unwrap().to_string(), "1.4.0");
+    }
+
+    #[test]
+    fn test_doctor_fix_leaves_corrupt_manifests_alone() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "invalid toml syntax [[[",
+        )
+        .unwrap();
+
+        let manager = VersionManager::new(temp_dir.path());
+        let exit_code = run_doctor_fix(&manager, &NullReporter, false, &|_| {
+            panic!("nothing repairable should be offered")
+        });
+
+        assert_eq!(exit_code, 1);
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("Cargo.toml")).unwrap(),
+            "invalid toml syntax [[["
+        );
+    }
 }
@@ -0,0 +1,412 @@
+//! Effective configuration report for the `config` command.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{Value, json};
+use versioneer::config::{TrailingNewline, VersionSource};
+use versioneer::output::Reporter;
+use versioneer::{
+    DEFAULT_TAG_PREFIX, DEFAULT_VERSION_FILE, DiscoveryOptions, PyProjectSource,
+    UntrackedManifests, VersionManager,
+};
+
+/// Where a resolved setting came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Source {
+    /// Built-in default
+    Default,
+    /// `.versioneer.toml`
+    ConfigFile,
+    /// A command-line flag
+    CommandLine,
+    /// An environment variable
+    Environment,
+    /// Found by looking at the project directory
+    Detected,
+    /// Decided by whether stdout is a terminal
+    Terminal,
+}
+
+impl Source {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::ConfigFile => "config file",
+            Self::CommandLine => "command line",
+            Self::Environment => "environment",
+            Self::Detected => "detected",
+            Self::Terminal => "terminal",
+        }
+    }
+}
+
+/// One resolved setting and where its value came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigEntry {
+    /// Dotted setting name
+    pub key: &'static str,
+    /// Effective value
+    pub value: Value,
+    /// Where the value came from
+    pub source: Source,
+}
+
+/// Resolve every setting the manager will use, annotated with its source
+///
+/// `file_keys` are the keys set in `.versioneer.toml` (see
+/// [`versioneer::config::Config::explicit_keys`]);
+/// `cli_keys` are the keys a command-line flag set (e.g. `version.v_prefix` for
+/// `--v-prefix`, or `version_file` for `--version-file`), and `color` whether output
+/// is styled.
+#[allow(clippy::too_many_lines)]
+pub fn effective_config(
+    manager: &VersionManager,
+    file_keys: &[String],
+    cli_keys: &[&str],
+    color: bool,
+) -> Vec<ConfigEntry> {
+    let config = &manager.config;
+    let discovery = DiscoveryOptions::default();
+    let from_file = |key: &str| {
+        if file_keys.iter().any(|k| k == key) {
+            Source::ConfigFile
+        } else {
+            Source::Default
+        }
+    };
+    let from_cli = |changed: bool| {
+        if changed {
+            Source::CommandLine
+        } else {
+            Source::Default
+        }
+    };
+    let from_cli_or_file = |key: &str| {
+        if cli_keys.contains(&key) {
+            Source::CommandLine
+        } else {
+            from_file(key)
+        }
+    };
+    let entry = |key, value, source| ConfigEntry { key, value, source };
+
+    let mut entries = vec![
+        entry(
+            "version_file",
+            json!(manager.version_file_path()),
+            if cli_keys.contains(&"version_file") {
+                Source::CommandLine
+            } else if manager.version_file == DEFAULT_VERSION_FILE {
+                Source::Default
+            } else {
+                Source::Detected
+            },
+        ),
+        entry(
+            "version.source",
+            json!(match config.version.source {
+                VersionSource::File => "file",
+                VersionSource::Env => "env",
+                VersionSource::Dotenv => "dotenv",
+            }),
+            from_file("version.source"),
+        ),
+        entry(
+            "version.env_var",
+            json!(config.version.env_var),
+            from_file("version.env_var"),
+        ),
+        entry(
+            "version.key",
+            json!(config.version.key),
+            from_file("version.key"),
+        ),
+        entry(
+            "version.write_file",
+            json!(config.version.write_file),
+            from_file("version.write_file"),
+        ),
+        // `null` means an existing prefix is kept and none is added
+        entry(
+            "version.v_prefix",
+            json!(config.version.v_prefix),
+            from_cli_or_file("version.v_prefix"),
+        ),
+        entry(
+            "version.strict",
+            json!(config.version.strict),
+            from_cli_or_file("version.strict"),
+        ),
+    ];
+    if config.version.source == VersionSource::Env {
+        entries.push(entry(
+            "version.env_value",
+            json!(std::env::var(&config.version.env_var).ok()),
+            Source::Environment,
+        ));
+    }
+
+    entries.push(entry(
+        "manifests",
+        json!(
+            manager
+                .detect_manifests()
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>()
+        ),
+        Source::Detected,
+    ));
+    entries.extend([
+        entry(
+            "pyproject_source",
+            json!(match manager.pyproject_source {
+                PyProjectSource::Project => "project",
+                PyProjectSource::Commitizen => "commitizen",
+            }),
+            from_cli(manager.pyproject_source != PyProjectSource::default()),
+        ),
+        entry(
+            "pyproject.pep440",
+            json!(config.pyproject.pep440),
+            from_file("pyproject.pep440"),
+        ),
+        entry(
+            "python_modules",
+            json!(
+                config
+                    .python_modules
+                    .iter()
+                    .map(|module| json!({ "path": module.path, "variable": module.variable }))
+                    .collect::<Vec<_>>()
+            ),
+            if config.python_modules.is_empty() {
+                Source::Default
+            } else {
+                Source::ConfigFile
+            },
+        ),
+        entry(
+            "json_files",
+            json!(
+                config
+                    .json_files
+                    .iter()
+                    .map(|file| json!({ "path": file.path, "key": file.key }))
+                    .collect::<Vec<_>>()
+            ),
+            if config.json_files.is_empty() {
+                Source::Default
+            } else {
+                Source::ConfigFile
+            },
+        ),
+        entry(
+            "manifest_overrides",
+            json!(
+                config
+                    .manifest_overrides
+                    .iter()
+                    .map(|manifest| json!({ "path": manifest.path, "format": manifest.format }))
+                    .collect::<Vec<_>>()
+            ),
+            if cli_keys.contains(&"manifest_overrides") {
+                Source::CommandLine
+            } else if config.manifest_overrides.is_empty() {
+                Source::Default
+            } else {
+                Source::ConfigFile
+            },
+        ),
+        entry(
+            "ignore_systems",
+            json!(config.ignore_systems),
+            if cli_keys.contains(&"ignore_systems") {
+                Source::CommandLine
+            } else if config.ignore_systems.is_empty() {
+                Source::Default
+            } else {
+                Source::ConfigFile
+            },
+        ),
+        entry(
+            "terraform.file",
+            json!(config.terraform.file),
+            from_file("terraform.file"),
+        ),
+        entry(
+            "terraform.name",
+            json!(config.terraform.name),
+            from_file("terraform.name"),
+        ),
+        entry(
+            "build_metadata.strip",
+            json!(config.build_metadata.strip),
+            from_cli_or_file("build_metadata.strip"),
+        ),
+        entry(
+            "allow_empty",
+            json!(manager.allow_empty),
+            from_cli(manager.allow_empty),
+        ),
+        entry(
+            "overwrite_placeholders",
+            json!(manager.overwrite_placeholders),
+            from_cli(manager.overwrite_placeholders),
+        ),
+        entry(
+            "bump_bundle_version",
+            json!(manager.bump_bundle_version),
+            from_cli(manager.bump_bundle_version),
+        ),
+        entry(
+            "cascade.max_depth",
+            json!(manager.discovery.max_depth),
+            from_cli(manager.discovery.max_depth != discovery.max_depth),
+        ),
+        entry(
+            "cascade.follow_symlinks",
+            json!(manager.discovery.follow_symlinks),
+            from_cli(manager.discovery.follow_symlinks != discovery.follow_symlinks),
+        ),
+        entry(
+            "cascade.default_excludes",
+            json!(manager.discovery.default_excludes),
+            from_cli(manager.discovery.default_excludes != discovery.default_excludes),
+        ),
+        entry(
+            "cascade.untracked",
+            json!(match manager.discovery.untracked {
+                UntrackedManifests::Skip => "skip",
+                UntrackedManifests::Include => "include",
+                UntrackedManifests::Abort => "abort",
+            }),
+            from_cli(manager.discovery.untracked != discovery.untracked),
+        ),
+        entry(
+            "verify.ignore_prerelease",
+            json!(config.verify.ignore_prerelease),
+            from_file("verify.ignore_prerelease"),
+        ),
+        entry(
+            "reset.min_version",
+            json!(config.reset.min_version),
+            from_file("reset.min_version"),
+        ),
+        entry(
+            "reset.max_version",
+            json!(config.reset.max_version),
+            from_file("reset.max_version"),
+        ),
+        manager.detect_tag_prefix().ok().flatten().map_or_else(
+            || entry("tag.prefix", json!(DEFAULT_TAG_PREFIX), Source::Default),
+            |prefix| entry("tag.prefix", json!(prefix), Source::Detected),
+        ),
+        entry(
+            "tag.block_prerelease_downgrade",
+            json!(config.tag.block_prerelease_downgrade),
+            from_cli_or_file("tag.block_prerelease_downgrade"),
+        ),
+        entry(
+            "json.trailing_newline",
+            json!(match config.json.trailing_newline {
+                TrailingNewline::Preserve => "preserve",
+                TrailingNewline::Always => "always",
+                TrailingNewline::Never => "never",
+            }),
+            from_file("json.trailing_newline"),
+        ),
+        entry(
+            "json.exact",
+            json!(config.json.exact),
+            from_cli_or_file("json.exact"),
+        ),
+        entry("color", json!(color), Source::Terminal),
+    ]);
+
+    entries
+}
+
+/// Report the effective configuration, as JSON or one `key = value (source)` line each
+///
+/// # Errors
+///
+/// Returns an error if JSON serialization fails.
+pub fn run_config(reporter: &dyn Reporter, entries: &[ConfigEntry], json: bool) -> Result<()> {
+    if json {
+        reporter.data(&reporter.json_style().render(entries)?);
+    } else {
+        let width = entries.iter().map(|e| e.key.len()).max().unwrap_or(0);
+        for e in entries {
+            reporter.data(&format!(
+                "{:<width$} = {}  ({})",
+                e.key,
+                e.value,
+                e.source.label()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+    use versioneer::config::{CONFIG_FILE, Config};
+
+    fn source_of(entries: &[ConfigEntry], key: &str) -> Source {
+        entries
+            .iter()
+            .find(|e| e.key == key)
+            .map(|e| e.source)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_effective_config_annotates_sources() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[pyproject]\npep440 = false\n",
+        )?;
+        let manager = VersionManager::new(temp_dir.path())
+            .with_config(Config::load(temp_dir.path())?)
+            .with_allow_empty(true);
+
+        let entries = effective_config(
+            &manager,
+            &Config::explicit_keys(temp_dir.path())?,
+            &[],
+            false,
+        );
+
+        assert_eq!(source_of(&entries, "pyproject.pep440"), Source::ConfigFile);
+        assert_eq!(source_of(&entries, "allow_empty"), Source::CommandLine);
+        assert_eq!(source_of(&entries, "terraform.file"), Source::Default);
+        assert!(!entries.iter().any(|e| e.key == "version.env_value"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_build_metadata_flag_overrides_config_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let manager = VersionManager::new(temp_dir.path());
+        let file_keys = vec!["build_metadata.strip".to_string()];
+
+        let entries = effective_config(&manager, &file_keys, &["build_metadata.strip"], false);
+        assert_eq!(
+            source_of(&entries, "build_metadata.strip"),
+            Source::CommandLine
+        );
+
+        let entries = effective_config(&manager, &file_keys, &[], false);
+        assert_eq!(
+            source_of(&entries, "build_metadata.strip"),
+            Source::ConfigFile
+        );
+        Ok(())
+    }
+}
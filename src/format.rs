@@ -0,0 +1,1297 @@
+//! Manifest formats: how each build system's file is found, read and rewritten.
+//!
+//! Every [`BuildSystem`](crate::BuildSystem) names its [`ManifestFormat`] in
+//! [`BuildSystem::info`](crate::BuildSystem::info), and detection, reading, updating
+//! and cascade discovery all go through that trait. A new format is one type
+//! implementing it plus one entry in that table.
+
+use crate::config::{Config, TerraformConfig};
+use crate::{PyProjectSource, VersionManager, trace};
+use anyhow::{Context, Result};
+use semver::Version;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// How one build system's manifest is located, read and rewritten
+///
+/// `read` and `write` work on the file's text, so a format never touches the
+/// filesystem unless its version lives in another file (see [`PyProject`]).
+pub trait ManifestFormat: Sync + std::fmt::Debug {
+    /// Manifest filenames, the default first; `*.ext` matches any file with the extension
+    fn filenames(&self) -> &[&str];
+
+    /// Parse the version declared in `content`, the text of the manifest at `path`
+    ///
+    /// `manager` is rooted at the manifest's directory and carries the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `content` declares no valid version.
+    fn read(&self, manager: &VersionManager, path: &Path, content: &str) -> Result<Version>;
+
+    /// `content` with its version replaced by `version`
+    ///
+    /// Returns `content` unchanged when there is nothing to write, such as a version
+    /// inherited from elsewhere.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `content` has no version to replace or cannot hold `version`.
+    fn write(
+        &self,
+        manager: &VersionManager,
+        path: &Path,
+        content: &str,
+        version: &Version,
+    ) -> Result<String>;
+
+    /// Whether the file at `path` is a manifest of this format
+    ///
+    /// Matches on the filename, so the file need not exist.
+    fn manages(&self, path: &Path, _config: &Config) -> bool {
+        path.file_name().is_some_and(|name| {
+            let name = name.to_string_lossy();
+            self.filenames()
+                .iter()
+                .any(|pattern| matches_filename(pattern, &name))
+        })
+    }
+
+    /// The manifests of this format in `manager`'s base directory, sorted by name
+    fn manifests(&self, manager: &VersionManager) -> Vec<PathBuf> {
+        if !self.filenames().iter().any(|name| name.starts_with('*')) {
+            return self
+                .filenames()
+                .iter()
+                .map(|name| manager.base_path.join(name))
+                .filter(|path| path.exists() && self.manages(path, &manager.config))
+                .collect();
+        }
+        let Ok(entries) = fs::read_dir(&manager.base_path) else {
+            return Vec::new();
+        };
+        let mut files: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && self.manages(path, &manager.config))
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Error for a directory holding no manifest of this format
+    fn missing_manifest(&self, dir: &Path) -> anyhow::Error {
+        anyhow::anyhow!(
+            "No {} file found in {}",
+            self.filenames()[0].trim_start_matches('*'),
+            dir.display()
+        )
+    }
+
+    /// Whether `content` takes its version from elsewhere, so it is never rewritten
+    fn inherits_version(&self, _content: &str) -> bool {
+        false
+    }
+
+    /// Whether `content` holds a placeholder that another tool replaces with the version,
+    /// such as the `$version$` of a .nuspec, instead of a version of its own
+    fn holds_placeholder(&self, _content: &str) -> bool {
+        false
+    }
+
+    /// Check that rewritten `content` still parses, before it replaces the file
+    ///
+    /// Formats with a parser (TOML, JSON) re-parse the whole document, so an edit
+    /// that corrupts it is refused instead of written. The default accepts anything.
+    ///
+    /// # Errors
+    ///
+    /// Returns the parse failure if `content` is no longer valid.
+    fn validate(&self, _content: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Read the version of the manifest at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or declares no valid version.
+    fn read_file(&self, manager: &VersionManager, path: &Path) -> Result<Version> {
+        self.read(manager, path, &read_manifest(path)?)
+    }
+
+    /// Write `version` to the manifest at `path`, returning whether the file changed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, updated, or written.
+    fn update_file(
+        &self,
+        manager: &VersionManager,
+        path: &Path,
+        version: &Version,
+    ) -> Result<bool> {
+        let content = read_manifest(path)?;
+        let updated = self.write(manager, path, &content, version)?;
+        write_validated(self, path, &content, &updated)
+    }
+}
+
+/// Whether `name` matches a [`ManifestFormat::filenames`] entry
+fn matches_filename(pattern: &str, name: &str) -> bool {
+    pattern
+        .strip_prefix('*')
+        .map_or(name == pattern, |suffix| name.ends_with(suffix))
+}
+
+fn read_manifest(path: &Path) -> Result<String> {
+    trace::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+fn write_manifest(path: &Path, original: &str, updated: &str) -> Result<bool> {
+    VersionManager::write_if_changed(path, original, updated)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// [`write_manifest`], refusing an edit that `format` no longer parses
+fn write_validated<F: ManifestFormat + ?Sized>(
+    format: &F,
+    path: &Path,
+    original: &str,
+    updated: &str,
+) -> Result<bool> {
+    if updated != original {
+        format.validate(updated).with_context(|| {
+            format!(
+                "Refusing to write {}: the updated content is no longer valid",
+                path.display()
+            )
+        })?;
+    }
+    write_manifest(path, original, updated)
+}
+
+/// `content` with the text at `range` replaced by `replacement`
+fn splice(content: &str, range: Range<usize>, replacement: &str) -> String {
+    format!(
+        "{}{replacement}{}",
+        &content[..range.start],
+        &content[range.end..]
+    )
+}
+
+/// Cargo.toml: `[package] version`, or `[workspace.package] version` in a workspace
+/// root that is virtual or whose package inherits it
+#[derive(Debug)]
+pub struct Cargo;
+
+impl ManifestFormat for Cargo {
+    fn filenames(&self) -> &[&str] {
+        &["Cargo.toml"]
+    }
+
+    fn read(&self, _manager: &VersionManager, _path: &Path, content: &str) -> Result<Version> {
+        let cargo_toml: toml::Value =
+            toml::from_str(content).with_context(|| "Failed to parse Cargo.toml")?;
+        VersionManager::cargo_version_from_doc(&cargo_toml)
+    }
+
+    fn write(
+        &self,
+        _manager: &VersionManager,
+        _path: &Path,
+        content: &str,
+        version: &Version,
+    ) -> Result<String> {
+        // Inherited versions live in the workspace root; never overwrite them with a literal
+        if VersionManager::cargo_version_is_inherited(content)
+            || VersionManager::cargo_is_unversioned_virtual_workspace(content)
+        {
+            return Ok(content.to_string());
+        }
+
+        let section = if toml::from_str::<toml::Value>(content)
+            .is_ok_and(|doc| VersionManager::cargo_version_in_workspace_table(&doc))
+        {
+            "workspace.package"
+        } else {
+            "package"
+        };
+        VersionManager::update_toml_version(content, version, section)
+    }
+
+    fn inherits_version(&self, content: &str) -> bool {
+        VersionManager::cargo_version_is_inherited(content)
+    }
+
+    fn validate(&self, content: &str) -> Result<()> {
+        VersionManager::validate_toml(content)
+    }
+}
+
+/// pyproject.toml: `[project] version` and `[tool.commitizen] version`, or the file a
+/// setuptools dynamic version points at
+#[derive(Debug)]
+pub struct PyProject;
+
+impl ManifestFormat for PyProject {
+    fn filenames(&self) -> &[&str] {
+        &["pyproject.toml"]
+    }
+
+    /// Prefers the configured authoritative table, falling back to the other.
+    /// Errors if `[project]` and `[tool.commitizen]` both declare a version and disagree.
+    fn read(&self, manager: &VersionManager, _path: &Path, content: &str) -> Result<Version> {
+        let pyproject_toml: toml::Value =
+            toml::from_str(content).with_context(|| "Failed to parse pyproject.toml")?;
+
+        let dynamic_version = match manager.pyproject_dynamic_source(&pyproject_toml)? {
+            Some(source) => Some(VersionManager::read_dynamic_version(&source)?),
+            None => None,
+        };
+        let project_version = pyproject_toml
+            .get("project")
+            .and_then(|p| p.get("version"))
+            .and_then(|v| v.as_str())
+            .or(dynamic_version.as_deref());
+        let commitizen_version = pyproject_toml
+            .get("tool")
+            .and_then(|t| t.get("commitizen"))
+            .and_then(|c| c.get("version"))
+            .and_then(|v| v.as_str());
+
+        if let (Some(project), Some(commitizen)) = (project_version, commitizen_version) {
+            if project != commitizen {
+                anyhow::bail!(
+                    "pyproject.toml [project] version {project} disagrees with [tool.commitizen] version {commitizen}"
+                );
+            }
+        }
+
+        let version_str = match manager.pyproject_source {
+            PyProjectSource::Project => project_version.or(commitizen_version),
+            PyProjectSource::Commitizen => commitizen_version.or(project_version),
+        }
+        .with_context(|| {
+            if VersionManager::pyproject_is_dynamic(&pyproject_toml) {
+                "pyproject.toml declares a dynamic version but no [tool.setuptools.dynamic] version or [tool.commitizen] version was found".to_string()
+            } else {
+                format!(
+                    "No version found in pyproject.toml [{}] section",
+                    manager.pyproject_source.section()
+                )
+            }
+        })?;
+
+        VersionManager::parse_pep440_version(version_str)
+            .with_context(|| format!("Invalid version format in pyproject.toml: {version_str}"))
+    }
+
+    /// Rewrites every table that declares a version so `[project]` and
+    /// `[tool.commitizen]` stay in sync.
+    fn write(
+        &self,
+        manager: &VersionManager,
+        _path: &Path,
+        content: &str,
+        version: &Version,
+    ) -> Result<String> {
+        let pyproject_toml: toml::Value =
+            toml::from_str(content).with_context(|| "Failed to parse pyproject.toml")?;
+
+        let version = manager.format_pyproject_version(version)?;
+        let mut updated_any = manager.pyproject_dynamic_source(&pyproject_toml)?.is_some();
+
+        let mut updated_content = content.to_string();
+        for source in [PyProjectSource::Project, PyProjectSource::Commitizen] {
+            if let Ok(updated) =
+                VersionManager::update_toml_version(&updated_content, &version, source.section())
+            {
+                updated_content = updated;
+                updated_any = true;
+            }
+        }
+        if !updated_any {
+            anyhow::bail!(
+                "No version field found in [{}] section",
+                manager.pyproject_source.section()
+            );
+        }
+        Ok(updated_content)
+    }
+
+    /// Also writes the file holding a dynamic version, which counts as a change.
+    fn update_file(
+        &self,
+        manager: &VersionManager,
+        path: &Path,
+        version: &Version,
+    ) -> Result<bool> {
+        let content = read_manifest(path)?;
+        let pyproject_toml: toml::Value =
+            toml::from_str(&content).with_context(|| "Failed to parse pyproject.toml")?;
+
+        // Check the pyproject.toml edit before touching the dynamic version file
+        let updated = self.write(manager, path, &content, version)?;
+        if updated != content {
+            self.validate(&updated).with_context(|| {
+                format!(
+                    "Refusing to write {}: the updated content is no longer valid",
+                    path.display()
+                )
+            })?;
+        }
+        let dynamic_changed = match manager.pyproject_dynamic_source(&pyproject_toml)? {
+            Some(source) => VersionManager::update_dynamic_version(
+                &source,
+                &manager.format_pyproject_version(version)?,
+            )?,
+            None => false,
+        };
+        Ok(write_manifest(path, &content, &updated)? || dynamic_changed)
+    }
+
+    fn validate(&self, content: &str) -> Result<()> {
+        VersionManager::validate_toml(content)
+    }
+}
+
+/// package.json: the top-level `"version"` string
+#[derive(Debug)]
+pub struct PackageJson;
+
+impl ManifestFormat for PackageJson {
+    fn filenames(&self) -> &[&str] {
+        &["package.json"]
+    }
+
+    fn read(&self, _manager: &VersionManager, _path: &Path, content: &str) -> Result<Version> {
+        let json: serde_json::Value =
+            serde_json::from_str(content).with_context(|| "Failed to parse package.json")?;
+
+        let version_str = VersionManager::json_version_str(&json, "package.json")?;
+
+        Version::parse(version_str)
+            .with_context(|| format!("Invalid version format in package.json: {version_str}"))
+    }
+
+    fn write(
+        &self,
+        manager: &VersionManager,
+        _path: &Path,
+        content: &str,
+        version: &Version,
+    ) -> Result<String> {
+        let mut json: serde_json::Value =
+            serde_json::from_str(content).with_context(|| "Failed to parse package.json")?;
+
+        // Re-serializing would reformat the file, so leave a current one alone
+        if json.get("version").and_then(serde_json::Value::as_str) == Some(&version.to_string()) {
+            return Ok(content.to_string());
+        }
+
+        // Splice the new value over the old one so key order, formatting and every
+        // other field (packageManager, engines, nested dependency versions) stay as-is
+        if let Some(span) = VersionManager::json_string_span(content, &["version"]) {
+            let updated_content = splice(content, span, &version.to_string());
+            return Ok(manager.finish_json(content, &updated_content));
+        }
+        if manager.config.json.exact {
+            anyhow::bail!(
+                "package.json has no string version to edit in place; add one or unset [json] exact"
+            );
+        }
+
+        // No string version to replace: add one
+        if let Some(obj) = json.as_object_mut() {
+            obj.insert(
+                "version".to_string(),
+                serde_json::Value::String(version.to_string()),
+            );
+        } else {
+            anyhow::bail!("package.json root is not a JSON object");
+        }
+
+        // Serialize with pretty printing (2-space indent, standard for Node.js)
+        let updated_content = serde_json::to_string_pretty(&json)
+            .with_context(|| "Failed to serialize package.json")?;
+
+        Ok(manager.finish_json(content, &updated_content))
+    }
+
+    fn validate(&self, content: &str) -> Result<()> {
+        VersionManager::validate_json(content)
+    }
+}
+
+/// CMakeLists.txt: the `VERSION` of the first `project()` call
+#[derive(Debug)]
+pub struct CMake;
+
+impl CMake {
+    /// Locate the version token inside the first `project(...)` call
+    ///
+    /// Tolerates multi-line calls and additional keywords such as `LANGUAGES`. The call
+    /// runs to its matching closing parenthesis and quoted arguments are skipped, so a
+    /// `DESCRIPTION "foo (bar)"` neither ends the call early nor supplies the version.
+    fn version_range(content: &str) -> Result<Range<usize>> {
+        use regex::Regex;
+
+        let call_re = Regex::new(r"\b(?i:project)\s*\(")
+            .context("Failed to create regex for CMake project() call")?;
+        let version_re = Regex::new(r"\bVERSION\s+([0-9][0-9.]*)")
+            .context("Failed to create regex for CMake VERSION keyword")?;
+
+        let call = call_re
+            .find(content)
+            .context("No project() call found in CMakeLists.txt")?;
+        let arguments = Self::unquoted_arguments(&content[call.end()..])?;
+        let token = version_re
+            .captures(&arguments)
+            .and_then(|caps| caps.get(1))
+            .context("No VERSION found in CMakeLists.txt project() call")?;
+
+        Ok(call.end() + token.start()..call.end() + token.end())
+    }
+
+    /// The arguments of a call up to its closing parenthesis, with quoted strings blanked
+    ///
+    /// `rest` starts just after the opening parenthesis. Blanking keeps byte offsets, so
+    /// matches in the result map straight back onto `rest`.
+    fn unquoted_arguments(rest: &str) -> Result<String> {
+        let mut arguments = String::with_capacity(rest.len());
+        let mut depth = 0usize;
+        let mut in_quote = false;
+        let mut escaped = false;
+        for c in rest.chars() {
+            if in_quote {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    in_quote = false;
+                }
+                arguments.extend(std::iter::repeat_n(' ', c.len_utf8()));
+                continue;
+            }
+            match c {
+                '"' => in_quote = true,
+                '(' => depth += 1,
+                ')' if depth == 0 => return Ok(arguments),
+                ')' => depth -= 1,
+                _ => {}
+            }
+            arguments.push(c);
+        }
+        anyhow::bail!("Unterminated project() call in CMakeLists.txt")
+    }
+
+    /// Parse a `project()` version (1-3 numeric components) into semver
+    ///
+    /// Missing components default to 0. A fourth (tweak) component has no semver
+    /// counterpart that survives a sync or bump, so it is refused.
+    fn parse_version(version_str: &str) -> Result<Version> {
+        let parts: Vec<&str> = version_str.split('.').collect();
+        if parts.len() == 4 {
+            anyhow::bail!(
+                "CMake project version {version_str} has a tweak component; only MAJOR.MINOR.PATCH is supported"
+            );
+        }
+        if parts.is_empty() || parts.len() > 4 {
+            anyhow::bail!("Invalid version format in CMakeLists.txt: {version_str}");
+        }
+        let numbers = parts
+            .iter()
+            .map(|part| part.parse::<u64>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .with_context(|| format!("Invalid version format in CMakeLists.txt: {version_str}"))?;
+
+        Ok(Version::new(
+            numbers[0],
+            numbers.get(1).copied().unwrap_or(0),
+            numbers.get(2).copied().unwrap_or(0),
+        ))
+    }
+
+    /// Format a version for `project()`, which holds neither a pre-release nor build metadata
+    fn format_version(version: &Version) -> Result<String> {
+        if !version.pre.is_empty() {
+            anyhow::bail!("CMake project versions cannot carry a pre-release: {version}");
+        }
+        if !version.build.is_empty() {
+            anyhow::bail!("CMake project versions cannot carry build metadata: {version}");
+        }
+        Ok(format!(
+            "{}.{}.{}",
+            version.major, version.minor, version.patch
+        ))
+    }
+}
+
+impl ManifestFormat for CMake {
+    fn filenames(&self) -> &[&str] {
+        &["CMakeLists.txt"]
+    }
+
+    fn read(&self, _manager: &VersionManager, _path: &Path, content: &str) -> Result<Version> {
+        let range = Self::version_range(content)?;
+        Self::parse_version(&content[range])
+    }
+
+    fn write(
+        &self,
+        _manager: &VersionManager,
+        _path: &Path,
+        content: &str,
+        version: &Version,
+    ) -> Result<String> {
+        let range = Self::version_range(content)?;
+        Ok(splice(content, range, &Self::format_version(version)?))
+    }
+}
+
+/// A Terraform module file: the configured local or variable (see [`TerraformConfig`])
+#[derive(Debug)]
+pub struct Terraform;
+
+impl Terraform {
+    /// Locate the quoted version string of the configured Terraform name
+    ///
+    /// Matches `variable "<name>" { default = "..." }` first, then a
+    /// `<name> = "..."` assignment such as an entry in a `locals` block.
+    fn version_range(terraform: &TerraformConfig, content: &str) -> Result<Range<usize>> {
+        use regex::Regex;
+
+        let name = regex::escape(&terraform.name);
+        let variable_re = Regex::new(&format!(
+            r#"(?s)\bvariable\s+"{name}"\s*\{{[^}}]*?\bdefault\s*=\s*"([^"]*)""#
+        ))
+        .context("Failed to create regex for Terraform variable")?;
+        let local_re = Regex::new(&format!(r#"(?m)^\s*{name}\s*=\s*"([^"]*)""#))
+            .context("Failed to create regex for Terraform local")?;
+
+        variable_re
+            .captures(content)
+            .or_else(|| local_re.captures(content))
+            .and_then(|caps| caps.get(1))
+            .map(|m| m.range())
+            .with_context(|| {
+                format!(
+                    "No local or variable named '{}' found in {}",
+                    terraform.name, terraform.file
+                )
+            })
+    }
+}
+
+impl ManifestFormat for Terraform {
+    /// The default; the file is configurable.
+    fn filenames(&self) -> &[&str] {
+        &["main.tf"]
+    }
+
+    fn manages(&self, path: &Path, config: &Config) -> bool {
+        path.file_name()
+            .is_some_and(|name| name.to_string_lossy() == config.terraform.file)
+    }
+
+    fn manifests(&self, manager: &VersionManager) -> Vec<PathBuf> {
+        let path = manager.base_path.join(&manager.config.terraform.file);
+        if path.exists() {
+            vec![path]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn read(&self, manager: &VersionManager, _path: &Path, content: &str) -> Result<Version> {
+        let range = Self::version_range(&manager.config.terraform, content)?;
+        let version_str = &content[range];
+        Version::parse(version_str).with_context(|| {
+            format!(
+                "Invalid version format in {}: {version_str}",
+                manager.config.terraform.file
+            )
+        })
+    }
+
+    fn write(
+        &self,
+        manager: &VersionManager,
+        _path: &Path,
+        content: &str,
+        version: &Version,
+    ) -> Result<String> {
+        let range = Self::version_range(&manager.config.terraform, content)?;
+        Ok(splice(content, range, &version.to_string()))
+    }
+}
+
+/// A `.nuspec` package manifest: the `<metadata><version>` element
+///
+/// Refuses to replace a `$version$` pack-time placeholder unless
+/// [`VersionManager::overwrite_placeholders`] is set.
+#[derive(Debug)]
+pub struct Nuspec;
+
+impl Nuspec {
+    /// Locate the text of the `<version>` element inside `<metadata>`
+    ///
+    /// Tolerates attributes and namespace prefixes on both elements.
+    fn version_range(content: &str) -> Result<Range<usize>> {
+        use regex::Regex;
+
+        let metadata_re =
+            Regex::new(r"(?s)<(?:[\w.-]+:)?metadata\b[^>]*>(.*?)</(?:[\w.-]+:)?metadata>")
+                .context("Failed to create regex for nuspec metadata")?;
+        let version_re =
+            Regex::new(r"(?s)<(?:[\w.-]+:)?version\b[^>]*>([^<]*)</(?:[\w.-]+:)?version>")
+                .context("Failed to create regex for nuspec version")?;
+
+        let metadata = metadata_re
+            .captures(content)
+            .and_then(|caps| caps.get(1))
+            .context("No <metadata> element found in .nuspec")?;
+        let version = version_re
+            .captures(metadata.as_str())
+            .and_then(|caps| caps.get(1))
+            .context("No <version> element found in .nuspec <metadata>")?;
+
+        Ok(metadata.start() + version.start()..metadata.start() + version.end())
+    }
+}
+
+impl ManifestFormat for Nuspec {
+    fn filenames(&self) -> &[&str] {
+        &["*.nuspec"]
+    }
+
+    fn read(&self, _manager: &VersionManager, path: &Path, content: &str) -> Result<Version> {
+        let range = Self::version_range(content)?;
+        let version_str = content[range].trim();
+        if version_str == "$version$" {
+            anyhow::bail!(
+                "{} uses the $version$ placeholder, which is supplied at pack time",
+                path.display()
+            );
+        }
+        Version::parse(version_str).with_context(|| {
+            format!(
+                "Invalid version format in {}: {version_str}",
+                path.display()
+            )
+        })
+    }
+
+    fn holds_placeholder(&self, content: &str) -> bool {
+        Self::version_range(content).is_ok_and(|range| content[range].trim() == "$version$")
+    }
+
+    fn write(
+        &self,
+        manager: &VersionManager,
+        path: &Path,
+        content: &str,
+        version: &Version,
+    ) -> Result<String> {
+        let range = Self::version_range(content)?;
+        if content[range.clone()].trim() == "$version$" && !manager.overwrite_placeholders {
+            anyhow::bail!(
+                "{} uses the $version$ placeholder; pass --overwrite-placeholders to replace it",
+                path.display()
+            );
+        }
+        Ok(splice(content, range, &version.to_string()))
+    }
+}
+
+/// gleam.toml: the top-level `version`
+#[derive(Debug)]
+pub struct Gleam;
+
+impl ManifestFormat for Gleam {
+    fn filenames(&self) -> &[&str] {
+        &["gleam.toml"]
+    }
+
+    fn read(&self, _manager: &VersionManager, _path: &Path, content: &str) -> Result<Version> {
+        let gleam_toml: toml::Value =
+            toml::from_str(content).with_context(|| "Failed to parse gleam.toml")?;
+        let version_str = gleam_toml
+            .get("version")
+            .and_then(|v| v.as_str())
+            .context("No top-level version found in gleam.toml")?;
+
+        Version::parse(version_str)
+            .with_context(|| format!("Invalid version format in gleam.toml: {version_str}"))
+    }
+
+    fn write(
+        &self,
+        _manager: &VersionManager,
+        _path: &Path,
+        content: &str,
+        version: &Version,
+    ) -> Result<String> {
+        VersionManager::update_toml_version(content, version, "")
+    }
+
+    fn validate(&self, content: &str) -> Result<()> {
+        VersionManager::validate_toml(content)
+    }
+}
+
+/// Directory.Packages.props: the `<Version>` or `<VersionPrefix>` `MSBuild` property
+#[derive(Debug)]
+pub struct PackagesProps;
+
+/// Byte ranges of the `MSBuild` version properties' text in a props file
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MsBuildVersionProperties {
+    /// `<Version>`
+    version: Option<Range<usize>>,
+    /// `<VersionPrefix>`
+    prefix: Option<Range<usize>>,
+    /// `<VersionSuffix>`
+    suffix: Option<Range<usize>>,
+}
+
+impl PackagesProps {
+    /// Locate the `Version`, `VersionPrefix` and `VersionSuffix` properties
+    ///
+    /// Only elements inside a `<PropertyGroup>` count; the first of each wins.
+    fn version_properties(content: &str) -> Result<MsBuildVersionProperties> {
+        use regex::Regex;
+
+        let group_re = Regex::new(r"(?s)<PropertyGroup\b[^>]*>(.*?)</PropertyGroup>")
+            .context("Failed to create regex for MSBuild property groups")?;
+        let property_re = |name: &str| {
+            Regex::new(&format!(r"(?s)<{name}\b[^>]*>([^<]*)</{name}>"))
+                .with_context(|| format!("Failed to create regex for MSBuild {name}"))
+        };
+        let find = |re: &Regex| {
+            group_re
+                .captures_iter(content)
+                .filter_map(|caps| caps.get(1))
+                .find_map(|group| {
+                    re.captures(group.as_str())
+                        .and_then(|caps| caps.get(1))
+                        .map(|value| group.start() + value.start()..group.start() + value.end())
+                })
+        };
+
+        Ok(MsBuildVersionProperties {
+            version: find(&property_re("Version")?),
+            prefix: find(&property_re("VersionPrefix")?),
+            suffix: find(&property_re("VersionSuffix")?),
+        })
+    }
+}
+
+impl ManifestFormat for PackagesProps {
+    fn filenames(&self) -> &[&str] {
+        &["Directory.Packages.props"]
+    }
+
+    /// `<Version>` is used as is; `<VersionPrefix>` is combined with `<VersionSuffix>`
+    /// when one is set. Errors if both forms are present and disagree.
+    fn read(&self, _manager: &VersionManager, _path: &Path, content: &str) -> Result<Version> {
+        let props = Self::version_properties(content)?;
+        let parse = |version_str: &str| {
+            Version::parse(version_str).with_context(|| {
+                format!("Invalid version format in Directory.Packages.props: {version_str}")
+            })
+        };
+        let version = props
+            .version
+            .map(|range| parse(content[range].trim()))
+            .transpose()?;
+        let prefixed = props
+            .prefix
+            .map(|range| {
+                let prefix = content[range].trim();
+                match props.suffix.map(|range| content[range].trim()) {
+                    Some(suffix) if !suffix.is_empty() => parse(&format!("{prefix}-{suffix}")),
+                    _ => parse(prefix),
+                }
+            })
+            .transpose()?;
+
+        match (version, prefixed) {
+            (Some(version), Some(prefixed))
+                if VersionManager::without_build_metadata(&version) != prefixed =>
+            {
+                anyhow::bail!(
+                    "Directory.Packages.props declares <Version> {version} but <VersionPrefix> {prefixed}"
+                )
+            }
+            (Some(version), _) | (None, Some(version)) => Ok(version),
+            (None, None) => anyhow::bail!(
+                "No <Version> or <VersionPrefix> property found in Directory.Packages.props"
+            ),
+        }
+    }
+
+    /// `<Version>` receives the full version. `<VersionPrefix>` receives
+    /// `major.minor.patch` and an existing `<VersionSuffix>` the prerelease; a prerelease
+    /// cannot be written to a `<VersionPrefix>` without a `<VersionSuffix>`.
+    fn write(
+        &self,
+        _manager: &VersionManager,
+        _path: &Path,
+        content: &str,
+        version: &Version,
+    ) -> Result<String> {
+        let props = Self::version_properties(content)?;
+        let mut replacements = Vec::new();
+        if let Some(range) = props.version {
+            replacements.push((range, version.to_string()));
+        }
+        if let Some(range) = props.prefix {
+            replacements.push((
+                range,
+                format!("{}.{}.{}", version.major, version.minor, version.patch),
+            ));
+            match props.suffix {
+                Some(range) => replacements.push((range, version.pre.to_string())),
+                None if !version.pre.is_empty() => anyhow::bail!(
+                    "Cannot write prerelease {} to Directory.Packages.props: <VersionPrefix> has no <VersionSuffix>",
+                    version.pre
+                ),
+                None => {}
+            }
+        }
+        if replacements.is_empty() {
+            anyhow::bail!(
+                "No <Version> or <VersionPrefix> property found in Directory.Packages.props"
+            );
+        }
+
+        replacements.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        let mut updated_content = content.to_string();
+        for (range, text) in replacements {
+            updated_content.replace_range(range, &text);
+        }
+        Ok(updated_content)
+    }
+}
+
+/// setup.cfg: `[metadata] version`, accepting PEP 440 as for pyproject.toml
+///
+/// setup.cfg often only configures tools (flake8, pytest), so it counts as a manifest
+/// only when it declares a version.
+#[derive(Debug)]
+pub struct SetupCfg;
+
+impl SetupCfg {
+    /// Byte range of the `version` value in the `[metadata]` section of setup.cfg
+    ///
+    /// Accepts both `version = x` and `version: x`; the value runs to the end of the line.
+    fn version_range(content: &str) -> Option<Range<usize>> {
+        let mut in_metadata = false;
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            let start = offset;
+            offset += line.len();
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                in_metadata = trimmed == "[metadata]";
+                continue;
+            }
+            if !in_metadata || line.starts_with(char::is_whitespace) {
+                continue;
+            }
+            let Some(sep) = line.find(['=', ':']) else {
+                continue;
+            };
+            if line[..sep].trim() != "version" {
+                continue;
+            }
+            let value = &line[sep + 1..];
+            let leading = value.len() - value.trim_start().len();
+            let value_start = start + sep + 1 + leading;
+            return Some(value_start..value_start + value.trim().len());
+        }
+        None
+    }
+}
+
+impl ManifestFormat for SetupCfg {
+    fn filenames(&self) -> &[&str] {
+        &["setup.cfg"]
+    }
+
+    fn manages(&self, path: &Path, _config: &Config) -> bool {
+        path.file_name().is_some_and(|name| name == "setup.cfg")
+            && trace::read_to_string(path)
+                .is_ok_and(|content| Self::version_range(&content).is_some())
+    }
+
+    fn read(&self, _manager: &VersionManager, _path: &Path, content: &str) -> Result<Version> {
+        let range = Self::version_range(content)
+            .context("No version found in the [metadata] section of setup.cfg")?;
+        let version_str = &content[range];
+        if version_str.starts_with("attr:") || version_str.starts_with("file:") {
+            anyhow::bail!(
+                "setup.cfg reads its version dynamically ({version_str}); remove it or set a literal version"
+            );
+        }
+        VersionManager::parse_pep440_version(version_str)
+            .with_context(|| format!("Invalid version format in setup.cfg: {version_str}"))
+    }
+
+    fn write(
+        &self,
+        manager: &VersionManager,
+        _path: &Path,
+        content: &str,
+        version: &Version,
+    ) -> Result<String> {
+        let range = Self::version_range(content)
+            .context("No version found in the [metadata] section of setup.cfg")?;
+        Ok(splice(
+            content,
+            range,
+            &manager.format_pyproject_version(version)?,
+        ))
+    }
+}
+
+/// A `.gemspec` file: a literal `spec.version = "..."` assignment
+///
+/// A gemspec taking its version from a constant (`spec.version = MyGem::VERSION`) is
+/// not a manifest, since there is no literal to rewrite.
+#[derive(Debug)]
+pub struct Gemspec;
+
+impl Gemspec {
+    /// Byte range of the quoted version in a `<spec>.version = "..."` assignment
+    ///
+    /// Only string literals (optionally `.freeze`d) qualify; a constant or other
+    /// expression is an error, since its value lives in another file.
+    fn version_range(content: &str, path: &Path) -> Result<Range<usize>> {
+        use regex::Regex;
+
+        let assignment_re =
+            Regex::new(r"(?m)^[ \t]*\w+\.version[ \t]*=[ \t]*([^=\s].*?)[ \t]*\r?$")
+                .context("Failed to create regex for gemspec version")?;
+        let literal_re = Regex::new(r#"^(?:"([^"\\#]*)"|'([^'\\]*)')(?:\.freeze)?$"#)
+            .context("Failed to create regex for gemspec literal")?;
+
+        let rhs = assignment_re
+            .captures(content)
+            .and_then(|caps| caps.get(1))
+            .with_context(|| format!("No spec.version assignment found in {}", path.display()))?;
+        let literal = literal_re
+            .captures(rhs.as_str())
+            .and_then(|caps| caps.get(1).or_else(|| caps.get(2)))
+            .with_context(|| {
+                format!(
+                    "{} sets spec.version to `{}` rather than a string literal; update the version where that is defined, or assign a literal version in the gemspec",
+                    path.display(),
+                    rhs.as_str()
+                )
+            })?;
+
+        Ok(rhs.start() + literal.start()..rhs.start() + literal.end())
+    }
+}
+
+impl ManifestFormat for Gemspec {
+    fn filenames(&self) -> &[&str] {
+        &["*.gemspec"]
+    }
+
+    fn manages(&self, path: &Path, _config: &Config) -> bool {
+        path.extension().is_some_and(|ext| ext == "gemspec")
+            && trace::read_to_string(path)
+                .is_ok_and(|content| Self::version_range(&content, path).is_ok())
+    }
+
+    /// Explains why a gemspec that is present was passed over.
+    fn missing_manifest(&self, dir: &Path) -> anyhow::Error {
+        let skipped = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .find(|path| path.extension().is_some_and(|ext| ext == "gemspec"));
+        if let Some(path) = skipped {
+            if let Err(err) =
+                read_manifest(&path).and_then(|content| Self::version_range(&content, &path))
+            {
+                return err;
+            }
+        }
+        anyhow::anyhow!("No .gemspec file found in {}", dir.display())
+    }
+
+    fn read(&self, _manager: &VersionManager, path: &Path, content: &str) -> Result<Version> {
+        let range = Self::version_range(content, path)?;
+        let version_str = &content[range];
+        Version::parse(version_str).with_context(|| {
+            format!(
+                "Invalid version format in {}: {version_str}",
+                path.display()
+            )
+        })
+    }
+
+    fn write(
+        &self,
+        _manager: &VersionManager,
+        path: &Path,
+        content: &str,
+        version: &Version,
+    ) -> Result<String> {
+        let range = Self::version_range(content, path)?;
+        Ok(splice(content, range, &version.to_string()))
+    }
+}
+
+/// Info.plist for macOS/iOS apps: the `CFBundleShortVersionString` string
+///
+/// Only XML property lists are supported; a binary plist is refused with the command
+/// that converts it. With [`VersionManager::bump_bundle_version`] the
+/// `CFBundleVersion` build number is incremented whenever the short version changes.
+#[derive(Debug)]
+pub struct InfoPlist;
+
+impl InfoPlist {
+    /// Locate the text of the `<string>` following `<key>{key}</key>`
+    fn value_range(content: &str, key: &str, path: &Path) -> Result<Range<usize>> {
+        use regex::Regex;
+
+        let key_re = Regex::new(&format!(
+            r"<key>\s*{}\s*</key>\s*<string>([^<]*)</string>",
+            regex::escape(key)
+        ))
+        .with_context(|| format!("Failed to create regex for plist key {key}"))?;
+
+        let value = key_re
+            .captures(content)
+            .and_then(|caps| caps.get(1))
+            .with_context(|| format!("No {key} string found in {}", path.display()))?;
+        let text = value.as_str().trim();
+        if text.starts_with("$(") {
+            anyhow::bail!(
+                "{} takes {key} from the build setting {text}; update the version in the Xcode project, or put a literal version in the plist",
+                path.display()
+            );
+        }
+        Ok(value.range())
+    }
+
+    /// Parse a short version string (1-3 numeric components, missing ones 0) into semver
+    fn parse_version(version_str: &str, path: &Path) -> Result<Version> {
+        let numbers = version_str
+            .trim()
+            .split('.')
+            .map(str::parse::<u64>)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .ok()
+            .filter(|numbers| numbers.len() <= 3)
+            .with_context(|| {
+                format!(
+                    "Invalid CFBundleShortVersionString in {}: {version_str}",
+                    path.display()
+                )
+            })?;
+        Ok(Version::new(
+            numbers[0],
+            numbers.get(1).copied().unwrap_or(0),
+            numbers.get(2).copied().unwrap_or(0),
+        ))
+    }
+
+    /// Format a version for `CFBundleShortVersionString`, which allows only three integers
+    fn format_version(version: &Version) -> Result<String> {
+        if !version.pre.is_empty() {
+            anyhow::bail!("CFBundleShortVersionString cannot carry a pre-release: {version}");
+        }
+        if !version.build.is_empty() {
+            anyhow::bail!(
+                "CFBundleShortVersionString cannot carry build metadata: {version}; add \"info-plist\" to [build_metadata] strip"
+            );
+        }
+        Ok(format!(
+            "{}.{}.{}",
+            version.major, version.minor, version.patch
+        ))
+    }
+
+    /// `build` with its last numeric component incremented (`41` → `42`, `1.2.9` → `1.2.10`)
+    fn next_build_number(build: &str, path: &Path) -> Result<String> {
+        let build = build.trim();
+        let (head, last) = build
+            .rsplit_once('.')
+            .map_or(("", build), |(head, last)| (&build[..=head.len()], last));
+        let number: u64 = last.parse().with_context(|| {
+            format!(
+                "CFBundleVersion in {} is not a build number: {build}",
+                path.display()
+            )
+        })?;
+        Ok(format!("{head}{}", number + 1))
+    }
+
+    /// Read the plist as text, refusing binary property lists
+    fn read_xml(path: &Path) -> Result<String> {
+        let bytes =
+            trace::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        if bytes.starts_with(b"bplist") {
+            anyhow::bail!(
+                "{} is a binary property list; convert it with `plutil -convert xml1 {}` first",
+                path.display(),
+                path.display()
+            );
+        }
+        String::from_utf8(bytes).with_context(|| format!("Failed to read {}", path.display()))
+    }
+}
+
+impl ManifestFormat for InfoPlist {
+    fn filenames(&self) -> &[&str] {
+        &["Info.plist"]
+    }
+
+    fn read(&self, _manager: &VersionManager, path: &Path, content: &str) -> Result<Version> {
+        let range = Self::value_range(content, "CFBundleShortVersionString", path)?;
+        Self::parse_version(&content[range], path)
+    }
+
+    fn write(
+        &self,
+        manager: &VersionManager,
+        path: &Path,
+        content: &str,
+        version: &Version,
+    ) -> Result<String> {
+        let range = Self::value_range(content, "CFBundleShortVersionString", path)?;
+        let short_version = Self::format_version(version)?;
+        if content[range.clone()].trim() == short_version {
+            return Ok(content.to_string());
+        }
+        let updated = splice(content, range, &short_version);
+        if !manager.bump_bundle_version {
+            return Ok(updated);
+        }
+        let range = Self::value_range(&updated, "CFBundleVersion", path)?;
+        let build = Self::next_build_number(&updated[range.clone()], path)?;
+        Ok(splice(&updated, range, &build))
+    }
+
+    fn read_file(&self, manager: &VersionManager, path: &Path) -> Result<Version> {
+        self.read(manager, path, &Self::read_xml(path)?)
+    }
+
+    fn update_file(
+        &self,
+        manager: &VersionManager,
+        path: &Path,
+        version: &Version,
+    ) -> Result<bool> {
+        let content = Self::read_xml(path)?;
+        let updated = self.write(manager, path, &content, version)?;
+        write_validated(self, path, &content, &updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BuildSystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_formats_rewrite_content_without_touching_files() -> Result<()> {
+        let manager = VersionManager::new("/nonexistent");
+        let path = Path::new("/nonexistent/CMakeLists.txt");
+        let content = "project(demo VERSION 1.2.3 LANGUAGES CXX)\n";
+
+        assert_eq!(CMake.read(&manager, path, content)?, Version::new(1, 2, 3));
+        assert_eq!(
+            CMake.write(&manager, path, content, &Version::new(1, 3, 0))?,
+            "project(demo VERSION 1.3.0 LANGUAGES CXX)\n"
+        );
+
+        // An inherited version is left alone rather than replaced by a literal
+        let member = "[package]\nname = \"member\"\nversion.workspace = true\n";
+        assert!(Cargo.inherits_version(member));
+        assert_eq!(
+            Cargo.write(&manager, path, member, &Version::new(2, 0, 0))?,
+            member
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmake_call_ends_at_its_own_closing_paren() -> Result<()> {
+        let manager = VersionManager::new("/nonexistent");
+        let path = Path::new("/nonexistent/CMakeLists.txt");
+        let content = "project(demo\n  DESCRIPTION \"Parser (fast) VERSION 9\"\n  VERSION 1.2.3\n  LANGUAGES CXX)\n";
+
+        assert_eq!(CMake.read(&manager, path, content)?, Version::new(1, 2, 3));
+        assert_eq!(
+            CMake.write(&manager, path, content, &Version::new(1, 3, 0))?,
+            content.replace("VERSION 1.2.3", "VERSION 1.3.0")
+        );
+
+        let unterminated = "project(demo VERSION 1.2.3 DESCRIPTION \"a)\"\n";
+        assert!(CMake.read(&manager, path, unterminated).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupting_edit_is_not_written() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cases: [(&dyn ManifestFormat, &str, &str, &str); 2] = [
+            (
+                &PackageJson,
+                "package.json",
+                "{\n  \"version\": \"1.0.0\"\n}\n",
+                "{\n  \"version\": \"1.0.1\"\n  \"name\": \"x\"\n}\n",
+            ),
+            (
+                &Cargo,
+                "Cargo.toml",
+                "[package]\nversion = \"1.0.0\"\n",
+                "[package]\nversion = \"1.0.1\"\nversion = \"1.0.0\"\n",
+            ),
+        ];
+        for (format, name, original, corrupted) in cases {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, original)?;
+            let err = write_validated(format, &path, original, corrupted).unwrap_err();
+            assert!(format!("{err:#}").contains("no longer valid"), "{err:#}");
+            assert_eq!(fs::read_to_string(&path)?, original);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_every_build_system_is_registered() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let manager = VersionManager::new(temp_dir.path());
+        for system in BuildSystem::ALL {
+            let format = system.format();
+            assert!(format.manifests(&manager).is_empty());
+            let manifest = temp_dir
+                .path()
+                .join(format.filenames()[0].replace('*', "app"));
+            if !matches!(system, BuildSystem::SetupCfg | BuildSystem::Gemspec) {
+                assert!(format.manages(&manifest, &manager.config), "{system:?}");
+            }
+        }
+
+        fs::write(
+            temp_dir.path().join("app.gemspec"),
+            "Gem::Specification.new do |spec|\n  spec.version = \"1.0.0\"\nend\n",
+        )?;
+        assert_eq!(
+            BuildSystem::Gemspec.format().manifests(&manager),
+            [temp_dir.path().join("app.gemspec")]
+        );
+        Ok(())
+    }
+}
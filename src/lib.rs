@@ -3,15 +3,22 @@
 //! This library provides functionality to read, parse, and update version information
 //! across different file formats including VERSION files, Cargo.toml, and pyproject.toml.
 
+pub mod config;
+pub mod format;
 pub mod output;
+pub mod release;
+pub mod report;
+pub mod trace;
 
 use anyhow::{Context, Result};
+use config::Config;
 use semver::Version;
-use std::fs;
+use serde::Serialize;
 use std::path::Path;
 
 /// Represents different types of build system files that can contain version information
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(try_from = "String")]
 pub enum BuildSystem {
     /// Cargo.toml file for Rust projects
     Cargo,
@@ -19,6 +26,221 @@ pub enum BuildSystem {
     PyProject,
     /// package.json file for Node.js/TypeScript projects
     PackageJson,
+    /// CMakeLists.txt file for C/C++ projects
+    CMake,
+    /// Terraform module file with a version local or variable (see [`config::TerraformConfig`])
+    Terraform,
+    /// `.nuspec` package manifest for `NuGet`
+    Nuspec,
+    /// gleam.toml file for Gleam projects, with a top-level `version`
+    Gleam,
+    /// Directory.Packages.props for .NET Central Package Management, with a `<Version>`
+    /// or `<VersionPrefix>` `MSBuild` property
+    PackagesProps,
+    /// setup.cfg file for setuptools projects, with a `[metadata] version`
+    SetupCfg,
+    /// `.gemspec` file for Ruby gems, with a literal `spec.version = "..."` assignment
+    Gemspec,
+    /// Info.plist file for macOS/iOS apps, with a `CFBundleShortVersionString`
+    InfoPlist,
+}
+
+impl BuildSystem {
+    /// Every supported build system
+    pub const ALL: [Self; 11] = [
+        Self::Cargo,
+        Self::PyProject,
+        Self::PackageJson,
+        Self::CMake,
+        Self::Terraform,
+        Self::Nuspec,
+        Self::Gleam,
+        Self::PackagesProps,
+        Self::SetupCfg,
+        Self::Gemspec,
+        Self::InfoPlist,
+    ];
+
+    /// The fixed facts about this build system: its name, description and format
+    #[must_use]
+    pub const fn info(&self) -> &'static BuildSystemInfo {
+        match self {
+            Self::Cargo => &BuildSystemInfo {
+                name: "cargo",
+                description: "Rust crate, [package] version or [workspace.package] version",
+                allows_multiple: false,
+                format: &format::Cargo,
+            },
+            Self::PyProject => &BuildSystemInfo {
+                name: "pyproject",
+                description: "Python project, [project] version or [tool.commitizen] version",
+                allows_multiple: false,
+                format: &format::PyProject,
+            },
+            Self::PackageJson => &BuildSystemInfo {
+                name: "package-json",
+                description: "Node.js/TypeScript package, top-level \"version\"",
+                allows_multiple: false,
+                format: &format::PackageJson,
+            },
+            Self::CMake => &BuildSystemInfo {
+                name: "cmake",
+                description: "C/C++ project, project(... VERSION x.y.z)",
+                allows_multiple: false,
+                format: &format::CMake,
+            },
+            Self::Terraform => &BuildSystemInfo {
+                name: "terraform",
+                description: "Terraform module, a version local or variable default",
+                allows_multiple: false,
+                format: &format::Terraform,
+            },
+            Self::Nuspec => &BuildSystemInfo {
+                name: "nuspec",
+                description: "NuGet package manifest, <version>",
+                allows_multiple: true,
+                format: &format::Nuspec,
+            },
+            Self::Gleam => &BuildSystemInfo {
+                name: "gleam",
+                description: "Gleam project, top-level version",
+                allows_multiple: false,
+                format: &format::Gleam,
+            },
+            Self::PackagesProps => &BuildSystemInfo {
+                name: "packages-props",
+                description: ".NET Central Package Management, <Version> or <VersionPrefix>",
+                allows_multiple: false,
+                format: &format::PackagesProps,
+            },
+            Self::SetupCfg => &BuildSystemInfo {
+                name: "setup-cfg",
+                description: "setuptools project, [metadata] version",
+                allows_multiple: false,
+                format: &format::SetupCfg,
+            },
+            Self::Gemspec => &BuildSystemInfo {
+                name: "gemspec",
+                description: "Ruby gem, a literal spec.version assignment",
+                allows_multiple: true,
+                format: &format::Gemspec,
+            },
+            Self::InfoPlist => &BuildSystemInfo {
+                name: "info-plist",
+                description: "macOS/iOS app, CFBundleShortVersionString",
+                allows_multiple: false,
+                format: &format::InfoPlist,
+            },
+        }
+    }
+
+    /// The manifest filename this build system reads and writes
+    ///
+    /// For [`BuildSystem::Terraform`] this is the default; the file is configurable.
+    /// For [`BuildSystem::Nuspec`] and [`BuildSystem::Gemspec`] this is a glob, since any
+    /// file with the extension qualifies.
+    #[must_use]
+    pub fn manifest_name(&self) -> &'static str {
+        self.format().filenames()[0]
+    }
+
+    /// How this build system's manifest is found, read and rewritten
+    #[must_use]
+    pub const fn format(&self) -> &'static dyn format::ManifestFormat {
+        self.info().format
+    }
+
+    /// Lowercase name, as accepted by [`std::str::FromStr`] and used in JSON output
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.info().name
+    }
+
+    /// Whether one directory may hold several manifests of this build system
+    ///
+    /// Such manifests are addressed by path (see [`VersionManager::detect_manifests`]).
+    #[must_use]
+    pub const fn allows_multiple(&self) -> bool {
+        self.info().allows_multiple
+    }
+
+    /// Comma-separated names of every build system, ending in `or`, for error messages
+    #[must_use]
+    pub fn names_list() -> String {
+        let names: Vec<&str> = Self::ALL.iter().map(Self::name).collect();
+        match names.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} or {last}", rest.join(", ")),
+            _ => names.join(""),
+        }
+    }
+
+    /// Name for messages about the manifest at `path`: `Cargo`, or `Nuspec (app.nuspec)`
+    /// for build systems that allow several manifests per directory
+    #[must_use]
+    pub fn describe(&self, path: &Path) -> String {
+        match path.file_name() {
+            Some(file_name) if self.allows_multiple() => {
+                format!("{self:?} ({})", file_name.to_string_lossy())
+            }
+            _ => format!("{self:?}"),
+        }
+    }
+}
+
+/// The fixed facts about a build system, from [`BuildSystem::info`]
+#[derive(Debug, Clone, Copy)]
+pub struct BuildSystemInfo {
+    /// Lowercase name, as accepted by [`std::str::FromStr`]
+    pub name: &'static str,
+    /// What the manifest is and where in it the version lives
+    pub description: &'static str,
+    /// Whether one directory may hold several manifests of this build system
+    pub allows_multiple: bool,
+    /// How the manifest is found, read and rewritten
+    pub format: &'static dyn format::ManifestFormat,
+}
+
+impl std::str::FromStr for BuildSystem {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|system| system.name() == s)
+            .with_context(|| {
+                format!(
+                    "Unknown build system '{s}' (expected {})",
+                    Self::names_list()
+                )
+            })
+    }
+}
+
+impl Serialize for BuildSystem {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl TryFrom<String> for BuildSystem {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl std::str::FromStr for BumpType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "major" => Ok(Self::Major),
+            "minor" => Ok(Self::Minor),
+            "patch" => Ok(Self::Patch),
+            other => anyhow::bail!("Unknown bump type '{other}' (expected major, minor or patch)"),
+        }
+    }
 }
 
 /// Represents a version bump type following semantic versioning
@@ -32,2068 +254,7800 @@ pub enum BumpType {
     Patch,
 }
 
-/// Result of a dry-run operation showing what would change
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct DryRunResult {
-    /// The new version that would be applied
-    pub new_version: Version,
-    /// List of files that would be updated
-    pub files_to_update: Vec<std::path::PathBuf>,
-}
+impl BumpType {
+    /// Every bump type, from largest to smallest
+    pub const ALL: [Self; 3] = [Self::Major, Self::Minor, Self::Patch];
 
-/// Default version filename
-pub const DEFAULT_VERSION_FILE: &str = "VERSION";
+    /// Lowercase name, as accepted by [`std::str::FromStr`]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Major => "major",
+            Self::Minor => "minor",
+            Self::Patch => "patch",
+        }
+    }
 
-/// Core version management functionality
-pub struct VersionManager {
-    /// The current working directory path
-    pub base_path: std::path::PathBuf,
-    /// The version filename (e.g. "VERSION" or "version.txt")
-    pub version_file: String,
+    /// Compute the version that results from applying this bump to `current`
+    #[must_use]
+    pub const fn apply(self, current: &Version) -> Version {
+        match self {
+            Self::Major => Version::new(current.major + 1, 0, 0),
+            Self::Minor => Version::new(current.major, current.minor + 1, 0),
+            Self::Patch => Version::new(current.major, current.minor, current.patch + 1),
+        }
+    }
 }
 
-impl VersionManager {
-    /// Create a new `VersionManager` for the given directory with the default version filename
-    pub fn new<P: AsRef<Path>>(base_path: P) -> Self {
-        Self {
-            base_path: base_path.as_ref().to_path_buf(),
-            version_file: DEFAULT_VERSION_FILE.to_string(),
+/// The next version in a prerelease cycle labelled `ident`
+///
+/// With `bump`, a new cycle starts on the bumped core version: a minor bump takes
+/// `1.2.3` to `1.3.0-rc.1`. Without it the current cycle continues, so `1.3.0-rc.1`
+/// becomes `1.3.0-rc.2`, and `1.3.0-beta.2` becomes `1.3.0-rc.1` when moving on to a
+/// later identifier.
+///
+/// # Errors
+///
+/// Returns an error if `ident` is not a single non-numeric identifier, if `current` is
+/// not a prerelease and no bump is given, or if the result would not be newer than
+/// `current`.
+pub fn next_prerelease(current: &Version, bump: Option<BumpType>, ident: &str) -> Result<Version> {
+    if ident.is_empty() || ident.contains('.') || ident.bytes().all(|b| b.is_ascii_digit()) {
+        anyhow::bail!(
+            "Invalid prerelease identifier '{ident}' (expected a single non-numeric identifier such as rc or beta)"
+        );
+    }
+    let mut next = match bump {
+        Some(bump) => bump.apply(current),
+        None if current.pre.is_empty() => anyhow::bail!(
+            "{current} is not a prerelease; pass major, minor or patch to start a prerelease cycle"
+        ),
+        None => Version::new(current.major, current.minor, current.patch),
+    };
+    let counter = match current.pre.as_str().split_once('.') {
+        Some((label, number)) if bump.is_none() && label == ident => {
+            number.parse::<u64>().map_or(1, |n| n + 1)
         }
+        _ => 1,
+    };
+    next.pre = semver::Prerelease::new(&format!("{ident}.{counter}"))
+        .with_context(|| format!("Invalid prerelease identifier '{ident}'"))?;
+    if next <= *current {
+        anyhow::bail!("Prerelease {next} would not advance past the current version {current}");
     }
+    Ok(next)
+}
 
-    /// Create a new `VersionManager` with a custom version filename
-    pub fn with_version_file<P: AsRef<Path>>(base_path: P, version_file: &str) -> Self {
-        Self {
-            base_path: base_path.as_ref().to_path_buf(),
-            version_file: version_file.to_string(),
+/// The label of a prerelease: its non-numeric identifiers (`rc` for `rc.2`)
+///
+/// Two prereleases with the same label belong to one cycle and differ only in their
+/// counters.
+fn prerelease_label(pre: &semver::Prerelease) -> Vec<&str> {
+    pre.as_str()
+        .split('.')
+        .filter(|part| !part.bytes().all(|b| b.is_ascii_digit()))
+        .collect()
+}
+
+/// A version field's value: an exact version or a semver requirement
+///
+/// Package versions are always exact, but dependency entries and similar fields may
+/// hold requirements such as `^1.2.3`, `~1.0` or `>=1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionSpec {
+    /// A complete version such as `1.2.3`
+    Exact(Version),
+    /// A requirement such as `^1.2.3` or `>=1, <2`
+    Requirement(semver::VersionReq),
+}
+
+impl VersionSpec {
+    /// Parse `text` as an exact version, falling back to a requirement
+    ///
+    /// A bare `1.2.3` is exact, although Cargo reads it as `^1.2.3` in dependencies.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `text` is neither.
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if let Ok(version) = Version::parse(text) {
+            return Ok(Self::Exact(version));
         }
+        semver::VersionReq::parse(text)
+            .map(Self::Requirement)
+            .with_context(|| format!("'{text}' is neither a version nor a version requirement"))
     }
 
-    /// Read the current version from the VERSION file
+    /// `text` pointed at `version`, keeping a requirement's operator and precision
+    ///
+    /// `^1.2.3` becomes `^2.0.0` and `~1.0` becomes `~2.0`; an exact version is replaced
+    /// outright. Build metadata is dropped, since requirements cannot carry it.
     ///
     /// # Errors
     ///
-    /// Returns an error if the VERSION file cannot be read or contains an invalid version format.
-    pub fn read_version_file(&self) -> Result<Version> {
-        let version_path = self.base_path.join(&self.version_file);
-        let content = fs::read_to_string(&version_path).with_context(|| {
-            format!("Failed to read VERSION file at {}", version_path.display())
-        })?;
+    /// Returns an error if `text` does not parse, or is a requirement without exactly one
+    /// non-wildcard comparator (`>=1, <2`, `1.*`), which has no single version to replace.
+    pub fn retarget(text: &str, version: &Version) -> Result<String> {
+        let requirement = match Self::parse(text)? {
+            Self::Exact(_) => return Ok(version.to_string()),
+            Self::Requirement(requirement) => requirement,
+        };
+        let text = text.trim();
+        let comparator = match requirement.comparators.as_slice() {
+            [comparator] if comparator.op != semver::Op::Wildcard => comparator,
+            _ => anyhow::bail!(
+                "Cannot update version requirement '{text}': it has no single version to replace"
+            ),
+        };
 
-        // Strip inline comments (e.g. "1.2.3 # x-release-please-version")
-        let version_str = content.trim().split('#').next().unwrap_or("").trim();
-        Version::parse(version_str)
-            .with_context(|| format!("Invalid version format in VERSION file: {version_str}"))
+        let operator = &text[..text.find(|c: char| c.is_ascii_digit()).unwrap_or(0)];
+        let core = match (comparator.minor, comparator.patch) {
+            (None, _) => version.major.to_string(),
+            (Some(_), None) => format!("{}.{}", version.major, version.minor),
+            (Some(_), Some(_)) => VersionManager::without_build_metadata(version).to_string(),
+        };
+        Ok(format!("{operator}{core}"))
     }
+}
 
-    /// Write a version to the VERSION file
+/// A calendar month used to derive `YYYY.MM.<n>` calendar versions
+///
+/// Parses from `YYYY-MM-DD` or `YYYY-MM`; the day only makes dates from other tools
+/// acceptable and does not affect the version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalVerDate {
+    /// Four-digit year, used as the major version
+    pub year: u64,
+    /// Month from 1 to 12, used as the minor version
+    pub month: u64,
+}
+
+impl CalVerDate {
+    /// The current month in UTC
     ///
     /// # Errors
     ///
-    /// Returns an error if the VERSION file cannot be written to.
-    pub fn write_version_file(&self, version: &Version) -> Result<()> {
-        let version_path = self.base_path.join(&self.version_file);
+    /// Returns an error if the system clock is set before 1970.
+    pub fn today() -> Result<Self> {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .context("System clock is set before 1970")?
+            .as_secs();
+        Ok(Self::from_days_since_epoch(secs / 86_400))
+    }
 
-        // Preserve inline comments (e.g. "# x-release-please-version")
-        let content = if version_path.exists() {
-            let existing = fs::read_to_string(&version_path).unwrap_or_default();
-            let trimmed = existing.trim();
-            trimmed.find('#').map_or_else(
-                || format!("{version}\n"),
-                |hash_pos| {
-                    let comment = trimmed[hash_pos..].trim();
-                    format!("{version} {comment}\n")
-                },
-            )
+    /// Civil year and month of a day count since 1970-01-01 (proleptic Gregorian)
+    const fn from_days_since_epoch(days: u64) -> Self {
+        // Shift to a March-based era so leap days fall at the end of the year
+        let days = days + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
         } else {
-            format!("{version}\n")
+            shifted_month - 9
         };
-
-        fs::write(&version_path, content)
-            .with_context(|| format!("Failed to write VERSION file at {}", version_path.display()))
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        Self { year, month }
     }
 
-    /// Detect which build system files are present
-    #[must_use]
-    pub fn detect_build_systems(&self) -> Vec<BuildSystem> {
-        let mut systems = Vec::new();
-
-        if self.base_path.join("Cargo.toml").exists() {
-            systems.push(BuildSystem::Cargo);
+    /// Compute the calendar version that follows `current` in this month
+    ///
+    /// Within the same month the counter increments (`2024.6.0` → `2024.6.1`); in any
+    /// other month it restarts at 0. Months are not zero-padded, since semver forbids
+    /// leading zeros.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the result would not be newer than `current` (the current
+    /// version is from a later month) or does not round-trip as semver.
+    pub fn next_version(self, current: &Version) -> Result<Version> {
+        let next = if current.major == self.year
+            && current.minor == self.month
+            && current.pre.is_empty()
+        {
+            Version::new(self.year, self.month, current.patch + 1)
+        } else {
+            Version::new(self.year, self.month, 0)
+        };
+        if next <= *current {
+            anyhow::bail!(
+                "Calendar version {next} would not advance past the current version {current}"
+            );
         }
+        let parsed = Version::parse(&next.to_string())
+            .with_context(|| format!("Calendar version {next} is not valid semver"))?;
+        Ok(parsed)
+    }
+}
 
-        if self.base_path.join("pyproject.toml").exists() {
-            systems.push(BuildSystem::PyProject);
+impl std::str::FromStr for CalVerDate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || anyhow::anyhow!("Invalid date '{s}' (expected YYYY-MM-DD or YYYY-MM)");
+        let mut parts = s.split('-');
+        let year = parts
+            .next()
+            .filter(|year| year.len() == 4)
+            .and_then(|year| year.parse::<u64>().ok())
+            .ok_or_else(invalid)?;
+        let month = parts
+            .next()
+            .filter(|month| month.len() == 2)
+            .and_then(|month| month.parse::<u64>().ok())
+            .filter(|month| (1..=12).contains(month))
+            .ok_or_else(invalid)?;
+        if let Some(day) = parts.next() {
+            let valid_day =
+                day.len() == 2 && day.parse::<u64>().is_ok_and(|day| (1..=31).contains(&day));
+            if !valid_day {
+                return Err(invalid());
+            }
+        }
+        if parts.next().is_some() {
+            return Err(invalid());
         }
+        Ok(Self { year, month })
+    }
+}
 
-        if self.base_path.join("package.json").exists() {
-            systems.push(BuildSystem::PackageJson);
+/// Result of a dry-run operation showing what would change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DryRunResult {
+    /// The new version that would be applied
+    pub new_version: Version,
+    /// List of files that would be updated
+    pub files_to_update: Vec<std::path::PathBuf>,
+    /// The git tag that would be created, when tagging was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag_name: Option<String>,
+}
+
+impl DryRunResult {
+    /// Return a copy with `files_to_update` expressed relative to `base`
+    ///
+    /// Paths outside `base` are left unchanged.
+    #[must_use]
+    pub fn relative_to(&self, base: &Path) -> Self {
+        Self {
+            new_version: self.new_version.clone(),
+            files_to_update: self
+                .files_to_update
+                .iter()
+                .map(|path| path.strip_prefix(base).unwrap_or(path).to_path_buf())
+                .collect(),
+            tag_name: self.tag_name.clone(),
         }
+    }
+}
+
+/// What a cascade write did, for an end-of-run report
+///
+/// Returned by the cascade bump, sync and reset operations. Every file the cascade
+/// considered is counted once under `updated`, `in_sync` or `skipped`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CascadeSummary {
+    /// Files considered: the VERSION file (when written), configured Python modules
+    /// and every discovered manifest
+    pub scanned: usize,
+    /// Files whose version was rewritten
+    pub updated: usize,
+    /// Files that already held the version and were left untouched
+    pub in_sync: usize,
+    /// Manifests left alone: those with no version of their own (see
+    /// [`VersionManager::build_system_has_no_version`]) and those git does not track
+    /// (see [`VersionManager::untracked_manifests`]); the latter are not in `scanned`
+    pub skipped: usize,
+    /// Paths of the updated files, in the order they were written
+    pub changed_paths: Vec<std::path::PathBuf>,
+}
 
-        systems
+impl CascadeSummary {
+    /// Whether any file changed
+    #[must_use]
+    pub const fn changed(&self) -> bool {
+        self.updated > 0
     }
 
-    /// Read version from a specific build system file
-    ///
-    /// # Errors
+    /// Return a copy with `changed_paths` expressed relative to `base`
     ///
-    /// Returns an error if the build system file cannot be read or parsed.
-    pub fn read_build_system_version(&self, system: &BuildSystem) -> Result<Version> {
-        match system {
-            BuildSystem::Cargo => self.read_cargo_version(),
-            BuildSystem::PyProject => self.read_pyproject_version(),
-            BuildSystem::PackageJson => self.read_package_json_version(),
+    /// Paths outside `base` are left unchanged.
+    #[must_use]
+    pub fn relative_to(&self, base: &Path) -> Self {
+        Self {
+            changed_paths: self
+                .changed_paths
+                .iter()
+                .map(|path| path.strip_prefix(base).unwrap_or(path).to_path_buf())
+                .collect(),
+            ..self.clone()
         }
     }
 
-    /// Update version in a specific build system file
+    /// Count a file the cascade wrote to (or found already current)
+    fn record(&mut self, path: &Path, changed: bool) {
+        self.scanned += 1;
+        if changed {
+            self.updated += 1;
+            self.changed_paths.push(path.to_path_buf());
+        } else {
+            self.in_sync += 1;
+        }
+    }
+}
+
+/// Contents of the files a write may touch, taken before it so the write can be undone
+///
+/// Built by [`VersionManager::snapshot_write_targets`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteSnapshot {
+    /// Each file and its content, `None` for a file that did not exist yet
+    files: Vec<(std::path::PathBuf, Option<String>)>,
+}
+
+impl WriteSnapshot {
+    /// Record the current content of each of `paths`
     ///
     /// # Errors
     ///
-    /// Returns an error if the build system file cannot be read, parsed, or written.
-    pub fn update_build_system_version(
-        &self,
-        system: &BuildSystem,
-        version: &Version,
-    ) -> Result<()> {
-        match system {
-            BuildSystem::Cargo => self.update_cargo_version(version),
-            BuildSystem::PyProject => self.update_pyproject_version(version),
-            BuildSystem::PackageJson => self.update_package_json_version(version),
+    /// Returns an error if a file exists but cannot be read.
+    pub(crate) fn take(paths: Vec<std::path::PathBuf>) -> Result<Self> {
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            let content = match trace::read_to_string(&path) {
+                Ok(content) => Some(content),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+                }
+            };
+            files.push((path, content));
         }
+        Ok(Self { files })
     }
 
-    /// Bump version according to semantic versioning rules
+    /// Files whose content no longer matches the snapshot, in snapshot order
+    pub(crate) fn changed_paths(&self) -> Vec<&Path> {
+        self.files
+            .iter()
+            .filter(|(path, original)| trace::read_to_string(path).ok() != *original)
+            .map(|(path, _)| path.as_path())
+            .collect()
+    }
+
+    /// Put every file back as it was when the snapshot was taken
+    ///
+    /// Files that still match are left alone and files created since are removed.
+    /// Returns how many files were restored.
     ///
     /// # Errors
     ///
-    /// Returns an error if version files are not synchronized or cannot be updated.
-    pub fn bump_version(&self, bump_type: BumpType) -> Result<()> {
-        // Ensure all versions are in sync before bumping
-        self.verify_versions_in_sync()?;
-
-        let current_version = self.read_version_file()?;
-        let new_version = match bump_type {
-            BumpType::Major => Version::new(current_version.major + 1, 0, 0),
-            BumpType::Minor => Version::new(current_version.major, current_version.minor + 1, 0),
-            BumpType::Patch => Version::new(
-                current_version.major,
-                current_version.minor,
-                current_version.patch + 1,
-            ),
-        };
+    /// Returns an error listing the files that could not be restored; every other file
+    /// is still restored.
+    pub fn restore(&self) -> Result<usize> {
+        let mut restored = 0;
+        let mut failures = Vec::new();
+        for (path, original) in &self.files {
+            if trace::read_to_string(path).ok() == *original {
+                continue;
+            }
+            let result = original.as_ref().map_or_else(
+                || trace::remove_file(path),
+                |content| trace::write(path, content),
+            );
+            match result {
+                Ok(()) => restored += 1,
+                Err(e) => failures.push(format!("{}: {e}", path.display())),
+            }
+        }
+        if !failures.is_empty() {
+            anyhow::bail!("Failed to restore {}", failures.join(", "));
+        }
+        Ok(restored)
+    }
+}
 
-        // Update VERSION file
-        self.write_version_file(&new_version)?;
+/// A manifest found by discovery and how its version compares to the VERSION file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ManifestStatus {
+    /// Path to the manifest file
+    pub path: std::path::PathBuf,
+    /// Build system the manifest belongs to
+    pub system: BuildSystem,
+    /// Version declared in the manifest, or `None` if it could not be read
+    pub version: Option<Version>,
+    /// Whether the manifest's version matches the VERSION file
+    pub in_sync: bool,
+}
 
-        // Update all detected build system files
-        let build_systems = self.detect_build_systems();
-        for system in &build_systems {
-            self.update_build_system_version(system, &new_version)
-                .with_context(|| format!("Failed to update {system:?} version"))?;
+impl ManifestStatus {
+    /// Return a copy with `path` expressed relative to `base`
+    ///
+    /// Paths outside `base` are left unchanged.
+    #[must_use]
+    pub fn relative_to(&self, base: &Path) -> Self {
+        Self {
+            path: self
+                .path
+                .strip_prefix(base)
+                .unwrap_or(&self.path)
+                .to_path_buf(),
+            ..self.clone()
         }
-
-        Ok(())
     }
+}
 
-    /// Reset the version to a specific version string
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the version string is invalid or if file operations fail.
-    pub fn reset_version(&self, version_str: &str) -> Result<()> {
-        // Parse the provided version string
-        let new_version = Version::parse(version_str)
-            .with_context(|| format!("Invalid semantic version format: '{version_str}'"))?;
+/// Where a pyproject `dynamic = ["version"]` project keeps its real version
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DynamicVersionSource {
+    /// A string attribute such as `__version__` in a Python module
+    Attr {
+        /// Module source file
+        path: std::path::PathBuf,
+        /// Attribute name
+        name: String,
+    },
+    /// A plain text file containing only the version
+    File(std::path::PathBuf),
+}
 
-        // Update VERSION file
-        self.write_version_file(&new_version)?;
+/// A single way in which a build system disagrees with the VERSION file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncIssue {
+    /// The build system declares a different version than the VERSION file
+    Drift {
+        /// Build system that drifted
+        system: BuildSystem,
+        /// The drifted manifest
+        path: std::path::PathBuf,
+        /// Version found in the build system file
+        found: Version,
+        /// Version in the VERSION file
+        expected: Version,
+        /// Whether only `major.minor.patch` were compared (see
+        /// [`config::VerifyConfig::ignore_prerelease`])
+        core_only: bool,
+    },
+    /// The build system's version could not be read or parsed
+    Unreadable {
+        /// Build system that failed
+        system: BuildSystem,
+        /// The manifest that could not be read
+        path: std::path::PathBuf,
+        /// Description of the read failure
+        error: String,
+    },
+    /// A configured Python module or JSON file declares a different version than the
+    /// VERSION file
+    ModuleDrift {
+        /// The module's file
+        path: std::path::PathBuf,
+        /// Variable or JSON key holding the version
+        variable: String,
+        /// Version found in the module
+        found: Version,
+        /// Version in the VERSION file
+        expected: Version,
+    },
+    /// A configured Python module's or JSON file's version could not be read or parsed
+    ModuleUnreadable {
+        /// The module's file
+        path: std::path::PathBuf,
+        /// Description of the read failure
+        error: String,
+    },
+    /// setup.cfg and pyproject.toml, two declarations of the same Python package's
+    /// version, disagree with each other
+    PythonSourcesDisagree {
+        /// Version in pyproject.toml
+        pyproject: Version,
+        /// Version in setup.cfg
+        setup_cfg: Version,
+    },
+    /// The git tag being released names a different version than the VERSION file
+    TagMismatch {
+        /// Tag name, prefix included
+        tag: String,
+        /// Version the tag names
+        found: Version,
+        /// Version in the VERSION file
+        expected: Version,
+    },
+}
 
-        // Update all detected build system files
-        let build_systems = self.detect_build_systems();
-        for system in &build_systems {
-            self.update_build_system_version(system, &new_version)
-                .with_context(|| format!("Failed to update {system:?} version"))?;
+impl SyncIssue {
+    /// The build system this issue concerns, or `None` for a Python module, JSON file or
+    /// tag
+    ///
+    /// [`SyncIssue::PythonSourcesDisagree`] is attributed to setup.cfg, the legacy side.
+    #[must_use]
+    pub const fn system(&self) -> Option<&BuildSystem> {
+        match self {
+            Self::Drift { system, .. } | Self::Unreadable { system, .. } => Some(system),
+            Self::PythonSourcesDisagree { .. } => Some(&BuildSystem::SetupCfg),
+            Self::ModuleDrift { .. } | Self::ModuleUnreadable { .. } | Self::TagMismatch { .. } => {
+                None
+            }
         }
+    }
 
-        Ok(())
+    /// Whether this issue is a read failure rather than a version mismatch
+    #[must_use]
+    pub const fn is_unreadable(&self) -> bool {
+        matches!(
+            self,
+            Self::Unreadable { .. } | Self::ModuleUnreadable { .. }
+        )
     }
+}
 
-    /// Verify that all version files are synchronized
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if version files are not synchronized or cannot be read.
-    pub fn verify_versions_in_sync(&self) -> Result<()> {
-        let version_file_version = self.read_version_file()?;
-        let build_systems = self.detect_build_systems();
+/// What a build system file says about its version, from a single read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestVersion {
+    /// The file declares a version
+    Declared {
+        /// Version found in the file
+        version: Version,
+        /// Whether it matches the VERSION file (see [`VersionManager::version_matches`])
+        in_sync: bool,
+    },
+    /// The version is inherited from the workspace (see
+    /// [`VersionManager::build_system_inherits_version`])
+    Inherited,
+    /// A virtual workspace root with no version (see
+    /// [`VersionManager::build_system_is_virtual_workspace`])
+    VirtualWorkspace,
+    /// A `$version$` placeholder filled in at pack time (see
+    /// [`VersionManager::overwrite_placeholders`])
+    Placeholder,
+    /// The file could not be read or parsed
+    Unreadable(String),
+}
 
-        let mut mismatched = Vec::new();
+/// The VERSION file and every detected build system, each read exactly once
+///
+/// Built by [`VersionManager::version_report`] for commands such as `status` and
+/// `verify` that need several facts about the same files. It is a snapshot: build a
+/// new one after writing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReport {
+    /// Version in the VERSION file
+    pub version: Version,
+    /// Each detected manifest (see [`VersionManager::detect_manifests`]) and what it
+    /// declares
+    pub systems: Vec<(BuildSystem, std::path::PathBuf, ManifestVersion)>,
+    /// Each configured Python module (see [`config::PythonModuleConfig`]) and what it
+    /// declares
+    pub modules: Vec<(config::PythonModuleConfig, ManifestVersion)>,
+    /// Each configured JSON file (see [`config::JsonFileConfig`]) and what it declares
+    pub json_files: Vec<(config::JsonFileConfig, ManifestVersion)>,
+    /// Whether versions were compared on `major.minor.patch` only
+    pub ignore_prerelease: bool,
+}
 
-        for system in &build_systems {
-            match self.read_build_system_version(system) {
-                Ok(system_version) => {
-                    if system_version != version_file_version {
-                        mismatched.push(format!(
-                            "{system:?} has version {system_version} but VERSION file has {version_file_version}"
-                        ));
-                    }
-                }
-                Err(e) => {
-                    mismatched.push(format!("Failed to read {system:?} version: {e}"));
+impl VersionReport {
+    /// Build systems that drifted or could not be read
+    ///
+    /// When setup.cfg and pyproject.toml both declare a version and the two differ, a
+    /// [`SyncIssue::PythonSourcesDisagree`] is reported as well, since they describe
+    /// the same package.
+    #[must_use]
+    pub fn issues(&self) -> Vec<SyncIssue> {
+        let mut issues: Vec<SyncIssue> = self
+            .systems
+            .iter()
+            .filter_map(|(system, path, state)| match state {
+                ManifestVersion::Declared { version, in_sync } if !in_sync => {
+                    Some(SyncIssue::Drift {
+                        system: system.clone(),
+                        path: path.clone(),
+                        found: version.clone(),
+                        expected: self.version.clone(),
+                        core_only: self.ignore_prerelease,
+                    })
                 }
+                ManifestVersion::Unreadable(error) => Some(SyncIssue::Unreadable {
+                    system: system.clone(),
+                    path: path.clone(),
+                    error: error.clone(),
+                }),
+                _ => None,
+            })
+            .chain(
+                self.modules
+                    .iter()
+                    .filter_map(|(module, state)| match state {
+                        ManifestVersion::Declared { version, in_sync } if !in_sync => {
+                            Some(SyncIssue::ModuleDrift {
+                                path: module.path.clone().into(),
+                                variable: module.variable.clone(),
+                                found: version.clone(),
+                                expected: self.version.clone(),
+                            })
+                        }
+                        ManifestVersion::Unreadable(error) => Some(SyncIssue::ModuleUnreadable {
+                            path: module.path.clone().into(),
+                            error: error.clone(),
+                        }),
+                        _ => None,
+                    }),
+            )
+            .chain(
+                self.json_files
+                    .iter()
+                    .filter_map(|(file, state)| match state {
+                        ManifestVersion::Declared { version, in_sync } if !in_sync => {
+                            Some(SyncIssue::ModuleDrift {
+                                path: file.path.clone().into(),
+                                variable: file.key.clone(),
+                                found: version.clone(),
+                                expected: self.version.clone(),
+                            })
+                        }
+                        ManifestVersion::Unreadable(error) => Some(SyncIssue::ModuleUnreadable {
+                            path: file.path.clone().into(),
+                            error: error.clone(),
+                        }),
+                        _ => None,
+                    }),
+            )
+            .collect();
+        if let (Some(pyproject), Some(setup_cfg)) = (
+            self.declared_version(&BuildSystem::PyProject),
+            self.declared_version(&BuildSystem::SetupCfg),
+        ) {
+            let differ = if self.ignore_prerelease {
+                (pyproject.major, pyproject.minor, pyproject.patch)
+                    != (setup_cfg.major, setup_cfg.minor, setup_cfg.patch)
+            } else {
+                pyproject != setup_cfg
+            };
+            if differ {
+                issues.push(SyncIssue::PythonSourcesDisagree {
+                    pyproject: pyproject.clone(),
+                    setup_cfg: setup_cfg.clone(),
+                });
             }
         }
+        issues
+    }
 
-        if !mismatched.is_empty() {
-            anyhow::bail!(
-                "Version files are not synchronized:\n{}\n\nRun 'versioneer sync' to synchronize all version files.",
-                mismatched.join("\n")
-            );
-        }
-
-        Ok(())
+    /// The version `system` declares, if it was detected and read
+    fn declared_version(&self, system: &BuildSystem) -> Option<&Version> {
+        self.systems
+            .iter()
+            .find_map(|(found, _, state)| match state {
+                ManifestVersion::Declared { version, .. } if found == system => Some(version),
+                _ => None,
+            })
     }
+}
 
-    /// Synchronize all version files to match the VERSION file
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if version files cannot be read or updated.
-    pub fn sync_versions(&self) -> Result<()> {
-        let version = self.read_version_file()?;
-        let build_systems = self.detect_build_systems();
+/// Failures callers may want to tell apart from other errors
+///
+/// Returned inside an [`anyhow::Error`]; see [`VersioneerError::kind_of`] to classify
+/// any error, typed or not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersioneerError {
+    /// Build system files drifted from the VERSION file or could not be read
+    OutOfSync(Vec<SyncIssue>),
+    /// A cascade under [`VersionManager::keep_going`] wrote what it could, but some
+    /// files failed
+    PartialCascade {
+        /// What was written
+        summary: CascadeSummary,
+        /// One `path: error` line per file that could not be updated
+        failures: Vec<String>,
+    },
+}
 
-        for system in &build_systems {
-            self.update_build_system_version(system, &version)
-                .with_context(|| format!("Failed to sync {system:?} version"))?;
+impl VersioneerError {
+    /// Stable name of this failure, for machine-readable output
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::OutOfSync(_) => "OutOfSync",
+            Self::PartialCascade { .. } => "PartialCascade",
         }
+    }
 
-        Ok(())
+    /// One line per underlying problem
+    #[must_use]
+    pub fn details(&self) -> Vec<String> {
+        match self {
+            Self::OutOfSync(issues) => issues.iter().map(ToString::to_string).collect(),
+            Self::PartialCascade { failures, .. } => failures.clone(),
+        }
     }
 
-    /// Bump version with cascade dry-run (preview what would change)
+    /// Classify any error by its cause
     ///
-    /// # Errors
-    ///
-    /// Returns an error if discovery fails or version reading fails.
-    pub fn bump_cascade_dry_run(&self, bump_type: BumpType) -> Result<DryRunResult> {
-        // Step 1: Discover all manifests
-        let manifests = self.discover_manifests()?;
+    /// A [`VersioneerError`] gives its own kind. Otherwise the cause chain decides:
+    /// `InvalidVersion` for semver parse failures, `Parse` for TOML or JSON, `Io` for
+    /// file system failures, and `Error` for anything else.
+    #[must_use]
+    pub fn kind_of(err: &anyhow::Error) -> &'static str {
+        if let Some(err) = err.downcast_ref::<Self>() {
+            return err.kind();
+        }
+        for cause in err.chain() {
+            if cause.is::<semver::Error>() {
+                return "InvalidVersion";
+            }
+            if cause.is::<toml::de::Error>()
+                || cause.is::<toml_edit::TomlError>()
+                || cause.is::<serde_json::Error>()
+            {
+                return "Parse";
+            }
+            if cause.is::<std::io::Error>() {
+                return "Io";
+            }
+        }
+        "Error"
+    }
 
-        // Step 2: Read current version and calculate new version
-        let current_version = self.read_version_file()?;
-        let new_version = match bump_type {
-            BumpType::Major => Version::new(current_version.major + 1, 0, 0),
-            BumpType::Minor => Version::new(current_version.major, current_version.minor + 1, 0),
-            BumpType::Patch => Version::new(
-                current_version.major,
-                current_version.minor,
-                current_version.patch + 1,
+    /// Details for any error: a [`VersioneerError`]'s own, otherwise the causes
+    /// beneath the top-level message
+    #[must_use]
+    pub fn details_of(err: &anyhow::Error) -> Vec<String> {
+        err.downcast_ref::<Self>().map_or_else(
+            || err.chain().skip(1).map(ToString::to_string).collect(),
+            Self::details,
+        )
+    }
+}
+
+impl std::fmt::Display for VersioneerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfSync(issues) => write!(
+                f,
+                "Version files are not synchronized:\n{}\n\nRun 'versioneer sync' to synchronize all version files.",
+                issues
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n")
             ),
-        };
+            Self::PartialCascade { summary, failures } => write!(
+                f,
+                "{} of {} files could not be updated; the others were written ({} rewritten), so the tree may be partly updated:\n{}\n\nFix these files and run the command again.",
+                failures.len(),
+                summary.scanned + failures.len(),
+                summary.updated,
+                failures.join("\n")
+            ),
+        }
+    }
+}
 
-        // Step 3: Collect all files that would be updated
-        let mut files_to_update = vec![self.base_path.join(&self.version_file)];
-        for (path, _) in manifests {
-            files_to_update.push(path);
+impl std::error::Error for VersioneerError {}
+
+impl std::fmt::Display for SyncIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Drift {
+                system,
+                path,
+                found,
+                expected,
+                core_only,
+            } => {
+                write!(
+                    f,
+                    "{} has version {found} but VERSION file has {expected}",
+                    system.describe(path)
+                )?;
+                if *core_only {
+                    write!(
+                        f,
+                        " (core versions {}.{}.{} and {}.{}.{} differ; prerelease ignored)",
+                        found.major,
+                        found.minor,
+                        found.patch,
+                        expected.major,
+                        expected.minor,
+                        expected.patch
+                    )?;
+                }
+                Ok(())
+            }
+            Self::Unreadable {
+                system,
+                path,
+                error,
+            } => {
+                write!(
+                    f,
+                    "Failed to read {} version: {error}",
+                    system.describe(path)
+                )
+            }
+            Self::ModuleDrift {
+                path,
+                variable,
+                found,
+                expected,
+            } => write!(
+                f,
+                "{} has {variable} = {found} but VERSION file has {expected}",
+                path.display()
+            ),
+            Self::ModuleUnreadable { path, error } => {
+                write!(f, "Failed to read version from {}: {error}", path.display())
+            }
+            Self::PythonSourcesDisagree {
+                pyproject,
+                setup_cfg,
+            } => write!(
+                f,
+                "setup.cfg has version {setup_cfg} but pyproject.toml has {pyproject}; both declare the same Python package"
+            ),
+            Self::TagMismatch {
+                tag,
+                found,
+                expected,
+            } => write!(
+                f,
+                "Tag {tag} is version {found} but VERSION file has {expected}"
+            ),
         }
+    }
+}
 
-        Ok(DryRunResult {
-            new_version,
-            files_to_update,
-        })
+/// Default version filename
+pub const DEFAULT_VERSION_FILE: &str = "VERSION";
+
+/// Conventional version filenames, in the order [`VersionManager::new`] looks for them
+pub const VERSION_FILE_NAMES: &[&str] =
+    &[DEFAULT_VERSION_FILE, "VERSION.txt", ".version", "version"];
+
+/// Default prefix prepended to versions when naming git tags
+pub const DEFAULT_TAG_PREFIX: &str = "v";
+
+/// Directory names that cascade discovery never descends into by default
+pub const DEFAULT_EXCLUDED_DIRS: &[&str] = &["target", "node_modules", ".git", "vendor"];
+
+/// The build system managing the manifest at `path`, or `None` for unrelated files
+///
+/// Matches on the filename, so the file need not exist, except for setup.cfg and
+/// `.gemspec` files: they count only when they declare a version (see
+/// [`format::ManifestFormat::manages`]). Terraform is recognized by its default file,
+/// `main.tf`; use [`VersionManager::classify_manifest`] to honor a configured one.
+#[must_use]
+pub fn classify_manifest(path: &Path) -> Option<BuildSystem> {
+    classify_manifest_with(path, &Config::default())
+}
+
+/// [`classify_manifest`] under `config`
+fn classify_manifest_with(path: &Path, config: &Config) -> Option<BuildSystem> {
+    BuildSystem::ALL.into_iter().find(|system| {
+        !config.ignore_systems.contains(system) && system.format().manages(path, config)
+    })
+}
+
+/// Top-level directory of the git repository enclosing `dir`
+///
+/// # Errors
+///
+/// Returns an error if git cannot be run or `dir` is not inside a git repository.
+pub fn git_root(dir: &Path) -> Result<std::path::PathBuf> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .current_dir(dir)
+        .output()
+        .context("Failed to run git. Is git installed?")?;
+    if !output.status.success() {
+        anyhow::bail!("{} is not inside a git repository", dir.display());
     }
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end().into())
+}
 
-    /// Bump version with cascade (update all discovered manifests)
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if discovery fails, versions are out of sync, or updates fail.
-    /// On error, all changes are rolled back.
-    pub fn bump_cascade(&self, bump_type: BumpType) -> Result<()> {
-        use std::collections::HashMap;
+/// Kind of git tag [`VersionManager::create_tag`] creates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TagKind {
+    /// A plain ref to the commit (the default)
+    #[default]
+    Lightweight,
+    /// An annotated tag object with a message
+    Annotated,
+    /// An annotated tag GPG-signed by git
+    Signed,
+}
 
-        // Step 1: Discover all manifests
-        let manifests = self.discover_manifests()?;
+/// Options controlling the recursive manifest walk used by cascade mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveryOptions {
+    /// Maximum directory depth to descend (the base path is depth 0); `None` is unbounded
+    pub max_depth: Option<usize>,
+    /// Descend into symlinked directories (loops are detected and reported)
+    pub follow_symlinks: bool,
+    /// Skip [`DEFAULT_EXCLUDED_DIRS`] during the walk
+    pub default_excludes: bool,
+    /// What to do with manifests git does not track
+    pub untracked: UntrackedManifests,
+}
 
-        // Step 2: Read current version and calculate new version
-        let current_version = self.read_version_file()?;
-        let new_version = match bump_type {
-            BumpType::Major => Version::new(current_version.major + 1, 0, 0),
-            BumpType::Minor => Version::new(current_version.major, current_version.minor + 1, 0),
-            BumpType::Patch => Version::new(
-                current_version.major,
-                current_version.minor,
-                current_version.patch + 1,
-            ),
-        };
+impl Default for DiscoveryOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            follow_symlinks: false,
+            default_excludes: true,
+            untracked: UntrackedManifests::default(),
+        }
+    }
+}
 
-        // Step 3: Read all files into memory for potential rollback
-        let mut original_contents: HashMap<std::path::PathBuf, String> = HashMap::new();
+/// What cascade discovery does with manifests that git does not track
+///
+/// Applies only inside a git work tree; elsewhere every manifest is tracked as far as
+/// discovery is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UntrackedManifests {
+    /// Leave untracked (including git-ignored) manifests out of the cascade
+    #[default]
+    Skip,
+    /// Treat untracked manifests like tracked ones
+    Include,
+    /// Fail discovery if any untracked manifest is found
+    Abort,
+}
 
-        // Read version file
-        let version_path = self.base_path.join(&self.version_file);
-        original_contents.insert(version_path.clone(), fs::read_to_string(&version_path)?);
+/// Which `pyproject.toml` table holds the authoritative version
+///
+/// When both `[project].version` and `[tool.commitizen].version` are present
+/// they are always kept in sync; this only decides which one is read first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PyProjectSource {
+    /// The standard `[project]` table
+    #[default]
+    Project,
+    /// The `[tool.commitizen]` table
+    Commitizen,
+}
 
-        // Read all manifests
-        for (path, _) in &manifests {
-            original_contents.insert(path.clone(), fs::read_to_string(path)?);
+impl PyProjectSource {
+    /// TOML section path for this source
+    const fn section(self) -> &'static str {
+        match self {
+            Self::Project => "project",
+            Self::Commitizen => "tool.commitizen",
         }
+    }
+}
 
-        // Step 4: Perform updates with rollback on error
-        let update_result = (|| -> Result<()> {
-            // Update VERSION file
-            self.write_version_file(&new_version)?;
+impl std::str::FromStr for PyProjectSource {
+    type Err = anyhow::Error;
 
-            // Update all manifests
-            for (path, system) in &manifests {
-                // Create a temporary VersionManager for this manifest's directory
-                let manifest_dir = path.parent().context("Manifest has no parent directory")?;
-                let temp_manager = Self::new(manifest_dir);
-                temp_manager
-                    .update_build_system_version(system, &new_version)
-                    .with_context(|| {
-                        format!("Failed to update {:?} at {}", system, path.display())
-                    })?;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "project" => Ok(Self::Project),
+            "commitizen" => Ok(Self::Commitizen),
+            other => {
+                anyhow::bail!("Unknown pyproject source '{other}' (expected project or commitizen)")
             }
+        }
+    }
+}
 
-            Ok(())
-        })();
+/// What one discovery walk found: the manifests to manage and those left out because
+/// git does not track them
+#[derive(Debug, Clone, Default)]
+struct DiscoveredManifests {
+    manifests: Vec<(std::path::PathBuf, BuildSystem)>,
+    untracked: Vec<std::path::PathBuf>,
+}
 
-        // Step 5: Rollback on error
-        if let Err(e) = update_result {
-            // Restore all original contents
-            for (path, content) in original_contents {
-                let _ = fs::write(&path, content); // Best effort rollback
-            }
-            return Err(e);
+/// Result of a cached tree walk (see [`VersionManager::with_detection_cache`])
+type DetectionCache = std::sync::Mutex<Option<DiscoveredManifests>>;
+
+/// Core version management functionality
+#[allow(clippy::struct_excessive_bools)]
+pub struct VersionManager {
+    /// The managed root: build systems are detected here and cascade discovery starts
+    /// here, wherever the version file lives
+    pub base_path: std::path::PathBuf,
+    /// The version filename (e.g. "VERSION" or "version.txt"), relative to `base_path`
+    /// or absolute
+    pub version_file: String,
+    /// Which `pyproject.toml` table is authoritative
+    pub pyproject_source: PyProjectSource,
+    /// Treat a directory with only a VERSION file (no build system files) as valid
+    pub allow_empty: bool,
+    /// Options for cascade manifest discovery
+    pub discovery: DiscoveryOptions,
+    /// Project configuration (normally loaded from `.versioneer.toml`)
+    pub config: Config,
+    /// Replace `$version$` pack-time placeholders instead of refusing to touch them
+    pub overwrite_placeholders: bool,
+    /// Increment the Info.plist `CFBundleVersion` build number whenever its short version
+    /// changes
+    pub bump_bundle_version: bool,
+    /// Cascade writes attempt every file and report all failures at the end instead of
+    /// stopping at the first and rolling back (see [`Self::with_keep_going`])
+    pub keep_going: bool,
+    /// Manifests from the last tree walk, when caching is enabled
+    detection_cache: Option<DetectionCache>,
+}
+
+impl VersionManager {
+    /// Create a new `VersionManager` for the given directory
+    ///
+    /// The version file is the first of [`VERSION_FILE_NAMES`] present in the directory,
+    /// or `VERSION` when there is none, so reads and writes keep going to the file the
+    /// project already has.
+    pub fn new<P: AsRef<Path>>(base_path: P) -> Self {
+        Self {
+            base_path: base_path.as_ref().to_path_buf(),
+            version_file: Self::discover_version_file(base_path.as_ref()).to_string(),
+            pyproject_source: PyProjectSource::default(),
+            allow_empty: false,
+            discovery: DiscoveryOptions::default(),
+            config: Config::default(),
+            overwrite_placeholders: false,
+            bump_bundle_version: false,
+            keep_going: false,
+            detection_cache: None,
         }
+    }
 
-        Ok(())
+    /// The first conventional version filename present in `dir`, or `VERSION`
+    fn discover_version_file(dir: &Path) -> &'static str {
+        VERSION_FILE_NAMES
+            .iter()
+            .copied()
+            .find(|name| dir.join(name).is_file())
+            .unwrap_or(DEFAULT_VERSION_FILE)
     }
 
-    /// Preview sync operation with cascade (dry-run mode)
+    /// Create a new `VersionManager` with a custom version filename
     ///
-    /// # Errors
+    /// Unlike [`Self::new`], no other filenames are tried.
+    pub fn with_version_file<P: AsRef<Path>>(base_path: P, version_file: &str) -> Self {
+        Self {
+            base_path: base_path.as_ref().to_path_buf(),
+            version_file: version_file.to_string(),
+            pyproject_source: PyProjectSource::default(),
+            allow_empty: false,
+            discovery: DiscoveryOptions::default(),
+            config: Config::default(),
+            overwrite_placeholders: false,
+            bump_bundle_version: false,
+            keep_going: false,
+            detection_cache: None,
+        }
+    }
+
+    /// Set which `pyproject.toml` table is authoritative
+    #[must_use]
+    pub const fn with_pyproject_source(mut self, source: PyProjectSource) -> Self {
+        self.pyproject_source = source;
+        self
+    }
+
+    /// Allow operating on just the VERSION file when no build systems are detected
+    #[must_use]
+    pub const fn with_allow_empty(mut self, allow_empty: bool) -> Self {
+        self.allow_empty = allow_empty;
+        self
+    }
+
+    /// Set the options used for cascade manifest discovery
+    #[must_use]
+    pub const fn with_discovery_options(mut self, discovery: DiscoveryOptions) -> Self {
+        self.discovery = discovery;
+        self
+    }
+
+    /// Use the given project configuration
+    #[must_use]
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Replace `$version$` placeholders in `.nuspec` files instead of refusing
+    #[must_use]
+    pub const fn with_overwrite_placeholders(mut self, overwrite: bool) -> Self {
+        self.overwrite_placeholders = overwrite;
+        self
+    }
+
+    /// Increment the Info.plist `CFBundleVersion` whenever `CFBundleShortVersionString` changes
+    #[must_use]
+    pub const fn with_bump_bundle_version(mut self, bump: bool) -> Self {
+        self.bump_bundle_version = bump;
+        self
+    }
+
+    /// Let cascade writes carry on past failing files instead of rolling everything back
     ///
-    /// Returns an error if discovery or version reading fails.
-    pub fn sync_cascade_dry_run(&self) -> Result<DryRunResult> {
-        // Step 1: Discover all manifests
-        let manifests = self.discover_manifests()?;
+    /// By default a cascade is all or nothing: the first failure restores every file it
+    /// wrote. With `keep_going`, every file is attempted, each written whole or not at
+    /// all, and the failures come back together as [`VersioneerError::PartialCascade`].
+    /// The files that succeeded stay written, so the tree can be left partly updated.
+    #[must_use]
+    pub const fn with_keep_going(mut self, keep_going: bool) -> Self {
+        self.keep_going = keep_going;
+        self
+    }
 
-        // Step 2: Read current version
-        let version = self.read_version_file()?;
+    /// Cache the manifests found by [`Self::discover_manifests`] between calls
+    ///
+    /// For long-lived embedders that run several operations on one tree: the first
+    /// discovery walks the tree and later ones reuse its result until
+    /// [`Self::refresh_detection`]. Manifests added in the meantime are not seen, and a
+    /// cached manifest that has since disappeared is an error rather than a stale write.
+    #[must_use]
+    pub fn with_detection_cache(mut self) -> Self {
+        self.detection_cache = Some(DetectionCache::default());
+        self
+    }
 
-        // Step 3: Collect all files that would be updated
-        let mut files_to_update = Vec::new();
-        for (path, _) in manifests {
-            files_to_update.push(path);
+    /// Create a manager for a discovered manifest directory, inheriting this manager's settings
+    fn for_manifest_dir(&self, dir: &Path) -> Self {
+        Self {
+            base_path: dir.to_path_buf(),
+            version_file: self.version_file.clone(),
+            pyproject_source: self.pyproject_source,
+            allow_empty: self.allow_empty,
+            discovery: self.discovery.clone(),
+            config: self.config.clone(),
+            overwrite_placeholders: self.overwrite_placeholders,
+            bump_bundle_version: self.bump_bundle_version,
+            keep_going: self.keep_going,
+            detection_cache: None,
         }
-
-        Ok(DryRunResult {
-            new_version: version,
-            files_to_update,
-        })
     }
 
-    /// Sync all manifests with cascade (update all to match VERSION file)
+    /// Read the current canonical version
+    ///
+    /// This is the VERSION file, the configured environment variable when
+    /// `[version] source = "env"`, or the configured key of a dotenv-style VERSION
+    /// file when `source = "dotenv"` (see [`config::VersionConfig`]).
     ///
     /// # Errors
     ///
-    /// Returns an error if discovery fails or updates fail.
-    /// On error, all changes are rolled back.
-    pub fn sync_cascade(&self) -> Result<()> {
-        use std::collections::HashMap;
-
-        // Step 1: Discover all manifests
-        let manifests = self.discover_manifests()?;
+    /// Returns an error if the version source cannot be read or contains an invalid version format.
+    pub fn read_version_file(&self) -> Result<Version> {
+        if self.config.version.source == config::VersionSource::Env {
+            return Self::read_env_version(&self.config.version.env_var);
+        }
 
-        // Step 2: Read current version
-        let version = self.read_version_file()?;
+        let version_path = self.base_path.join(&self.version_file);
+        let content = trace::read_to_string(&version_path).with_context(|| {
+            if self.version_file == DEFAULT_VERSION_FILE && !version_path.exists() {
+                format!(
+                    "No version file found in {} (tried {})",
+                    self.base_path.display(),
+                    VERSION_FILE_NAMES.join(", ")
+                )
+            } else {
+                format!("Failed to read VERSION file at {}", version_path.display())
+            }
+        })?;
 
-        // Step 3: Read all manifest files into memory for potential rollback
-        let mut original_contents: HashMap<std::path::PathBuf, String> = HashMap::new();
+        if self.config.version.source == config::VersionSource::Dotenv {
+            let key = &self.config.version.key;
+            let span = Self::dotenv_value_span(&content, key)
+                .with_context(|| format!("No {key} key found in {}", version_path.display()))?;
+            let version_str = &content[span];
+            return Version::parse(Self::strip_v_prefix(version_str).1).with_context(|| {
+                format!(
+                    "Invalid version format for {key} in {}: {version_str}",
+                    version_path.display()
+                )
+            });
+        }
 
-        for (path, _) in &manifests {
-            original_contents.insert(path.clone(), fs::read_to_string(path)?);
+        // Strip inline comments (e.g. "1.2.3 # x-release-please-version")
+        let version_str = content.trim().split('#').next().unwrap_or("").trim();
+        let unprefixed = Self::strip_v_prefix(version_str).1;
+        let version = Version::parse(unprefixed).with_context(|| {
+            let hint = Self::without_leading_zeros(unprefixed)
+                .map(|canonical| format!(" (canonical form: {canonical})"))
+                .unwrap_or_default();
+            format!("Invalid version format in VERSION file: {version_str}{hint}")
+        })?;
+        if self.config.version.strict {
+            Self::check_canonical_version_file(&content, &version)?;
         }
+        Ok(version)
+    }
 
-        // Step 4: Perform updates with rollback on error
-        let update_result = (|| -> Result<()> {
-            for (path, system) in &manifests {
-                let manifest_dir = path.parent().context("Manifest has no parent directory")?;
-                let temp_manager = Self::new(manifest_dir);
-                temp_manager
-                    .update_build_system_version(system, &version)
-                    .with_context(|| {
-                        format!("Failed to sync {:?} at {}", system, path.display())
-                    })?;
-            }
-            Ok(())
-        })();
+    /// Reject VERSION file `content` unless it is the canonical form of `version`
+    ///
+    /// Allows an optional `v` prefix, an inline `# comment` after at most one space and
+    /// one trailing line ending, but no other whitespace or blank lines.
+    fn check_canonical_version_file(content: &str, version: &Version) -> Result<()> {
+        let line = content
+            .strip_suffix('\n')
+            .map_or(content, |line| line.strip_suffix('\r').unwrap_or(line));
+        let written = line
+            .split_once('#')
+            .map_or(line, |(text, _)| text.strip_suffix(' ').unwrap_or(text));
+        let canonical = version.to_string();
+        if Self::strip_v_prefix(written).1 != canonical {
+            anyhow::bail!(
+                "Non-canonical version in VERSION file: {written:?} differs from its canonical form {canonical}"
+            );
+        }
+        Ok(())
+    }
 
-        // Step 5: Rollback on error
-        if let Err(e) = update_result {
-            for (path, content) in original_contents {
-                let _ = fs::write(&path, content);
+    /// `text` with leading zeros dropped from each numeric identifier, if that makes it
+    /// a valid version (`01.2.3` → `1.2.3`)
+    fn without_leading_zeros(text: &str) -> Option<Version> {
+        let mut canonical = String::with_capacity(text.len());
+        let mut at_identifier_start = true;
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if at_identifier_start && c == '0' && chars.peek().is_some_and(char::is_ascii_digit) {
+                continue;
             }
-            return Err(e);
+            at_identifier_start = matches!(c, '.' | '-' | '+');
+            canonical.push(c);
         }
-
-        Ok(())
+        Version::parse(&canonical).ok()
     }
 
-    /// Preview reset operation with cascade (dry-run mode)
+    /// Byte range of the value assigned to `key` in dotenv-style `content`
     ///
-    /// # Errors
-    ///
-    /// Returns an error if discovery fails or version is invalid.
-    pub fn reset_cascade_dry_run(&self, version_str: &str) -> Result<DryRunResult> {
-        // Step 1: Parse and validate version
-        let new_version = Version::parse(version_str)
-            .with_context(|| format!("Invalid semantic version format: '{version_str}'"))?;
-
-        // Step 2: Discover all manifests
-        let manifests = self.discover_manifests()?;
+    /// Accepts `KEY=value`, `export KEY=value`, quoted values (`KEY="1.2.3"`) and
+    /// trailing comments (`KEY=1.2.3 # released`). The range excludes quotes and
+    /// surrounding whitespace.
+    fn dotenv_value_span(content: &str, key: &str) -> Option<std::ops::Range<usize>> {
+        let mut line_start = 0;
+        for line in content.split_inclusive('\n') {
+            let start = line_start;
+            line_start += line.len();
+
+            let Some((name, value)) = line.split_once('=') else {
+                continue;
+            };
+            let name = name.trim();
+            let name = name.strip_prefix("export ").map_or(name, str::trim_start);
+            if name != key {
+                continue;
+            }
 
-        // Step 3: Collect all files that would be updated
-        let mut files_to_update = vec![self.base_path.join(&self.version_file)];
-        for (path, _) in manifests {
-            files_to_update.push(path);
+            let value_start = start + line.len() - value.len();
+            let leading = value.len() - value.trim_start().len();
+            let value = value.trim_start();
+            let (offset, value) = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let inner = &value[1..];
+                    (1, &inner[..inner.find(quote)?])
+                }
+                _ => (0, value.split('#').next().unwrap_or("").trim_end()),
+            };
+            let begin = value_start + leading + offset;
+            return Some(begin..begin + value.len());
         }
+        None
+    }
 
-        Ok(DryRunResult {
-            new_version,
-            files_to_update,
-        })
+    /// Split an optional leading `v` or `V` (as in `v1.2.3`) from a version string
+    fn strip_v_prefix(version_str: &str) -> (Option<char>, &str) {
+        match version_str.chars().next() {
+            Some(prefix @ ('v' | 'V')) => (Some(prefix), &version_str[1..]),
+            _ => (None, version_str),
+        }
     }
 
-    /// Reset version with cascade (reset all discovered manifests)
+    /// Write a version to the VERSION file
+    ///
+    /// With an environment variable version source this does nothing unless
+    /// `write_file` is set. Returns whether the file changed; a file that already
+    /// holds the version is not rewritten.
     ///
     /// # Errors
     ///
-    /// Returns an error if discovery fails, version is invalid, or updates fail.
-    /// On error, all changes are rolled back.
-    pub fn reset_cascade(&self, version_str: &str) -> Result<()> {
-        use std::collections::HashMap;
+    /// Returns an error if the VERSION file cannot be written to.
+    pub fn write_version_file(&self, version: &Version) -> Result<bool> {
+        if !self.writes_version_file() {
+            return Ok(false);
+        }
+        let version_path = self.base_path.join(&self.version_file);
+        let existing = trace::read_to_string(&version_path).unwrap_or_default();
+
+        if self.config.version.source == config::VersionSource::Dotenv {
+            let key = &self.config.version.key;
+            let span = Self::dotenv_value_span(&existing, key)
+                .with_context(|| format!("No {key} key found in {}", version_path.display()))?;
+            let version = self.with_v_prefix(&existing[span.clone()], version);
+            let content = format!(
+                "{}{version}{}",
+                &existing[..span.start],
+                &existing[span.end..]
+            );
+            return Self::write_if_changed(&version_path, &existing, &content).with_context(|| {
+                format!("Failed to write VERSION file at {}", version_path.display())
+            });
+        }
 
-        // Step 1: Parse and validate version
-        let new_version = Version::parse(version_str)
-            .with_context(|| format!("Invalid semantic version format: '{version_str}'"))?;
+        let trimmed = existing.trim();
 
-        // Step 2: Discover all manifests
-        let manifests = self.discover_manifests()?;
+        let version = self.with_v_prefix(trimmed, version);
 
-        // Step 3: Read all files into memory for potential rollback
-        let mut original_contents: HashMap<std::path::PathBuf, String> = HashMap::new();
+        // Preserve inline comments (e.g. "# x-release-please-version")
+        let content = trimmed.find('#').map_or_else(
+            || format!("{version}\n"),
+            |hash_pos| {
+                let comment = trimmed[hash_pos..].trim();
+                format!("{version} {comment}\n")
+            },
+        );
 
-        let version_path = self.base_path.join(&self.version_file);
-        original_contents.insert(version_path.clone(), fs::read_to_string(&version_path)?);
+        Self::write_if_changed(&version_path, &existing, &content)
+            .with_context(|| format!("Failed to write VERSION file at {}", version_path.display()))
+    }
 
-        for (path, _) in &manifests {
-            original_contents.insert(path.clone(), fs::read_to_string(path)?);
-        }
+    /// Render `version` with the `v` prefix `existing` uses, unless configured otherwise
+    fn with_v_prefix(&self, existing: &str, version: &Version) -> String {
+        let prefix = match (
+            self.config.version.v_prefix,
+            Self::strip_v_prefix(existing).0,
+        ) {
+            (Some(false), _) => None,
+            (Some(true), existing) => Some(existing.unwrap_or('v')),
+            (None, existing) => existing,
+        };
+        prefix.map_or_else(|| version.to_string(), |p| format!("{p}{version}"))
+    }
 
-        // Step 4: Perform updates with rollback on error
-        let update_result = (|| -> Result<()> {
-            self.write_version_file(&new_version)?;
+    /// Whether the VERSION file is written when the version changes
+    #[must_use]
+    pub fn writes_version_file(&self) -> bool {
+        self.config.version.source != config::VersionSource::Env || self.config.version.write_file
+    }
 
-            for (path, system) in &manifests {
-                let manifest_dir = path.parent().context("Manifest has no parent directory")?;
-                let temp_manager = Self::new(manifest_dir);
-                temp_manager
-                    .update_build_system_version(system, &new_version)
-                    .with_context(|| {
-                        format!("Failed to reset {:?} at {}", system, path.display())
-                    })?;
-            }
-            Ok(())
-        })();
+    /// Read and parse a version from an environment variable
+    fn read_env_version(env_var: &str) -> Result<Version> {
+        let value = std::env::var(env_var)
+            .with_context(|| format!("Environment variable {env_var} is not set"))?;
+        Version::parse(Self::strip_v_prefix(value.trim()).1)
+            .with_context(|| format!("Invalid version format in {env_var}: {value}"))
+    }
 
-        // Step 5: Rollback on error
-        if let Err(e) = update_result {
-            for (path, content) in original_contents {
-                let _ = fs::write(&path, content);
-            }
-            return Err(e);
+    /// The VERSION file, if versions are written to it, and the configured Python
+    /// modules and JSON files, as a list of files to update
+    fn version_file_targets(&self) -> Vec<std::path::PathBuf> {
+        let mut targets = Vec::new();
+        if self.writes_version_file() {
+            targets.push(self.version_file_path());
         }
-
-        Ok(())
+        targets.extend(self.python_module_paths());
+        targets.extend(self.json_file_paths());
+        targets
     }
 
-    /// Discover all manifest files recursively in subdirectories
+    /// Snapshot every file a bump, sync or reset may write, so it can be undone
     ///
-    /// Respects .gitignore patterns. Errors if nested VERSION files are found.
+    /// Covers the VERSION file (when written), the top-level manifests (or every
+    /// discovered manifest with `cascade`), the files dynamic pyproject.toml versions
+    /// point at, and configured Python modules and JSON files.
     ///
     /// # Errors
     ///
-    /// Returns an error if directory traversal fails or nested VERSION files are found.
-    pub fn discover_manifests(&self) -> Result<Vec<(std::path::PathBuf, BuildSystem)>> {
-        use ignore::WalkBuilder;
-
-        let mut manifests = Vec::new();
-
-        // Use ignore crate to respect .gitignore
-        let walker = WalkBuilder::new(&self.base_path)
-            .hidden(false)
-            .git_ignore(true)
-            .build();
-
-        for entry in walker {
-            let entry = entry?;
-            let path = entry.path();
+    /// Returns an error if manifest discovery fails or a file exists but cannot be read.
+    pub fn snapshot_write_targets(&self, cascade: bool) -> Result<WriteSnapshot> {
+        WriteSnapshot::take(self.write_targets(cascade)?)
+    }
 
-            // Check for symlinks (error condition)
-            let metadata = fs::symlink_metadata(path)?;
-            if metadata.is_symlink() {
-                anyhow::bail!(
-                    "Symlink found at {}. Symlinks are not supported in cascade mode.",
-                    path.display()
-                );
+    /// Every file a bump, sync or reset may write (see [`Self::snapshot_write_targets`])
+    fn write_targets(&self, cascade: bool) -> Result<Vec<std::path::PathBuf>> {
+        let manifests = if cascade {
+            self.discover_manifests()?
+        } else {
+            self.detect_manifests()
+        };
+        let mut paths = self.version_file_targets();
+        for (path, system) in manifests {
+            if system == BuildSystem::PyProject {
+                paths.extend(self.pyproject_dynamic_path(&path));
             }
+            paths.push(path);
+        }
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
+    }
 
-            if path.is_file() {
-                if let Some(filename) = path.file_name() {
-                    let filename_str = filename.to_string_lossy();
-
-                    // Check for nested version files
-                    if filename_str == self.version_file {
-                        // Version file in base_path is OK, but not in subdirectories
-                        if path.parent() != Some(&self.base_path) {
-                            anyhow::bail!(
-                                "Nested {} file found at {}. Only one {} file is allowed at the root directory.",
-                                self.version_file,
-                                path.display(),
-                                self.version_file,
-                            );
-                        }
-                    } else if filename_str == "Cargo.toml" {
-                        manifests.push((path.to_path_buf(), BuildSystem::Cargo));
-                    } else if filename_str == "pyproject.toml" {
-                        manifests.push((path.to_path_buf(), BuildSystem::PyProject));
-                    } else if filename_str == "package.json" {
-                        manifests.push((path.to_path_buf(), BuildSystem::PackageJson));
-                    }
-                }
+    /// The file a dynamic pyproject.toml at `path` keeps its version in, if any
+    fn pyproject_dynamic_path(&self, path: &Path) -> Option<std::path::PathBuf> {
+        let doc: toml::Value = toml::from_str(&trace::read_to_string(path).ok()?).ok()?;
+        let manager = self.for_manifest_dir(path.parent()?);
+        match manager.pyproject_dynamic_source(&doc).ok().flatten()? {
+            DynamicVersionSource::Attr { path, .. } | DynamicVersionSource::File(path) => {
+                Some(path)
             }
         }
+    }
 
-        Ok(manifests)
+    /// Paths of the configured Python modules
+    fn python_module_paths(&self) -> impl Iterator<Item = std::path::PathBuf> + '_ {
+        self.config
+            .python_modules
+            .iter()
+            .map(|module| self.base_path.join(&module.path))
     }
 
-    /// Count existing RC tags for a given base version.
-    ///
-    /// Queries git for tags matching `v{major}.{minor}.{patch}-rc.*`
-    /// and returns the highest RC number found, or 0 if none exist.
-    fn count_rc_tags(&self, base_version: &Version) -> Result<u64> {
-        let pattern = format!(
-            "v{}.{}.{}-rc.*",
-            base_version.major, base_version.minor, base_version.patch
-        );
+    /// Read the version assigned to a configured Python module's variable
+    fn read_python_module_version(&self, module: &config::PythonModuleConfig) -> Result<Version> {
+        let path = self.base_path.join(&module.path);
+        let version_str = Self::read_dynamic_version(&DynamicVersionSource::Attr {
+            path,
+            name: module.variable.clone(),
+        })?;
+        Self::parse_pep440_version(&version_str).with_context(|| {
+            format!(
+                "Invalid version format for {} in {}: {version_str}",
+                module.variable, module.path
+            )
+        })
+    }
 
-        let output = std::process::Command::new("git")
-            .args(["tag", "-l", &pattern])
-            .current_dir(&self.base_path)
-            .output()
-            .context("Failed to run git. Is git installed and is this a git repository?")?;
+    /// Write `version` to one configured Python module, returning whether it changed
+    fn update_python_module(
+        &self,
+        module: &config::PythonModuleConfig,
+        version: &Version,
+    ) -> Result<bool> {
+        let formatted = self.format_pyproject_version(version)?;
+        Self::update_dynamic_version(
+            &DynamicVersionSource::Attr {
+                path: self.base_path.join(&module.path),
+                name: module.variable.clone(),
+            },
+            &formatted,
+        )
+    }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("git tag failed: {stderr}");
+    /// Write `version` to every configured Python module and JSON file, returning
+    /// whether any changed
+    fn update_configured_files(&self, version: &Version) -> Result<bool> {
+        let mut changed = false;
+        for module in &self.config.python_modules {
+            changed |= self.update_python_module(module, version)?;
         }
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut max_rc: u64 = 0;
-
-        for line in stdout.lines() {
-            let line = line.trim();
-            if let Some(rc_part) = line.rsplit("-rc.").next() {
-                if let Ok(n) = rc_part.parse::<u64>() {
-                    max_rc = max_rc.max(n);
-                }
-            }
+        for file in &self.config.json_files {
+            changed |= self.update_json_file(file, version)?;
         }
+        Ok(changed)
+    }
 
-        Ok(max_rc)
+    /// Paths of the configured JSON files
+    fn json_file_paths(&self) -> impl Iterator<Item = std::path::PathBuf> + '_ {
+        self.config
+            .json_files
+            .iter()
+            .map(|file| self.base_path.join(&file.path))
     }
 
-    /// Compute the next RC version from the VERSION file and git tags.
+    /// Read the version from a configured JSON file's version field
+    fn read_json_file_version(&self, file: &config::JsonFileConfig) -> Result<Version> {
+        let path = self.base_path.join(&file.path);
+        let content = trace::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let span = Self::json_file_version_span(&content, file)?;
+        let version_str = &content[span];
+        Version::parse(version_str).with_context(|| {
+            format!(
+                "Invalid version format for {} in {}: {version_str}",
+                file.key, file.path
+            )
+        })
+    }
+
+    /// Write `version` to a configured JSON file's version field, returning whether it
+    /// changed
     ///
-    /// Reads VERSION (must be a clean M.m.p without pre-release suffix),
-    /// counts existing RC tags, and returns the next RC version.
+    /// Only the string value is replaced, so the rest of the document keeps its layout.
+    fn update_json_file(&self, file: &config::JsonFileConfig, version: &Version) -> Result<bool> {
+        let path = self.base_path.join(&file.path);
+        let content = trace::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let span = Self::json_file_version_span(&content, file)?;
+        let updated_content = format!(
+            "{}{version}{}",
+            &content[..span.start],
+            &content[span.end..]
+        );
+        let updated_content = self.finish_json(&content, &updated_content);
+        Self::validate_json(&updated_content).with_context(|| {
+            format!(
+                "Refusing to write {}: the updated content is no longer valid",
+                path.display()
+            )
+        })?;
+
+        Self::write_if_changed(&path, &content, &updated_content)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Byte range of the string at a configured JSON file's version field, erroring if
+    /// the field is missing or not a string
+    fn json_file_version_span(
+        content: &str,
+        file: &config::JsonFileConfig,
+    ) -> Result<std::ops::Range<usize>> {
+        let json: serde_json::Value = serde_json::from_str(content)
+            .with_context(|| format!("Failed to parse {}", file.path))?;
+        let value = json
+            .pointer(&file.pointer())
+            .with_context(|| format!("No {} found in {}", file.key, file.path))?;
+        if !value.is_string() {
+            anyhow::bail!("{} in {} is not a string: {value}", file.key, file.path);
+        }
+        let segments = file.segments();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        Self::json_string_span(content, &segments).with_context(|| {
+            format!(
+                "Cannot locate {} in {}: only string fields reached through object keys written without escapes are supported",
+                file.key, file.path
+            )
+        })
+    }
+
+    /// Detect which build system files are present
     ///
-    /// # Errors
+    /// Build systems in [`config::Config::ignore_systems`] are never detected. A
+    /// `[[manifest_overrides]]` entry that exists counts for its forced build system.
+    #[must_use]
+    pub fn detect_build_systems(&self) -> Vec<BuildSystem> {
+        self.managed_systems()
+            .filter(|system| {
+                !system.format().manifests(self).is_empty()
+                    || self
+                        .configured_manifests()
+                        .any(|(path, configured)| configured == *system && path.is_file())
+            })
+            .collect()
+    }
+
+    /// Build systems not excluded by [`config::Config::ignore_systems`]
+    fn managed_systems(&self) -> impl Iterator<Item = BuildSystem> + '_ {
+        BuildSystem::ALL
+            .into_iter()
+            .filter(|system| !self.config.ignore_systems.contains(system))
+    }
+
+    /// Manifests in the base directory whose build system is ignored
     ///
-    /// Returns an error if the VERSION file cannot be read, contains a pre-release
-    /// suffix, or if git tag querying fails.
-    pub fn next_rc_version(&self) -> Result<Version> {
-        let base_version = self.read_version_file()?;
+    /// These are present but left alone because of [`config::Config::ignore_systems`].
+    #[must_use]
+    pub fn ignored_manifests(&self) -> Vec<(std::path::PathBuf, BuildSystem)> {
+        self.config
+            .ignore_systems
+            .iter()
+            .flat_map(|system| {
+                system
+                    .format()
+                    .manifests(self)
+                    .into_iter()
+                    .map(move |path| (path, system.clone()))
+            })
+            .collect()
+    }
 
-        if !base_version.pre.is_empty() {
-            anyhow::bail!(
-                "VERSION must be a clean M.m.p version without pre-release suffix, got: {base_version}"
-            );
+    /// Detect every build system manifest in the base directory, with its path
+    ///
+    /// Unlike [`Self::detect_build_systems`], a build system that allows several
+    /// manifests per directory (see [`BuildSystem::allows_multiple`]) appears once per
+    /// file, so two `.nuspec` files are both listed. `[[manifest_overrides]]` entries
+    /// follow, whether or not they exist, so a missing one is reported rather than
+    /// passed over.
+    #[must_use]
+    pub fn detect_manifests(&self) -> Vec<(std::path::PathBuf, BuildSystem)> {
+        let mut manifests: Vec<_> = self
+            .managed_systems()
+            .flat_map(|system| {
+                system
+                    .format()
+                    .manifests(self)
+                    .into_iter()
+                    .map(move |path| (path, system.clone()))
+            })
+            .collect();
+        for (path, system) in self.configured_manifests() {
+            if !manifests.iter().any(|(detected, _)| *detected == path) {
+                manifests.push((path, system));
+            }
         }
+        manifests
+    }
 
-        let max_rc = self.count_rc_tags(&base_version)?;
-        let mut rc_version = base_version;
-        rc_version.pre = semver::Prerelease::new(&format!("rc.{}", max_rc + 1))
-            .context("Failed to construct pre-release version")?;
+    /// The `[[manifest_overrides]]` entries with their forced build systems, skipping
+    /// ignored systems (see [`config::ManifestOverride`])
+    fn configured_manifests(&self) -> impl Iterator<Item = (std::path::PathBuf, BuildSystem)> + '_ {
+        self.config
+            .manifest_overrides
+            .iter()
+            .filter(|manifest| !self.config.ignore_systems.contains(&manifest.format))
+            .map(|manifest| (self.base_path.join(&manifest.path), manifest.format.clone()))
+    }
 
-        Ok(rc_version)
+    /// The build system managing the manifest at `path`, or `None` for unrelated files
+    ///
+    /// Like the free [`classify_manifest`], but recognizes the configured Terraform
+    /// file (see [`config::TerraformConfig`]) and `[[manifest_overrides]]` entries, whose
+    /// format is forced whatever their filename.
+    #[must_use]
+    pub fn classify_manifest(&self, path: &Path) -> Option<BuildSystem> {
+        self.configured_manifests()
+            .find(|(configured, _)| configured == path)
+            .map(|(_, system)| system)
+            .or_else(|| classify_manifest_with(path, &self.config))
     }
 
-    /// Read version from Cargo.toml
-    fn read_cargo_version(&self) -> Result<Version> {
-        let cargo_path = self.base_path.join("Cargo.toml");
-        let content = fs::read_to_string(&cargo_path)
-            .with_context(|| format!("Failed to read Cargo.toml at {}", cargo_path.display()))?;
+    /// Path to the VERSION file managed by this instance
+    #[must_use]
+    pub fn version_file_path(&self) -> std::path::PathBuf {
+        self.base_path.join(&self.version_file)
+    }
 
-        let cargo_toml: toml::Value =
-            toml::from_str(&content).with_context(|| "Failed to parse Cargo.toml")?;
+    /// Whether the version file lives outside the managed root ([`Self::base_path`])
+    ///
+    /// Build systems are still detected under the root and compared against it.
+    #[must_use]
+    pub fn version_file_outside_root(&self) -> bool {
+        !self.version_file_path().starts_with(&self.base_path)
+    }
 
-        let version_str = cargo_toml
-            .get("package")
-            .and_then(|p| p.get("version"))
-            .and_then(|v| v.as_str())
-            .context("No version found in Cargo.toml [package] section")?;
+    /// Path to the manifest file for a build system in the base directory
+    ///
+    /// For [`BuildSystem::Nuspec`] and [`BuildSystem::Gemspec`] this is the first such
+    /// file found, if any.
+    #[must_use]
+    pub fn manifest_path(&self, system: &BuildSystem) -> std::path::PathBuf {
+        system
+            .format()
+            .manifests(self)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| match system {
+                BuildSystem::Terraform => self.base_path.join(&self.config.terraform.file),
+                _ => self.base_path.join(system.manifest_name()),
+            })
+    }
 
-        Version::parse(version_str)
-            .with_context(|| format!("Invalid version format in Cargo.toml: {version_str}"))
+    /// The manifest of `system` in the base directory, erroring when a build system
+    /// that allows several per directory has none or more than one
+    fn single_manifest_path(&self, system: &BuildSystem) -> Result<std::path::PathBuf> {
+        if !system.allows_multiple() {
+            return Ok(self.manifest_path(system));
+        }
+        let mut files = system.format().manifests(self);
+        match files.len() {
+            0 => Err(system.format().missing_manifest(&self.base_path)),
+            1 => Ok(files.remove(0)),
+            _ => anyhow::bail!(
+                "Multiple {} files found in {}: {}",
+                system.manifest_name().trim_start_matches('*'),
+                self.base_path.display(),
+                files
+                    .iter()
+                    .filter_map(|f| f.file_name())
+                    .map(|f| f.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
     }
 
-    /// Update version in Cargo.toml
-    fn update_cargo_version(&self, version: &Version) -> Result<()> {
-        let cargo_path = self.base_path.join("Cargo.toml");
-        let content = fs::read_to_string(&cargo_path)
-            .with_context(|| format!("Failed to read Cargo.toml at {}", cargo_path.display()))?;
+    /// Read version from a specific build system file
+    ///
+    /// `manifest` names the file, e.g. one found by [`Self::detect_manifests`] or cascade
+    /// discovery. Without it the build system's manifest in the base directory is used,
+    /// which is an error when several exist (see [`BuildSystem::allows_multiple`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the build system file cannot be read or parsed.
+    pub fn read_build_system_version(
+        &self,
+        system: &BuildSystem,
+        manifest: Option<&Path>,
+    ) -> Result<Version> {
+        let Some(path) = manifest else {
+            return system
+                .format()
+                .read_file(self, &self.single_manifest_path(system)?);
+        };
+        let manifest_dir = path.parent().context("Manifest has no parent directory")?;
+        system
+            .format()
+            .read_file(&self.for_manifest_dir(manifest_dir), path)
+    }
 
-        let updated_content = Self::update_toml_version(&content, version, "package")?;
+    /// Update version in a specific build system file
+    ///
+    /// `manifest` selects the file as for [`Self::read_build_system_version`]. Returns
+    /// whether the file changed; a file that already holds the version is not written,
+    /// so its modification time is kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the build system file cannot be read, parsed, or written.
+    pub fn update_build_system_version(
+        &self,
+        system: &BuildSystem,
+        manifest: Option<&Path>,
+        version: &Version,
+    ) -> Result<bool> {
+        let stripped;
+        let version = if self.strips_build_metadata(system) {
+            stripped = Self::without_build_metadata(version);
+            &stripped
+        } else {
+            version
+        };
+        let Some(path) = manifest else {
+            return system
+                .format()
+                .update_file(self, &self.single_manifest_path(system)?, version);
+        };
+        let manifest_dir = path.parent().context("Manifest has no parent directory")?;
+        system
+            .format()
+            .update_file(&self.for_manifest_dir(manifest_dir), path, version)
+    }
 
-        fs::write(&cargo_path, updated_content)
-            .with_context(|| format!("Failed to write Cargo.toml at {}", cargo_path.display()))
+    /// Write `version` to the manifest at `path`, bypassing detection
+    ///
+    /// The build system is inferred with [`Self::classify_manifest`]; a relative `path`
+    /// is resolved against the base path. The file is left alone if it already holds
+    /// the version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` is not a recognized manifest, has no version field, or
+    /// cannot be read, parsed, or written.
+    pub fn set_build_system_version_by_path(&self, path: &Path, version: &Version) -> Result<()> {
+        let path = self.base_path.join(path);
+        let system = self
+            .classify_manifest(&path)
+            .with_context(|| format!("{} is not a recognized manifest", path.display()))?;
+        // Refuse files without a version field rather than inserting one
+        self.read_build_system_version(&system, Some(&path))
+            .with_context(|| format!("Failed to read {system:?} version"))?;
+        self.update_build_system_version(&system, Some(&path), version)
+            .with_context(|| format!("Failed to update {system:?} version"))?;
+        Ok(())
     }
 
-    /// Read version from pyproject.toml
-    fn read_pyproject_version(&self) -> Result<Version> {
-        let pyproject_path = self.base_path.join("pyproject.toml");
-        let content = fs::read_to_string(&pyproject_path).with_context(|| {
-            format!(
-                "Failed to read pyproject.toml at {}",
-                pyproject_path.display()
-            )
-        })?;
+    /// Whether build metadata is dropped when writing to this build system
+    ///
+    /// See [`config::BuildMetadataConfig`].
+    #[must_use]
+    pub fn strips_build_metadata(&self, system: &BuildSystem) -> bool {
+        self.config.build_metadata.strip.contains(system)
+    }
 
-        let pyproject_toml: toml::Value =
-            toml::from_str(&content).with_context(|| "Failed to parse pyproject.toml")?;
+    /// Whether a version read from `system` matches the VERSION file version
+    ///
+    /// Build metadata is ignored for systems that strip it on write. With
+    /// [`config::VerifyConfig::ignore_prerelease`] only `major.minor.patch` are compared.
+    #[must_use]
+    pub fn version_matches(
+        &self,
+        system: &BuildSystem,
+        found: &Version,
+        expected: &Version,
+    ) -> bool {
+        if self.config.verify.ignore_prerelease {
+            (found.major, found.minor, found.patch)
+                == (expected.major, expected.minor, expected.patch)
+        } else if self.strips_build_metadata(system) {
+            Self::without_build_metadata(found) == Self::without_build_metadata(expected)
+        } else {
+            found == expected
+        }
+    }
 
-        let version_str = pyproject_toml
-            .get("project")
-            .and_then(|p| p.get("version"))
-            .and_then(|v| v.as_str())
-            .context("No version found in pyproject.toml [project] section")?;
+    fn without_build_metadata(version: &Version) -> Version {
+        let mut version = version.clone();
+        version.build = semver::BuildMetadata::EMPTY;
+        version
+    }
 
-        Version::parse(version_str)
-            .with_context(|| format!("Invalid version format in pyproject.toml: {version_str}"))
+    /// Whether a build system file inherits its version from elsewhere
+    ///
+    /// Cargo members declaring `version.workspace = true` (or the inline
+    /// `version = { workspace = true }`) take their version from the workspace
+    /// root. Such files are skipped by verification and never rewritten.
+    #[must_use]
+    pub fn build_system_inherits_version(&self, system: &BuildSystem) -> bool {
+        trace::read_to_string(self.manifest_path(system))
+            .is_ok_and(|content| system.format().inherits_version(&content))
     }
 
-    /// Update version in pyproject.toml
-    fn update_pyproject_version(&self, version: &Version) -> Result<()> {
-        let pyproject_path = self.base_path.join("pyproject.toml");
-        let content = fs::read_to_string(&pyproject_path).with_context(|| {
-            format!(
-                "Failed to read pyproject.toml at {}",
-                pyproject_path.display()
-            )
-        })?;
+    /// Whether a build system file is a virtual Cargo workspace root with no version
+    ///
+    /// A `Cargo.toml` with `[workspace]` but no `[package]` only carries a version if it
+    /// declares `[workspace.package] version`; otherwise there is nothing to read or write.
+    #[must_use]
+    pub fn build_system_is_virtual_workspace(&self, system: &BuildSystem) -> bool {
+        *system == BuildSystem::Cargo
+            && trace::read_to_string(self.base_path.join("Cargo.toml"))
+                .is_ok_and(|content| Self::cargo_is_unversioned_virtual_workspace(&content))
+    }
 
-        let updated_content = Self::update_toml_version(&content, version, "project")?;
+    /// Whether a build system file has no version of its own to verify or rewrite
+    ///
+    /// True for files that inherit their version (see [`Self::build_system_inherits_version`]),
+    /// for unversioned virtual workspace roots
+    /// (see [`Self::build_system_is_virtual_workspace`]), and for `.nuspec` files holding
+    /// the `$version$` pack-time placeholder unless [`Self::overwrite_placeholders`] is set.
+    #[must_use]
+    pub fn build_system_has_no_version(&self, system: &BuildSystem) -> bool {
+        self.manifest_has_no_version(&self.manifest_path(system), system)
+    }
 
-        fs::write(&pyproject_path, updated_content).with_context(|| {
-            format!(
-                "Failed to write pyproject.toml at {}",
-                pyproject_path.display()
-            )
+    /// Whether the manifest at `path` has no version of its own (see
+    /// [`Self::build_system_has_no_version`])
+    ///
+    /// Reads `path` itself, so a manifest with a nonstandard name (see
+    /// [`config::ManifestOverride`]) is judged by its own content. A pack-time
+    /// placeholder counts as no version unless [`Self::overwrite_placeholders`] is set.
+    fn manifest_has_no_version(&self, path: &Path, system: &BuildSystem) -> bool {
+        trace::read_to_string(path).is_ok_and(|content| {
+            system.format().inherits_version(&content)
+                || (*system == BuildSystem::Cargo
+                    && Self::cargo_is_unversioned_virtual_workspace(&content))
+                || (!self.overwrite_placeholders && system.format().holds_placeholder(&content))
         })
     }
 
-    /// Read version from package.json
-    fn read_package_json_version(&self) -> Result<Version> {
-        let package_json_path = self.base_path.join("package.json");
-        let content = fs::read_to_string(&package_json_path).with_context(|| {
-            format!(
-                "Failed to read package.json at {}",
-                package_json_path.display()
-            )
-        })?;
+    /// Bump version according to semantic versioning rules
+    ///
+    /// Returns whether any file changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if version files are not synchronized or cannot be updated.
+    pub fn bump_version(&self, bump_type: BumpType) -> Result<bool> {
+        // Ensure all versions are in sync before bumping
+        self.verify_versions_in_sync()?;
+
+        let current_version = self.read_version_file()?;
+        let new_version = bump_type.apply(&current_version);
+        self.write_all_versions(&new_version)
+    }
 
-        let json: serde_json::Value =
-            serde_json::from_str(&content).with_context(|| "Failed to parse package.json")?;
+    /// Write `version` to the VERSION file and every detected build system file
+    ///
+    /// Returns whether any file changed.
+    fn write_all_versions(&self, version: &Version) -> Result<bool> {
+        // Update VERSION file
+        let mut changed = self.write_version_file(version)?;
 
-        let version_str = json
-            .get("version")
-            .and_then(|v| v.as_str())
-            .context("No version found in package.json")?;
+        // Update all detected build system files that carry a version of their own
+        for (path, system) in &self.detect_manifests() {
+            if self.manifest_has_no_version(path, system) {
+                continue;
+            }
+            changed |= self
+                .update_build_system_version(system, Some(path), version)
+                .with_context(|| format!("Failed to update {} version", system.describe(path)))?;
+        }
+        changed |= self.update_configured_files(version)?;
 
-        Version::parse(version_str)
-            .with_context(|| format!("Invalid version format in package.json: {version_str}"))
+        Ok(changed)
     }
 
-    /// Update version in package.json
-    fn update_package_json_version(&self, version: &Version) -> Result<()> {
-        let package_json_path = self.base_path.join("package.json");
-        let content = fs::read_to_string(&package_json_path).with_context(|| {
-            format!(
-                "Failed to read package.json at {}",
-                package_json_path.display()
-            )
-        })?;
+    /// The current VERSION file's prerelease, to carry over to a `reset` target
+    ///
+    /// With `reset_counter`, a trailing numeric identifier restarts at 1
+    /// (`beta.4` becomes `beta.1`). Returns `None` when the current version has no
+    /// prerelease.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the VERSION file cannot be read.
+    pub fn carried_prerelease(&self, reset_counter: bool) -> Result<Option<semver::Prerelease>> {
+        let current = self.read_version_file()?;
+        if current.pre.is_empty() {
+            return Ok(None);
+        }
+        if !reset_counter {
+            return Ok(Some(current.pre));
+        }
 
-        let mut json: serde_json::Value =
-            serde_json::from_str(&content).with_context(|| "Failed to parse package.json")?;
+        let mut identifiers: Vec<&str> = current.pre.split('.').collect();
+        if let Some(last) = identifiers
+            .last_mut()
+            .filter(|last| last.parse::<u64>().is_ok())
+        {
+            *last = "1";
+        }
+        let pre = identifiers.join(".");
+        Ok(Some(
+            semver::Prerelease::new(&pre).with_context(|| format!("Invalid prerelease '{pre}'"))?,
+        ))
+    }
 
-        // Update the version field
-        if let Some(obj) = json.as_object_mut() {
-            obj.insert(
-                "version".to_string(),
-                serde_json::Value::String(version.to_string()),
+    /// Check a `reset` target against the configured bounds (see [`config::ResetConfig`])
+    ///
+    /// A target equal to a bound is allowed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version is below the minimum or above the maximum.
+    pub fn check_version_bounds(&self, version: &Version) -> Result<()> {
+        let bounds = &self.config.reset;
+        if let Some(min) = bounds.min_version.as_ref().filter(|min| version < *min) {
+            anyhow::bail!(
+                "Version {version} is below the minimum allowed version {min} (--min-version or [reset] min_version)"
             );
-        } else {
-            anyhow::bail!("package.json root is not a JSON object");
         }
+        if let Some(max) = bounds.max_version.as_ref().filter(|max| version > *max) {
+            anyhow::bail!(
+                "Version {version} is above the maximum allowed version {max} (--max-version or [reset] max_version)"
+            );
+        }
+        Ok(())
+    }
 
-        // Serialize with pretty printing (2-space indent, standard for Node.js)
-        let updated_content = serde_json::to_string_pretty(&json)
-            .with_context(|| "Failed to serialize package.json")?;
-
-        // Add trailing newline (Node.js convention)
-        let updated_content = format!("{updated_content}\n");
-
-        fs::write(&package_json_path, updated_content).with_context(|| {
-            format!(
-                "Failed to write package.json at {}",
-                package_json_path.display()
-            )
-        })
+    /// Parse a `reset` target and check it against the configured bounds
+    fn parse_reset_target(&self, version_str: &str) -> Result<Version> {
+        let version = Version::parse(version_str)
+            .with_context(|| format!("Invalid semantic version format: '{version_str}'"))?;
+        self.check_version_bounds(&version)?;
+        Ok(version)
     }
 
-    /// Helper to update version in TOML content
-    fn update_toml_version(content: &str, version: &Version, section: &str) -> Result<String> {
-        use regex::Regex;
+    /// Reset the version to a specific version string
+    ///
+    /// Returns whether any file changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version string is invalid or outside the configured
+    /// bounds, or if file operations fail.
+    pub fn reset_version(&self, version_str: &str) -> Result<bool> {
+        let new_version = self.parse_reset_target(version_str)?;
+        self.write_all_versions(&new_version)
+    }
 
-        // More flexible regex that handles multiline TOML sections with better whitespace handling
-        let pattern = format!(r#"(?s)(\[{section}\][^\[]*?version\s*=\s*")[^"]*(")"#);
-        let re = Regex::new(&pattern).context("Failed to create regex for version replacement")?;
+    /// Verify that all version files are synchronized
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if version files are not synchronized or cannot be read.
+    pub fn verify_versions_in_sync(&self) -> Result<()> {
+        let issues = self.sync_issues()?;
 
-        // Check if the regex can find a match at all
-        if !re.is_match(content) {
-            anyhow::bail!("No version field found in [{section}] section");
+        if !issues.is_empty() {
+            return Err(VersioneerError::OutOfSync(issues).into());
         }
 
-        let result = re.replace(content, format!("${{1}}{version}${{2}}"));
+        Ok(())
+    }
 
-        Ok(result.to_string())
+    /// Compare every detected build system against the VERSION file
+    ///
+    /// Returns one [`SyncIssue`] per build system that drifted or could not be read;
+    /// an empty list means everything is in sync.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the VERSION file cannot be read.
+    pub fn sync_issues(&self) -> Result<Vec<SyncIssue>> {
+        Ok(self.version_report()?.issues())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::TempDir;
+    /// Read the VERSION file and every detected build system once
+    ///
+    /// Each manifest is read and parsed a single time, even when several facts
+    /// (inheritance, workspace shape, version) are needed from it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the VERSION file cannot be read.
+    pub fn version_report(&self) -> Result<VersionReport> {
+        let version = self.read_version_file()?;
+        let systems = self
+            .detect_manifests()
+            .into_iter()
+            .map(|(path, system)| {
+                let state = self.manifest_version(&system, &path, &version);
+                (system, path, state)
+            })
+            .collect();
+        let modules = self
+            .config
+            .python_modules
+            .iter()
+            .map(|module| {
+                let state = match self.read_python_module_version(module) {
+                    Ok(found) => ManifestVersion::Declared {
+                        in_sync: self.version_matches(&BuildSystem::PyProject, &found, &version),
+                        version: found,
+                    },
+                    Err(e) => ManifestVersion::Unreadable(format!("{e:#}")),
+                };
+                (module.clone(), state)
+            })
+            .collect();
+        let json_files = self
+            .config
+            .json_files
+            .iter()
+            .map(|file| {
+                let state = match self.read_json_file_version(file) {
+                    Ok(found) => ManifestVersion::Declared {
+                        in_sync: self.version_matches(&BuildSystem::PackageJson, &found, &version),
+                        version: found,
+                    },
+                    Err(e) => ManifestVersion::Unreadable(format!("{e:#}")),
+                };
+                (file.clone(), state)
+            })
+            .collect();
+        Ok(VersionReport {
+            version,
+            systems,
+            modules,
+            json_files,
+            ignore_prerelease: self.config.verify.ignore_prerelease,
+        })
+    }
 
-    fn create_test_files(dir: &Path, version: &str) -> Result<()> {
-        // Create VERSION file
-        fs::write(dir.join("VERSION"), version)?;
+    /// Read one manifest and classify what it declares
+    fn manifest_version(
+        &self,
+        system: &BuildSystem,
+        path: &Path,
+        expected: &Version,
+    ) -> ManifestVersion {
+        let declared = if *system == BuildSystem::Cargo {
+            let doc = trace::read_to_string(path)
+                .with_context(|| format!("Failed to read Cargo.toml at {}", path.display()))
+                .and_then(|content| {
+                    toml::from_str::<toml::Value>(&content)
+                        .with_context(|| "Failed to parse Cargo.toml")
+                });
+            match doc {
+                Ok(doc) if Self::cargo_doc_is_inherited(&doc) => {
+                    return ManifestVersion::Inherited;
+                }
+                Ok(doc) if Self::cargo_doc_is_unversioned_virtual(&doc) => {
+                    return ManifestVersion::VirtualWorkspace;
+                }
+                Ok(doc) => Self::cargo_version_from_doc(&doc),
+                Err(e) => Err(e),
+            }
+        } else {
+            self.read_build_system_version(system, Some(path))
+        };
 
-        // Create Cargo.toml
-        let cargo_content = format!(
+        // Only a file that failed to parse can be a placeholder, so in-sync files are
+        // still read once
+        if declared.is_err()
+            && !self.overwrite_placeholders
+            && trace::read_to_string(path)
+                .is_ok_and(|content| system.format().holds_placeholder(&content))
+        {
+            return ManifestVersion::Placeholder;
+        }
+        match declared {
+            Ok(version) => ManifestVersion::Declared {
+                in_sync: self.version_matches(system, &version, expected),
+                version,
+            },
+            Err(e) => ManifestVersion::Unreadable(e.to_string()),
+        }
+    }
+
+    /// Verify that every manifest discovered in cascade mode matches the VERSION file
+    ///
+    /// Manifests with no version of their own (see [`Self::build_system_has_no_version`])
+    /// are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if discovery fails, any manifest cannot be read, or any
+    /// manifest's version differs from the VERSION file.
+    pub fn verify_cascade_in_sync(&self) -> Result<()> {
+        let version_file_version = self.read_version_file()?;
+        let manifests = self.discover_manifests()?;
+
+        let mut mismatched = Vec::new();
+        for (path, system) in &manifests {
+            if self.manifest_has_no_version(path, system) {
+                continue;
+            }
+            match self.read_build_system_version(system, Some(path)) {
+                Ok(system_version)
+                    if !self.version_matches(system, &system_version, &version_file_version) =>
+                {
+                    mismatched.push(format!(
+                        "{} has version {system_version} but VERSION file has {version_file_version}",
+                        path.display()
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    mismatched.push(format!("Failed to read {}: {e}", path.display()));
+                }
+            }
+        }
+
+        if !mismatched.is_empty() {
+            anyhow::bail!(
+                "Version files are not synchronized:\n{}\n\nRun 'versioneer sync --cascade' to synchronize all version files.",
+                mismatched.join("\n")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Synchronize all version files to match the VERSION file
+    ///
+    /// Files that already hold the version are not written. Returns how many files
+    /// changed; zero means everything was already in sync.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if version files cannot be read or updated.
+    pub fn sync_versions(&self) -> Result<usize> {
+        let version = self.read_version_file()?;
+
+        let mut changed = 0;
+        for (path, system) in &self.detect_manifests() {
+            if self
+                .update_build_system_version(system, Some(path), &version)
+                .with_context(|| format!("Failed to sync {} version", system.describe(path)))?
+            {
+                changed += 1;
+            }
+        }
+        for module in &self.config.python_modules {
+            if self.update_python_module(module, &version)? {
+                changed += 1;
+            }
+        }
+        for file in &self.config.json_files {
+            if self.update_json_file(file, &version)? {
+                changed += 1;
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Adopt a build system's version as canonical (the inverse of [`Self::sync_versions`])
+    ///
+    /// Writes the adopted version to the VERSION file and every other build system, then
+    /// verifies the result. Without `source`, the detected build systems must all agree.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `source` is not detected, no build system declares a version,
+    /// the build systems disagree and no `source` was given, or any write or the final
+    /// verification fails.
+    pub fn adopt_build_system_version(&self, source: Option<&BuildSystem>) -> Result<Version> {
+        let version = if let Some(source) = source {
+            if !self
+                .adoptable_manifests()
+                .any(|(_, system)| system == *source)
+            {
+                anyhow::bail!("{source:?} is not present in {}", self.base_path.display());
+            }
+            self.read_build_system_version(source, None)?
+        } else {
+            let versions = self.declared_versions()?;
+            let (_, _, first) = versions
+                .first()
+                .context("No build system files found to adopt a version from")?;
+            if versions.iter().any(|(_, _, v)| v != first) {
+                let listing: Vec<String> = versions
+                    .iter()
+                    .map(|(path, system, v)| format!("{} {v}", system.describe(path)))
+                    .collect();
+                anyhow::bail!(
+                    "Build systems disagree ({}); pass --source to choose one",
+                    listing.join(", ")
+                );
+            }
+            first.clone()
+        };
+
+        self.adopt_version(&version)?;
+        Ok(version)
+    }
+
+    /// The version each detected manifest declares, in detection order
+    ///
+    /// Manifests with no version of their own (see [`Self::build_system_has_no_version`])
+    /// are left out. Lets callers choose among disagreeing build systems before calling
+    /// [`Self::adopt_version`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any manifest cannot be read or parsed.
+    pub fn declared_versions(&self) -> Result<Vec<(std::path::PathBuf, BuildSystem, Version)>> {
+        self.adoptable_manifests()
+            .map(|(path, system)| {
+                let version = self
+                    .read_build_system_version(&system, Some(&path))
+                    .with_context(|| {
+                        format!("Failed to read {} version", system.describe(&path))
+                    })?;
+                Ok((path, system, version))
+            })
+            .collect()
+    }
+
+    /// Write `version` to the VERSION file, every build system and the configured files,
+    /// then verify the result
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any write or the final verification fails.
+    pub fn adopt_version(&self, version: &Version) -> Result<()> {
+        self.write_version_file(version)?;
+        for (path, system) in self.adoptable_manifests() {
+            self.update_build_system_version(&system, Some(&path), version)
+                .with_context(|| format!("Failed to sync {} version", system.describe(&path)))?;
+        }
+        self.update_configured_files(version)?;
+
+        self.verify_versions_in_sync()
+    }
+
+    /// Detected manifests that declare a version of their own
+    fn adoptable_manifests(&self) -> impl Iterator<Item = (std::path::PathBuf, BuildSystem)> + '_ {
+        self.detect_manifests()
+            .into_iter()
+            .filter(|(path, system)| !self.manifest_has_no_version(path, system))
+    }
+
+    /// Bump version with cascade dry-run (preview what would change)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if discovery fails or version reading fails.
+    pub fn bump_cascade_dry_run(&self, bump_type: BumpType) -> Result<DryRunResult> {
+        // Step 1: Discover all manifests
+        let manifests = self.discover_manifests()?;
+
+        // Step 2: Read current version and calculate new version
+        let current_version = self.read_version_file()?;
+        let new_version = bump_type.apply(&current_version);
+
+        // Step 3: Collect all files that would be updated
+        let mut files_to_update = self.version_file_targets();
+        for (path, system) in manifests {
+            if !self.manifest_has_no_version(&path, &system) {
+                files_to_update.push(path);
+            }
+        }
+
+        Ok(DryRunResult {
+            new_version,
+            files_to_update,
+            tag_name: None,
+        })
+    }
+
+    /// Bump version with cascade (update all discovered manifests)
+    ///
+    /// Returns a [`CascadeSummary`] of the files considered and changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if discovery fails, versions are out of sync, or updates fail.
+    /// On error, all changes are rolled back unless [`Self::keep_going`] is set.
+    pub fn bump_cascade(&self, bump_type: BumpType) -> Result<CascadeSummary> {
+        self.bump_cascade_with_progress(bump_type, &mut |_, _, _| {})
+    }
+
+    /// Like [`Self::bump_cascade`], calling `progress(done, total, path)` after each manifest
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if discovery fails, versions are out of sync, or updates fail.
+    /// On error, all changes are rolled back unless [`Self::keep_going`] is set.
+    pub fn bump_cascade_with_progress(
+        &self,
+        bump_type: BumpType,
+        progress: &mut dyn FnMut(usize, usize, &Path),
+    ) -> Result<CascadeSummary> {
+        // Step 1: Discover all manifests
+        let discovered = self.discover_for_cascade()?;
+
+        // Step 2: Read current version and calculate new version
+        let current_version = self.read_version_file()?;
+        let new_version = bump_type.apply(&current_version);
+
+        // Step 3: Write everything, rolling back on error
+        self.write_cascade(&new_version, &discovered, true, "update", progress)
+    }
+
+    /// Write `version` to every cascade target, rolling back all files on error
+    ///
+    /// The VERSION file is written only with `include_version_file`; configured Python
+    /// modules and the `discovered` manifests always are, and its untracked manifests
+    /// count as skipped. `action` names the operation in error messages.
+    fn write_cascade(
+        &self,
+        version: &Version,
+        discovered: &DiscoveredManifests,
+        include_version_file: bool,
+        action: &str,
+        progress: &mut dyn FnMut(usize, usize, &Path),
+    ) -> Result<CascadeSummary> {
+        use std::collections::HashMap;
+
+        if self.keep_going {
+            return self.write_cascade_keep_going(
+                version,
+                discovered,
+                include_version_file,
+                action,
+                progress,
+            );
+        }
+
+        // Read all files into memory for potential rollback
+        let mut original_contents: HashMap<std::path::PathBuf, String> = HashMap::new();
+
+        let version_path = self.base_path.join(&self.version_file);
+        if include_version_file && version_path.exists() {
+            original_contents.insert(version_path.clone(), trace::read_to_string(&version_path)?);
+        }
+        for path in self.python_module_paths().chain(self.json_file_paths()) {
+            let content = trace::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            original_contents.insert(path, content);
+        }
+
+        let manifests = &discovered.manifests;
+        for (path, _) in manifests {
+            original_contents.insert(path.clone(), trace::read_to_string(path)?);
+        }
+
+        // Perform updates with rollback on error
+        let update_result = (|| -> Result<CascadeSummary> {
+            let mut summary = CascadeSummary {
+                skipped: discovered.untracked.len(),
+                ..CascadeSummary::default()
+            };
+            if include_version_file && self.writes_version_file() {
+                let changed = self.write_version_file(version)?;
+                summary.record(&self.version_file_path(), changed);
+            }
+            for module in &self.config.python_modules {
+                let changed = self.update_python_module(module, version)?;
+                summary.record(&self.base_path.join(&module.path), changed);
+            }
+            for file in &self.config.json_files {
+                let changed = self.update_json_file(file, version)?;
+                summary.record(&self.base_path.join(&file.path), changed);
+            }
+
+            for (index, (path, system)) in manifests.iter().enumerate() {
+                if self.manifest_has_no_version(path, system) {
+                    summary.scanned += 1;
+                    summary.skipped += 1;
+                } else {
+                    let changed = self
+                        .update_build_system_version(system, Some(path), version)
+                        .with_context(|| {
+                            format!("Failed to {action} {:?} at {}", system, path.display())
+                        })?;
+                    summary.record(path, changed);
+                }
+                progress(index + 1, manifests.len(), path);
+            }
+            Ok(summary)
+        })();
+
+        update_result.inspect_err(|_| {
+            // Restore original contents, leaving files the failed run never changed alone
+            for (path, content) in original_contents {
+                let current = trace::read_to_string(&path).unwrap_or_default();
+                let _ = Self::write_if_changed(&path, &current, &content); // Best effort rollback
+            }
+        })
+    }
+
+    /// [`Self::write_cascade`] under [`Self::keep_going`]: attempt every file, roll
+    /// nothing back, and report the failures together at the end
+    fn write_cascade_keep_going(
+        &self,
+        version: &Version,
+        discovered: &DiscoveredManifests,
+        include_version_file: bool,
+        action: &str,
+        progress: &mut dyn FnMut(usize, usize, &Path),
+    ) -> Result<CascadeSummary> {
+        let manifests = &discovered.manifests;
+        let mut summary = CascadeSummary {
+            skipped: discovered.untracked.len(),
+            ..CascadeSummary::default()
+        };
+        let mut failures = Vec::new();
+        let mut attempt =
+            |summary: &mut CascadeSummary, path: &Path, result: Result<bool>| match result {
+                Ok(changed) => summary.record(path, changed),
+                Err(e) => failures.push(format!("{}: {e:#}", path.display())),
+            };
+
+        if include_version_file && self.writes_version_file() {
+            let result = self.write_version_file(version);
+            attempt(&mut summary, &self.version_file_path(), result);
+        }
+        for module in &self.config.python_modules {
+            let result = self.update_python_module(module, version);
+            attempt(&mut summary, &self.base_path.join(&module.path), result);
+        }
+        for file in &self.config.json_files {
+            let result = self.update_json_file(file, version);
+            attempt(&mut summary, &self.base_path.join(&file.path), result);
+        }
+        for (index, (path, system)) in manifests.iter().enumerate() {
+            if self.manifest_has_no_version(path, system) {
+                summary.scanned += 1;
+                summary.skipped += 1;
+            } else {
+                let result = self
+                    .update_build_system_version(system, Some(path), version)
+                    .with_context(|| format!("Failed to {action} {system:?}"));
+                attempt(&mut summary, path, result);
+            }
+            progress(index + 1, manifests.len(), path);
+        }
+
+        if failures.is_empty() {
+            Ok(summary)
+        } else {
+            Err(VersioneerError::PartialCascade { summary, failures }.into())
+        }
+    }
+
+    /// Preview sync operation with cascade (dry-run mode)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if discovery or version reading fails.
+    pub fn sync_cascade_dry_run(&self) -> Result<DryRunResult> {
+        // Step 1: Discover all manifests
+        let manifests = self.discover_manifests()?;
+
+        // Step 2: Read current version
+        let version = self.read_version_file()?;
+
+        // Step 3: Collect all files that would be updated
+        let mut files_to_update: Vec<_> = self
+            .python_module_paths()
+            .chain(self.json_file_paths())
+            .collect();
+        for (path, system) in manifests {
+            if !self.manifest_has_no_version(&path, &system) {
+                files_to_update.push(path);
+            }
+        }
+
+        Ok(DryRunResult {
+            new_version: version,
+            files_to_update,
+            tag_name: None,
+        })
+    }
+
+    /// Sync all manifests with cascade (update all to match VERSION file)
+    ///
+    /// Manifests that already hold the version are not written. Returns a
+    /// [`CascadeSummary`]; its `updated` count is zero when everything was in sync.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if discovery fails or updates fail.
+    /// On error, all changes are rolled back unless [`Self::keep_going`] is set.
+    pub fn sync_cascade(&self) -> Result<CascadeSummary> {
+        self.sync_cascade_with_progress(&mut |_, _, _| {})
+    }
+
+    /// Like [`Self::sync_cascade`], calling `progress(done, total, path)` after each manifest
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if discovery fails or updates fail.
+    /// On error, all changes are rolled back unless [`Self::keep_going`] is set.
+    pub fn sync_cascade_with_progress(
+        &self,
+        progress: &mut dyn FnMut(usize, usize, &Path),
+    ) -> Result<CascadeSummary> {
+        // Step 1: Discover all manifests
+        let discovered = self.discover_for_cascade()?;
+
+        // Step 2: Read current version
+        let version = self.read_version_file()?;
+
+        // Step 3: Write the manifests and Python modules, rolling back on error
+        self.write_cascade(&version, &discovered, false, "sync", progress)
+    }
+
+    /// Preview reset operation with cascade (dry-run mode)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if discovery fails or the version is invalid or out of bounds.
+    pub fn reset_cascade_dry_run(&self, version_str: &str) -> Result<DryRunResult> {
+        // Step 1: Parse and validate version
+        let new_version = self.parse_reset_target(version_str)?;
+
+        // Step 2: Discover all manifests
+        let manifests = self.discover_manifests()?;
+
+        // Step 3: Collect all files that would be updated
+        let mut files_to_update = self.version_file_targets();
+        for (path, system) in manifests {
+            if !self.manifest_has_no_version(&path, &system) {
+                files_to_update.push(path);
+            }
+        }
+
+        Ok(DryRunResult {
+            new_version,
+            files_to_update,
+            tag_name: None,
+        })
+    }
+
+    /// Reset version with cascade (reset all discovered manifests)
+    ///
+    /// Returns a [`CascadeSummary`] of the files considered and changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if discovery fails, the version is invalid or out of bounds, or
+    /// updates fail.
+    /// On error, all changes are rolled back unless [`Self::keep_going`] is set.
+    pub fn reset_cascade(&self, version_str: &str) -> Result<CascadeSummary> {
+        self.reset_cascade_with_progress(version_str, &mut |_, _, _| {})
+    }
+
+    /// Like [`Self::reset_cascade`], calling `progress(done, total, path)` after each manifest
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if discovery fails, the version is invalid or out of bounds, or
+    /// updates fail.
+    /// On error, all changes are rolled back unless [`Self::keep_going`] is set.
+    pub fn reset_cascade_with_progress(
+        &self,
+        version_str: &str,
+        progress: &mut dyn FnMut(usize, usize, &Path),
+    ) -> Result<CascadeSummary> {
+        // Step 1: Parse and validate version
+        let new_version = self.parse_reset_target(version_str)?;
+
+        // Step 2: Discover all manifests
+        let discovered = self.discover_for_cascade()?;
+
+        // Step 3: Write everything, rolling back on error
+        self.write_cascade(&new_version, &discovered, true, "reset", progress)
+    }
+
+    /// Discover all manifest files recursively in subdirectories
+    ///
+    /// Respects .gitignore patterns and the manager's [`DiscoveryOptions`]: depth is
+    /// bounded by `max_depth`, [`DEFAULT_EXCLUDED_DIRS`] are pruned, and symlinked
+    /// directories are skipped unless `follow_symlinks` is set. Inside a git work tree,
+    /// manifests git does not track are handled according to `untracked` (skipped by
+    /// default; see [`Self::untracked_manifests`]). Errors if nested VERSION files or
+    /// symlinked files are found.
+    ///
+    /// With [`Self::with_detection_cache`], the walk happens once (together with the
+    /// untracked manifests) and every cached manifest is checked to still exist before
+    /// the cached list is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if directory traversal fails (including symlink loops),
+    /// nested VERSION files are found, a symlinked file is found, an untracked
+    /// manifest is found with [`UntrackedManifests::Abort`], or a cached manifest no
+    /// longer exists.
+    pub fn discover_manifests(&self) -> Result<Vec<(std::path::PathBuf, BuildSystem)>> {
+        Ok(self.discover_for_cascade()?.manifests)
+    }
+
+    /// [`Self::discover`], refusing untracked manifests under [`UntrackedManifests::Abort`]
+    fn discover_for_cascade(&self) -> Result<DiscoveredManifests> {
+        let discovered = self.discover()?;
+        if let (UntrackedManifests::Abort, Some(path)) =
+            (self.discovery.untracked, discovered.untracked.first())
+        {
+            anyhow::bail!(
+                "Manifest {} is not tracked by git; refusing to cascade (--abort-on-untracked-manifest)",
+                path.display()
+            );
+        }
+        Ok(discovered)
+    }
+
+    /// Walk the tree once, or serve the cached walk when detection is cached
+    fn discover(&self) -> Result<DiscoveredManifests> {
+        let Some(cache) = &self.detection_cache else {
+            return self.walk_discovered();
+        };
+        // Holding the lock across the walk keeps concurrent callers from walking twice
+        let mut cached = cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(discovered) = cached.as_ref() {
+            if let Some((path, _)) = discovered
+                .manifests
+                .iter()
+                .find(|(path, _)| !path.is_file())
+            {
+                anyhow::bail!(
+                    "Manifest {} has disappeared since it was detected; refresh detection before writing",
+                    path.display()
+                );
+            }
+            return Ok(discovered.clone());
+        }
+        let discovered = self.walk_discovered()?;
+        *cached = Some(discovered.clone());
+        drop(cached);
+        Ok(discovered)
+    }
+
+    /// Walk the tree, sorting manifests git does not track out of the managed set
+    fn walk_discovered(&self) -> Result<DiscoveredManifests> {
+        let tracked = match self.discovery.untracked {
+            UntrackedManifests::Include => None,
+            UntrackedManifests::Skip | UntrackedManifests::Abort => self.tracked_files(),
+        };
+        let mut discovered = DiscoveredManifests::default();
+        for manifest in self.walk_all_manifests() {
+            let (path, system) = manifest?;
+            if tracked
+                .as_ref()
+                .is_some_and(|tracked| !tracked.contains(&path))
+            {
+                discovered.untracked.push(path);
+            } else {
+                discovered.manifests.push((path, system));
+            }
+        }
+        Ok(discovered)
+    }
+
+    /// Drop the cached manifests and walk the tree again
+    ///
+    /// Returns the number of manifests found. Without [`Self::with_detection_cache`]
+    /// nothing is cached, so this only checks that the walk succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if discovery fails (see [`Self::discover_manifests`]); the
+    /// cache is then left empty.
+    pub fn refresh_detection(&self) -> Result<usize> {
+        if let Some(cache) = &self.detection_cache {
+            *cache
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+        }
+        Ok(self.discover_manifests()?.len())
+    }
+
+    /// Lazily walk the tree, yielding each manifest as it is found
+    ///
+    /// Same rules as [`Self::discover_manifests`], but nothing is buffered, so callers
+    /// can act on early manifests before the walk finishes. Stop at the first error.
+    pub fn walk_manifests(
+        &self,
+    ) -> impl Iterator<Item = Result<(std::path::PathBuf, BuildSystem)>> + '_ {
+        let untracked = self.discovery.untracked;
+        let tracked = match untracked {
+            UntrackedManifests::Include => None,
+            UntrackedManifests::Skip | UntrackedManifests::Abort => self.tracked_files(),
+        };
+        self.walk_all_manifests()
+            .filter_map(move |manifest| match (manifest, &tracked) {
+                (Ok((path, _)), Some(tracked)) if !tracked.contains(&path) => {
+                    (untracked == UntrackedManifests::Abort).then(|| {
+                        Err(anyhow::anyhow!(
+                            "Manifest {} is not tracked by git; refusing to cascade (--abort-on-untracked-manifest)",
+                            path.display()
+                        ))
+                    })
+                }
+                (manifest, _) => Some(manifest),
+            })
+    }
+
+    /// Manifests discovery leaves out because git does not track them
+    ///
+    /// Empty outside a git work tree or when untracked manifests are included. Comes
+    /// from the same walk as [`Self::discover_manifests`], so a cached manager does not
+    /// walk again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if discovery fails.
+    pub fn untracked_manifests(&self) -> Result<Vec<std::path::PathBuf>> {
+        Ok(self.discover()?.untracked)
+    }
+
+    /// Manifests committed more recently than the VERSION file
+    ///
+    /// Compares the timestamp of the last commit touching each file
+    /// (`git log -1 --format=%ct`). A manifest newer than VERSION suggests its version
+    /// was edited by hand; any other change to the manifest is reported as well, so the
+    /// result is advisory. Empty outside a git work tree or when VERSION was never
+    /// committed; manifests that were never committed are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if git fails or prints a timestamp that is not a number.
+    pub fn manifests_newer_than_version_file(
+        &self,
+    ) -> Result<Vec<(std::path::PathBuf, BuildSystem)>> {
+        if self.tracked_files().is_none() {
+            return Ok(Vec::new());
+        }
+        let Some(version_time) = self.last_commit_time(&self.version_file_path())? else {
+            return Ok(Vec::new());
+        };
+        let mut newer = Vec::new();
+        for (path, system) in self.detect_manifests() {
+            if self
+                .last_commit_time(&path)?
+                .is_some_and(|time| time > version_time)
+            {
+                newer.push((path, system));
+            }
+        }
+        Ok(newer)
+    }
+
+    /// Unix time of the last commit touching `path`, or `None` if it was never committed
+    fn last_commit_time(&self, path: &Path) -> Result<Option<i64>> {
+        let path = path.to_string_lossy();
+        let output = self.run_git(&["log", "-1", "--format=%ct", "--", &path])?;
+        let output = output.trim();
+        if output.is_empty() {
+            return Ok(None);
+        }
+        output
+            .parse()
+            .map(Some)
+            .with_context(|| format!("Unexpected commit time from git log: {output}"))
+    }
+
+    /// Files git tracks under the base path, or `None` outside a git work tree
+    fn tracked_files(&self) -> Option<std::collections::HashSet<std::path::PathBuf>> {
+        let listing = self.run_git(&["ls-files", "-z"]).ok()?;
+        Some(
+            listing
+                .split('\0')
+                .filter(|path| !path.is_empty())
+                .map(|path| self.base_path.join(path))
+                .collect(),
+        )
+    }
+
+    /// Walk the tree yielding every manifest, tracked by git or not
+    fn walk_all_manifests(
+        &self,
+    ) -> impl Iterator<Item = Result<(std::path::PathBuf, BuildSystem)>> + '_ {
+        use ignore::WalkBuilder;
+
+        let default_excludes = self.discovery.default_excludes;
+
+        // Use ignore crate to respect .gitignore
+        let walker = WalkBuilder::new(&self.base_path)
+            .hidden(false)
+            .git_ignore(true)
+            .max_depth(self.discovery.max_depth)
+            .follow_links(self.discovery.follow_symlinks)
+            .filter_entry(move |entry| {
+                let is_excluded_dir = entry.depth() > 0
+                    && entry.file_type().is_some_and(|ft| ft.is_dir())
+                    && DEFAULT_EXCLUDED_DIRS
+                        .iter()
+                        .any(|name| entry.file_name() == *name);
+                !(default_excludes && is_excluded_dir)
+            })
+            .build();
+
+        walker.filter_map(move |entry| {
+            entry
+                .context("Failed to walk directory tree")
+                .and_then(|entry| self.classify_entry(entry.path(), entry.path_is_symlink()))
+                .transpose()
+        })
+    }
+
+    /// Decide whether a walked path is a manifest, something to skip, or an error
+    fn classify_entry(
+        &self,
+        path: &Path,
+        is_symlink: bool,
+    ) -> Result<Option<(std::path::PathBuf, BuildSystem)>> {
+        // Symlinked directories are not descended into unless following is enabled;
+        // symlinked files are rejected because writes would land on the link target
+        if is_symlink && !self.discovery.follow_symlinks {
+            if path.is_dir() {
+                return Ok(None);
+            }
+            anyhow::bail!(
+                "Symlink found at {}. Symlinked files are not supported in cascade mode.",
+                path.display()
+            );
+        }
+
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let Some(filename) = path.file_name() else {
+            return Ok(None);
+        };
+        let filename_str = filename.to_string_lossy();
+
+        // Check for nested version files
+        if filename_str == self.version_file {
+            // Version file in base_path is OK, but not in subdirectories
+            if path.parent() != Some(&self.base_path) {
+                anyhow::bail!(
+                    "Nested {} file found at {}. Only one {} file is allowed at the root directory.",
+                    self.version_file,
+                    path.display(),
+                    self.version_file,
+                );
+            }
+            return Ok(None);
+        }
+
+        let Some(system) = self.classify_manifest(path) else {
+            return Ok(None);
+        };
+        Ok(Some((path.to_path_buf(), system)))
+    }
+
+    /// Lazily walk the tree, yielding each manifest's version and sync state as it is found
+    ///
+    /// Manifests with no version of their own (see [`Self::build_system_has_no_version`])
+    /// are skipped. A manifest that cannot be read is reported with no version and
+    /// out of sync rather than ending the walk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the VERSION file cannot be read. Items are errors if
+    /// discovery fails.
+    pub fn manifest_statuses(&self) -> Result<impl Iterator<Item = Result<ManifestStatus>> + '_> {
+        let expected = self.read_version_file()?;
+        Ok(self
+            .walk_manifests()
+            .filter(|manifest| {
+                manifest.as_ref().map_or(true, |(path, system)| {
+                    !self.manifest_has_no_version(path, system)
+                })
+            })
+            .map(move |manifest| {
+                let (path, system) = manifest?;
+                let version = self.read_build_system_version(&system, Some(&path)).ok();
+                let in_sync = version
+                    .as_ref()
+                    .is_some_and(|found| self.version_matches(&system, found, &expected));
+                Ok(ManifestStatus {
+                    path,
+                    system,
+                    version,
+                    in_sync,
+                })
+            }))
+    }
+
+    /// Count existing RC tags for a given base version.
+    ///
+    /// Queries git for tags matching `v{major}.{minor}.{patch}-rc.*`
+    /// and returns the highest RC number found, or 0 if none exist.
+    fn count_rc_tags(&self, base_version: &Version) -> Result<u64> {
+        let pattern = format!(
+            "v{}.{}.{}-rc.*",
+            base_version.major, base_version.minor, base_version.patch
+        );
+
+        let output = std::process::Command::new("git")
+            .args(["tag", "-l", &pattern])
+            .current_dir(&self.base_path)
+            .output()
+            .context("Failed to run git. Is git installed and is this a git repository?")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git tag failed: {stderr}");
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut max_rc: u64 = 0;
+
+        for line in stdout.lines() {
+            let line = line.trim();
+            if let Some(rc_part) = line.rsplit("-rc.").next() {
+                if let Ok(n) = rc_part.parse::<u64>() {
+                    max_rc = max_rc.max(n);
+                }
+            }
+        }
+
+        Ok(max_rc)
+    }
+
+    /// Compute the next RC version from the VERSION file and git tags.
+    ///
+    /// Reads VERSION (must be a clean M.m.p without pre-release suffix),
+    /// counts existing RC tags, and returns the next RC version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the VERSION file cannot be read, contains a pre-release
+    /// suffix, or if git tag querying fails.
+    pub fn next_rc_version(&self) -> Result<Version> {
+        let base_version = self.read_version_file()?;
+
+        if !base_version.pre.is_empty() {
+            anyhow::bail!(
+                "VERSION must be a clean M.m.p version without pre-release suffix, got: {base_version}"
+            );
+        }
+
+        let max_rc = self.count_rc_tags(&base_version)?;
+        let mut rc_version = base_version;
+        rc_version.pre = semver::Prerelease::new(&format!("rc.{}", max_rc + 1))
+            .context("Failed to construct pre-release version")?;
+
+        Ok(rc_version)
+    }
+
+    /// Run `command` through the shell in the base path, with `version` exported as
+    /// `$VERSIONEER_VERSION`
+    ///
+    /// Meant for a follow-up step after versioneer writes its files, such as
+    /// regenerating a lockfile. The command's stdout is sent to stderr so versioneer's own
+    /// stdout stays parseable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shell cannot be started or the command exits non-zero.
+    pub fn run_after_write_command(&self, command: &str, version: &Version) -> Result<()> {
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
+        };
+        let status = std::process::Command::new(shell)
+            .args([flag, command])
+            .env("VERSIONEER_VERSION", version.to_string())
+            .current_dir(&self.base_path)
+            .stdout(std::io::stderr())
+            .status()
+            .with_context(|| format!("Failed to run after-write command `{command}`"))?;
+        if !status.success() {
+            anyhow::bail!("After-write command `{command}` failed ({status})");
+        }
+        Ok(())
+    }
+
+    /// Run a git command in the base path and return its stdout
+    fn run_git(&self, args: &[&str]) -> Result<String> {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(&self.base_path)
+            .output()
+            .context("Failed to run git. Is git installed and is this a git repository?")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git {} failed: {}", args.join(" "), stderr.trim());
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Infer the tag prefix from the repository's existing tags.
+    ///
+    /// Each tag ending in a semver version contributes the text before that version
+    /// (`v1.2.3` → `v`, `release-1.2.3` → `release-`, `1.2.3` → no prefix). A prefix
+    /// is returned only if it accounts for more than half of those tags; with no
+    /// versioned tags, or no clear winner, the result is `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if git tag querying fails.
+    pub fn detect_tag_prefix(&self) -> Result<Option<String>> {
+        let stdout = self.run_git(&["tag", "-l"])?;
+        let prefixes: Vec<&str> = stdout
+            .lines()
+            .filter_map(|tag| Self::split_tag_version(tag.trim()))
+            .collect();
+
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for prefix in &prefixes {
+            match counts.iter_mut().find(|(p, _)| p == prefix) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((prefix, 1)),
+            }
+        }
+
+        Ok(counts
+            .into_iter()
+            .find(|(_, count)| *count * 2 > prefixes.len())
+            .map(|(prefix, _)| prefix.to_string()))
+    }
+
+    /// The prefix a tag puts before its semver version, if it ends in one
+    fn split_tag_version(tag: &str) -> Option<&str> {
+        tag.char_indices()
+            .filter(|&(i, c)| {
+                c.is_ascii_digit() && !tag[..i].ends_with(|p: char| p.is_ascii_digit() || p == '.')
+            })
+            .find(|&(i, _)| Version::parse(&tag[i..]).is_ok())
+            .map(|(i, _)| &tag[..i])
+    }
+
+    /// The version a git tag names, with its prefix stripped
+    ///
+    /// `tag` is split like the tags [`Self::detect_tag_prefix`] counts and need not exist.
+    /// Without it, the versioned tags pointing at HEAD are used, preferring those with
+    /// the resolved prefix (see [`Self::resolve_tag_prefix`]) and then the highest
+    /// version. Returns the tag name and its version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `tag` does not end in a semantic version, if git fails, or
+    /// if no versioned tag points at HEAD.
+    pub fn tag_version(&self, tag: Option<&str>) -> Result<(String, Version)> {
+        let parse = |tag: &str| {
+            Self::split_tag_version(tag)
+                .and_then(|prefix| Version::parse(&tag[prefix.len()..]).ok())
+                .map(|version| (tag.to_string(), version))
+        };
+        if let Some(tag) = tag {
+            return parse(tag)
+                .with_context(|| format!("Tag {tag} does not end in a semantic version"));
+        }
+
+        let stdout = self.run_git(&["tag", "--points-at", "HEAD"])?;
+        let tags: Vec<(String, Version)> =
+            stdout.lines().filter_map(|tag| parse(tag.trim())).collect();
+        let prefix = self.resolve_tag_prefix(None);
+        let preferred: Vec<_> = tags
+            .iter()
+            .filter(|(name, version)| *name == format!("{prefix}{version}"))
+            .collect();
+        let candidates = if preferred.is_empty() {
+            tags.iter().collect()
+        } else {
+            preferred
+        };
+        candidates
+            .into_iter()
+            .max_by(|(_, a), (_, b)| a.cmp_precedence(b))
+            .cloned()
+            .context("No version tag points at HEAD; pass --tag <name> to name the release tag")
+    }
+
+    /// Compare the VERSION file against the version `tag` names
+    ///
+    /// Build metadata is ignored; with [`config::VerifyConfig::ignore_prerelease`] only
+    /// `major.minor.patch` are compared. Returns the mismatch, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the VERSION file cannot be read.
+    pub fn tag_mismatch(&self, tag: &str, tag_version: &Version) -> Result<Option<SyncIssue>> {
+        let expected = self.read_version_file()?;
+        let matches = if self.config.verify.ignore_prerelease {
+            (tag_version.major, tag_version.minor, tag_version.patch)
+                == (expected.major, expected.minor, expected.patch)
+        } else {
+            tag_version.cmp_precedence(&expected).is_eq()
+        };
+        Ok((!matches).then(|| SyncIssue::TagMismatch {
+            tag: tag.to_string(),
+            found: tag_version.clone(),
+            expected,
+        }))
+    }
+
+    /// The tag prefix to use: `explicit` if given, else the one detected from
+    /// existing tags (see [`Self::detect_tag_prefix`]), else [`DEFAULT_TAG_PREFIX`]
+    #[must_use]
+    pub fn resolve_tag_prefix(&self, explicit: Option<&str>) -> String {
+        explicit.map_or_else(
+            || {
+                self.detect_tag_prefix()
+                    .ok()
+                    .flatten()
+                    .unwrap_or_else(|| DEFAULT_TAG_PREFIX.to_string())
+            },
+            ToString::to_string,
+        )
+    }
+
+    /// Find the highest existing git tag version carrying the given prefix.
+    ///
+    /// Tags whose remainder after the prefix is not valid semver are ignored.
+    /// Returns `None` if no matching tags exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if git tag querying fails.
+    pub fn latest_tag_version(&self, tag_prefix: &str) -> Result<Option<Version>> {
+        let pattern = format!("{tag_prefix}*");
+        let stdout = self.run_git(&["tag", "-l", &pattern])?;
+
+        Ok(stdout
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix(tag_prefix))
+            .filter_map(|rest| Version::parse(rest).ok())
+            .max_by(Version::cmp_precedence))
+    }
+
+    /// Ensure `version` is strictly greater than the latest existing tag.
+    ///
+    /// Comparison uses semver precedence, so build metadata is ignored. A prerelease
+    /// of the latest tag's own `major.minor.patch` starts a new cycle rather than going
+    /// back when the latest tag is a release (`1.2.0` → `1.2.0-rc.1`) or a prerelease
+    /// with a different label (`1.2.0-rc.2` → `1.2.0-beta.1`), and is allowed unless
+    /// [`config::TagConfig::block_prerelease_downgrade`] is set. Within one label the
+    /// counter must still go up: `1.2.0-rc.2` → `1.2.0-rc.1` is refused.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if git tag querying fails or if `version` does not
+    /// advance past the latest tag.
+    pub fn check_tag_advances(&self, version: &Version, tag_prefix: &str) -> Result<()> {
+        if let Some(latest) = self.latest_tag_version(tag_prefix)? {
+            let same_core = (version.major, version.minor, version.patch)
+                == (latest.major, latest.minor, latest.patch);
+            let new_prerelease = same_core
+                && !version.pre.is_empty()
+                && (latest.pre.is_empty()
+                    || prerelease_label(&version.pre) != prerelease_label(&latest.pre));
+            if new_prerelease && self.config.tag.block_prerelease_downgrade {
+                anyhow::bail!(
+                    "Version {version} is a prerelease of the latest tag {tag_prefix}{latest}, \
+                     and prerelease downgrades are blocked. Pass --allow-retag to tag anyway."
+                );
+            }
+            if version.cmp_precedence(&latest) != std::cmp::Ordering::Greater && !new_prerelease {
+                anyhow::bail!(
+                    "Version {version} is not greater than the latest tag {tag_prefix}{latest}. \
+                     Did you forget to bump? Pass --allow-retag to tag anyway."
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Find a version whose tag does not exist yet, starting from `version`.
+    ///
+    /// Returns `version` itself when its tag is free. Otherwise a numeric prerelease
+    /// identifier is appended or incremented (`1.2.3` → `1.2.3-1` → `1.2.3-2`) until
+    /// an unused tag name is found.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if git tag querying fails, or if the numeric identifier
+    /// would pass `u64::MAX` before a free tag is found.
+    pub fn resolve_tag_collision(&self, version: &Version, tag_prefix: &str) -> Result<Version> {
+        let pattern = format!("{tag_prefix}*");
+        let stdout = self.run_git(&["tag", "-l", &pattern])?;
+        let existing: std::collections::HashSet<&str> = stdout.lines().map(str::trim).collect();
+
+        if !existing.contains(format!("{tag_prefix}{version}").as_str()) {
+            return Ok(version.clone());
+        }
+
+        // Continue counting from a trailing numeric identifier rather than nesting suffixes
+        let pre = version.pre.as_str();
+        let (rest, last) = pre.rsplit_once('.').unwrap_or(("", pre));
+        let (base, mut next) = last
+            .parse::<u64>()
+            .map_or((pre, Some(1)), |n| (rest, n.checked_add(1)));
+
+        while let Some(n) = next {
+            let pre = if base.is_empty() {
+                n.to_string()
+            } else {
+                format!("{base}.{n}")
+            };
+            let mut candidate = version.clone();
+            candidate.pre = semver::Prerelease::new(&pre)
+                .with_context(|| format!("Invalid prerelease identifier '{pre}'"))?;
+            if !existing.contains(format!("{tag_prefix}{candidate}").as_str()) {
+                return Ok(candidate);
+            }
+            next = n.checked_add(1);
+        }
+        anyhow::bail!("No free tag for {tag_prefix}{version}: the prerelease counter is exhausted")
+    }
+
+    /// Create a git tag for `version` and return the tag name.
+    ///
+    /// Unless `allow_retag` is set, refuses to tag a version that is not
+    /// strictly greater than the latest existing tag. With `allow_retag`,
+    /// an existing tag of the same name is moved. Annotated and signed tags carry
+    /// the message `Release <tag>`, as [`Self::release`] tags do.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the precedence check fails or git cannot create the tag.
+    pub fn create_tag(
+        &self,
+        version: &Version,
+        tag_prefix: &str,
+        allow_retag: bool,
+        kind: TagKind,
+    ) -> Result<String> {
+        let tag_name = format!("{tag_prefix}{version}");
+
+        if !allow_retag {
+            self.check_tag_advances(version, tag_prefix)?;
+        }
+        let message = format!("Release {tag_name}");
+        let mut args = vec!["tag"];
+        if allow_retag {
+            args.push("-f");
+        }
+        match kind {
+            TagKind::Lightweight => {}
+            TagKind::Annotated => args.extend(["-a", "-m", &message]),
+            TagKind::Signed => args.extend(["-s", "-m", &message]),
+        }
+        args.push(&tag_name);
+        self.run_git(&args)?;
+
+        Ok(tag_name)
+    }
+
+    /// Extract the version from a parsed Cargo.toml
+    fn cargo_version_from_doc(cargo_toml: &toml::Value) -> Result<Version> {
+        if Self::cargo_doc_is_inherited(cargo_toml) {
+            anyhow::bail!(
+                "Cargo.toml inherits its version from the workspace (version.workspace = true)"
+            );
+        }
+
+        let version_str = if Self::cargo_version_in_workspace_table(cargo_toml) {
+            Self::cargo_workspace_version(cargo_toml)
+                .and_then(|v| v.as_str())
+                .context("Cargo.toml is a virtual workspace manifest: virtual workspace has no package version (declare [workspace.package] version to manage it)")?
+        } else {
+            cargo_toml
+                .get("package")
+                .and_then(|p| p.get("version"))
+                .and_then(|v| v.as_str())
+                .context("No version found in Cargo.toml [package] section")?
+        };
+
+        match VersionSpec::parse(version_str) {
+            Ok(VersionSpec::Exact(version)) => Ok(version),
+            Ok(VersionSpec::Requirement(_)) => anyhow::bail!(
+                "Invalid version format in Cargo.toml: {version_str} is a version requirement, but the version must be exact (e.g. 1.2.3)"
+            ),
+            Err(err) => Err(err)
+                .with_context(|| format!("Invalid version format in Cargo.toml: {version_str}")),
+        }
+    }
+
+    /// Where a `dynamic = ["version"]` project keeps its version, if it declares one
+    ///
+    /// Follows `[tool.setuptools.dynamic].version` to the file that really holds it.
+    fn pyproject_dynamic_source(
+        &self,
+        pyproject_toml: &toml::Value,
+    ) -> Result<Option<DynamicVersionSource>> {
+        if !Self::pyproject_is_dynamic(pyproject_toml) {
+            return Ok(None);
+        }
+
+        // Other tools (e.g. commitizen) may own a dynamic version instead of setuptools
+        let Some(spec) = pyproject_toml
+            .get("tool")
+            .and_then(|t| t.get("setuptools"))
+            .and_then(|s| s.get("dynamic"))
+            .and_then(|d| d.get("version"))
+        else {
+            return Ok(None);
+        };
+
+        if let Some(attr) = spec.get("attr").and_then(|a| a.as_str()) {
+            let (module, name) = attr
+                .rsplit_once('.')
+                .with_context(|| format!("Invalid setuptools version attr '{attr}'"))?;
+            return Ok(Some(DynamicVersionSource::Attr {
+                path: self.find_python_module(module)?,
+                name: name.to_string(),
+            }));
+        }
+
+        let file = match spec.get("file") {
+            Some(toml::Value::String(file)) => Some(file.as_str()),
+            Some(toml::Value::Array(files)) if files.len() == 1 => files[0].as_str(),
+            Some(toml::Value::Array(_)) => anyhow::bail!(
+                "setuptools dynamic version reads several files; only a single file is supported"
+            ),
+            _ => None,
+        };
+        let file =
+            file.context("[tool.setuptools.dynamic].version must have an 'attr' or 'file' entry")?;
+
+        Ok(Some(DynamicVersionSource::File(self.base_path.join(file))))
+    }
+
+    /// Format a version for pyproject.toml, honoring the `[pyproject] pep440` setting
+    fn format_pyproject_version(&self, version: &Version) -> Result<String> {
+        if self.config.pyproject.pep440 {
+            Self::format_pep440_version(version)
+        } else {
+            Ok(version.to_string())
+        }
+    }
+
+    /// Translate a semver version to PEP 440
+    ///
+    /// Prereleases `alpha.N`, `beta.N`, `rc.N` and `dev.N` map to `aN`, `bN`, `rcN` and
+    /// `.devN`; build metadata becomes a local version label. A prerelease without `N` is
+    /// refused, since PEP 440 would read it back as `N` = 0 and never verify.
+    fn format_pep440_version(version: &Version) -> Result<String> {
+        let pre = if version.pre.is_empty() {
+            String::new()
+        } else {
+            let pre = version.pre.as_str();
+            let Some((label, number)) = pre.split_once('.') else {
+                anyhow::bail!(
+                    "Pre-release '{pre}' has no number, which PEP 440 requires (use e.g. {pre}.1); set pep440 = false under [pyproject] in .versioneer.toml to write it verbatim"
+                );
+            };
+            let number: u64 = number.parse().with_context(|| {
+                format!("Pre-release '{pre}' has no PEP 440 equivalent (expected e.g. rc.1)")
+            })?;
+            let suffix = match label {
+                "alpha" => "a",
+                "beta" => "b",
+                "rc" => "rc",
+                "dev" => ".dev",
+                _ => anyhow::bail!(
+                    "Pre-release '{pre}' has no PEP 440 equivalent (expected alpha, beta, rc or dev); set pep440 = false under [pyproject] in .versioneer.toml to write it verbatim"
+                ),
+            };
+            format!("{suffix}{number}")
+        };
+        let local = if version.build.is_empty() {
+            String::new()
+        } else {
+            format!("+{}", version.build)
+        };
+        Ok(format!(
+            "{}.{}.{}{pre}{local}",
+            version.major, version.minor, version.patch
+        ))
+    }
+
+    /// Parse a pyproject version written either as semver or as PEP 440
+    ///
+    /// The inverse of [`Self::format_pep440_version`], so verify compares like with like.
+    fn parse_pep440_version(version_str: &str) -> Result<Version> {
+        if let Ok(version) = Version::parse(version_str) {
+            return Ok(version);
+        }
+
+        let re = regex::Regex::new(
+            r"^(\d+)\.(\d+)\.(\d+)(?:(a|b|rc|\.dev)(\d+))?(?:\+([0-9A-Za-z.-]+))?$",
+        )
+        .context("Failed to create regex for PEP 440 version")?;
+        let caps = re
+            .captures(version_str)
+            .with_context(|| format!("Unsupported version format: {version_str}"))?;
+
+        let mut version = Version::new(caps[1].parse()?, caps[2].parse()?, caps[3].parse()?);
+        if let (Some(label), Some(number)) = (caps.get(4), caps.get(5)) {
+            let label = match label.as_str() {
+                "a" => "alpha",
+                "b" => "beta",
+                "rc" => "rc",
+                _ => "dev",
+            };
+            version.pre = semver::Prerelease::new(&format!("{label}.{}", number.as_str()))?;
+        }
+        if let Some(local) = caps.get(6) {
+            version.build = semver::BuildMetadata::new(local.as_str())?;
+        }
+        Ok(version)
+    }
+
+    /// Whether `[project].dynamic` lists `version`
+    fn pyproject_is_dynamic(pyproject_toml: &toml::Value) -> bool {
+        pyproject_toml
+            .get("project")
+            .and_then(|p| p.get("dynamic"))
+            .and_then(|d| d.as_array())
+            .is_some_and(|fields| fields.iter().any(|f| f.as_str() == Some("version")))
+    }
+
+    /// Locate the source file for a dotted module name, in flat or `src/` layout
+    fn find_python_module(&self, module: &str) -> Result<std::path::PathBuf> {
+        let relative: std::path::PathBuf = module.split('.').collect();
+        for root in [self.base_path.clone(), self.base_path.join("src")] {
+            let candidates = [
+                root.join(&relative).with_extension("py"),
+                root.join(&relative).join("__init__.py"),
+            ];
+            if let Some(found) = candidates.into_iter().find(|c| c.is_file()) {
+                return Ok(found);
+            }
+        }
+        anyhow::bail!("Could not find module '{module}' for setuptools dynamic version")
+    }
+
+    /// Read the version string from a dynamic version source
+    fn read_dynamic_version(source: &DynamicVersionSource) -> Result<String> {
+        match source {
+            DynamicVersionSource::Attr { path, name } => {
+                let content = trace::read_to_string(path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                let range = Self::python_attr_range(&content, name).with_context(|| {
+                    format!("No string assignment to {name} found in {}", path.display())
+                })?;
+                Ok(content[range].to_string())
+            }
+            DynamicVersionSource::File(path) => trace::read_to_string(path)
+                .map(|content| content.trim().to_string())
+                .with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Write a new version to a dynamic version source
+    fn update_dynamic_version(source: &DynamicVersionSource, version: &str) -> Result<bool> {
+        let (path, content, updated_content) = match source {
+            DynamicVersionSource::Attr { path, name } => {
+                let content = trace::read_to_string(path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                let range = Self::python_attr_range(&content, name).with_context(|| {
+                    format!("No string assignment to {name} found in {}", path.display())
+                })?;
+                let updated = format!(
+                    "{}{version}{}",
+                    &content[..range.start],
+                    &content[range.end..]
+                );
+                (path, content, updated)
+            }
+            DynamicVersionSource::File(path) => (
+                path,
+                trace::read_to_string(path).unwrap_or_default(),
+                format!("{version}\n"),
+            ),
+        };
+
+        Self::write_if_changed(path, &content, &updated_content)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Locate the quoted value of a top-level `name = "..."` assignment in Python source
+    fn python_attr_range(content: &str, name: &str) -> Option<std::ops::Range<usize>> {
+        let re = regex::Regex::new(&format!(
+            r#"(?m)^{}\s*(?::[^=\n]*)?=\s*(?:"([^"\n]*)"|'([^'\n]*)')"#,
+            regex::escape(name)
+        ))
+        .ok()?;
+        let caps = re.captures(content)?;
+        caps.get(1).or_else(|| caps.get(2)).map(|m| m.range())
+    }
+
+    /// Extract the top-level `version` string from a JSON manifest
+    ///
+    /// Distinguishes a missing field from one holding a non-string value.
+    fn json_version_str<'a>(json: &'a serde_json::Value, file: &str) -> Result<&'a str> {
+        let found = match json.get("version") {
+            None => anyhow::bail!("No version found in {file}"),
+            Some(serde_json::Value::String(version)) => return Ok(version),
+            Some(serde_json::Value::Null) => "null",
+            Some(serde_json::Value::Bool(_)) => "boolean",
+            Some(serde_json::Value::Number(_)) => "number",
+            Some(serde_json::Value::Array(_)) => "array",
+            Some(serde_json::Value::Object(_)) => "object",
+        };
+        anyhow::bail!("version field in {file} is not a string (found: {found})")
+    }
+
+    /// Match rewritten JSON to the original file's line endings and apply the
+    /// configured trailing-newline policy
+    ///
+    /// With [`config::JsonConfig::exact`] `updated` is returned as is, since only the
+    /// version text was replaced.
+    fn finish_json(&self, original: &str, updated: &str) -> String {
+        if self.config.json.exact {
+            return updated.to_string();
+        }
+        let newline = if original.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let body = updated.trim_end_matches(['\r', '\n']);
+        let body = if newline == "\r\n" && !body.contains("\r\n") {
+            body.replace('\n', newline)
+        } else {
+            body.to_string()
+        };
+        let trailing = match self.config.json.trailing_newline {
+            config::TrailingNewline::Preserve => original.ends_with('\n'),
+            config::TrailingNewline::Always => true,
+            config::TrailingNewline::Never => false,
+        };
+        if trailing { body + newline } else { body }
+    }
+
+    /// Byte range of the contents of the string value reached by following the object
+    /// keys in `path` from the root of well-formed JSON `content`, excluding the quotes
+    ///
+    /// Keys are compared as written, so escaped key names never match.
+    fn json_string_span(content: &str, path: &[&str]) -> Option<std::ops::Range<usize>> {
+        let (key, rest) = path.split_first()?;
+        let value = Self::top_level_value_start(content, key)?;
+        if rest.is_empty() {
+            let bytes = content.as_bytes();
+            if bytes.get(value) != Some(&b'"') {
+                return None;
+            }
+            return Some(value + 1..Self::json_string_end(bytes, value)? - 1);
+        }
+        // Descend into the nested object, which becomes the new top level
+        let span = Self::json_string_span(&content[value..], rest)?;
+        Some(value + span.start..value + span.end)
+    }
+
+    /// Index just past the closing quote of the JSON string opening at `start`
+    fn json_string_end(bytes: &[u8], start: usize) -> Option<usize> {
+        let mut i = start + 1;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => i += 2,
+                b'"' => return Some(i + 1),
+                _ => i += 1,
+            }
+        }
+        None
+    }
+
+    /// Index of the value stored under `key` in the object `content` starts with
+    ///
+    /// Scanning stops where that object closes, so keys of later siblings never match.
+    fn top_level_value_start(content: &str, key: &str) -> Option<usize> {
+        let bytes = content.as_bytes();
+        let skip_whitespace = |mut i: usize| {
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            i
+        };
+
+        let mut depth = 0usize;
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return None;
+                    }
+                }
+                b'"' => {
+                    let end = Self::json_string_end(bytes, i)?;
+                    let after = skip_whitespace(end);
+                    let is_key = depth == 1 && bytes.get(after) == Some(&b':');
+                    if is_key && &content[i + 1..end - 1] == key {
+                        return Some(skip_whitespace(after + 1));
+                    }
+                    i = end;
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Write `updated` to `path` unless it equals `original`, returning whether it wrote
+    ///
+    /// `original` is the file's current text, so a byte-identical result is never
+    /// written. Skipping unchanged files keeps their modification time, so syncing an
+    /// already current tree touches nothing and file watchers are not re-triggered.
+    fn write_if_changed(path: &Path, original: &str, updated: &str) -> std::io::Result<bool> {
+        if original == updated {
+            trace::skip(path, "unchanged");
+            return Ok(false);
+        }
+        trace::write(path, updated)?;
+        Ok(true)
+    }
+
+    /// Whether Cargo.toml content declares `[package] version` as workspace-inherited
+    fn cargo_version_is_inherited(content: &str) -> bool {
+        toml::from_str::<toml::Value>(content).is_ok_and(|doc| Self::cargo_doc_is_inherited(&doc))
+    }
+
+    /// Whether a parsed Cargo.toml declares `[package] version` as inherited from a
+    /// workspace root elsewhere
+    ///
+    /// A root package inheriting from its own `[workspace.package]` is not: the version
+    /// is declared in the same file.
+    fn cargo_doc_is_inherited(doc: &toml::Value) -> bool {
+        Self::is_workspace_inherited(doc.get("package").and_then(|p| p.get("version")))
+            && Self::cargo_workspace_version(doc).is_none()
+    }
+
+    /// Whether a parsed Cargo.toml keeps its version in its own `[workspace.package]`
+    ///
+    /// True for a virtual workspace root, and for a root package whose version is
+    /// `version.workspace = true`.
+    fn cargo_version_in_workspace_table(doc: &toml::Value) -> bool {
+        Self::cargo_is_virtual_workspace(doc)
+            || (Self::is_workspace_inherited(doc.get("package").and_then(|p| p.get("version")))
+                && Self::cargo_workspace_version(doc).is_some())
+    }
+
+    /// Whether a parsed Cargo.toml is a virtual workspace root (`[workspace]` without `[package]`)
+    fn cargo_is_virtual_workspace(doc: &toml::Value) -> bool {
+        doc.get("workspace").is_some() && doc.get("package").is_none()
+    }
+
+    /// The `[workspace.package] version` value that members inherit, if declared
+    fn cargo_workspace_version(doc: &toml::Value) -> Option<&toml::Value> {
+        doc.get("workspace")
+            .and_then(|w| w.get("package"))
+            .and_then(|p| p.get("version"))
+    }
+
+    /// Whether Cargo.toml content is a virtual workspace root with no version to manage
+    fn cargo_is_unversioned_virtual_workspace(content: &str) -> bool {
+        toml::from_str::<toml::Value>(content)
+            .is_ok_and(|doc| Self::cargo_doc_is_unversioned_virtual(&doc))
+    }
+
+    /// Whether a parsed Cargo.toml is a virtual workspace root with no version to manage
+    fn cargo_doc_is_unversioned_virtual(doc: &toml::Value) -> bool {
+        Self::cargo_is_virtual_workspace(doc) && Self::cargo_workspace_version(doc).is_none()
+    }
+
+    /// Whether a TOML value is the table `{ workspace = true }`
+    ///
+    /// Both `version.workspace = true` and `version = { workspace = true }`
+    /// parse to this shape.
+    fn is_workspace_inherited(value: Option<&toml::Value>) -> bool {
+        value
+            .and_then(|v| v.get("workspace"))
+            .and_then(toml::Value::as_bool)
+            == Some(true)
+    }
+
+    /// Check that `content` parses as a TOML document
+    fn validate_toml(content: &str) -> Result<()> {
+        content
+            .parse::<toml_edit::DocumentMut>()
+            .map(drop)
+            .context("Invalid TOML")
+    }
+
+    /// Check that `content` parses as a JSON document
+    fn validate_json(content: &str) -> Result<()> {
+        serde_json::from_str::<serde_json::Value>(content)
+            .map(drop)
+            .context("Invalid JSON")
+    }
+
+    /// Helper to update version in TOML content
+    ///
+    /// `section` is a dotted table path such as `package` or `tool.commitizen`, or empty
+    /// for a top-level `version` key before any table. The `version` key is found by
+    /// walking the parsed document, so key order and subtables such as
+    /// `[package.metadata]` cannot redirect the write. Comments and formatting are
+    /// preserved, as is the quote style: a literal string (`'1.2.3'`) is rewritten as one.
+    fn update_toml_version(
+        content: &str,
+        version: &impl std::fmt::Display,
+        section: &str,
+    ) -> Result<String> {
+        let mut doc: toml_edit::DocumentMut = content
+            .parse()
+            .context("Failed to parse TOML for version update")?;
+
+        let value = section
+            .split('.')
+            .filter(|key| !key.is_empty())
+            .try_fold(doc.as_item_mut(), |item, key| item.get_mut(key))
+            .and_then(|table| table.get_mut("version"))
+            .and_then(toml_edit::Item::as_value_mut)
+            .filter(|value| value.is_str())
+            .with_context(|| {
+                if section.is_empty() {
+                    "No top-level version field found".to_string()
+                } else {
+                    format!("No version field found in [{section}] section")
+                }
+            })?;
+
+        // Keep a literal string literal: `version = '1.2.3'` stays single-quoted
+        let literal = match &*value {
+            toml_edit::Value::String(text) => text
+                .as_repr()
+                .and_then(|repr| repr.as_raw().as_str())
+                .is_some_and(|raw| raw.starts_with('\'')),
+            _ => false,
+        };
+        let decor = value.decor().clone();
+        *value = if literal {
+            format!("'{version}'")
+                .parse()
+                .context("Failed to format version as a TOML literal string")?
+        } else {
+            toml_edit::Value::from(version.to_string())
+        };
+        *value.decor_mut() = decor;
+
+        Ok(doc.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_test_files(dir: &Path, version: &str) -> Result<()> {
+        // Create VERSION file
+        fs::write(dir.join("VERSION"), version)?;
+
+        // Create Cargo.toml
+        let cargo_content = format!(
             r#"[package]
 name = "test"
-version = "{version}"
-edition = "2021"
+version = "{version}"
+edition = "2021"
+
+[dependencies]
+"#
+        );
+        fs::write(dir.join("Cargo.toml"), cargo_content)?;
+
+        // Create pyproject.toml
+        let pyproject_content = format!(
+            r#"[project]
+name = "test"
+version = "{version}"
+description = "Test project"
+
+[build-system]
+requires = ["setuptools"]
+build-backend = "setuptools.build_meta"
+"#
+        );
+        fs::write(dir.join("pyproject.toml"), pyproject_content)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_version_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.2.3")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let version = manager.read_version_file()?;
+
+        assert_eq!(version, Version::new(1, 2, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_build_systems() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.0.0")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let systems = manager.detect_build_systems();
+
+        assert!(systems.contains(&BuildSystem::Cargo));
+        assert!(systems.contains(&BuildSystem::PyProject));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bump_major() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.2.3")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        manager.bump_version(BumpType::Major)?;
+
+        let version = manager.read_version_file()?;
+        assert_eq!(version, Version::new(2, 0, 0));
+
+        let cargo_version = manager.read_build_system_version(&BuildSystem::Cargo, None)?;
+        assert_eq!(cargo_version, Version::new(2, 0, 0));
+
+        let pyproject_version = manager.read_build_system_version(&BuildSystem::PyProject, None)?;
+        assert_eq!(pyproject_version, Version::new(2, 0, 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bump_minor() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.2.3")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        manager.bump_version(BumpType::Minor)?;
+
+        let version = manager.read_version_file()?;
+        assert_eq!(version, Version::new(1, 3, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_prerelease() -> Result<()> {
+        let v = |text: &str| Version::parse(text).unwrap();
+
+        // A bump starts a cycle on the bumped core version
+        assert_eq!(
+            next_prerelease(&v("1.2.3"), Some(BumpType::Minor), "rc")?,
+            v("1.3.0-rc.1")
+        );
+        assert_eq!(
+            next_prerelease(&v("1.3.0-rc.2"), Some(BumpType::Major), "beta")?,
+            v("2.0.0-beta.1")
+        );
+        // Without one the cycle continues, or moves on to a later identifier
+        assert_eq!(
+            next_prerelease(&v("1.3.0-rc.1"), None, "rc")?,
+            v("1.3.0-rc.2")
+        );
+        assert_eq!(
+            next_prerelease(&v("1.3.0-rc"), None, "rc")?,
+            v("1.3.0-rc.1")
+        );
+        assert_eq!(
+            next_prerelease(&v("1.3.0-beta.4"), None, "rc")?,
+            v("1.3.0-rc.1")
+        );
+
+        assert!(next_prerelease(&v("1.2.3"), None, "rc").is_err());
+        assert!(next_prerelease(&v("1.3.0-rc.1"), None, "beta").is_err());
+        for ident in ["", "rc.1", "7", "r c"] {
+            assert!(next_prerelease(&v("1.2.3"), Some(BumpType::Patch), ident).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bump_patch() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.2.3")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        manager.bump_version(BumpType::Patch)?;
+
+        let version = manager.read_version_file()?;
+        assert_eq!(version, Version::new(1, 2, 4));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset_version_to_default() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.2.3")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        manager.reset_version("0.0.0")?;
+
+        let version = manager.read_version_file()?;
+        assert_eq!(version, Version::new(0, 0, 0));
+
+        let cargo_version = manager.read_build_system_version(&BuildSystem::Cargo, None)?;
+        assert_eq!(cargo_version, Version::new(0, 0, 0));
+
+        let pyproject_version = manager.read_build_system_version(&BuildSystem::PyProject, None)?;
+        assert_eq!(pyproject_version, Version::new(0, 0, 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset_version_to_specific_version() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.2.3")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        manager.reset_version("3.5.7")?;
+
+        let version = manager.read_version_file()?;
+        assert_eq!(version, Version::new(3, 5, 7));
+
+        let cargo_version = manager.read_build_system_version(&BuildSystem::Cargo, None)?;
+        assert_eq!(cargo_version, Version::new(3, 5, 7));
+
+        let pyproject_version = manager.read_build_system_version(&BuildSystem::PyProject, None)?;
+        assert_eq!(pyproject_version, Version::new(3, 5, 7));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset_version_with_prerelease() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.0.0")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        manager.reset_version("2.0.0-alpha.1")?;
+
+        let version = manager.read_version_file()?;
+        assert_eq!(version.major, 2);
+        assert_eq!(version.minor, 0);
+        assert_eq!(version.patch, 0);
+        assert_eq!(version.pre.as_str(), "alpha.1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_carried_prerelease() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.2.3-beta.4")?;
+        let manager = VersionManager::new(temp_dir.path());
+
+        assert_eq!(
+            manager.carried_prerelease(false)?,
+            Some(semver::Prerelease::new("beta.4")?)
+        );
+        assert_eq!(
+            manager.carried_prerelease(true)?,
+            Some(semver::Prerelease::new("beta.1")?)
+        );
+
+        fs::write(temp_dir.path().join("VERSION"), "1.2.3-beta\n")?;
+        assert_eq!(
+            manager.carried_prerelease(true)?,
+            Some(semver::Prerelease::new("beta")?)
+        );
+
+        fs::write(temp_dir.path().join("VERSION"), "1.2.3\n")?;
+        assert_eq!(manager.carried_prerelease(false)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset_version_respects_bounds() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.0.0")?;
+        let mut config = Config::default();
+        config.reset.min_version = Some(Version::new(1, 0, 0));
+        config.reset.max_version = Some(Version::new(9, 0, 0));
+        let manager = VersionManager::new(temp_dir.path()).with_config(config);
+
+        let err = manager.reset_version("9.0.1").unwrap_err().to_string();
+        assert!(err.contains("above the maximum allowed version 9.0.0"));
+        // A prerelease of the minimum has lower precedence than the minimum itself
+        let err = manager.reset_version("1.0.0-rc.1").unwrap_err().to_string();
+        assert!(err.contains("below the minimum allowed version 1.0.0"));
+        assert!(manager.reset_cascade_dry_run("10.0.0").is_err());
+        assert_eq!(manager.read_version_file()?, Version::new(1, 0, 0));
+
+        manager.reset_version("9.0.0")?;
+        assert_eq!(manager.read_version_file()?, Version::new(9, 0, 0));
+        manager.reset_version("1.0.0")?;
+        assert_eq!(manager.read_version_file()?, Version::new(1, 0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset_version_invalid_format() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.0.0")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.reset_version("invalid-version");
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Invalid semantic version format")
+        );
+
+        // Verify original version is unchanged
+        let version = manager.read_version_file()?;
+        assert_eq!(version, Version::new(1, 0, 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset_version_empty_string() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.0.0")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.reset_version("");
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Invalid semantic version format")
+        );
+
+        Ok(())
+    }
+
+    fn create_package_json(dir: &Path, version: &str, with_dependencies: bool) -> Result<()> {
+        let package_json_content = if with_dependencies {
+            format!(
+                r#"{{
+  "name": "test-package",
+  "version": "{version}",
+  "description": "A test package",
+  "main": "index.js",
+  "scripts": {{
+    "test": "jest",
+    "build": "tsc"
+  }},
+  "dependencies": {{
+    "express": "^4.18.0"
+  }},
+  "devDependencies": {{
+    "typescript": "^5.0.0"
+  }}
+}}
+"#
+            )
+        } else {
+            format!(
+                r#"{{
+  "name": "test-package",
+  "version": "{version}"
+}}
+"#
+            )
+        };
+        fs::write(dir.join("package.json"), package_json_content)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_file_falls_back_to_conventional_names() -> Result<()> {
+        for name in ["VERSION.txt", ".version", "version"] {
+            let temp_dir = TempDir::new()?;
+            let dir = temp_dir.path();
+            fs::write(dir.join(name), "1.2.3\n")?;
+            let manager = VersionManager::new(dir);
+            assert_eq!(manager.version_file, name);
+            assert_eq!(manager.read_version_file()?, Version::parse("1.2.3")?);
+
+            // Writes go back to the discovered file rather than creating VERSION
+            manager.write_version_file(&Version::parse("1.3.0")?)?;
+            assert_eq!(fs::read_to_string(dir.join(name))?, "1.3.0\n");
+            assert_eq!(fs::read_dir(dir)?.count(), 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_absolute_version_file_outside_managed_root() -> Result<()> {
+        let project = TempDir::new()?;
+        let elsewhere = TempDir::new()?;
+        let version_path = elsewhere.path().join("VERSION");
+        fs::write(&version_path, "1.0.0\n")?;
+        fs::write(
+            project.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+        )?;
+        let manager =
+            VersionManager::with_version_file(project.path(), &version_path.to_string_lossy());
+        assert!(manager.version_file_outside_root());
+        assert!(!VersionManager::new(project.path()).version_file_outside_root());
+
+        // Manifests are found under the root and compared with the outside VERSION file
+        manager.verify_versions_in_sync()?;
+        manager.bump_version(BumpType::Minor)?;
+        assert_eq!(fs::read_to_string(&version_path)?, "1.1.0\n");
+        assert!(fs::read_to_string(project.path().join("Cargo.toml"))?.contains("1.1.0"));
+        assert!(!project.path().join("VERSION").exists());
+
+        fs::write(&version_path, "2.0.0\n")?;
+        assert!(manager.verify_versions_in_sync().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_file_prefers_earlier_names() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let dir = temp_dir.path();
+        fs::write(dir.join(".version"), "2.0.0\n")?;
+        fs::write(dir.join("VERSION.txt"), "1.0.0\n")?;
+        assert_eq!(VersionManager::new(dir).version_file, "VERSION.txt");
+
+        fs::write(dir.join("VERSION"), "3.0.0\n")?;
+        assert_eq!(VersionManager::new(dir).version_file, "VERSION");
+
+        // An explicit filename is used as given
+        let manager = VersionManager::with_version_file(dir, "missing.txt");
+        assert!(manager.read_version_file().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_version_file_lists_names_tried() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let err = VersionManager::new(temp_dir.path())
+            .read_version_file()
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("tried VERSION, VERSION.txt, .version, version"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_build_system_version_by_path() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let dir = temp_dir.path();
+        fs::create_dir(dir.join("web"))?;
+        fs::write(
+            dir.join("web/package.json"),
+            "{\n  \"name\": \"web\",\n  \"version\": \"1.0.0\"\n}\n",
+        )?;
+        fs::write(dir.join("README.md"), "# demo\n")?;
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"demo\"\n")?;
+        let manager = VersionManager::new(dir);
+        let version = Version::parse("2.0.0")?;
+
+        manager.set_build_system_version_by_path(Path::new("web/package.json"), &version)?;
+        assert_eq!(
+            manager.read_build_system_version(
+                &BuildSystem::PackageJson,
+                Some(&dir.join("web/package.json"))
+            )?,
+            version
+        );
+
+        let err = manager
+            .set_build_system_version_by_path(&dir.join("README.md"), &version)
+            .unwrap_err();
+        assert!(err.to_string().contains("is not a recognized manifest"));
+        assert!(
+            manager
+                .set_build_system_version_by_path(Path::new("Cargo.toml"), &version)
+                .is_err()
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("Cargo.toml"))?,
+            "[package]\nname = \"demo\"\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_classify_manifest() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let dir = temp_dir.path();
+        for (name, system) in [
+            ("Cargo.toml", BuildSystem::Cargo),
+            ("pyproject.toml", BuildSystem::PyProject),
+            ("package.json", BuildSystem::PackageJson),
+            ("CMakeLists.txt", BuildSystem::CMake),
+            ("main.tf", BuildSystem::Terraform),
+            ("MyLib.nuspec", BuildSystem::Nuspec),
+            ("gleam.toml", BuildSystem::Gleam),
+            ("Directory.Packages.props", BuildSystem::PackagesProps),
+        ] {
+            assert_eq!(classify_manifest(&dir.join("sub").join(name)), Some(system));
+        }
+        for name in [
+            "VERSION",
+            "README.md",
+            "Cargo.lock",
+            "variables.tf",
+            "setup.py",
+        ] {
+            assert_eq!(classify_manifest(&dir.join(name)), None);
+        }
+
+        // setup.cfg counts only when it declares a version
+        let setup_cfg = dir.join("setup.cfg");
+        assert_eq!(classify_manifest(&setup_cfg), None);
+        fs::write(&setup_cfg, "[flake8]\nmax-line-length = 100\n")?;
+        assert_eq!(classify_manifest(&setup_cfg), None);
+        fs::write(&setup_cfg, "[metadata]\nname = demo\nversion = 1.2.3\n")?;
+        assert_eq!(classify_manifest(&setup_cfg), Some(BuildSystem::SetupCfg));
+
+        // The manager honors a configured Terraform file
+        let mut manager = VersionManager::new(dir);
+        manager.config.terraform.file = "versions.tf".to_string();
+        assert_eq!(
+            manager.classify_manifest(&dir.join("versions.tf")),
+            Some(BuildSystem::Terraform)
+        );
+        assert_eq!(manager.classify_manifest(&dir.join("main.tf")), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_package_json() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
+        create_package_json(temp_dir.path(), "1.0.0", false)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let systems = manager.detect_build_systems();
+
+        assert!(systems.contains(&BuildSystem::PackageJson));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_package_json_version() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_package_json(temp_dir.path(), "2.3.4", false)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let version = manager.read_build_system_version(&BuildSystem::PackageJson, None)?;
+
+        assert_eq!(version, Version::new(2, 3, 4));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_package_json_version_with_dependencies() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_package_json(temp_dir.path(), "1.5.0", true)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let version = manager.read_build_system_version(&BuildSystem::PackageJson, None)?;
+
+        assert_eq!(version, Version::new(1, 5, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_package_json_version() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_package_json(temp_dir.path(), "1.0.0", false)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let new_version = Version::new(2, 0, 0);
+        manager.update_build_system_version(&BuildSystem::PackageJson, None, &new_version)?;
+
+        let version = manager.read_build_system_version(&BuildSystem::PackageJson, None)?;
+        assert_eq!(version, Version::new(2, 0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_package_json_preserves_other_fields() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_package_json(temp_dir.path(), "1.0.0", true)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let new_version = Version::new(3, 2, 1);
+        manager.update_build_system_version(&BuildSystem::PackageJson, None, &new_version)?;
+
+        // Read the file and verify other fields are preserved
+        let content = fs::read_to_string(temp_dir.path().join("package.json"))?;
+        let json: serde_json::Value = serde_json::from_str(&content)?;
+
+        assert_eq!(json["version"], "3.2.1");
+        assert_eq!(json["name"], "test-package");
+        assert_eq!(json["description"], "A test package");
+        assert!(json["dependencies"].is_object());
+        assert!(json["devDependencies"].is_object());
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_spec_distinguishes_requirements() -> Result<()> {
+        assert_eq!(
+            VersionSpec::parse("1.2.3")?,
+            VersionSpec::Exact(Version::new(1, 2, 3))
+        );
+        assert!(matches!(
+            VersionSpec::parse("^1.2.3")?,
+            VersionSpec::Requirement(_)
+        ));
+        assert!(VersionSpec::parse("not a version").is_err());
+
+        let next = Version::parse("2.0.0-rc.1+build.5")?;
+        assert_eq!(VersionSpec::retarget("^1.2.3", &next)?, "^2.0.0-rc.1");
+        assert_eq!(VersionSpec::retarget("~1.0", &next)?, "~2.0");
+        assert_eq!(VersionSpec::retarget(">= 1", &next)?, ">= 2");
+        assert_eq!(VersionSpec::retarget("1.2.3", &next)?, "2.0.0-rc.1+build.5");
+        assert!(VersionSpec::retarget(">=1, <2", &next).is_err());
+        assert!(VersionSpec::retarget("1.*", &next).is_err());
+
+        // A package version must be exact, not a requirement read as one
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"^1.2.3\"\n",
+        )?;
+        let err = VersionManager::new(temp_dir.path())
+            .read_build_system_version(&BuildSystem::Cargo, None)
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("^1.2.3 is a version requirement"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_calver_next_version() -> Result<()> {
+        let june: CalVerDate = "2024-06-15".parse()?;
+        assert_eq!(
+            june,
+            CalVerDate {
+                year: 2024,
+                month: 6
+            }
+        );
+        assert_eq!("2024-06".parse::<CalVerDate>()?, june);
+        assert!("2024-13-01".parse::<CalVerDate>().is_err());
+        assert!("24-06-01".parse::<CalVerDate>().is_err());
+
+        assert_eq!(
+            june.next_version(&Version::new(2024, 6, 2))?,
+            Version::new(2024, 6, 3)
+        );
+        assert_eq!(
+            june.next_version(&Version::new(2024, 5, 7))?,
+            Version::new(2024, 6, 0)
+        );
+        assert_eq!(
+            june.next_version(&Version::new(1, 4, 0))?,
+            Version::new(2024, 6, 0)
+        );
+        assert!(june.next_version(&Version::new(2024, 7, 0)).is_err());
+
+        assert_eq!(
+            CalVerDate::from_days_since_epoch(0),
+            CalVerDate {
+                year: 1970,
+                month: 1
+            }
+        );
+        // 2024-02-29 and 2024-03-01
+        assert_eq!(
+            CalVerDate::from_days_since_epoch(19_782),
+            CalVerDate {
+                year: 2024,
+                month: 2
+            }
+        );
+        assert_eq!(
+            CalVerDate::from_days_since_epoch(19_783),
+            CalVerDate {
+                year: 2024,
+                month: 3
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_package_json_only_touches_top_level_version() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let original = r#"{
+    "name": "test-package",
+    "version": "1.0.0",
+    "packageManager": "yarn@3.2.1",
+    "engines": { "node": ">=18.0.0", "version": "9.9.9" },
+    "dependencies": {
+        "lodash": "^4.17.21",
+        "nested": { "version": "1.0.0" }
+    },
+    "description": "escaped \"version\": \"1.0.0\""
+}
+"#;
+        fs::write(temp_dir.path().join("package.json"), original)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        assert!(manager.update_build_system_version(
+            &BuildSystem::PackageJson,
+            None,
+            &Version::new(2, 1, 0)
+        )?);
+
+        let content = fs::read_to_string(temp_dir.path().join("package.json"))?;
+        assert_eq!(
+            content,
+            original.replacen(r#""version": "1.0.0""#, r#""version": "2.1.0""#, 1)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_package_json_keeps_missing_trailing_newline() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("package.json");
+
+        // Spliced in place
+        fs::write(&path, r#"{"name": "test-package", "version": "1.0.0"}"#)?;
+        let manager = VersionManager::new(temp_dir.path());
+        assert!(manager.update_build_system_version(
+            &BuildSystem::PackageJson,
+            None,
+            &Version::new(1, 1, 0)
+        )?);
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            r#"{"name": "test-package", "version": "1.1.0"}"#
+        );
+
+        // Re-serialized, with CRLF line endings carried over
+        fs::write(&path, "{\r\n  \"name\": \"test-package\"\r\n}")?;
+        assert!(manager.update_build_system_version(
+            &BuildSystem::PackageJson,
+            None,
+            &Version::new(1, 1, 0)
+        )?);
+        let content = fs::read_to_string(&path)?;
+        assert!(!content.ends_with('\n'));
+        assert_eq!(
+            content.matches('\n').count(),
+            content.matches("\r\n").count()
+        );
+        assert!(content.contains("\"version\": \"1.1.0\""));
+
+        // An explicit policy overrides the original
+        let mut manager = VersionManager::new(temp_dir.path());
+        manager.config.json.trailing_newline = config::TrailingNewline::Always;
+        assert!(manager.update_build_system_version(
+            &BuildSystem::PackageJson,
+            None,
+            &Version::new(1, 2, 0)
+        )?);
+        assert!(fs::read_to_string(&path)?.ends_with("}\r\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_exact_json_edits_only_the_version_text() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("package.json");
+        let mut manager = VersionManager::new(temp_dir.path());
+        manager.config.json.exact = true;
+        manager.config.json.trailing_newline = config::TrailingNewline::Always;
+
+        // The trailing-newline policy is not applied; only the version text changes
+        let original = "{\n  \"name\": \"caf\\u00e9\",\n  \"version\": \"1.0.0\"\n}";
+        fs::write(&path, original)?;
+        assert!(manager.update_build_system_version(
+            &BuildSystem::PackageJson,
+            None,
+            &Version::new(1, 1, 0)
+        )?);
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            original.replace("1.0.0", "1.1.0")
+        );
+
+        // Without a string version there is nothing to edit in place
+        fs::write(&path, "{\"name\": \"test-package\"}\n")?;
+        let err = manager
+            .update_build_system_version(&BuildSystem::PackageJson, None, &Version::new(1, 1, 0))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("no string version to edit in place"), "{err}");
+        assert_eq!(fs::read_to_string(&path)?, "{\"name\": \"test-package\"}\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_bump_version_with_package_json() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.2.3")?;
+        create_package_json(temp_dir.path(), "1.2.3", true)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        manager.bump_version(BumpType::Minor)?;
+
+        let version = manager.read_version_file()?;
+        assert_eq!(version, Version::new(1, 3, 0));
+
+        let package_json_version =
+            manager.read_build_system_version(&BuildSystem::PackageJson, None)?;
+        assert_eq!(package_json_version, Version::new(1, 3, 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_all_build_systems() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.0.0")?;
+        create_package_json(temp_dir.path(), "1.0.0", false)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let systems = manager.detect_build_systems();
+
+        assert_eq!(systems.len(), 3);
+        assert!(systems.contains(&BuildSystem::Cargo));
+        assert!(systems.contains(&BuildSystem::PyProject));
+        assert!(systems.contains(&BuildSystem::PackageJson));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_versions_leaves_current_files_untouched() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.0.0")?;
+        create_package_json(temp_dir.path(), "0.9.0", false)?;
+        let cargo_path = temp_dir.path().join("Cargo.toml");
+        let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        fs::File::options()
+            .write(true)
+            .open(&cargo_path)?
+            .set_modified(old)?;
+        let before = fs::read(&cargo_path)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        assert_eq!(manager.sync_versions()?, 1);
+        assert_eq!(fs::read(&cargo_path)?, before);
+        assert_eq!(fs::metadata(&cargo_path)?.modified()?, old);
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PackageJson, None)?,
+            Version::new(1, 0, 0)
+        );
+
+        assert_eq!(manager.sync_versions()?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_versions_with_package_json() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "2.0.0")?;
+        create_package_json(temp_dir.path(), "1.0.0", true)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        manager.sync_versions()?;
+
+        let package_json_version =
+            manager.read_build_system_version(&BuildSystem::PackageJson, None)?;
+        assert_eq!(package_json_version, Version::new(2, 0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_versions_with_package_json_mismatch() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "2.0.0")?;
+        create_package_json(temp_dir.path(), "1.0.0", false)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.verify_versions_in_sync();
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Version files are not synchronized")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_package_json_with_prerelease() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_package_json(temp_dir.path(), "1.0.0-beta.2", false)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let version = manager.read_build_system_version(&BuildSystem::PackageJson, None)?;
+
+        assert_eq!(version.major, 1);
+        assert_eq!(version.minor, 0);
+        assert_eq!(version.patch, 0);
+        assert_eq!(version.pre.as_str(), "beta.2");
+        Ok(())
+    }
+
+    #[test]
+    fn test_package_json_missing_version_field() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let package_json_content = r#"{"name": "test-package"}"#;
+        fs::write(temp_dir.path().join("package.json"), package_json_content)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.read_build_system_version(&BuildSystem::PackageJson, None);
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("No version found in package.json")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pyproject_toml_missing_version_field() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let pyproject_content = r#"[project]
+name = "test"
+"#;
+        fs::write(temp_dir.path().join("pyproject.toml"), pyproject_content)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.read_build_system_version(&BuildSystem::PyProject, None);
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("No version found in pyproject.toml")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pyproject_version_in_later_table_is_not_edited() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let pyproject_content = r#"[project]
+name = "test"
+description = "No version here"
+
+[build-system]
+requires = ["setuptools"]
+build-backend = "setuptools.build_meta"
+
+[tool.something]
+version = "9.9.9"
+"#;
+        let path = temp_dir.path().join("pyproject.toml");
+        fs::write(&path, pyproject_content)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let err = manager
+            .update_build_system_version(&BuildSystem::PyProject, None, &Version::new(1, 2, 3))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "No version field found in [project] section");
+        assert_eq!(fs::read_to_string(&path)?, pyproject_content);
+
+        let err = manager
+            .read_build_system_version(&BuildSystem::PyProject, None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("No version found in pyproject.toml"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_cargo_toml_missing_version_field() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cargo_content = r#"[package]
+name = "test"
+"#;
+        fs::write(temp_dir.path().join("Cargo.toml"), cargo_content)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.read_build_system_version(&BuildSystem::Cargo, None);
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("No version found in Cargo.toml")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_package_json_invalid_json() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("package.json"), "not valid json {{")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.read_build_system_version(&BuildSystem::PackageJson, None);
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Failed to parse package.json")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pyproject_toml_invalid_toml() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("pyproject.toml"), "invalid toml [[[")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.read_build_system_version(&BuildSystem::PyProject, None);
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Failed to parse pyproject.toml")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cargo_toml_invalid_toml() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("Cargo.toml"), "invalid toml [[[")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.read_build_system_version(&BuildSystem::Cargo, None);
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Failed to parse Cargo.toml")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_file_invalid_semver() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "not-a-version")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.read_version_file();
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Invalid version format")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_file_with_whitespace() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "  1.2.3  \n")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let version = manager.read_version_file()?;
+
+        assert_eq!(version, Version::new(1, 2, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bump_version_with_out_of_sync_error() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.0.0")?;
+
+        // Manually modify Cargo.toml to be out of sync
+        let cargo_content = r#"[package]
+name = "test"
+version = "2.0.0"
+"#;
+        fs::write(temp_dir.path().join("Cargo.toml"), cargo_content)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.bump_version(BumpType::Patch);
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Version files are not synchronized")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_versions_with_all_three_build_systems() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        // Create files with 1.0.0 first
+        create_test_files(temp_dir.path(), "1.0.0")?;
+        create_package_json(temp_dir.path(), "1.0.0", false)?;
+
+        // Then update VERSION file to 5.0.0
+        fs::write(temp_dir.path().join("VERSION"), "5.0.0")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        manager.sync_versions()?;
+
+        // Verify all versions are now 5.0.0
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Cargo, None)?,
+            Version::new(5, 0, 0)
+        );
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PyProject, None)?,
+            Version::new(5, 0, 0)
+        );
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PackageJson, None)?,
+            Version::new(5, 0, 0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_versions_with_all_systems_in_sync() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "3.2.1")?;
+        create_package_json(temp_dir.path(), "3.2.1", false)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.verify_versions_in_sync();
+
+        assert!(result.is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_semver_with_build_metadata() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_test_files(temp_dir.path(), "1.0.0")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        manager.reset_version("1.0.0+build.123")?;
+
+        let version = manager.read_version_file()?;
+        assert_eq!(version.major, 1);
+        assert_eq!(version.minor, 0);
+        assert_eq!(version.patch, 0);
+        assert_eq!(version.build.as_str(), "build.123");
+        Ok(())
+    }
+
+    #[test]
+    fn test_package_json_not_an_object() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("package.json"), "[]")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.read_build_system_version(&BuildSystem::PackageJson, None);
+
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_package_json_not_an_object() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("package.json"), "[]")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.update_build_system_version(
+            &BuildSystem::PackageJson,
+            None,
+            &Version::new(1, 0, 0),
+        );
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("package.json root is not a JSON object")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_toml_version_update_no_version_field() {
+        let content = "[package]\nname = \"test\"\n";
+        let result =
+            VersionManager::update_toml_version(content, &Version::new(1, 0, 0), "package");
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("No version field found")
+        );
+    }
+
+    #[test]
+    fn test_cargo_toml_with_workspace() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cargo_content = r#"[workspace]
+members = ["member1", "member2"]
+
+[package]
+name = "test"
+version = "1.2.3"
+"#;
+        fs::write(temp_dir.path().join("Cargo.toml"), cargo_content)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let version = manager.read_build_system_version(&BuildSystem::Cargo, None)?;
+
+        assert_eq!(version, Version::new(1, 2, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_virtual_workspace_without_version_is_skipped() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.2.3\n")?;
+        let cargo_content = "[workspace]\nmembers = [\"member\"]\n";
+        fs::write(temp_dir.path().join("Cargo.toml"), cargo_content)?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let err = manager
+            .read_build_system_version(&BuildSystem::Cargo, None)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("virtual workspace has no package version"),
+            "{err}"
+        );
+        assert!(manager.build_system_is_virtual_workspace(&BuildSystem::Cargo));
+        assert!(manager.sync_issues()?.is_empty());
+
+        manager.bump_version(BumpType::Patch)?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("Cargo.toml"))?,
+            cargo_content
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_report_classifies_each_build_system() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.2.3\n")?;
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"member\"\nversion.workspace = true\n",
+        )?;
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{"name": "test", "version": "1.2.0"}"#,
+        )?;
+        fs::write(temp_dir.path().join("pyproject.toml"), "[project\n")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let report = manager.version_report()?;
+
+        assert_eq!(report.version, Version::new(1, 2, 3));
+        assert_eq!(
+            report.systems[0],
+            (
+                BuildSystem::Cargo,
+                temp_dir.path().join("Cargo.toml"),
+                ManifestVersion::Inherited
+            )
+        );
+        assert!(matches!(
+            report.systems[1],
+            (BuildSystem::PyProject, _, ManifestVersion::Unreadable(_))
+        ));
+        assert_eq!(
+            report.systems[2],
+            (
+                BuildSystem::PackageJson,
+                temp_dir.path().join("package.json"),
+                ManifestVersion::Declared {
+                    version: Version::new(1, 2, 0),
+                    in_sync: false,
+                }
+            )
+        );
+        assert_eq!(report.issues().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_virtual_workspace_package_version_is_managed() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.2.3\n")?;
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"member\"]\n\n[workspace.package]\nversion = \"1.2.3\"\nedition = \"2024\"\n",
+        )?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        assert!(!manager.build_system_is_virtual_workspace(&BuildSystem::Cargo));
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Cargo, None)?,
+            Version::new(1, 2, 3)
+        );
+
+        manager.bump_version(BumpType::Minor)?;
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Cargo, None)?,
+            Version::new(1, 3, 0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pure_inheritance_workspace_root_is_cargo_source() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let dir = temp_dir.path();
+        fs::write(dir.join("VERSION"), "1.2.3\n")?;
+        let root = "[workspace]\nmembers = [\"a\", \"b\"]\n\n[workspace.package]\nversion = \"1.2.3\"\n\n[package]\nname = \"root\"\nversion.workspace = true\n";
+        fs::write(dir.join("Cargo.toml"), root)?;
+        for member in ["a", "b"] {
+            fs::create_dir(dir.join(member))?;
+            fs::write(
+                dir.join(member).join("Cargo.toml"),
+                format!("[package]\nname = \"{member}\"\nversion.workspace = true\n"),
+            )?;
+        }
+
+        let manager = VersionManager::new(dir);
+        // The root package inherits from its own workspace table, which is read directly
+        assert!(!manager.build_system_has_no_version(&BuildSystem::Cargo));
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Cargo, None)?,
+            Version::new(1, 2, 3)
+        );
+        manager.verify_cascade_in_sync()?;
+
+        fs::write(dir.join("VERSION"), "1.3.0\n")?;
+        assert!(manager.verify_cascade_in_sync().is_err());
+        manager.sync_cascade()?;
+        manager.verify_cascade_in_sync()?;
+        assert_eq!(
+            fs::read_to_string(dir.join("Cargo.toml"))?,
+            root.replace("version = \"1.2.3\"", "version = \"1.3.0\"")
+        );
+        // Members keep inheriting rather than getting a literal version
+        for member in ["a", "b"] {
+            assert_eq!(
+                fs::read_to_string(dir.join(member).join("Cargo.toml"))?,
+                format!("[package]\nname = \"{member}\"\nversion.workspace = true\n")
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_snapshot_restores_written_and_created_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cargo = "[package]\nname = \"test\"\nversion = \"1.0.0\"\n";
+        fs::write(temp_dir.path().join("Cargo.toml"), cargo)?;
+        let manager = VersionManager::new(temp_dir.path());
+
+        // VERSION does not exist yet, so restoring removes it again
+        let snapshot = manager.snapshot_write_targets(false)?;
+        manager.reset_version("2.0.0")?;
+        assert!(temp_dir.path().join("VERSION").exists());
+        assert_eq!(snapshot.restore()?, 2);
+        assert!(!temp_dir.path().join("VERSION").exists());
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("Cargo.toml"))?,
+            cargo
+        );
+        assert_eq!(snapshot.restore()?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_detection_cache_reuses_walk_until_refresh() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
+        fs::create_dir(temp_dir.path().join("tool"))?;
+        fs::write(
+            temp_dir.path().join("tool/Cargo.toml"),
+            "[package]\nname = \"tool\"\nversion = \"1.0.0\"\n",
+        )?;
+        let manager = VersionManager::new(temp_dir.path()).with_detection_cache();
+        assert_eq!(manager.discover_manifests()?.len(), 1);
+
+        // A manifest added after the walk is not seen until a refresh
+        fs::create_dir(temp_dir.path().join("web"))?;
+        fs::write(
+            temp_dir.path().join("web/package.json"),
+            r#"{"name": "web", "version": "1.0.0"}"#,
+        )?;
+        assert_eq!(manager.discover_manifests()?.len(), 1);
+        assert_eq!(manager.refresh_detection()?, 2);
+
+        // A cached manifest that disappeared stops a write instead of going stale
+        fs::remove_file(temp_dir.path().join("tool/Cargo.toml"))?;
+        let err = manager.sync_cascade().unwrap_err().to_string();
+        assert!(err.contains("has disappeared"), "{err}");
+        assert_eq!(manager.refresh_detection()?, 1);
+        manager.sync_cascade()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_manifests_in_subdirectories() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        // Create subdirectories with manifest files
+        fs::create_dir(temp_dir.path().join("rust-tool"))?;
+        fs::write(
+            temp_dir.path().join("rust-tool/Cargo.toml"),
+            "[package]\nname = \"rust-tool\"\nversion = \"1.0.0\"\n",
+        )?;
+
+        fs::create_dir(temp_dir.path().join("python-client"))?;
+        fs::write(
+            temp_dir.path().join("python-client/pyproject.toml"),
+            "[project]\nname = \"python-client\"\nversion = \"1.0.0\"\n",
+        )?;
+
+        fs::create_dir(temp_dir.path().join("js-sdk"))?;
+        fs::write(
+            temp_dir.path().join("js-sdk/package.json"),
+            r#"{"name": "js-sdk", "version": "1.0.0"}"#,
+        )?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let manifests = manager.discover_manifests()?;
+
+        // Should find all three manifest files
+        assert_eq!(manifests.len(), 3);
+
+        // Verify each type is found
+        let cargo_found = manifests.iter().any(|(_, sys)| *sys == BuildSystem::Cargo);
+        let pyproject_found = manifests
+            .iter()
+            .any(|(_, sys)| *sys == BuildSystem::PyProject);
+        let packagejson_found = manifests
+            .iter()
+            .any(|(_, sys)| *sys == BuildSystem::PackageJson);
+
+        assert!(cargo_found, "Should find Cargo.toml");
+        assert!(pyproject_found, "Should find pyproject.toml");
+        assert!(packagejson_found, "Should find package.json");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_manifest_statuses_report_drift_and_unreadable() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
+        fs::create_dir(temp_dir.path().join("ok"))?;
+        fs::write(
+            temp_dir.path().join("ok/Cargo.toml"),
+            "[package]\nname = \"ok\"\nversion = \"1.0.0\"\n",
+        )?;
+        fs::create_dir(temp_dir.path().join("broken"))?;
+        fs::write(temp_dir.path().join("broken/package.json"), "{}")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let mut statuses = manager
+            .manifest_statuses()?
+            .map(|status| status.map(|s| s.relative_to(temp_dir.path())))
+            .collect::<Result<Vec<_>>>()?;
+        statuses.sort_by(|a, b| a.path.cmp(&b.path));
+
+        assert_eq!(
+            statuses,
+            vec![
+                ManifestStatus {
+                    path: "broken/package.json".into(),
+                    system: BuildSystem::PackageJson,
+                    version: None,
+                    in_sync: false,
+                },
+                ManifestStatus {
+                    path: "ok/Cargo.toml".into(),
+                    system: BuildSystem::Cargo,
+                    version: Some(Version::new(1, 0, 0)),
+                    in_sync: true,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_manifests_respects_gitignore() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        // Initialize git repo (required for ignore crate to respect .gitignore)
+        fs::create_dir(temp_dir.path().join(".git"))?;
+
+        // Create .gitignore
+        fs::write(
+            temp_dir.path().join(".gitignore"),
+            "target/\nnode_modules/\n.venv/\n",
+        )?;
+
+        // Create manifests in ignored directories
+        fs::create_dir(temp_dir.path().join("target"))?;
+        fs::write(
+            temp_dir.path().join("target/Cargo.toml"),
+            "[package]\nname = \"ignored\"\nversion = \"1.0.0\"\n",
+        )?;
+
+        fs::create_dir(temp_dir.path().join("node_modules"))?;
+        fs::write(
+            temp_dir.path().join("node_modules/package.json"),
+            r#"{"name": "ignored", "version": "1.0.0"}"#,
+        )?;
+
+        // Create manifests in non-ignored directory
+        fs::create_dir(temp_dir.path().join("src"))?;
+        fs::write(
+            temp_dir.path().join("src/Cargo.toml"),
+            "[package]\nname = \"not-ignored\"\nversion = \"1.0.0\"\n",
+        )?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let manifests = manager.discover_manifests()?;
+
+        // Should only find the non-ignored manifest
+        assert_eq!(
+            manifests.len(),
+            1,
+            "Should only find 1 manifest (not in ignored dirs)"
+        );
+
+        let found_path = &manifests[0].0;
+        assert!(
+            found_path.ends_with("src/Cargo.toml"),
+            "Should find src/Cargo.toml, got: {}",
+            found_path.display()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_manifests_skips_untracked_in_git_repo() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cargo = "[package]\nname = \"x\"\nversion = \"1.0.0\"\n";
+        for dir in ["tracked", "untracked"] {
+            fs::create_dir(temp_dir.path().join(dir))?;
+            fs::write(temp_dir.path().join(dir).join("Cargo.toml"), cargo)?;
+        }
+        // Outside a git repository every manifest is included
+        let with_untracked = |untracked| {
+            VersionManager::new(temp_dir.path()).with_discovery_options(DiscoveryOptions {
+                untracked,
+                ..DiscoveryOptions::default()
+            })
+        };
+        assert_eq!(
+            with_untracked(UntrackedManifests::Abort)
+                .discover_manifests()?
+                .len(),
+            2
+        );
+
+        init_git_repo(temp_dir.path())?;
+        std::process::Command::new("git")
+            .args(["add", "tracked/Cargo.toml"])
+            .current_dir(temp_dir.path())
+            .output()?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let manifests = manager.discover_manifests()?;
+        assert_eq!(manifests.len(), 1);
+        assert!(manifests[0].0.ends_with("tracked/Cargo.toml"));
+        assert_eq!(
+            manager.untracked_manifests()?,
+            vec![temp_dir.path().join("untracked/Cargo.toml")]
+        );
+
+        let include = with_untracked(UntrackedManifests::Include);
+        assert_eq!(include.discover_manifests()?.len(), 2);
+        assert!(include.untracked_manifests()?.is_empty());
+
+        let err = with_untracked(UntrackedManifests::Abort)
+            .discover_manifests()
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("untracked/Cargo.toml is not tracked by git"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_detection_cache_covers_untracked_manifests() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cargo = "[package]\nname = \"x\"\nversion = \"1.0.0\"\n";
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
+        for dir in ["tracked", "untracked"] {
+            fs::create_dir(temp_dir.path().join(dir))?;
+            fs::write(temp_dir.path().join(dir).join("Cargo.toml"), cargo)?;
+        }
+        init_git_repo(temp_dir.path())?;
+        std::process::Command::new("git")
+            .args(["add", "tracked/Cargo.toml"])
+            .current_dir(temp_dir.path())
+            .output()?;
+
+        let manager = VersionManager::new(temp_dir.path()).with_detection_cache();
+        assert_eq!(manager.discover_manifests()?.len(), 1);
+
+        // The untracked list and the cascade's skipped count come from the cached walk
+        fs::create_dir(temp_dir.path().join("later"))?;
+        fs::write(temp_dir.path().join("later/Cargo.toml"), cargo)?;
+        assert_eq!(manager.untracked_manifests()?.len(), 1);
+        assert_eq!(manager.sync_cascade()?.skipped, 1);
+
+        assert_eq!(manager.refresh_detection()?, 1);
+        assert_eq!(manager.untracked_manifests()?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_manifests_rejects_nested_version_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        // Create top-level VERSION
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
+
+        // Create nested VERSION file (should be error)
+        fs::create_dir(temp_dir.path().join("subproject"))?;
+        fs::write(temp_dir.path().join("subproject/VERSION"), "2.0.0")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.discover_manifests();
+
+        assert!(result.is_err(), "Should error on nested VERSION file");
+        let err_msg = result.unwrap_err().to_string();
+        assert!(
+            err_msg.contains("VERSION") || err_msg.contains("nested"),
+            "Error should mention nested VERSION file, got: {err_msg}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_discover_manifests_skips_symlinked_dirs() -> Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new()?;
+
+        // Create a real directory with manifest
+        fs::create_dir(temp_dir.path().join("real-dir"))?;
+        fs::write(
+            temp_dir.path().join("real-dir/Cargo.toml"),
+            "[package]\nname = \"real\"\nversion = \"1.0.0\"\n",
+        )?;
+
+        // Create a symlink to the directory, and a loop back to the root
+        symlink(
+            temp_dir.path().join("real-dir"),
+            temp_dir.path().join("link-dir"),
+        )?;
+        symlink(temp_dir.path(), temp_dir.path().join("real-dir/loop"))?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let manifests = manager.discover_manifests()?;
+
+        assert_eq!(
+            manifests.len(),
+            1,
+            "Symlinked directories should be skipped"
+        );
+        assert!(manifests[0].0.ends_with("real-dir/Cargo.toml"));
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_discover_manifests_follow_symlinks_detects_loop() -> Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new()?;
+        fs::create_dir(temp_dir.path().join("real-dir"))?;
+        symlink(temp_dir.path(), temp_dir.path().join("real-dir/loop"))?;
+
+        let manager =
+            VersionManager::new(temp_dir.path()).with_discovery_options(DiscoveryOptions {
+                follow_symlinks: true,
+                ..DiscoveryOptions::default()
+            });
+        assert!(manager.discover_manifests().is_err());
+
+        Ok(())
+    }
 
-[dependencies]
-"#
+    #[test]
+    #[cfg(unix)]
+    fn test_discover_manifests_rejects_symlinked_files() -> Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("real.toml"),
+            "[package]\nname = \"real\"\nversion = \"1.0.0\"\n",
+        )?;
+        symlink(
+            temp_dir.path().join("real.toml"),
+            temp_dir.path().join("Cargo.toml"),
+        )?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let err_msg = manager.discover_manifests().unwrap_err().to_string();
+        assert!(err_msg.contains("Symlink"), "{err_msg}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_manifests_default_excludes_and_depth() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cargo = "[package]\nname = \"x\"\nversion = \"1.0.0\"\n";
+        for dir in ["target", "node_modules", "vendor", "a", "a/b"] {
+            fs::create_dir_all(temp_dir.path().join(dir))?;
+            fs::write(temp_dir.path().join(dir).join("Cargo.toml"), cargo)?;
+        }
+
+        let manager = VersionManager::new(temp_dir.path());
+        assert_eq!(manager.discover_manifests()?.len(), 2);
+
+        let shallow =
+            VersionManager::new(temp_dir.path()).with_discovery_options(DiscoveryOptions {
+                max_depth: Some(2),
+                ..DiscoveryOptions::default()
+            });
+        let manifests = shallow.discover_manifests()?;
+        assert_eq!(manifests.len(), 1);
+        assert!(manifests[0].0.ends_with("a/Cargo.toml"));
+
+        let everything =
+            VersionManager::new(temp_dir.path()).with_discovery_options(DiscoveryOptions {
+                default_excludes: false,
+                ..DiscoveryOptions::default()
+            });
+        assert_eq!(everything.discover_manifests()?.len(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bump_cascade_updates_all_manifests() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        // Create top-level VERSION
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
+
+        // Create subdirectories with different manifest types
+        fs::create_dir(temp_dir.path().join("rust-tool"))?;
+        fs::write(
+            temp_dir.path().join("rust-tool/Cargo.toml"),
+            "[package]\nname = \"rust-tool\"\nversion = \"1.0.0\"\n",
+        )?;
+
+        fs::create_dir(temp_dir.path().join("python-client"))?;
+        fs::write(
+            temp_dir.path().join("python-client/pyproject.toml"),
+            "[project]\nname = \"python-client\"\nversion = \"1.0.0\"\n",
+        )?;
+
+        fs::create_dir(temp_dir.path().join("js-sdk"))?;
+        fs::write(
+            temp_dir.path().join("js-sdk/package.json"),
+            r#"{"name": "js-sdk", "version": "1.0.0"}"#,
+        )?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        manager.bump_cascade(BumpType::Minor)?;
+
+        // Verify VERSION was bumped
+        let version = manager.read_version_file()?;
+        assert_eq!(version, Version::new(1, 1, 0));
+
+        // Verify all manifests were updated
+        let cargo_content = fs::read_to_string(temp_dir.path().join("rust-tool/Cargo.toml"))?;
+        assert!(cargo_content.contains("version = \"1.1.0\""));
+
+        let pyproject_content =
+            fs::read_to_string(temp_dir.path().join("python-client/pyproject.toml"))?;
+        assert!(pyproject_content.contains("version = \"1.1.0\""));
+
+        let package_content = fs::read_to_string(temp_dir.path().join("js-sdk/package.json"))?;
+        assert!(package_content.contains("\"version\": \"1.1.0\""));
+
+        Ok(())
+    }
+
+    // Helper to initialize a git repo in a temp directory with an initial commit
+    fn init_git_repo(dir: &Path) -> Result<()> {
+        use std::process::Command;
+        let run = |args: &[&str]| -> Result<()> {
+            let output = Command::new("git").args(args).current_dir(dir).output()?;
+            if !output.status.success() {
+                anyhow::bail!(
+                    "git {:?} failed: {}",
+                    args,
+                    String::from_utf8_lossy(&output.stderr)
+                );
+            }
+            Ok(())
+        };
+
+        run(&["init"])?;
+        run(&["config", "user.email", "test@test.com"])?;
+        run(&["config", "user.name", "Test"])?;
+        run(&["commit", "--allow-empty", "-m", "init"])?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_tag_version_prefers_detected_prefix_at_head() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        init_git_repo(temp_dir.path())?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0+build.7\n")?;
+        for tag in ["v0.9.0", "v1.0.0", "docs-2.0.0"] {
+            std::process::Command::new("git")
+                .args(["tag", tag])
+                .current_dir(temp_dir.path())
+                .output()?;
+        }
+        let manager = VersionManager::new(temp_dir.path());
+
+        let (tag, version) = manager.tag_version(None)?;
+        assert_eq!((tag.as_str(), version), ("v1.0.0", Version::new(1, 0, 0)));
+        // Build metadata never makes a tag disagree
+        assert_eq!(manager.tag_mismatch(&tag, &Version::new(1, 0, 0))?, None);
+
+        let (tag, version) = manager.tag_version(Some("release-1.1.0"))?;
+        assert!(matches!(
+            manager.tag_mismatch(&tag, &version)?,
+            Some(SyncIssue::TagMismatch { .. })
+        ));
+        assert!(manager.tag_version(Some("latest")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_rc_tags_no_git() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let version = Version::new(1, 0, 0);
+        let result = manager.count_rc_tags(&version);
+
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_rc_tags_no_tags() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        init_git_repo(temp_dir.path())?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let version = Version::new(1, 0, 0);
+        let count = manager.count_rc_tags(&version)?;
+
+        assert_eq!(count, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_rc_tags_with_existing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        init_git_repo(temp_dir.path())?;
+
+        // Create RC tags
+        std::process::Command::new("git")
+            .args(["tag", "v1.0.0-rc.1"])
+            .current_dir(temp_dir.path())
+            .output()?;
+        std::process::Command::new("git")
+            .args(["tag", "v1.0.0-rc.2"])
+            .current_dir(temp_dir.path())
+            .output()?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let version = Version::new(1, 0, 0);
+        let count = manager.count_rc_tags(&version)?;
+
+        assert_eq!(count, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_rc_tags_ignores_other_versions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        init_git_repo(temp_dir.path())?;
+
+        std::process::Command::new("git")
+            .args(["tag", "v2.0.0-rc.1"])
+            .current_dir(temp_dir.path())
+            .output()?;
+        std::process::Command::new("git")
+            .args(["tag", "v1.0.0-rc.1"])
+            .current_dir(temp_dir.path())
+            .output()?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let version = Version::new(1, 0, 0);
+        let count = manager.count_rc_tags(&version)?;
+
+        assert_eq!(count, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_rc_version_first() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        init_git_repo(temp_dir.path())?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let rc = manager.next_rc_version()?;
+
+        assert_eq!(rc.to_string(), "1.0.0-rc.1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_rc_version_increment() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        init_git_repo(temp_dir.path())?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
+
+        std::process::Command::new("git")
+            .args(["tag", "v1.0.0-rc.1"])
+            .current_dir(temp_dir.path())
+            .output()?;
+        std::process::Command::new("git")
+            .args(["tag", "v1.0.0-rc.2"])
+            .current_dir(temp_dir.path())
+            .output()?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let rc = manager.next_rc_version()?;
+
+        assert_eq!(rc.to_string(), "1.0.0-rc.3");
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_rc_version_rejects_prerelease() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        init_git_repo(temp_dir.path())?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0-beta.1")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let result = manager.next_rc_version();
+
+        assert!(result.is_err());
+        let err_msg = result.unwrap_err().to_string();
+        assert!(
+            err_msg.contains("clean M.m.p"),
+            "Error should mention clean version requirement, got: {err_msg}"
         );
-        fs::write(dir.join("Cargo.toml"), cargo_content)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_version_file_with_inline_comment() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("VERSION"),
+            "1.2.3 # x-release-please-version\n",
+        )?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let version = manager.read_version_file()?;
+
+        assert_eq!(version, Version::new(1, 2, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_version_file_with_comment_no_space() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "2.0.0#some-comment\n")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let version = manager.read_version_file()?;
+
+        assert_eq!(version, Version::new(2, 0, 0));
+        Ok(())
+    }
 
-        // Create pyproject.toml
-        let pyproject_content = format!(
-            r#"[project]
-name = "test"
-version = "{version}"
-description = "Test project"
+    #[test]
+    fn test_strict_version_file_rejects_non_canonical_text() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut manager = VersionManager::new(temp_dir.path());
+        let version_path = temp_dir.path().join("VERSION");
 
-[build-system]
-requires = ["setuptools"]
-build-backend = "setuptools.build_meta"
-"#
+        // Leading zeros fail in either mode, naming the canonical form
+        fs::write(&version_path, "01.2.3\n")?;
+        let err = manager.read_version_file().unwrap_err();
+        assert!(
+            err.to_string().contains("01.2.3 (canonical form: 1.2.3)"),
+            "got: {err}"
         );
-        fs::write(dir.join("pyproject.toml"), pyproject_content)?;
+        fs::write(&version_path, "1.02.3-rc.01\n")?;
+        let err = manager.read_version_file().unwrap_err();
+        assert!(err.to_string().contains("(canonical form: 1.2.3-rc.1)"));
+
+        // Surrounding whitespace is trimmed unless strict
+        for content in ["  1.2.3\n", "1.2.3 \n", "1.2.3\n\n", "\t1.2.3"] {
+            fs::write(&version_path, content)?;
+            manager.config.version.strict = false;
+            assert_eq!(manager.read_version_file()?, Version::new(1, 2, 3));
+            manager.config.version.strict = true;
+            let err = manager.read_version_file().unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("differs from its canonical form 1.2.3"),
+                "{content:?}: {err}"
+            );
+        }
 
+        // Prefixes, comments and one line ending are canonical
+        for content in [
+            "1.2.3",
+            "1.2.3\n",
+            "1.2.3\r\n",
+            "v1.2.3\n",
+            "1.2.3 # x-release-please-version\n",
+            "1.2.3#note\n",
+        ] {
+            fs::write(&version_path, content)?;
+            assert_eq!(manager.read_version_file()?, Version::new(1, 2, 3));
+        }
         Ok(())
     }
 
     #[test]
-    fn test_read_version_file() -> Result<()> {
+    fn test_write_version_file_preserves_comment() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "1.2.3")?;
+        fs::write(
+            temp_dir.path().join("VERSION"),
+            "1.0.0 # x-release-please-version\n",
+        )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let version = manager.read_version_file()?;
+        manager.write_version_file(&Version::new(2, 3, 4))?;
 
-        assert_eq!(version, Version::new(1, 2, 3));
+        let content = fs::read_to_string(temp_dir.path().join("VERSION"))?;
+        assert_eq!(content, "2.3.4 # x-release-please-version\n");
         Ok(())
     }
 
     #[test]
-    fn test_detect_build_systems() -> Result<()> {
+    fn test_write_version_file_no_comment() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "1.0.0")?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let systems = manager.detect_build_systems();
+        manager.write_version_file(&Version::new(3, 0, 0))?;
 
-        assert!(systems.contains(&BuildSystem::Cargo));
-        assert!(systems.contains(&BuildSystem::PyProject));
+        let content = fs::read_to_string(temp_dir.path().join("VERSION"))?;
+        assert_eq!(content, "3.0.0\n");
         Ok(())
     }
 
     #[test]
-    fn test_bump_major() -> Result<()> {
+    fn test_v_prefixed_version_file_is_read_and_preserved() -> Result<()> {
         let temp_dir = TempDir::new()?;
         create_test_files(temp_dir.path(), "1.2.3")?;
+        fs::write(temp_dir.path().join("VERSION"), "v1.2.3 # release\n")?;
 
         let manager = VersionManager::new(temp_dir.path());
-        manager.bump_version(BumpType::Major)?;
+        assert_eq!(manager.read_version_file()?, Version::new(1, 2, 3));
 
-        let version = manager.read_version_file()?;
-        assert_eq!(version, Version::new(2, 0, 0));
+        manager.bump_version(BumpType::Minor)?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("VERSION"))?,
+            "v1.3.0 # release\n"
+        );
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Cargo, None)?,
+            Version::new(1, 3, 0)
+        );
+        Ok(())
+    }
 
-        let cargo_version = manager.read_cargo_version()?;
-        assert_eq!(cargo_version, Version::new(2, 0, 0));
+    #[test]
+    fn test_v_prefix_config_adds_or_removes_prefix() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
 
-        let pyproject_version = manager.read_pyproject_version()?;
-        assert_eq!(pyproject_version, Version::new(2, 0, 0));
+        let mut config = Config::default();
+        config.version.v_prefix = Some(true);
+        let manager = VersionManager::new(temp_dir.path()).with_config(config.clone());
+        manager.write_version_file(&Version::new(1, 1, 0))?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("VERSION"))?,
+            "v1.1.0\n"
+        );
 
+        config.version.v_prefix = Some(false);
+        let manager = VersionManager::new(temp_dir.path()).with_config(config);
+        manager.write_version_file(&Version::new(1, 2, 0))?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("VERSION"))?,
+            "1.2.0\n"
+        );
         Ok(())
     }
 
     #[test]
-    fn test_bump_minor() -> Result<()> {
+    fn test_write_version_file_new_file() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "1.2.3")?;
+        // No VERSION file exists
 
         let manager = VersionManager::new(temp_dir.path());
-        manager.bump_version(BumpType::Minor)?;
+        manager.write_version_file(&Version::new(1, 0, 0))?;
 
-        let version = manager.read_version_file()?;
-        assert_eq!(version, Version::new(1, 3, 0));
+        let content = fs::read_to_string(temp_dir.path().join("VERSION"))?;
+        assert_eq!(content, "1.0.0\n");
         Ok(())
     }
 
     #[test]
-    fn test_bump_patch() -> Result<()> {
+    fn test_bump_roundtrip_with_comment() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "1.2.3")?;
+        fs::write(
+            temp_dir.path().join("VERSION"),
+            "1.0.0 # x-release-please-version\n",
+        )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        manager.bump_version(BumpType::Patch)?;
 
+        // Read should strip comment
+        let version = manager.read_version_file()?;
+        assert_eq!(version, Version::new(1, 0, 0));
+
+        // Write should preserve comment
+        let bumped = Version::new(1, 1, 0);
+        manager.write_version_file(&bumped)?;
+
+        let content = fs::read_to_string(temp_dir.path().join("VERSION"))?;
+        assert_eq!(content, "1.1.0 # x-release-please-version\n");
+
+        // Read again should work
         let version = manager.read_version_file()?;
-        assert_eq!(version, Version::new(1, 2, 4));
+        assert_eq!(version, Version::new(1, 1, 0));
         Ok(())
     }
 
     #[test]
-    fn test_reset_version_to_default() -> Result<()> {
+    fn test_with_version_file_reads_custom_filename() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "1.2.3")?;
-
-        let manager = VersionManager::new(temp_dir.path());
-        manager.reset_version("0.0.0")?;
+        fs::write(temp_dir.path().join("version.txt"), "3.2.1\n")?;
 
+        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
         let version = manager.read_version_file()?;
-        assert_eq!(version, Version::new(0, 0, 0));
 
-        let cargo_version = manager.read_cargo_version()?;
-        assert_eq!(cargo_version, Version::new(0, 0, 0));
+        assert_eq!(version, Version::new(3, 2, 1));
+        Ok(())
+    }
 
-        let pyproject_version = manager.read_pyproject_version()?;
-        assert_eq!(pyproject_version, Version::new(0, 0, 0));
+    #[test]
+    fn test_with_version_file_writes_custom_filename() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("version.txt"), "1.0.0\n")?;
+
+        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
+        manager.write_version_file(&Version::new(2, 0, 0))?;
+
+        let content = fs::read_to_string(temp_dir.path().join("version.txt"))?;
+        assert_eq!(content, "2.0.0\n");
 
+        // Ensure no "VERSION" file was created
+        assert!(!temp_dir.path().join("VERSION").exists());
         Ok(())
     }
 
     #[test]
-    fn test_reset_version_to_specific_version() -> Result<()> {
+    fn test_with_version_file_bump_syncs_all() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "1.2.3")?;
+        fs::write(temp_dir.path().join("version.txt"), "1.0.0\n")?;
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+        )?;
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"test\"\nversion = \"1.0.0\"\n",
+        )?;
 
-        let manager = VersionManager::new(temp_dir.path());
-        manager.reset_version("3.5.7")?;
+        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
+        manager.bump_version(BumpType::Minor)?;
 
-        let version = manager.read_version_file()?;
-        assert_eq!(version, Version::new(3, 5, 7));
+        assert_eq!(manager.read_version_file()?, Version::new(1, 1, 0));
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Cargo, None)?,
+            Version::new(1, 1, 0)
+        );
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PyProject, None)?,
+            Version::new(1, 1, 0)
+        );
+        Ok(())
+    }
 
-        let cargo_version = manager.read_cargo_version()?;
-        assert_eq!(cargo_version, Version::new(3, 5, 7));
+    #[test]
+    fn test_with_version_file_sync() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("version.txt"), "5.0.0\n")?;
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+        )?;
 
-        let pyproject_version = manager.read_pyproject_version()?;
-        assert_eq!(pyproject_version, Version::new(3, 5, 7));
+        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
+        manager.sync_versions()?;
 
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Cargo, None)?,
+            Version::new(5, 0, 0)
+        );
         Ok(())
     }
 
     #[test]
-    fn test_reset_version_with_prerelease() -> Result<()> {
+    fn test_with_version_file_verify_in_sync() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "1.0.0")?;
+        fs::write(temp_dir.path().join("version.txt"), "2.0.0\n")?;
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"2.0.0\"\n",
+        )?;
 
-        let manager = VersionManager::new(temp_dir.path());
-        manager.reset_version("2.0.0-alpha.1")?;
+        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
+        assert!(manager.verify_versions_in_sync().is_ok());
+        Ok(())
+    }
 
-        let version = manager.read_version_file()?;
-        assert_eq!(version.major, 2);
-        assert_eq!(version.minor, 0);
-        assert_eq!(version.patch, 0);
-        assert_eq!(version.pre.as_str(), "alpha.1");
+    #[test]
+    fn test_with_version_file_verify_out_of_sync() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("version.txt"), "2.0.0\n")?;
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+        )?;
 
+        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
+        assert!(manager.verify_versions_in_sync().is_err());
         Ok(())
     }
 
     #[test]
-    fn test_reset_version_invalid_format() -> Result<()> {
+    fn test_with_version_file_comment_preservation() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "1.0.0")?;
-
-        let manager = VersionManager::new(temp_dir.path());
-        let result = manager.reset_version("invalid-version");
+        fs::write(
+            temp_dir.path().join("version.txt"),
+            "1.0.0 # x-release-please-version\n",
+        )?;
 
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("Invalid semantic version format")
-        );
+        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
 
-        // Verify original version is unchanged
+        // Read strips comment
         let version = manager.read_version_file()?;
         assert_eq!(version, Version::new(1, 0, 0));
 
+        // Write preserves comment
+        manager.write_version_file(&Version::new(2, 0, 0))?;
+        let content = fs::read_to_string(temp_dir.path().join("version.txt"))?;
+        assert_eq!(content, "2.0.0 # x-release-please-version\n");
         Ok(())
     }
 
     #[test]
-    fn test_reset_version_empty_string() -> Result<()> {
+    fn test_default_version_file_constant() {
+        assert_eq!(DEFAULT_VERSION_FILE, "VERSION");
+    }
+
+    #[test]
+    fn test_new_uses_default_filename() {
+        let manager = VersionManager::new("/tmp");
+        assert_eq!(manager.version_file, "VERSION");
+    }
+
+    #[test]
+    fn test_bump_cascade_dry_run_does_not_write_files() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "1.0.0")?;
 
-        let manager = VersionManager::new(temp_dir.path());
-        let result = manager.reset_version("");
+        // Create top-level VERSION
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
+
+        // Create subdirectories with different manifest types
+        fs::create_dir(temp_dir.path().join("rust-tool"))?;
+        fs::write(
+            temp_dir.path().join("rust-tool/Cargo.toml"),
+            "[package]\nname = \"rust-tool\"\nversion = \"1.0.0\"\n",
+        )?;
+
+        fs::create_dir(temp_dir.path().join("python-client"))?;
+        fs::write(
+            temp_dir.path().join("python-client/pyproject.toml"),
+            "[project]\nname = \"python-client\"\nversion = \"1.0.0\"\n",
+        )?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        let changes = manager.bump_cascade_dry_run(BumpType::Patch)?;
+
+        // Should return preview of changes
+        assert_eq!(changes.new_version, Version::new(1, 0, 1));
+        assert_eq!(changes.files_to_update.len(), 3); // VERSION + Cargo.toml + pyproject.toml
+
+        // Verify files were NOT changed
+        let version = manager.read_version_file()?;
+        assert_eq!(
+            version,
+            Version::new(1, 0, 0),
+            "VERSION should not change in dry-run"
+        );
+
+        let cargo_content = fs::read_to_string(temp_dir.path().join("rust-tool/Cargo.toml"))?;
+        assert!(
+            cargo_content.contains("version = \"1.0.0\""),
+            "Cargo.toml should not change in dry-run"
+        );
 
-        assert!(result.is_err());
+        let pyproject_content =
+            fs::read_to_string(temp_dir.path().join("python-client/pyproject.toml"))?;
         assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("Invalid semantic version format")
+            pyproject_content.contains("version = \"1.0.0\""),
+            "pyproject.toml should not change in dry-run"
         );
 
         Ok(())
     }
 
-    fn create_package_json(dir: &Path, version: &str, with_dependencies: bool) -> Result<()> {
-        let package_json_content = if with_dependencies {
-            format!(
-                r#"{{
-  "name": "test-package",
-  "version": "{version}",
-  "description": "A test package",
-  "main": "index.js",
-  "scripts": {{
-    "test": "jest",
-    "build": "tsc"
-  }},
-  "dependencies": {{
-    "express": "^4.18.0"
-  }},
-  "devDependencies": {{
-    "typescript": "^5.0.0"
-  }}
-}}
-"#
-            )
-        } else {
-            format!(
-                r#"{{
-  "name": "test-package",
-  "version": "{version}"
-}}
-"#
-            )
-        };
-        fs::write(dir.join("package.json"), package_json_content)?;
+    fn git_tag(dir: &Path, name: &str) -> Result<()> {
+        std::process::Command::new("git")
+            .args(["tag", name])
+            .current_dir(dir)
+            .output()?;
         Ok(())
     }
 
     #[test]
-    fn test_detect_package_json() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
-        create_package_json(temp_dir.path(), "1.0.0", false)?;
+    fn test_split_tag_version() {
+        assert_eq!(VersionManager::split_tag_version("v1.2.3"), Some("v"));
+        assert_eq!(
+            VersionManager::split_tag_version("release-1.2.3-rc.1"),
+            Some("release-")
+        );
+        assert_eq!(VersionManager::split_tag_version("1.2.3"), Some(""));
+        assert_eq!(
+            VersionManager::split_tag_version("app2-1.0.0"),
+            Some("app2-")
+        );
+        assert_eq!(VersionManager::split_tag_version("nightly"), None);
+    }
 
+    #[test]
+    fn test_detect_tag_prefix_uses_dominant_prefix() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        init_git_repo(temp_dir.path())?;
         let manager = VersionManager::new(temp_dir.path());
-        let systems = manager.detect_build_systems();
-
-        assert!(systems.contains(&BuildSystem::PackageJson));
+        assert_eq!(manager.detect_tag_prefix()?, None);
+        assert_eq!(manager.resolve_tag_prefix(None), DEFAULT_TAG_PREFIX);
+
+        git_tag(temp_dir.path(), "release-1.0.0")?;
+        git_tag(temp_dir.path(), "release-1.1.0")?;
+        git_tag(temp_dir.path(), "v0.9.0")?;
+        git_tag(temp_dir.path(), "nightly")?;
+        assert_eq!(manager.detect_tag_prefix()?, Some("release-".to_string()));
+        assert_eq!(manager.resolve_tag_prefix(None), "release-");
+        assert_eq!(manager.resolve_tag_prefix(Some("v")), "v");
+
+        // A tie has no dominant prefix
+        git_tag(temp_dir.path(), "v0.8.0")?;
+        assert_eq!(manager.detect_tag_prefix()?, None);
+        assert_eq!(manager.resolve_tag_prefix(None), DEFAULT_TAG_PREFIX);
         Ok(())
     }
 
     #[test]
-    fn test_read_package_json_version() -> Result<()> {
+    fn test_latest_tag_version_ignores_non_semver() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_package_json(temp_dir.path(), "2.3.4", false)?;
+        init_git_repo(temp_dir.path())?;
+        git_tag(temp_dir.path(), "v1.2.0")?;
+        git_tag(temp_dir.path(), "v1.10.0")?;
+        git_tag(temp_dir.path(), "v-nightly")?;
+        git_tag(temp_dir.path(), "release-9.0.0")?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let version = manager.read_package_json_version()?;
-
-        assert_eq!(version, Version::new(2, 3, 4));
+        assert_eq!(
+            manager.latest_tag_version("v")?,
+            Some(Version::new(1, 10, 0))
+        );
+        assert_eq!(
+            manager.latest_tag_version("release-")?,
+            Some(Version::new(9, 0, 0))
+        );
         Ok(())
     }
 
     #[test]
-    fn test_read_package_json_version_with_dependencies() -> Result<()> {
+    fn test_check_tag_advances_rejects_same_or_lower() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_package_json(temp_dir.path(), "1.5.0", true)?;
+        init_git_repo(temp_dir.path())?;
+        git_tag(temp_dir.path(), "v1.2.3")?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let version = manager.read_package_json_version()?;
-
-        assert_eq!(version, Version::new(1, 5, 0));
+        assert!(
+            manager
+                .check_tag_advances(&Version::new(1, 2, 3), "v")
+                .is_err()
+        );
+        assert!(
+            manager
+                .check_tag_advances(&Version::new(1, 2, 2), "v")
+                .is_err()
+        );
+        assert!(
+            manager
+                .check_tag_advances(&Version::new(1, 2, 4), "v")
+                .is_ok()
+        );
         Ok(())
     }
 
     #[test]
-    fn test_update_package_json_version() -> Result<()> {
+    fn test_check_tag_advances_refuses_lower_counter_of_same_label() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_package_json(temp_dir.path(), "1.0.0", false)?;
+        init_git_repo(temp_dir.path())?;
+        git_tag(temp_dir.path(), "v1.2.0-rc.2")?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let new_version = Version::new(2, 0, 0);
-        manager.update_package_json_version(&new_version)?;
-
-        let version = manager.read_package_json_version()?;
-        assert_eq!(version, Version::new(2, 0, 0));
+        let err = manager
+            .check_tag_advances(&Version::parse("1.2.0-rc.1")?, "v")
+            .unwrap_err();
+        assert!(err.to_string().contains("is not greater"), "{err}");
+        assert!(
+            manager
+                .check_tag_advances(&Version::parse("1.2.0-rc.2")?, "v")
+                .is_err()
+        );
+        for ok in ["1.2.0-rc.3", "1.2.0-beta.1", "1.2.0"] {
+            assert!(
+                manager
+                    .check_tag_advances(&Version::parse(ok)?, "v")
+                    .is_ok(),
+                "{ok}"
+            );
+        }
         Ok(())
     }
 
     #[test]
-    fn test_update_package_json_preserves_other_fields() -> Result<()> {
+    fn test_check_tag_advances_prerelease_of_same_core() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_package_json(temp_dir.path(), "1.0.0", true)?;
-
-        let manager = VersionManager::new(temp_dir.path());
-        let new_version = Version::new(3, 2, 1);
-        manager.update_package_json_version(&new_version)?;
+        init_git_repo(temp_dir.path())?;
+        git_tag(temp_dir.path(), "v1.2.0")?;
+        git_tag(temp_dir.path(), "v1.2.0-rc.2")?;
 
-        // Read the file and verify other fields are preserved
-        let content = fs::read_to_string(temp_dir.path().join("package.json"))?;
-        let json: serde_json::Value = serde_json::from_str(&content)?;
+        let mut manager = VersionManager::new(temp_dir.path());
+        // A new prerelease cycle of the latest core version is allowed
+        assert!(
+            manager
+                .check_tag_advances(&Version::parse("1.2.0-rc.1")?, "v")
+                .is_ok()
+        );
+        assert!(
+            manager
+                .check_tag_advances(&Version::parse("1.2.0-alpha")?, "v")
+                .is_ok()
+        );
+        // Core decreases and the exact latest version are still refused
+        assert!(
+            manager
+                .check_tag_advances(&Version::parse("1.1.9-rc.1")?, "v")
+                .is_err()
+        );
+        assert!(
+            manager
+                .check_tag_advances(&Version::new(1, 2, 0), "v")
+                .is_err()
+        );
 
-        assert_eq!(json["version"], "3.2.1");
-        assert_eq!(json["name"], "test-package");
-        assert_eq!(json["description"], "A test package");
-        assert!(json["dependencies"].is_object());
-        assert!(json["devDependencies"].is_object());
+        manager.config.tag.block_prerelease_downgrade = true;
+        let err = manager
+            .check_tag_advances(&Version::parse("1.2.0-rc.1")?, "v")
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("prerelease downgrades are blocked")
+        );
+        assert!(
+            manager
+                .check_tag_advances(&Version::parse("1.2.1-rc.1")?, "v")
+                .is_ok()
+        );
         Ok(())
     }
 
     #[test]
-    fn test_bump_version_with_package_json() -> Result<()> {
+    fn test_create_tag_with_and_without_retag() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("VERSION"), "1.2.3")?;
-        create_package_json(temp_dir.path(), "1.2.3", true)?;
+        init_git_repo(temp_dir.path())?;
 
         let manager = VersionManager::new(temp_dir.path());
-        manager.bump_version(BumpType::Minor)?;
-
-        let version = manager.read_version_file()?;
-        assert_eq!(version, Version::new(1, 3, 0));
+        let version = Version::new(1, 0, 0);
+        assert_eq!(
+            manager.create_tag(&version, "v", false, TagKind::Lightweight)?,
+            "v1.0.0"
+        );
 
-        let package_json_version = manager.read_package_json_version()?;
-        assert_eq!(package_json_version, Version::new(1, 3, 0));
+        let err = manager
+            .create_tag(&version, "v", false, TagKind::Lightweight)
+            .unwrap_err();
+        assert!(err.to_string().contains("--allow-retag"));
 
+        assert_eq!(
+            manager.create_tag(&version, "v", true, TagKind::Lightweight)?,
+            "v1.0.0"
+        );
         Ok(())
     }
 
     #[test]
-    fn test_detect_all_build_systems() -> Result<()> {
+    fn test_create_annotated_tag() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "1.0.0")?;
-        create_package_json(temp_dir.path(), "1.0.0", false)?;
+        init_git_repo(temp_dir.path())?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let systems = manager.detect_build_systems();
+        manager.create_tag(&Version::new(1, 0, 0), "v", false, TagKind::Annotated)?;
+        assert_eq!(
+            manager.run_git(&["cat-file", "-t", "v1.0.0"])?.trim(),
+            "tag"
+        );
+        assert!(
+            manager
+                .run_git(&["tag", "-l", "--format=%(contents)", "v1.0.0"])?
+                .contains("Release v1.0.0")
+        );
 
-        assert_eq!(systems.len(), 3);
-        assert!(systems.contains(&BuildSystem::Cargo));
-        assert!(systems.contains(&BuildSystem::PyProject));
-        assert!(systems.contains(&BuildSystem::PackageJson));
+        // Moving a tag replaces it with the requested kind
+        manager.create_tag(&Version::new(1, 0, 0), "v", true, TagKind::Lightweight)?;
+        assert_eq!(
+            manager.run_git(&["cat-file", "-t", "v1.0.0"])?.trim(),
+            "commit"
+        );
         Ok(())
     }
 
     #[test]
-    fn test_sync_versions_with_package_json() -> Result<()> {
+    fn test_cargo_inherited_version_dotted_is_skipped() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("VERSION"), "2.0.0")?;
-        create_package_json(temp_dir.path(), "1.0.0", true)?;
+        let cargo_content =
+            "[package]\nname = \"member\"\nversion.workspace = true\nrust-version = \"1.85\"\n";
+        fs::write(temp_dir.path().join("Cargo.toml"), cargo_content)?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
 
         let manager = VersionManager::new(temp_dir.path());
-        manager.sync_versions()?;
+        assert!(manager.build_system_inherits_version(&BuildSystem::Cargo));
+        assert!(manager.verify_versions_in_sync().is_ok());
 
-        let package_json_version = manager.read_package_json_version()?;
-        assert_eq!(package_json_version, Version::new(2, 0, 0));
+        manager.update_build_system_version(&BuildSystem::Cargo, None, &Version::new(2, 0, 0))?;
+        let content = fs::read_to_string(temp_dir.path().join("Cargo.toml"))?;
+        assert_eq!(content, cargo_content);
         Ok(())
     }
 
     #[test]
-    fn test_verify_versions_with_package_json_mismatch() -> Result<()> {
+    fn test_cargo_inherited_version_inline_table_is_skipped() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("VERSION"), "2.0.0")?;
-        create_package_json(temp_dir.path(), "1.0.0", false)?;
+        let cargo_content =
+            "[package]\nname = \"member\"\nversion = { workspace = true }\nedition = \"2021\"\n";
+        fs::write(temp_dir.path().join("Cargo.toml"), cargo_content)?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let result = manager.verify_versions_in_sync();
-
-        assert!(result.is_err());
+        assert!(manager.build_system_inherits_version(&BuildSystem::Cargo));
         assert!(
-            result
+            manager
+                .read_build_system_version(&BuildSystem::Cargo, None)
                 .unwrap_err()
                 .to_string()
-                .contains("Version files are not synchronized")
+                .contains("inherits its version")
         );
+
+        manager.update_build_system_version(&BuildSystem::Cargo, None, &Version::new(2, 0, 0))?;
+        let content = fs::read_to_string(temp_dir.path().join("Cargo.toml"))?;
+        assert_eq!(content, cargo_content);
         Ok(())
     }
 
     #[test]
-    fn test_package_json_with_prerelease() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        create_package_json(temp_dir.path(), "1.0.0-beta.2", false)?;
+    fn test_toml_version_update_targets_package_table_after_subtables() -> Result<()> {
+        let content = r#"[package.metadata.docs.rs]
+all-features = true
+version = "0.0.1"
 
-        let manager = VersionManager::new(temp_dir.path());
-        let version = manager.read_package_json_version()?;
+[package]
+name = "test"
+edition = "2021"
+version = "0.1.0" # keep me
 
-        assert_eq!(version.major, 1);
-        assert_eq!(version.minor, 0);
-        assert_eq!(version.patch, 0);
-        assert_eq!(version.pre.as_str(), "beta.2");
+[dependencies]
+serde = { version = "1.0" }
+"#;
+        let updated =
+            VersionManager::update_toml_version(content, &Version::new(1, 0, 0), "package")?;
+
+        assert_eq!(
+            updated,
+            content.replace(r#""0.1.0" # keep me"#, r#""1.0.0" # keep me"#)
+        );
         Ok(())
     }
 
     #[test]
-    fn test_package_json_missing_version_field() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        let package_json_content = r#"{"name": "test-package"}"#;
-        fs::write(temp_dir.path().join("package.json"), package_json_content)?;
+    fn test_toml_version_update_root_table() -> Result<()> {
+        let content = r#"# top comment
+name = "app"
+version = "0.1.0" # keep me
 
-        let manager = VersionManager::new(temp_dir.path());
-        let result = manager.read_package_json_version();
+[tool]
+version = "9.9.9"
+"#;
+        let updated = VersionManager::update_toml_version(content, &Version::new(1, 0, 0), "")?;
 
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("No version found in package.json")
+        assert_eq!(
+            updated,
+            content.replace(r#""0.1.0" # keep me"#, r#""1.0.0" # keep me"#)
         );
+
+        let err =
+            VersionManager::update_toml_version("[tool]\nversion = \"1.0.0\"\n", &"2.0.0", "")
+                .unwrap_err()
+                .to_string();
+        assert!(err.contains("No top-level version field found"));
         Ok(())
     }
 
     #[test]
-    fn test_pyproject_toml_missing_version_field() -> Result<()> {
+    fn test_toml_literal_string_versions_keep_their_quotes() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        let pyproject_content = r#"[project]
-name = "test"
-"#;
-        fs::write(temp_dir.path().join("pyproject.toml"), pyproject_content)?;
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = 'test'\nversion = '1.2.3' # keep me\n",
+        )?;
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = 'test'\nversion = '1.2.3'\n",
+        )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let result = manager.read_pyproject_version();
-
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("No version found in pyproject.toml")
+        for system in [BuildSystem::Cargo, BuildSystem::PyProject] {
+            assert_eq!(
+                manager.read_build_system_version(&system, None)?,
+                Version::new(1, 2, 3)
+            );
+            manager.update_build_system_version(&system, None, &Version::new(1, 3, 0))?;
+            assert_eq!(
+                manager.read_build_system_version(&system, None)?,
+                Version::new(1, 3, 0)
+            );
+        }
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("Cargo.toml"))?,
+            "[package]\nname = 'test'\nversion = '1.3.0' # keep me\n"
+        );
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("pyproject.toml"))?,
+            "[project]\nname = 'test'\nversion = '1.3.0'\n"
         );
         Ok(())
     }
 
     #[test]
-    fn test_cargo_toml_missing_version_field() -> Result<()> {
+    fn test_setup_cfg_read_and_update() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        let cargo_content = r#"[package]
-name = "test"
-"#;
-        fs::write(temp_dir.path().join("Cargo.toml"), cargo_content)?;
+        let content = "[metadata]\nname = pkg\nversion = 1.2.3rc1  \nlong_description =\n    version = 0.0.1\n\n[options]\nversion = 9.9.9\n";
+        fs::write(temp_dir.path().join("setup.cfg"), content)?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let result = manager.read_cargo_version();
+        assert_eq!(manager.detect_build_systems(), vec![BuildSystem::SetupCfg]);
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::SetupCfg, None)?,
+            Version::parse("1.2.3-rc.1")?
+        );
 
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("No version found in Cargo.toml")
+        manager.update_build_system_version(
+            &BuildSystem::SetupCfg,
+            None,
+            &Version::parse("1.3.0-beta.2")?,
+        )?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("setup.cfg"))?,
+            content.replace("1.2.3rc1", "1.3.0b2")
         );
+
+        // A setup.cfg that only configures tools is not a build system
+        fs::write(
+            temp_dir.path().join("setup.cfg"),
+            "[flake8]\nmax-line-length = 99\n",
+        )?;
+        assert!(manager.detect_build_systems().is_empty());
         Ok(())
     }
 
     #[test]
-    fn test_package_json_invalid_json() -> Result<()> {
+    fn test_setup_cfg_and_pyproject_cross_checked() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("package.json"), "not valid json {{")?;
+        fs::write(temp_dir.path().join("VERSION"), "1.2.0\n")?;
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"pkg\"\nversion = \"1.2.0\"\n",
+        )?;
+        fs::write(
+            temp_dir.path().join("setup.cfg"),
+            "[metadata]\nname = pkg\nversion = 1.1.0\n",
+        )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let result = manager.read_package_json_version();
+        let issues = manager.sync_issues()?;
+        assert!(issues.contains(&SyncIssue::PythonSourcesDisagree {
+            pyproject: Version::new(1, 2, 0),
+            setup_cfg: Version::new(1, 1, 0),
+        }));
+        assert_eq!(issues.len(), 2);
 
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("Failed to parse package.json")
+        manager.sync_versions()?;
+        assert!(manager.sync_issues()?.is_empty());
+        manager.bump_version(BumpType::Minor)?;
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::SetupCfg, None)?,
+            Version::new(1, 3, 0)
         );
+        assert!(manager.verify_versions_in_sync().is_ok());
         Ok(())
     }
 
     #[test]
-    fn test_pyproject_toml_invalid_toml() -> Result<()> {
+    fn test_gleam_version_read_and_update() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("pyproject.toml"), "invalid toml [[[")?;
+        let content = r#"name = "my_app"
+version = "1.2.3"
+description = "An app" # keep me
+
+[dependencies]
+gleam_stdlib = ">= 0.34.0"
+"#;
+        fs::write(temp_dir.path().join("gleam.toml"), content)?;
+        fs::write(temp_dir.path().join("VERSION"), "1.2.3\n")?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let result = manager.read_pyproject_version();
+        assert_eq!(manager.detect_build_systems(), vec![BuildSystem::Gleam]);
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Gleam, None)?,
+            Version::new(1, 2, 3)
+        );
+        assert!(manager.verify_versions_in_sync().is_ok());
 
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("Failed to parse pyproject.toml")
+        manager.update_build_system_version(&BuildSystem::Gleam, None, &Version::new(1, 3, 0))?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("gleam.toml"))?,
+            content.replace(r#"version = "1.2.3""#, r#"version = "1.3.0""#)
         );
         Ok(())
     }
 
     #[test]
-    fn test_cargo_toml_invalid_toml() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("Cargo.toml"), "invalid toml [[[")?;
-
-        let manager = VersionManager::new(temp_dir.path());
-        let result = manager.read_cargo_version();
-
+    fn test_toml_version_update_ignores_rust_version() {
+        let content = "[package]\nname = \"test\"\nrust-version = \"1.85\"\n";
+        let result =
+            VersionManager::update_toml_version(content, &Version::new(1, 0, 0), "package");
         assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("Failed to parse Cargo.toml")
-        );
-        Ok(())
     }
 
     #[test]
-    fn test_version_file_invalid_semver() -> Result<()> {
+    fn test_bump_cascade_skips_inherited_members() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("VERSION"), "not-a-version")?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
+        fs::create_dir(temp_dir.path().join("dotted"))?;
+        fs::write(
+            temp_dir.path().join("dotted/Cargo.toml"),
+            "[package]\nname = \"dotted\"\nversion.workspace = true\n",
+        )?;
+        fs::create_dir(temp_dir.path().join("inline"))?;
+        fs::write(
+            temp_dir.path().join("inline/Cargo.toml"),
+            "[package]\nname = \"inline\"\nversion = { workspace = true }\n",
+        )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let result = manager.read_version_file();
+        let preview = manager.bump_cascade_dry_run(BumpType::Patch)?;
+        assert_eq!(preview.files_to_update.len(), 1);
 
-        assert!(result.is_err());
+        manager.bump_cascade(BumpType::Patch)?;
+        assert_eq!(manager.read_version_file()?, Version::new(1, 0, 1));
         assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("Invalid version format")
+            fs::read_to_string(temp_dir.path().join("dotted/Cargo.toml"))?
+                .contains("version.workspace = true")
+        );
+        assert!(
+            fs::read_to_string(temp_dir.path().join("inline/Cargo.toml"))?
+                .contains("version = { workspace = true }")
         );
         Ok(())
     }
 
     #[test]
-    fn test_version_file_with_whitespace() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("VERSION"), "  1.2.3  \n")?;
-
-        let manager = VersionManager::new(temp_dir.path());
-        let version = manager.read_version_file()?;
-
-        assert_eq!(version, Version::new(1, 2, 3));
-        Ok(())
+    fn test_manifest_paths() {
+        let manager = VersionManager::with_version_file("/repo", "version.txt");
+        assert_eq!(
+            manager.version_file_path(),
+            Path::new("/repo").join("version.txt")
+        );
+        assert_eq!(
+            manager.manifest_path(&BuildSystem::PyProject),
+            Path::new("/repo").join("pyproject.toml")
+        );
     }
 
     #[test]
-    fn test_bump_version_with_out_of_sync_error() -> Result<()> {
+    fn test_pyproject_commitizen_only() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "1.0.0")?;
-
-        // Manually modify Cargo.toml to be out of sync
-        let cargo_content = r#"[package]
-name = "test"
-version = "2.0.0"
-"#;
-        fs::write(temp_dir.path().join("Cargo.toml"), cargo_content)?;
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"test\"\ndynamic = [\"version\"]\n\n[tool.commitizen]\nversion = \"1.2.3\"\n",
+        )?;
 
-        let manager = VersionManager::new(temp_dir.path());
-        let result = manager.bump_version(BumpType::Patch);
+        let manager =
+            VersionManager::new(temp_dir.path()).with_pyproject_source(PyProjectSource::Commitizen);
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PyProject, None)?,
+            Version::new(1, 2, 3)
+        );
 
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("Version files are not synchronized")
+        manager.update_build_system_version(
+            &BuildSystem::PyProject,
+            None,
+            &Version::new(1, 3, 0),
+        )?;
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PyProject, None)?,
+            Version::new(1, 3, 0)
         );
         Ok(())
     }
 
     #[test]
-    fn test_sync_versions_with_all_three_build_systems() -> Result<()> {
+    fn test_pyproject_updates_both_sections() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        // Create files with 1.0.0 first
-        create_test_files(temp_dir.path(), "1.0.0")?;
-        create_package_json(temp_dir.path(), "1.0.0", false)?;
-
-        // Then update VERSION file to 5.0.0
-        fs::write(temp_dir.path().join("VERSION"), "5.0.0")?;
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"test\"\nversion = \"1.0.0\"\n\n[tool.commitizen]\nversion = \"1.0.0\"\n",
+        )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        manager.sync_versions()?;
+        manager.update_build_system_version(
+            &BuildSystem::PyProject,
+            None,
+            &Version::new(2, 0, 0),
+        )?;
 
-        // Verify all versions are now 5.0.0
-        assert_eq!(manager.read_cargo_version()?, Version::new(5, 0, 0));
-        assert_eq!(manager.read_pyproject_version()?, Version::new(5, 0, 0));
-        assert_eq!(manager.read_package_json_version()?, Version::new(5, 0, 0));
+        let content = fs::read_to_string(temp_dir.path().join("pyproject.toml"))?;
+        assert_eq!(content.matches("version = \"2.0.0\"").count(), 2);
         Ok(())
     }
 
     #[test]
-    fn test_verify_versions_with_all_systems_in_sync() -> Result<()> {
+    fn test_verify_flags_pyproject_internal_disagreement() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "3.2.1")?;
-        create_package_json(temp_dir.path(), "3.2.1", false)?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"test\"\nversion = \"1.0.0\"\n\n[tool.commitizen]\nversion = \"0.9.0\"\n",
+        )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let result = manager.verify_versions_in_sync();
+        let err = manager.verify_versions_in_sync().unwrap_err().to_string();
+        assert!(err.contains("disagrees with [tool.commitizen]"), "{err}");
 
-        assert!(result.is_ok());
+        manager.sync_versions()?;
+        assert!(manager.verify_versions_in_sync().is_ok());
         Ok(())
     }
 
     #[test]
-    fn test_semver_with_build_metadata() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        create_test_files(temp_dir.path(), "1.0.0")?;
+    fn test_pyproject_source_from_str() {
+        assert_eq!(
+            "commitizen".parse::<PyProjectSource>().ok(),
+            Some(PyProjectSource::Commitizen)
+        );
+        assert!("poetry".parse::<PyProjectSource>().is_err());
+    }
 
-        let manager = VersionManager::new(temp_dir.path());
-        manager.reset_version("1.0.0+build.123")?;
+    #[test]
+    fn test_dry_run_result_relative_json() -> Result<()> {
+        let result = DryRunResult {
+            new_version: Version::new(2, 0, 0),
+            files_to_update: vec![
+                Path::new("/repo/Cargo.toml").to_path_buf(),
+                Path::new("/repo/sub/pyproject.toml").to_path_buf(),
+            ],
+            tag_name: None,
+        };
 
-        let version = manager.read_version_file()?;
-        assert_eq!(version.major, 1);
-        assert_eq!(version.minor, 0);
-        assert_eq!(version.patch, 0);
-        assert_eq!(version.build.as_str(), "build.123");
+        let json = serde_json::to_string(&result.relative_to(Path::new("/repo")))?;
+        assert_eq!(
+            json,
+            r#"{"new_version":"2.0.0","files_to_update":["Cargo.toml","sub/pyproject.toml"]}"#
+        );
         Ok(())
     }
 
     #[test]
-    fn test_package_json_not_an_object() -> Result<()> {
+    fn test_cmake_read_and_update_multiline_project() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("package.json"), "[]")?;
+        let cmake_content = "cmake_minimum_required(VERSION 3.16)\n\nproject(\n  MyLib\n  VERSION 1.2.3\n  LANGUAGES CXX\n)\n\nproject(Other VERSION 9.9.9)\n";
+        fs::write(temp_dir.path().join("CMakeLists.txt"), cmake_content)?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let result = manager.read_package_json_version();
+        assert_eq!(manager.detect_build_systems(), vec![BuildSystem::CMake]);
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::CMake, None)?,
+            Version::new(1, 2, 3)
+        );
 
-        assert!(result.is_err());
+        manager.update_build_system_version(&BuildSystem::CMake, None, &Version::new(1, 3, 0))?;
+        let content = fs::read_to_string(temp_dir.path().join("CMakeLists.txt"))?;
+        assert_eq!(
+            content,
+            cmake_content.replace("VERSION 1.2.3", "VERSION 1.3.0")
+        );
         Ok(())
     }
 
     #[test]
-    fn test_update_package_json_not_an_object() -> Result<()> {
+    fn test_cmake_four_component_version_is_refused() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("package.json"), "[]")?;
+        let cmake_path = temp_dir.path().join("CMakeLists.txt");
+        fs::write(&cmake_path, "project(MyLib VERSION 1.2.3.4 LANGUAGES C)\n")?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let result = manager.update_package_json_version(&Version::new(1, 0, 0));
-
-        assert!(result.is_err());
+        let err = manager
+            .read_build_system_version(&BuildSystem::CMake, None)
+            .unwrap_err();
         assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("package.json root is not a JSON object")
+            format!("{err:#}").contains("has a tweak component"),
+            "{err:#}"
+        );
+
+        fs::write(&cmake_path, "project(MyLib VERSION 1.2.3 LANGUAGES C)\n")?;
+        for version in ["2.0.0+7", "2.0.0-rc.1"] {
+            assert!(
+                manager
+                    .update_build_system_version(
+                        &BuildSystem::CMake,
+                        None,
+                        &Version::parse(version)?
+                    )
+                    .is_err()
+            );
+        }
+        assert_eq!(
+            fs::read_to_string(&cmake_path)?,
+            "project(MyLib VERSION 1.2.3 LANGUAGES C)\n"
         );
         Ok(())
     }
 
     #[test]
-    fn test_toml_version_update_no_version_field() {
-        let content = "[package]\nname = \"test\"\n";
-        let result =
-            VersionManager::update_toml_version(content, &Version::new(1, 0, 0), "package");
+    fn test_cmake_project_without_version() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("CMakeLists.txt"),
+            "project(MyLib LANGUAGES C)\nproject(Other VERSION 1.0.0)\n",
+        )?;
 
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("No version field found")
-        );
+        let manager = VersionManager::new(temp_dir.path());
+        let err = manager
+            .read_build_system_version(&BuildSystem::CMake, None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("No VERSION found"), "{err}");
+        Ok(())
     }
 
     #[test]
-    fn test_cargo_toml_with_workspace() -> Result<()> {
+    fn test_terraform_locals_version() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        let cargo_content = r#"[workspace]
-members = ["member1", "member2"]
-
-[package]
-name = "test"
-version = "1.2.3"
-"#;
-        fs::write(temp_dir.path().join("Cargo.toml"), cargo_content)?;
+        let tf_content = "locals {\n  module_name    = \"net\"\n  module_version = \"1.2.3\"\n}\n\nresource \"null_resource\" \"x\" {}\n";
+        fs::write(temp_dir.path().join("main.tf"), tf_content)?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let version = manager.read_cargo_version()?;
+        assert_eq!(manager.detect_build_systems(), vec![BuildSystem::Terraform]);
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Terraform, None)?,
+            Version::new(1, 2, 3)
+        );
 
-        assert_eq!(version, Version::new(1, 2, 3));
+        manager.update_build_system_version(
+            &BuildSystem::Terraform,
+            None,
+            &Version::new(1, 3, 0),
+        )?;
+        let content = fs::read_to_string(temp_dir.path().join("main.tf"))?;
+        assert_eq!(content, tf_content.replace("1.2.3", "1.3.0"));
         Ok(())
     }
 
     #[test]
-    fn test_discover_manifests_in_subdirectories() -> Result<()> {
+    fn test_terraform_variable_default_with_config() -> Result<()> {
         let temp_dir = TempDir::new()?;
-
-        // Create subdirectories with manifest files
-        fs::create_dir(temp_dir.path().join("rust-tool"))?;
         fs::write(
-            temp_dir.path().join("rust-tool/Cargo.toml"),
-            "[package]\nname = \"rust-tool\"\nversion = \"1.0.0\"\n",
+            temp_dir.path().join("variables.tf"),
+            "variable \"version\" {\n  type    = string\n  default = \"0.4.0\"\n}\n",
         )?;
 
-        fs::create_dir(temp_dir.path().join("python-client"))?;
-        fs::write(
-            temp_dir.path().join("python-client/pyproject.toml"),
-            "[project]\nname = \"python-client\"\nversion = \"1.0.0\"\n",
-        )?;
+        let config = Config {
+            terraform: config::TerraformConfig {
+                file: "variables.tf".to_string(),
+                name: "version".to_string(),
+            },
+            ..Config::default()
+        };
+        let manager = VersionManager::new(temp_dir.path()).with_config(config);
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Terraform, None)?,
+            Version::new(0, 4, 0)
+        );
 
-        fs::create_dir(temp_dir.path().join("js-sdk"))?;
-        fs::write(
-            temp_dir.path().join("js-sdk/package.json"),
-            r#"{"name": "js-sdk", "version": "1.0.0"}"#,
+        manager.update_build_system_version(
+            &BuildSystem::Terraform,
+            None,
+            &Version::new(0, 5, 0),
         )?;
-
-        let manager = VersionManager::new(temp_dir.path());
-        let manifests = manager.discover_manifests()?;
-
-        // Should find all three manifest files
-        assert_eq!(manifests.len(), 3);
-
-        // Verify each type is found
-        let cargo_found = manifests.iter().any(|(_, sys)| *sys == BuildSystem::Cargo);
-        let pyproject_found = manifests
-            .iter()
-            .any(|(_, sys)| *sys == BuildSystem::PyProject);
-        let packagejson_found = manifests
-            .iter()
-            .any(|(_, sys)| *sys == BuildSystem::PackageJson);
-
-        assert!(cargo_found, "Should find Cargo.toml");
-        assert!(pyproject_found, "Should find pyproject.toml");
-        assert!(packagejson_found, "Should find package.json");
-
+        let content = fs::read_to_string(temp_dir.path().join("variables.tf"))?;
+        assert!(content.contains("default = \"0.5.0\""));
         Ok(())
     }
 
     #[test]
-    fn test_discover_manifests_respects_gitignore() -> Result<()> {
+    fn test_terraform_missing_name_errors() -> Result<()> {
         let temp_dir = TempDir::new()?;
-
-        // Initialize git repo (required for ignore crate to respect .gitignore)
-        fs::create_dir(temp_dir.path().join(".git"))?;
-
-        // Create .gitignore
         fs::write(
-            temp_dir.path().join(".gitignore"),
-            "target/\nnode_modules/\n.venv/\n",
+            temp_dir.path().join("main.tf"),
+            "locals {\n  other = \"1.0.0\"\n}\n",
         )?;
 
-        // Create manifests in ignored directories
-        fs::create_dir(temp_dir.path().join("target"))?;
-        fs::write(
-            temp_dir.path().join("target/Cargo.toml"),
-            "[package]\nname = \"ignored\"\nversion = \"1.0.0\"\n",
-        )?;
+        let manager = VersionManager::new(temp_dir.path());
+        let err = manager
+            .read_build_system_version(&BuildSystem::Terraform, None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("module_version"), "{err}");
+        Ok(())
+    }
 
-        fs::create_dir(temp_dir.path().join("node_modules"))?;
+    #[test]
+    fn test_verify_cascade_in_sync() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
+        fs::create_dir(temp_dir.path().join("a"))?;
         fs::write(
-            temp_dir.path().join("node_modules/package.json"),
-            r#"{"name": "ignored", "version": "1.0.0"}"#,
+            temp_dir.path().join("a/Cargo.toml"),
+            "[package]\nname = \"a\"\nversion = \"1.0.0\"\n",
         )?;
-
-        // Create manifests in non-ignored directory
-        fs::create_dir(temp_dir.path().join("src"))?;
+        fs::create_dir(temp_dir.path().join("b"))?;
         fs::write(
-            temp_dir.path().join("src/Cargo.toml"),
-            "[package]\nname = \"not-ignored\"\nversion = \"1.0.0\"\n",
+            temp_dir.path().join("b/package.json"),
+            r#"{"name": "b", "version": "0.9.0"}"#,
         )?;
 
-        let manager = VersionManager::new(temp_dir.path());
-        let manifests = manager.discover_manifests()?;
-
-        // Should only find the non-ignored manifest
-        assert_eq!(
-            manifests.len(),
-            1,
-            "Should only find 1 manifest (not in ignored dirs)"
-        );
-
-        let found_path = &manifests[0].0;
-        assert!(
-            found_path.ends_with("src/Cargo.toml"),
-            "Should find src/Cargo.toml, got: {}",
-            found_path.display()
-        );
+        let manager = VersionManager::new(temp_dir.path());
+        let err = manager.verify_cascade_in_sync().unwrap_err().to_string();
+        assert!(err.contains("package.json has version 0.9.0"), "{err}");
 
+        manager.sync_cascade()?;
+        assert!(manager.verify_cascade_in_sync().is_ok());
         Ok(())
     }
 
+    const NUSPEC: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<package xmlns="http://schemas.microsoft.com/packaging/2013/05/nuspec.xsd">
+  <metadata minClientVersion="3.3">
+    <id>Contoso.Utility</id>
+    <version>1.2.3</version>
+    <dependencies>
+      <dependency id="Other" version="9.9.9" />
+    </dependencies>
+  </metadata>
+</package>
+"#;
+
     #[test]
-    fn test_discover_manifests_rejects_nested_version_files() -> Result<()> {
+    fn test_nuspec_read_and_update() -> Result<()> {
         let temp_dir = TempDir::new()?;
-
-        // Create top-level VERSION
-        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
-
-        // Create nested VERSION file (should be error)
-        fs::create_dir(temp_dir.path().join("subproject"))?;
-        fs::write(temp_dir.path().join("subproject/VERSION"), "2.0.0")?;
+        fs::write(temp_dir.path().join("Contoso.Utility.nuspec"), NUSPEC)?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let result = manager.discover_manifests();
-
-        assert!(result.is_err(), "Should error on nested VERSION file");
-        let err_msg = result.unwrap_err().to_string();
-        assert!(
-            err_msg.contains("VERSION") || err_msg.contains("nested"),
-            "Error should mention nested VERSION file, got: {err_msg}"
+        assert_eq!(manager.detect_build_systems(), vec![BuildSystem::Nuspec]);
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Nuspec, None)?,
+            Version::new(1, 2, 3)
         );
 
+        manager.update_build_system_version(&BuildSystem::Nuspec, None, &Version::new(1, 3, 0))?;
+        let content = fs::read_to_string(temp_dir.path().join("Contoso.Utility.nuspec"))?;
+        assert_eq!(
+            content,
+            NUSPEC.replace("<version>1.2.3</version>", "<version>1.3.0</version>")
+        );
         Ok(())
     }
 
     #[test]
-    #[cfg(unix)]
-    fn test_discover_manifests_rejects_symlinks() -> Result<()> {
-        use std::os::unix::fs::symlink;
-
+    fn test_gemspec_literal_version() -> Result<()> {
         let temp_dir = TempDir::new()?;
-
-        // Create a real directory with manifest
-        fs::create_dir(temp_dir.path().join("real-dir"))?;
+        let gemspec = temp_dir.path().join("my_gem.gemspec");
         fs::write(
-            temp_dir.path().join("real-dir/Cargo.toml"),
-            "[package]\nname = \"real\"\nversion = \"1.0.0\"\n",
+            &gemspec,
+            "Gem::Specification.new do |spec|\n  spec.name = \"my_gem\"\n  spec.version = \"1.2.3\".freeze\n  spec.required_ruby_version = \">= 3.0\"\nend\n",
         )?;
 
-        // Create a symlink to the directory
-        let symlink_path = temp_dir.path().join("link-dir");
-        symlink(temp_dir.path().join("real-dir"), &symlink_path)?;
-
         let manager = VersionManager::new(temp_dir.path());
-        let result = manager.discover_manifests();
-
-        assert!(result.is_err(), "Should error on symlink");
-        let err_msg = result.unwrap_err().to_string().to_lowercase();
-        assert!(
-            err_msg.contains("symlink") || err_msg.contains("symbolic"),
-            "Error should mention symlink, got: {err_msg}"
+        assert_eq!(manager.detect_build_systems(), vec![BuildSystem::Gemspec]);
+        assert_eq!(classify_manifest(&gemspec), Some(BuildSystem::Gemspec));
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Gemspec, None)?,
+            Version::new(1, 2, 3)
         );
 
+        manager.update_build_system_version(&BuildSystem::Gemspec, None, &Version::new(1, 3, 0))?;
+        let content = fs::read_to_string(&gemspec)?;
+        assert!(content.contains("spec.version = \"1.3.0\".freeze"));
+        assert!(content.contains("spec.required_ruby_version = \">= 3.0\""));
+
+        // Single quotes work too
+        fs::write(
+            &gemspec,
+            "Gem::Specification.new do |s|\n  s.version = '0.1.0'\nend\n",
+        )?;
+        manager.update_build_system_version(&BuildSystem::Gemspec, None, &Version::new(0, 2, 0))?;
+        assert!(fs::read_to_string(&gemspec)?.contains("s.version = '0.2.0'"));
         Ok(())
     }
 
+    const INFO_PLIST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>CFBundleIdentifier</key>
+	<string>com.example.app</string>
+	<key>CFBundleShortVersionString</key>
+	<string>1.2</string>
+	<key>CFBundleVersion</key>
+	<string>41</string>
+</dict>
+</plist>
+"#;
+
     #[test]
-    fn test_bump_cascade_updates_all_manifests() -> Result<()> {
+    fn test_info_plist_read_and_update() -> Result<()> {
         let temp_dir = TempDir::new()?;
+        let plist = temp_dir.path().join("Info.plist");
+        fs::write(&plist, INFO_PLIST)?;
 
-        // Create top-level VERSION
-        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
+        let manager = VersionManager::new(temp_dir.path());
+        assert_eq!(manager.detect_build_systems(), vec![BuildSystem::InfoPlist]);
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::InfoPlist, None)?,
+            Version::new(1, 2, 0)
+        );
 
-        // Create subdirectories with different manifest types
-        fs::create_dir(temp_dir.path().join("rust-tool"))?;
-        fs::write(
-            temp_dir.path().join("rust-tool/Cargo.toml"),
-            "[package]\nname = \"rust-tool\"\nversion = \"1.0.0\"\n",
+        manager.update_build_system_version(
+            &BuildSystem::InfoPlist,
+            None,
+            &Version::new(1, 3, 0),
         )?;
+        assert_eq!(
+            fs::read_to_string(&plist)?,
+            INFO_PLIST.replace("<string>1.2</string>", "<string>1.3.0</string>")
+        );
 
-        fs::create_dir(temp_dir.path().join("python-client"))?;
-        fs::write(
-            temp_dir.path().join("python-client/pyproject.toml"),
-            "[project]\nname = \"python-client\"\nversion = \"1.0.0\"\n",
+        // The build number only moves with the flag, and only when the version changes
+        let manager = manager.with_bump_bundle_version(true);
+        manager.update_build_system_version(
+            &BuildSystem::InfoPlist,
+            None,
+            &Version::new(1, 3, 0),
         )?;
+        assert!(fs::read_to_string(&plist)?.contains("<string>41</string>"));
+        manager.update_build_system_version(
+            &BuildSystem::InfoPlist,
+            None,
+            &Version::new(1, 4, 0),
+        )?;
+        let content = fs::read_to_string(&plist)?;
+        assert!(content.contains("<string>1.4.0</string>"));
+        assert!(content.contains("<key>CFBundleVersion</key>\n\t<string>42</string>"));
+
+        let err = manager
+            .update_build_system_version(
+                &BuildSystem::InfoPlist,
+                None,
+                &Version::parse("2.0.0-rc.1")?,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("pre-release"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_info_plist_refuses_unsupported_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let plist = temp_dir.path().join("Info.plist");
+        let manager = VersionManager::new(temp_dir.path());
+
+        fs::write(&plist, b"bplist00\xd1\x01\x02")?;
+        let err = manager
+            .read_build_system_version(&BuildSystem::InfoPlist, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("plutil -convert xml1"), "{err}");
+
+        let without_key = INFO_PLIST.replace("CFBundleShortVersionString", "CFBundleName");
+        fs::write(&plist, &without_key)?;
+        let err = manager
+            .update_build_system_version(&BuildSystem::InfoPlist, None, &Version::new(1, 3, 0))
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("No CFBundleShortVersionString"),
+            "{err}"
+        );
+        assert_eq!(fs::read_to_string(&plist)?, without_key);
 
-        fs::create_dir(temp_dir.path().join("js-sdk"))?;
         fs::write(
-            temp_dir.path().join("js-sdk/package.json"),
-            r#"{"name": "js-sdk", "version": "1.0.0"}"#,
+            &plist,
+            INFO_PLIST.replace(
+                "<string>1.2</string>",
+                "<string>$(MARKETING_VERSION)</string>",
+            ),
         )?;
+        let err = manager
+            .read_build_system_version(&BuildSystem::InfoPlist, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("$(MARKETING_VERSION)"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_gemspec_constant_version_is_skipped() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let gemspec = temp_dir.path().join("my_gem.gemspec");
+        let original = "require_relative \"lib/my_gem/version\"\n\nGem::Specification.new do |spec|\n  spec.name = \"my_gem\"\n  spec.version = MyGem::VERSION\nend\n";
+        fs::write(&gemspec, original)?;
 
         let manager = VersionManager::new(temp_dir.path());
-        manager.bump_cascade(BumpType::Minor)?;
+        assert!(manager.detect_build_systems().is_empty());
+        assert_eq!(classify_manifest(&gemspec), None);
+
+        let err = manager
+            .update_build_system_version(&BuildSystem::Gemspec, None, &Version::new(1, 3, 0))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("MyGem::VERSION"), "{err}");
+        assert!(err.contains("literal"), "{err}");
+        assert_eq!(fs::read_to_string(&gemspec)?, original);
+        Ok(())
+    }
 
-        // Verify VERSION was bumped
-        let version = manager.read_version_file()?;
-        assert_eq!(version, Version::new(1, 1, 0));
+    #[test]
+    fn test_nuspec_multiple_in_one_directory() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.2.3\n")?;
+        fs::write(temp_dir.path().join("A.nuspec"), NUSPEC)?;
+        fs::write(temp_dir.path().join("B.nuspec"), NUSPEC)?;
 
-        // Verify all manifests were updated
-        let cargo_content = fs::read_to_string(temp_dir.path().join("rust-tool/Cargo.toml"))?;
-        assert!(cargo_content.contains("version = \"1.1.0\""));
+        let manager = VersionManager::new(temp_dir.path());
+        let err = manager
+            .read_build_system_version(&BuildSystem::Nuspec, None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Multiple .nuspec files"), "{err}");
+        assert_eq!(
+            manager.detect_manifests(),
+            vec![
+                (temp_dir.path().join("A.nuspec"), BuildSystem::Nuspec),
+                (temp_dir.path().join("B.nuspec"), BuildSystem::Nuspec),
+            ]
+        );
 
-        let pyproject_content =
-            fs::read_to_string(temp_dir.path().join("python-client/pyproject.toml"))?;
-        assert!(pyproject_content.contains("version = \"1.1.0\""));
+        manager.update_build_system_version(
+            &BuildSystem::Nuspec,
+            Some(&temp_dir.path().join("B.nuspec")),
+            &Version::new(1, 2, 5),
+        )?;
+        let issues = manager.sync_issues()?;
+        assert_eq!(issues.len(), 1);
+        assert!(
+            issues[0]
+                .to_string()
+                .starts_with("Nuspec (B.nuspec) has version 1.2.5"),
+            "{}",
+            issues[0]
+        );
 
-        let package_content = fs::read_to_string(temp_dir.path().join("js-sdk/package.json"))?;
-        assert!(package_content.contains("\"version\": \"1.1.0\""));
+        manager.sync_versions()?;
+        manager.bump_version(BumpType::Patch)?;
+        for name in ["A.nuspec", "B.nuspec"] {
+            let content = fs::read_to_string(temp_dir.path().join(name))?;
+            assert!(content.contains("<version>1.2.4</version>"));
+        }
 
+        manager.bump_cascade(BumpType::Patch)?;
+        for name in ["A.nuspec", "B.nuspec"] {
+            let content = fs::read_to_string(temp_dir.path().join(name))?;
+            assert!(content.contains("<version>1.2.5</version>"));
+        }
+        assert!(manager.verify_cascade_in_sync().is_ok());
         Ok(())
     }
 
-    // Helper to initialize a git repo in a temp directory with an initial commit
-    fn init_git_repo(dir: &Path) -> Result<()> {
-        use std::process::Command;
-        let run = |args: &[&str]| -> Result<()> {
-            let output = Command::new("git").args(args).current_dir(dir).output()?;
-            if !output.status.success() {
-                anyhow::bail!(
-                    "git {:?} failed: {}",
-                    args,
-                    String::from_utf8_lossy(&output.stderr)
-                );
-            }
-            Ok(())
-        };
-
-        run(&["init"])?;
-        run(&["config", "user.email", "test@test.com"])?;
-        run(&["config", "user.name", "Test"])?;
-        run(&["commit", "--allow-empty", "-m", "init"])?;
+    #[test]
+    fn test_cascade_judges_inheritance_by_the_manifest_itself() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
+        fs::create_dir(temp_dir.path().join("tpl"))?;
+        fs::write(
+            temp_dir.path().join("tpl/Cargo.toml.tpl"),
+            "[package]\nname = \"member\"\nversion.workspace = true\n",
+        )?;
+        let mut config = Config::default();
+        config.manifest_overrides.push(config::ManifestOverride {
+            path: "tpl/Cargo.toml.tpl".to_string(),
+            format: BuildSystem::Cargo,
+        });
+        let manager = VersionManager::new(temp_dir.path()).with_config(config);
+
+        // The template inherits its version, though its directory has no Cargo.toml
+        let summary = manager.sync_cascade()?;
+        assert_eq!((summary.scanned, summary.skipped), (1, 1));
+        manager.verify_cascade_in_sync()?;
         Ok(())
     }
 
     #[test]
-    fn test_count_rc_tags_no_git() -> Result<()> {
+    fn test_nuspec_placeholder_requires_overwrite() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
+        let placeholder = NUSPEC.replace("1.2.3", "$version$");
+        fs::write(temp_dir.path().join("pkg.nuspec"), &placeholder)?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let version = Version::new(1, 0, 0);
-        let result = manager.count_rc_tags(&version);
+        assert!(manager.build_system_has_no_version(&BuildSystem::Nuspec));
+        assert!(
+            manager
+                .update_build_system_version(&BuildSystem::Nuspec, None, &Version::new(2, 0, 0))
+                .is_err()
+        );
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("pkg.nuspec"))?,
+            placeholder
+        );
 
-        assert!(result.is_err());
+        let manager = manager.with_overwrite_placeholders(true);
+        assert!(!manager.build_system_has_no_version(&BuildSystem::Nuspec));
+        manager.update_build_system_version(&BuildSystem::Nuspec, None, &Version::new(2, 0, 0))?;
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Nuspec, None)?,
+            Version::new(2, 0, 0)
+        );
         Ok(())
     }
 
     #[test]
-    fn test_count_rc_tags_no_tags() -> Result<()> {
+    fn test_sync_issues_distinguishes_drift_from_unreadable() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        init_git_repo(temp_dir.path())?;
-        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"2.0.0\"\n",
+        )?;
+        fs::write(temp_dir.path().join("package.json"), "not json")?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let version = Version::new(1, 0, 0);
-        let count = manager.count_rc_tags(&version)?;
+        let issues = manager.sync_issues()?;
 
-        assert_eq!(count, 0);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(
+            issues[0],
+            SyncIssue::Drift {
+                system: BuildSystem::Cargo,
+                path: temp_dir.path().join("Cargo.toml"),
+                found: Version::new(2, 0, 0),
+                expected: Version::new(1, 0, 0),
+                core_only: false,
+            }
+        );
+        assert!(matches!(
+            issues[1],
+            SyncIssue::Unreadable {
+                system: BuildSystem::PackageJson,
+                ..
+            }
+        ));
         Ok(())
     }
 
     #[test]
-    fn test_count_rc_tags_with_existing() -> Result<()> {
+    fn test_pyproject_dynamic_version_attr() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        init_git_repo(temp_dir.path())?;
-
-        // Create RC tags
-        std::process::Command::new("git")
-            .args(["tag", "v1.0.0-rc.1"])
-            .current_dir(temp_dir.path())
-            .output()?;
-        std::process::Command::new("git")
-            .args(["tag", "v1.0.0-rc.2"])
-            .current_dir(temp_dir.path())
-            .output()?;
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"pkg\"\ndynamic = [\"version\"]\n\n[tool.setuptools.dynamic]\nversion = { attr = \"pkg.__version__\" }\n",
+        )?;
+        fs::create_dir_all(temp_dir.path().join("src/pkg"))?;
+        fs::write(
+            temp_dir.path().join("src/pkg/__init__.py"),
+            "\"\"\"Package.\"\"\"\n\n__version__: str = '1.2.3'\n",
+        )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let version = Version::new(1, 0, 0);
-        let count = manager.count_rc_tags(&version)?;
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PyProject, None)?,
+            Version::new(1, 2, 3)
+        );
 
-        assert_eq!(count, 2);
+        manager.update_build_system_version(
+            &BuildSystem::PyProject,
+            None,
+            &Version::new(1, 3, 0),
+        )?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("src/pkg/__init__.py"))?,
+            "\"\"\"Package.\"\"\"\n\n__version__: str = '1.3.0'\n"
+        );
         Ok(())
     }
 
     #[test]
-    fn test_count_rc_tags_ignores_other_versions() -> Result<()> {
+    fn test_pyproject_dynamic_version_file() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        init_git_repo(temp_dir.path())?;
-
-        std::process::Command::new("git")
-            .args(["tag", "v2.0.0-rc.1"])
-            .current_dir(temp_dir.path())
-            .output()?;
-        std::process::Command::new("git")
-            .args(["tag", "v1.0.0-rc.1"])
-            .current_dir(temp_dir.path())
-            .output()?;
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"pkg\"\ndynamic = [\"version\"]\n\n[tool.setuptools.dynamic]\nversion = { file = \"pkg/VERSION.txt\" }\n",
+        )?;
+        fs::create_dir_all(temp_dir.path().join("pkg"))?;
+        fs::write(temp_dir.path().join("pkg/VERSION.txt"), "0.4.0\n")?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let version = Version::new(1, 0, 0);
-        let count = manager.count_rc_tags(&version)?;
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PyProject, None)?,
+            Version::new(0, 4, 0)
+        );
 
-        assert_eq!(count, 1);
+        manager.update_build_system_version(
+            &BuildSystem::PyProject,
+            None,
+            &Version::new(0, 5, 0),
+        )?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("pkg/VERSION.txt"))?,
+            "0.5.0\n"
+        );
         Ok(())
     }
 
     #[test]
-    fn test_next_rc_version_first() -> Result<()> {
+    fn test_pyproject_dynamic_version_without_setuptools_config() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        init_git_repo(temp_dir.path())?;
-        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"pkg\"\ndynamic = [\"version\"]\n",
+        )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let rc = manager.next_rc_version()?;
-
-        assert_eq!(rc.to_string(), "1.0.0-rc.1");
+        let err = manager
+            .read_build_system_version(&BuildSystem::PyProject, None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("dynamic version"), "{err}");
         Ok(())
     }
 
     #[test]
-    fn test_next_rc_version_increment() -> Result<()> {
+    fn test_adopt_build_system_version() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        init_git_repo(temp_dir.path())?;
-        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
-
-        std::process::Command::new("git")
-            .args(["tag", "v1.0.0-rc.1"])
-            .current_dir(temp_dir.path())
-            .output()?;
-        std::process::Command::new("git")
-            .args(["tag", "v1.0.0-rc.2"])
-            .current_dir(temp_dir.path())
-            .output()?;
+        fs::write(temp_dir.path().join("VERSION"), "0.1.0\n")?;
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"1.4.0\"\n",
+        )?;
+        create_package_json(temp_dir.path(), "1.3.0", false)?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let rc = manager.next_rc_version()?;
+        let err = manager
+            .adopt_build_system_version(None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("--source"), "{err}");
+        assert_eq!(manager.read_version_file()?, Version::new(0, 1, 0));
+
+        let declared: Vec<_> = manager
+            .declared_versions()?
+            .into_iter()
+            .map(|(_, system, version)| (system, version))
+            .collect();
+        assert_eq!(
+            declared,
+            vec![
+                (BuildSystem::Cargo, Version::new(1, 4, 0)),
+                (BuildSystem::PackageJson, Version::new(1, 3, 0)),
+            ]
+        );
 
-        assert_eq!(rc.to_string(), "1.0.0-rc.3");
+        let adopted = manager.adopt_build_system_version(Some(&BuildSystem::Cargo))?;
+        assert_eq!(adopted, Version::new(1, 4, 0));
+        assert_eq!(manager.read_version_file()?, Version::new(1, 4, 0));
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PackageJson, None)?,
+            Version::new(1, 4, 0)
+        );
         Ok(())
     }
 
     #[test]
-    fn test_next_rc_version_rejects_prerelease() -> Result<()> {
+    fn test_adopt_single_build_system_without_source() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        init_git_repo(temp_dir.path())?;
-        fs::write(temp_dir.path().join("VERSION"), "1.0.0-beta.1")?;
+        fs::write(temp_dir.path().join("VERSION"), "0.1.0\n")?;
+        create_package_json(temp_dir.path(), "2.0.0", false)?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let result = manager.next_rc_version();
-
-        assert!(result.is_err());
-        let err_msg = result.unwrap_err().to_string();
+        assert_eq!(
+            manager.adopt_build_system_version(None)?,
+            Version::new(2, 0, 0)
+        );
         assert!(
-            err_msg.contains("clean M.m.p"),
-            "Error should mention clean version requirement, got: {err_msg}"
+            manager
+                .adopt_build_system_version(Some(&BuildSystem::Cargo))
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_tag_collision() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        init_git_repo(temp_dir.path())?;
+        git_tag(temp_dir.path(), "v1.2.3")?;
+        git_tag(temp_dir.path(), "v1.2.3-1")?;
+        git_tag(temp_dir.path(), "v2.0.0-rc.1")?;
+
+        let manager = VersionManager::new(temp_dir.path());
+        assert_eq!(
+            manager.resolve_tag_collision(&Version::new(1, 2, 4), "v")?,
+            Version::new(1, 2, 4)
+        );
+        assert_eq!(
+            manager.resolve_tag_collision(&Version::new(1, 2, 3), "v")?,
+            Version::parse("1.2.3-2")?
+        );
+        assert_eq!(
+            manager.resolve_tag_collision(&Version::parse("1.2.3-1")?, "v")?,
+            Version::parse("1.2.3-2")?
+        );
+        assert_eq!(
+            manager.resolve_tag_collision(&Version::parse("2.0.0-rc.1")?, "v")?,
+            Version::parse("2.0.0-rc.2")?
         );
+
+        git_tag(temp_dir.path(), &format!("v3.0.0-{}", u64::MAX))?;
+        let err = manager
+            .resolve_tag_collision(&Version::parse(&format!("3.0.0-{}", u64::MAX))?, "v")
+            .unwrap_err();
+        assert!(err.to_string().contains("exhausted"), "{err}");
         Ok(())
     }
 
     #[test]
-    fn test_read_version_file_with_inline_comment() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        fs::write(
-            temp_dir.path().join("VERSION"),
-            "1.2.3 # x-release-please-version\n",
-        )?;
-
-        let manager = VersionManager::new(temp_dir.path());
-        let version = manager.read_version_file()?;
-
-        assert_eq!(version, Version::new(1, 2, 3));
+    fn test_pep440_round_trip() -> Result<()> {
+        for (semver, pep440) in [
+            ("1.2.3", "1.2.3"),
+            ("1.2.3-alpha.1", "1.2.3a1"),
+            ("1.2.3-beta.2", "1.2.3b2"),
+            ("1.2.3-rc.2", "1.2.3rc2"),
+            ("1.2.3-dev.4", "1.2.3.dev4"),
+            ("1.2.3-rc.1+build.5", "1.2.3rc1+build.5"),
+        ] {
+            let version = Version::parse(semver)?;
+            assert_eq!(VersionManager::format_pep440_version(&version)?, pep440);
+            assert_eq!(VersionManager::parse_pep440_version(pep440)?, version);
+        }
+        assert!(VersionManager::format_pep440_version(&Version::parse("1.0.0-nightly")?).is_err());
+        let err = VersionManager::format_pep440_version(&Version::parse("1.2.3-alpha")?)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("has no number"), "{err}");
         Ok(())
     }
 
     #[test]
-    fn test_read_version_file_with_comment_no_space() -> Result<()> {
+    fn test_pyproject_prerelease_written_as_pep440() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("VERSION"), "2.0.0#some-comment\n")?;
+        fs::write(temp_dir.path().join("VERSION"), "1.2.3-rc.2\n")?;
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"test\"\nversion = \"1.0.0\"\n",
+        )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let version = manager.read_version_file()?;
+        manager.sync_versions()?;
+        assert!(
+            fs::read_to_string(temp_dir.path().join("pyproject.toml"))?
+                .contains("version = \"1.2.3rc2\"")
+        );
+        assert!(manager.verify_versions_in_sync().is_ok());
 
-        assert_eq!(version, Version::new(2, 0, 0));
+        let mut config = Config::default();
+        config.pyproject.pep440 = false;
+        let manager = VersionManager::new(temp_dir.path()).with_config(config);
+        manager.sync_versions()?;
+        assert!(
+            fs::read_to_string(temp_dir.path().join("pyproject.toml"))?
+                .contains("version = \"1.2.3-rc.2\"")
+        );
+        assert!(manager.verify_versions_in_sync().is_ok());
         Ok(())
     }
 
     #[test]
-    fn test_write_version_file_preserves_comment() -> Result<()> {
+    fn test_build_metadata_stripped_for_cargo_and_package_json() -> Result<()> {
         let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.2.3+ci.5\n")?;
         fs::write(
-            temp_dir.path().join("VERSION"),
-            "1.0.0 # x-release-please-version\n",
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+        )?;
+        create_package_json(temp_dir.path(), "1.0.0", false)?;
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"test\"\nversion = \"1.0.0\"\n",
         )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        manager.write_version_file(&Version::new(2, 3, 4))?;
+        manager.sync_versions()?;
 
-        let content = fs::read_to_string(temp_dir.path().join("VERSION"))?;
-        assert_eq!(content, "2.3.4 # x-release-please-version\n");
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::Cargo, None)?,
+            Version::new(1, 2, 3)
+        );
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PackageJson, None)?,
+            Version::new(1, 2, 3)
+        );
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PyProject, None)?,
+            Version::parse("1.2.3+ci.5")?
+        );
+        assert_eq!(manager.read_version_file()?, Version::parse("1.2.3+ci.5")?);
+        assert!(manager.verify_versions_in_sync().is_ok());
+
+        let mut config = Config::default();
+        config.build_metadata.strip.clear();
+        let manager = VersionManager::new(temp_dir.path()).with_config(config);
+        assert!(manager.verify_versions_in_sync().is_err());
         Ok(())
     }
 
     #[test]
-    fn test_write_version_file_no_comment() -> Result<()> {
+    fn test_ignore_prerelease_compares_core_version() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
+        fs::write(temp_dir.path().join("VERSION"), "1.2.0-rc.3\n")?;
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"1.2.0\"\n",
+        )?;
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{"name": "test", "version": "1.3.0"}"#,
+        )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        manager.write_version_file(&Version::new(3, 0, 0))?;
+        assert_eq!(manager.sync_issues()?.len(), 2);
 
-        let content = fs::read_to_string(temp_dir.path().join("VERSION"))?;
-        assert_eq!(content, "3.0.0\n");
+        let mut config = Config::default();
+        config.verify.ignore_prerelease = true;
+        let manager = VersionManager::new(temp_dir.path()).with_config(config);
+        let issues = manager.sync_issues()?;
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].system(), Some(&BuildSystem::PackageJson));
+        assert!(
+            issues[0]
+                .to_string()
+                .contains("core versions 1.3.0 and 1.2.0 differ; prerelease ignored")
+        );
         Ok(())
     }
 
     #[test]
-    fn test_write_version_file_new_file() -> Result<()> {
+    fn test_packages_props_version_prefix_and_suffix() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        // No VERSION file exists
+        let content = r#"<Project>
+  <PropertyGroup>
+    <ManagePackageVersionsCentrally>true</ManagePackageVersionsCentrally>
+    <VersionPrefix>1.2.3</VersionPrefix>
+    <VersionSuffix>rc.1</VersionSuffix>
+  </PropertyGroup>
+  <ItemGroup>
+    <PackageVersion Include="Newtonsoft.Json" Version="13.0.3" />
+  </ItemGroup>
+</Project>
+"#;
+        let props_path = temp_dir.path().join("Directory.Packages.props");
+        fs::write(&props_path, content)?;
 
         let manager = VersionManager::new(temp_dir.path());
-        manager.write_version_file(&Version::new(1, 0, 0))?;
+        assert_eq!(
+            manager.detect_build_systems(),
+            vec![BuildSystem::PackagesProps]
+        );
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PackagesProps, None)?,
+            Version::parse("1.2.3-rc.1")?
+        );
 
-        let content = fs::read_to_string(temp_dir.path().join("VERSION"))?;
-        assert_eq!(content, "1.0.0\n");
+        manager.update_build_system_version(
+            &BuildSystem::PackagesProps,
+            None,
+            &Version::new(2, 0, 0),
+        )?;
+        assert_eq!(
+            fs::read_to_string(&props_path)?,
+            content
+                .replace("<VersionPrefix>1.2.3<", "<VersionPrefix>2.0.0<")
+                .replace("<VersionSuffix>rc.1<", "<VersionSuffix><")
+        );
         Ok(())
     }
 
     #[test]
-    fn test_bump_roundtrip_with_comment() -> Result<()> {
+    fn test_packages_props_version_and_prefix_disagree() -> Result<()> {
         let temp_dir = TempDir::new()?;
+        let props_path = temp_dir.path().join("Directory.Packages.props");
         fs::write(
-            temp_dir.path().join("VERSION"),
-            "1.0.0 # x-release-please-version\n",
+            &props_path,
+            "<Project>\n  <PropertyGroup>\n    <Version>1.0.0</Version>\n  </PropertyGroup>\n  <PropertyGroup>\n    <VersionPrefix>1.1.0</VersionPrefix>\n  </PropertyGroup>\n</Project>\n",
         )?;
 
         let manager = VersionManager::new(temp_dir.path());
+        let err = manager
+            .read_build_system_version(&BuildSystem::PackagesProps, None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("<Version> 1.0.0 but <VersionPrefix> 1.1.0"));
+
+        manager.update_build_system_version(
+            &BuildSystem::PackagesProps,
+            None,
+            &Version::new(1, 2, 0),
+        )?;
+        assert_eq!(
+            manager.read_build_system_version(&BuildSystem::PackagesProps, None)?,
+            Version::new(1, 2, 0)
+        );
 
-        // Read should strip comment
-        let version = manager.read_version_file()?;
-        assert_eq!(version, Version::new(1, 0, 0));
-
-        // Write should preserve comment
-        let bumped = Version::new(1, 1, 0);
-        manager.write_version_file(&bumped)?;
-
-        let content = fs::read_to_string(temp_dir.path().join("VERSION"))?;
-        assert_eq!(content, "1.1.0 # x-release-please-version\n");
-
-        // Read again should work
-        let version = manager.read_version_file()?;
-        assert_eq!(version, Version::new(1, 1, 0));
+        let err = manager
+            .update_build_system_version(
+                &BuildSystem::PackagesProps,
+                None,
+                &Version::parse("1.3.0-beta.1")?,
+            )
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("<VersionPrefix> has no <VersionSuffix>"));
         Ok(())
     }
 
     #[test]
-    fn test_with_version_file_reads_custom_filename() -> Result<()> {
+    fn test_package_json_version_not_a_string() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("version.txt"), "3.2.1\n")?;
+        let manager = VersionManager::new(temp_dir.path());
 
-        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
-        let version = manager.read_version_file()?;
+        for (value, found) in [("123", "number"), ("null", "null")] {
+            fs::write(
+                temp_dir.path().join("package.json"),
+                format!("{{\"name\": \"test\", \"version\": {value}}}"),
+            )?;
+            let err = manager
+                .read_build_system_version(&BuildSystem::PackageJson, None)
+                .unwrap_err()
+                .to_string();
+            assert_eq!(
+                err,
+                format!("version field in package.json is not a string (found: {found})")
+            );
+        }
 
-        assert_eq!(version, Version::new(3, 2, 1));
+        fs::write(temp_dir.path().join("package.json"), "{\"name\": \"test\"}")?;
+        let err = manager
+            .read_build_system_version(&BuildSystem::PackageJson, None)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "No version found in package.json");
         Ok(())
     }
 
     #[test]
-    fn test_with_version_file_writes_custom_filename() -> Result<()> {
+    fn test_json_files_nested_version() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("version.txt"), "1.0.0\n")?;
+        create_test_files(temp_dir.path(), "1.2.3")?;
+        let original = "{\n    \"version\": \"0.0.1\",\n    \"other\": { \"version\": \"9.9.9\" },\n    \"meta\": {\n        \"name\": \"app\",\n        \"version\": \"1.0.0\"\n    }\n}\n";
+        fs::write(temp_dir.path().join("app.json"), original)?;
 
-        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
-        manager.write_version_file(&Version::new(2, 0, 0))?;
+        let mut config = Config::default();
+        config.json_files = vec![config::JsonFileConfig {
+            path: "app.json".to_string(),
+            key: "meta.version".to_string(),
+        }];
+        let manager = VersionManager::new(temp_dir.path()).with_config(config.clone());
 
-        let content = fs::read_to_string(temp_dir.path().join("version.txt"))?;
-        assert_eq!(content, "2.0.0\n");
+        let issues = manager.sync_issues()?;
+        assert_eq!(
+            issues[0].to_string(),
+            "app.json has meta.version = 1.0.0 but VERSION file has 1.2.3"
+        );
 
-        // Ensure no "VERSION" file was created
-        assert!(!temp_dir.path().join("VERSION").exists());
+        assert_eq!(manager.sync_versions()?, 1);
+        manager.verify_versions_in_sync()?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("app.json"))?,
+            original.replace("\"1.0.0\"", "\"1.2.3\"")
+        );
+
+        // A path that does not end at a string is an error, and the file is left alone
+        config.json_files[0].key = "meta".to_string();
+        let manager = VersionManager::new(temp_dir.path()).with_config(config.clone());
+        let err = manager.sync_versions().unwrap_err().to_string();
+        assert!(err.contains("meta in app.json is not a string"), "{err}");
+        config.json_files[0].key = "/meta/missing".to_string();
+        let manager = VersionManager::new(temp_dir.path()).with_config(config);
+        let err = manager.sync_versions().unwrap_err().to_string();
+        assert!(err.contains("No /meta/missing found in app.json"), "{err}");
         Ok(())
     }
 
     #[test]
-    fn test_with_version_file_bump_syncs_all() -> Result<()> {
+    fn test_python_modules_are_synced_and_verified_per_file() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("version.txt"), "1.0.0\n")?;
+        create_test_files(temp_dir.path(), "1.2.3")?;
+        fs::create_dir_all(temp_dir.path().join("src/pkg"))?;
+        fs::create_dir_all(temp_dir.path().join("docs"))?;
         fs::write(
-            temp_dir.path().join("Cargo.toml"),
-            "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+            temp_dir.path().join("src/pkg/__init__.py"),
+            "\"\"\"Package.\"\"\"\n\n__version__ = \"1.2.3\"\n",
         )?;
         fs::write(
-            temp_dir.path().join("pyproject.toml"),
-            "[project]\nname = \"test\"\nversion = \"1.0.0\"\n",
+            temp_dir.path().join("docs/conf.py"),
+            "project = \"pkg\"\nrelease = '1.0.0'\nversion = '1.2'\n",
         )?;
 
-        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
-        manager.bump_version(BumpType::Minor)?;
-
-        assert_eq!(manager.read_version_file()?, Version::new(1, 1, 0));
-        assert_eq!(manager.read_cargo_version()?, Version::new(1, 1, 0));
-        assert_eq!(manager.read_pyproject_version()?, Version::new(1, 1, 0));
-        Ok(())
-    }
+        let mut config = Config::default();
+        config.python_modules = vec![
+            config::PythonModuleConfig {
+                path: "src/pkg/__init__.py".to_string(),
+                variable: "__version__".to_string(),
+            },
+            config::PythonModuleConfig {
+                path: "docs/conf.py".to_string(),
+                variable: "release".to_string(),
+            },
+        ];
+        let manager = VersionManager::new(temp_dir.path()).with_config(config);
+
+        let issues = manager.sync_issues()?;
+        assert_eq!(
+            issues,
+            vec![SyncIssue::ModuleDrift {
+                path: "docs/conf.py".into(),
+                variable: "release".to_string(),
+                found: Version::new(1, 0, 0),
+                expected: Version::new(1, 2, 3),
+            }]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "docs/conf.py has release = 1.0.0 but VERSION file has 1.2.3"
+        );
 
-    #[test]
-    fn test_with_version_file_sync() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("version.txt"), "5.0.0\n")?;
-        fs::write(
-            temp_dir.path().join("Cargo.toml"),
-            "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
-        )?;
+        assert_eq!(manager.sync_versions()?, 1);
+        manager.verify_versions_in_sync()?;
 
-        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
-        manager.sync_versions()?;
+        manager.bump_version(BumpType::Minor)?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("src/pkg/__init__.py"))?,
+            "\"\"\"Package.\"\"\"\n\n__version__ = \"1.3.0\"\n"
+        );
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("docs/conf.py"))?,
+            "project = \"pkg\"\nrelease = '1.3.0'\nversion = '1.2'\n"
+        );
 
-        assert_eq!(manager.read_cargo_version()?, Version::new(5, 0, 0));
+        fs::remove_file(temp_dir.path().join("src/pkg/__init__.py"))?;
+        let issues = manager.sync_issues()?;
+        assert!(matches!(
+            issues.as_slice(),
+            [SyncIssue::ModuleUnreadable { path, .. }] if path == Path::new("src/pkg/__init__.py")
+        ));
+        assert!(issues[0].is_unreadable());
         Ok(())
     }
 
     #[test]
-    fn test_with_version_file_verify_in_sync() -> Result<()> {
+    fn test_dotenv_version_source_reads_and_writes_one_key() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("version.txt"), "2.0.0\n")?;
-        fs::write(
-            temp_dir.path().join("Cargo.toml"),
-            "[package]\nname = \"test\"\nversion = \"2.0.0\"\n",
-        )?;
+        let original = "# release settings\nAPP_NAME=demo\nexport VERSION=\"1.2.3\" # bumped by CI\nOTHER_VERSION=9.9.9\n";
+        fs::write(temp_dir.path().join("version.env"), original)?;
 
-        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
-        assert!(manager.verify_versions_in_sync().is_ok());
-        Ok(())
-    }
+        let mut config = Config::default();
+        config.version.source = config::VersionSource::Dotenv;
+        let manager =
+            VersionManager::with_version_file(temp_dir.path(), "version.env").with_config(config);
+
+        assert_eq!(manager.read_version_file()?, Version::new(1, 2, 3));
+        manager.write_version_file(&Version::new(1, 3, 0))?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("version.env"))?,
+            original.replace("\"1.2.3\"", "\"1.3.0\"")
+        );
 
-    #[test]
-    fn test_with_version_file_verify_out_of_sync() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        fs::write(temp_dir.path().join("version.txt"), "2.0.0\n")?;
         fs::write(
-            temp_dir.path().join("Cargo.toml"),
-            "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+            temp_dir.path().join("version.env"),
+            "VERSION = v2.0.0 # note\n",
         )?;
+        assert_eq!(manager.read_version_file()?, Version::new(2, 0, 0));
+        manager.write_version_file(&Version::new(2, 0, 1))?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("version.env"))?,
+            "VERSION = v2.0.1 # note\n"
+        );
 
-        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
-        assert!(manager.verify_versions_in_sync().is_err());
+        fs::write(temp_dir.path().join("version.env"), "APP_NAME=demo\n")?;
+        let err = manager.read_version_file().unwrap_err().to_string();
+        assert!(err.contains("No VERSION key found"), "{err}");
+        assert!(manager.write_version_file(&Version::new(3, 0, 0)).is_err());
         Ok(())
     }
 
     #[test]
-    fn test_with_version_file_comment_preservation() -> Result<()> {
+    fn test_env_version_source_errors_when_unset() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        fs::write(
-            temp_dir.path().join("version.txt"),
-            "1.0.0 # x-release-please-version\n",
-        )?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
 
-        let manager = VersionManager::with_version_file(temp_dir.path(), "version.txt");
+        let mut config = Config::default();
+        config.version.source = config::VersionSource::Env;
+        config.version.env_var = "VERSIONEER_TEST_UNSET_VARIABLE".to_string();
+        let manager = VersionManager::new(temp_dir.path()).with_config(config);
 
-        // Read strips comment
-        let version = manager.read_version_file()?;
-        assert_eq!(version, Version::new(1, 0, 0));
+        let err = manager.read_version_file().unwrap_err().to_string();
+        assert!(
+            err.contains("VERSIONEER_TEST_UNSET_VARIABLE is not set"),
+            "{err}"
+        );
 
-        // Write preserves comment
+        // The VERSION file is left alone unless write_file is set
         manager.write_version_file(&Version::new(2, 0, 0))?;
-        let content = fs::read_to_string(temp_dir.path().join("version.txt"))?;
-        assert_eq!(content, "2.0.0 # x-release-please-version\n");
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("VERSION"))?,
+            "1.0.0\n"
+        );
         Ok(())
     }
 
     #[test]
-    fn test_default_version_file_constant() {
-        assert_eq!(DEFAULT_VERSION_FILE, "VERSION");
-    }
+    fn test_sync_cascade_reports_progress() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
+        create_package_json(temp_dir.path(), "0.9.0", false)?;
+        fs::create_dir(temp_dir.path().join("sub"))?;
+        create_package_json(&temp_dir.path().join("sub"), "0.9.0", false)?;
 
-    #[test]
-    fn test_new_uses_default_filename() {
-        let manager = VersionManager::new("/tmp");
-        assert_eq!(manager.version_file, "VERSION");
+        let manager = VersionManager::new(temp_dir.path());
+        let mut seen = Vec::new();
+        manager.sync_cascade_with_progress(&mut |done, total, _path| seen.push((done, total)))?;
+
+        assert_eq!(seen, vec![(1, 2), (2, 2)]);
+        Ok(())
     }
 
     #[test]
-    fn test_bump_cascade_dry_run_does_not_write_files() -> Result<()> {
+    fn test_cascade_summary_counts() -> Result<()> {
         let temp_dir = TempDir::new()?;
-
-        // Create top-level VERSION
-        fs::write(temp_dir.path().join("VERSION"), "1.0.0")?;
-
-        // Create subdirectories with different manifest types
-        fs::create_dir(temp_dir.path().join("rust-tool"))?;
-        fs::write(
-            temp_dir.path().join("rust-tool/Cargo.toml"),
-            "[package]\nname = \"rust-tool\"\nversion = \"1.0.0\"\n",
-        )?;
-
-        fs::create_dir(temp_dir.path().join("python-client"))?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
+        create_package_json(temp_dir.path(), "1.0.0", false)?;
+        fs::create_dir(temp_dir.path().join("sub"))?;
+        create_package_json(&temp_dir.path().join("sub"), "0.9.0", false)?;
+        fs::create_dir(temp_dir.path().join("member"))?;
         fs::write(
-            temp_dir.path().join("python-client/pyproject.toml"),
-            "[project]\nname = \"python-client\"\nversion = \"1.0.0\"\n",
+            temp_dir.path().join("member/Cargo.toml"),
+            "[package]\nname = \"member\"\nversion.workspace = true\n",
         )?;
 
         let manager = VersionManager::new(temp_dir.path());
-        let changes = manager.bump_cascade_dry_run(BumpType::Patch)?;
-
-        // Should return preview of changes
-        assert_eq!(changes.new_version, Version::new(1, 0, 1));
-        assert_eq!(changes.files_to_update.len(), 3); // VERSION + Cargo.toml + pyproject.toml
-
-        // Verify files were NOT changed
-        let version = manager.read_version_file()?;
+        let summary = manager.sync_cascade()?;
         assert_eq!(
-            version,
-            Version::new(1, 0, 0),
-            "VERSION should not change in dry-run"
+            summary,
+            CascadeSummary {
+                scanned: 3,
+                updated: 1,
+                in_sync: 1,
+                skipped: 1,
+                changed_paths: vec![temp_dir.path().join("sub/package.json")],
+            }
         );
-
-        let cargo_content = fs::read_to_string(temp_dir.path().join("rust-tool/Cargo.toml"))?;
-        assert!(
-            cargo_content.contains("version = \"1.0.0\""),
-            "Cargo.toml should not change in dry-run"
+        assert_eq!(
+            summary.relative_to(temp_dir.path()).changed_paths,
+            vec![Path::new("sub/package.json")]
         );
 
-        let pyproject_content =
-            fs::read_to_string(temp_dir.path().join("python-client/pyproject.toml"))?;
-        assert!(
-            pyproject_content.contains("version = \"1.0.0\""),
-            "pyproject.toml should not change in dry-run"
-        );
+        // The VERSION file counts too when the cascade writes it
+        let summary = manager.bump_cascade(BumpType::Patch)?;
+        assert_eq!((summary.scanned, summary.updated), (4, 3));
+        assert!(summary.changed());
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_going_writes_what_it_can() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n")?;
+        create_package_json(temp_dir.path(), "0.9.0", false)?;
+        fs::create_dir(temp_dir.path().join("broken"))?;
+        fs::write(temp_dir.path().join("broken/package.json"), "{ not json")?;
 
+        // Fail-fast by default: the good file is rolled back
+        let manager = VersionManager::new(temp_dir.path());
+        assert!(manager.sync_cascade().is_err());
+        assert!(fs::read_to_string(temp_dir.path().join("package.json"))?.contains("0.9.0"));
+
+        let manager = manager.with_keep_going(true);
+        let err = manager.sync_cascade().unwrap_err();
+        assert_eq!(VersioneerError::kind_of(&err), "PartialCascade");
+        let details = VersioneerError::details_of(&err);
+        assert_eq!(details.len(), 1);
+        assert!(details[0].contains("broken/package.json"), "{details:?}");
+        assert!(err.to_string().contains("1 of 2 files"), "{err}");
+        assert!(fs::read_to_string(temp_dir.path().join("package.json"))?.contains("1.0.0"));
         Ok(())
     }
 }
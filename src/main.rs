@@ -1,34 +1,453 @@
 //! Versioneer CLI - A tool to synchronize VERSION files with build system version declarations
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::env;
-use versioneer::{BumpType, DEFAULT_VERSION_FILE, VersionManager, output::OutputFormatter};
+use std::path::Path;
+use versioneer::config::{Config, ManifestOverride, VersionSource};
+use versioneer::output::{
+    ColorChoice, JsonStyle, NullReporter, OutputFormatter, Reporter, WriterReporter, assume_yes,
+    env_file_lines, error_envelope, github_output_lines, running_in_ci,
+};
+use versioneer::report::OperationReport;
+use versioneer::trace;
+use versioneer::{
+    BuildSystem, BumpType, CalVerDate, CascadeSummary, DiscoveryOptions, DryRunResult,
+    ManifestVersion, PyProjectSource, SyncIssue, TagKind, UntrackedManifests, VersionManager,
+    VersionReport, VersioneerError, WriteSnapshot, next_prerelease,
+};
 use workhelix_cli_common::LicenseType;
 
+mod build_info;
+mod completions;
 mod doctor;
+mod effective_config;
+mod systems;
 
 #[derive(Parser)]
 #[command(name = "versioneer")]
 #[command(about = "A tool to synchronize VERSION files with build system version declarations")]
 #[command(version)]
+#[allow(clippy::struct_excessive_bools)]
 struct Cli {
-    /// Version filename to use (default: VERSION)
-    #[arg(long, global = true, default_value = DEFAULT_VERSION_FILE)]
-    version_file: String,
+    /// Project directory to manage (default: the current directory); build systems are
+    /// detected under it and a relative --version-file is resolved against it
+    #[arg(long, short = 'C', global = true, value_name = "DIR")]
+    base_path: Option<std::path::PathBuf>,
+
+    /// Manage the whole git repository: widen the project directory to the top level of
+    /// the repository enclosing it, wherever versioneer is run from
+    #[arg(long, global = true, visible_alias = "limit-to-git-root")]
+    git_root: bool,
+
+    /// Version filename to use (default: the first of VERSION, VERSION.txt, .version and
+    /// version that exists, else VERSION)
+    #[arg(long, global = true, value_name = "FILE")]
+    version_file: Option<String>,
+
+    /// Authoritative pyproject.toml table: project or commitizen
+    #[arg(long, global = true, default_value = "project")]
+    pyproject_source: PyProjectSource,
+
+    /// Output format for machine-readable results (dry-run previews, GitHub step outputs)
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Human)]
+    format: OutputFormat,
+
+    /// Indent JSON output over several lines for reading (`--format ndjson` stays one
+    /// object per line)
+    #[arg(long, global = true, conflicts_with = "json_compact")]
+    json_pretty: bool,
+
+    /// Print each JSON document on a single line (the default)
+    #[arg(long, global = true)]
+    json_compact: bool,
+
+    /// When to use colors and emojis: auto, always or never. `auto` honors
+    /// `CLICOLOR_FORCE`, then `NO_COLOR`, then whether stdout is a terminal outside CI
+    #[arg(long, global = true, value_name = "WHEN", default_value = "auto")]
+    color: ColorChoice,
+
+    /// Operate on just the VERSION file when no build system files are detected
+    #[arg(long, global = true)]
+    allow_empty: bool,
+
+    /// Maximum directory depth for cascade discovery (the project root is depth 0)
+    #[arg(long, global = true)]
+    max_depth: Option<usize>,
+
+    /// Descend into symlinked directories during cascade discovery
+    #[arg(long, global = true)]
+    follow_symlinks: bool,
+
+    /// Do not skip the default excluded directories (target, .git, vendor, ...) during cascade discovery
+    #[arg(long, global = true)]
+    no_default_excludes: bool,
+
+    /// Cascade into manifests git does not track (skipped by default inside a git repository)
+    #[arg(long, global = true)]
+    include_untracked: bool,
+
+    /// Fail cascade discovery if a manifest is not tracked by git, instead of skipping it
+    #[arg(long, global = true, conflicts_with = "include_untracked")]
+    abort_on_untracked_manifest: bool,
+
+    /// Let a cascade write every file it can and list the failures at the end, instead
+    /// of stopping at the first and rolling back; may leave the tree partly updated
+    #[arg(long, global = true)]
+    keep_going: bool,
+
+    /// Replace `$version$` pack-time placeholders in .nuspec files
+    #[arg(long, global = true)]
+    overwrite_placeholders: bool,
+
+    /// Increment the Info.plist `CFBundleVersion` build number whenever the version changes
+    #[arg(long, global = true)]
+    bump_bundle_version: bool,
+
+    /// Strip `+build` metadata when writing to every build system, not only the
+    /// configured ones (Cargo.toml and package.json by default)
+    #[arg(long, global = true)]
+    no_build_metadata: bool,
+
+    /// Write the VERSION file with a leading `v` (`v1.2.3`); by default an existing
+    /// prefix is kept and none is added
+    #[arg(long, global = true)]
+    v_prefix: bool,
+
+    /// Refuse to tag a prerelease of the latest tag's major.minor.patch (`v1.2.0` →
+    /// `1.2.0-rc.1`); core version decreases are always refused
+    #[arg(long, global = true)]
+    block_prerelease_downgrade: bool,
+
+    /// Edit JSON manifests by replacing only the version string's text, never
+    /// re-serializing the document (same as `[json] exact = true`)
+    #[arg(long, global = true)]
+    no_package_json_reserialize: bool,
+
+    /// Reject a VERSION file that is not exactly the canonical version, e.g. with stray
+    /// whitespace (same as `[version] strict = true`)
+    #[arg(long, global = true)]
+    strict_version: bool,
+
+    /// Leave this build system alone even when its manifest is present (repeatable);
+    /// adds to `ignore_systems` in the config file
+    #[arg(long, global = true, value_name = "SYSTEM")]
+    skip: Vec<BuildSystem>,
+
+    /// Also manage this file, whatever its name, as the format given by the matching
+    /// --manifest-format (repeatable, paired in order); adds to `[[manifest_overrides]]`
+    #[arg(long, global = true, value_name = "PATH", requires = "manifest_format")]
+    manifest: Vec<String>,
+
+    /// Format of the matching --manifest: cargo, pyproject, package-json, cmake,
+    /// terraform, nuspec, gleam, packages-props, setup-cfg, gemspec or info-plist
+    #[arg(long, global = true, value_name = "SYSTEM", requires = "manifest")]
+    manifest_format: Vec<BuildSystem>,
+
+    /// Log every file read and write (path, action, outcome; never contents) to this
+    /// file as NDJSON, for diagnosing what versioneer touched
+    #[arg(long, global = true, value_name = "PATH")]
+    trace_file: Option<std::path::PathBuf>,
 
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+impl Cli {
+    /// Layout for JSON output: pretty only when asked for and not streaming NDJSON
+    fn json_style(&self) -> JsonStyle {
+        if self.json_pretty && self.format != OutputFormat::Ndjson {
+            JsonStyle::Pretty
+        } else {
+            JsonStyle::Compact
+        }
+    }
+}
+
+/// Options shared by the `major`, `minor`, `patch`, `bump`, `calver` and `prerelease` subcommands
+#[derive(Args)]
+#[allow(clippy::struct_excessive_bools)]
+struct BumpArgs {
+    /// Update all manifests in subdirectories recursively
+    #[arg(long)]
+    cascade: bool,
+    /// Preview changes without writing files (requires --cascade)
+    #[arg(long)]
+    dry_run: bool,
+    /// Suppress output (only show errors)
+    #[arg(long, short)]
+    quiet: bool,
+    /// On success print only the new version (e.g. `1.2.4`) to stdout, for
+    /// `NEW=$(versioneer patch --print-version-only)`
+    #[arg(long, conflicts_with_all = ["quiet", "dry_run", "summary"])]
+    print_version_only: bool,
+    /// With --cascade, end with a summary: files scanned, updated, already in sync and
+    /// skipped, plus the updated paths
+    #[arg(long, requires = "cascade", conflicts_with = "dry_run")]
+    summary: bool,
+    /// Create a git tag for the new version
+    #[arg(long)]
+    tag: bool,
+    /// With --tag, skip tagging (and exit 0) when the bump left every file unchanged
+    #[arg(long, requires = "tag")]
+    only_if_changed: bool,
+    /// With --tag, accept a bump that lands on the version already on disk: skip the
+    /// write and tag that version (tag collisions are still refused)
+    #[arg(long, requires = "tag", conflicts_with = "only_if_changed")]
+    allow_same_version: bool,
+    /// Re-verify all files are in sync after writing; fail if any are not
+    #[arg(long)]
+    verify_after: bool,
+    /// Bump from this version instead of reading the VERSION file
+    #[arg(long, value_name = "VERSION")]
+    from: Option<semver::Version>,
+    /// Apply the bump this many times in sequence, writing only the final version
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    count: u32,
+    /// File to read the current version from: version-file, or a build system name
+    /// (cargo, pyproject, ...); the result is written everywhere
+    #[arg(
+        long,
+        value_name = "SOURCE",
+        default_value = "version-file",
+        conflicts_with = "from"
+    )]
+    source: BumpSource,
+    /// Also write `VERSION`, `VERSION_MAJOR`, ... as unquoted `KEY=value` lines to this
+    /// file, replacing it
+    #[arg(long, value_name = "PATH", conflicts_with = "dry_run")]
+    env_file: Option<std::path::PathBuf>,
+    /// Run this shell command in the project directory after writing, with the new
+    /// version in `$VERSIONEER_VERSION`; fail if it exits non-zero
+    #[arg(long, value_name = "COMMAND", conflicts_with = "dry_run")]
+    after_write_command: Option<String>,
+    /// Write a JSON report of the operation (versions, changed files, tag) to this
+    /// file, replacing it; dry runs record `"applied": false`
+    #[arg(long, value_name = "PATH")]
+    report_file: Option<std::path::PathBuf>,
+    /// At the end, print the same report as one JSON line on stderr, leaving stdout
+    /// human-readable; dry runs print it with `"applied": false`
+    #[arg(long)]
+    summary_json_on_stderr: bool,
+    /// After writing, print one line with the number of files changed and the version
+    /// transition (e.g. `3 files changed, version 1.2.3 → 1.3.0`)
+    #[arg(long, conflicts_with = "dry_run")]
+    show_diff_stat: bool,
+    #[command(flatten)]
+    tag_args: TagArgs,
+}
+
+impl BumpArgs {
+    /// Whether decorative output (success prose, progress, warnings) is suppressed
+    const fn silent(&self) -> bool {
+        self.quiet || self.print_version_only
+    }
+
+    /// Whether a report of the operation is wanted, in a file or on stderr
+    const fn reports(&self) -> bool {
+        self.report_file.is_some() || self.summary_json_on_stderr
+    }
+
+    /// Deliver `report` wherever it was asked for (see [`emit_report`])
+    fn emit_report(&self, report: &OperationReport) -> Result<()> {
+        emit_report(
+            self.report_file.as_deref(),
+            self.summary_json_on_stderr,
+            report,
+        )
+    }
+}
+
+/// How a bump computes the next version
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Bump {
+    /// Increment a semver component
+    Semver(BumpType),
+    /// Derive `YYYY.MM.<n>` from a calendar month
+    CalVer(CalVerDate),
+    /// Start a prerelease cycle on a bumped core version, or continue the current one
+    Prerelease {
+        bump: Option<BumpType>,
+        ident: String,
+    },
+}
+
+impl Bump {
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::Semver(bump_type) => bump_type.name(),
+            Self::CalVer(_) => "calver",
+            Self::Prerelease { .. } => "prerelease",
+        }
+    }
+
+    fn apply(&self, current: &semver::Version) -> Result<semver::Version> {
+        match self {
+            Self::Semver(bump_type) => Ok(bump_type.apply(current)),
+            Self::CalVer(date) => date.next_version(current),
+            Self::Prerelease { bump, ident } => next_prerelease(current, *bump, ident),
+        }
+    }
+
+    /// The bump for each repetition after the first: a prerelease cycle, once started,
+    /// continues rather than bumping the core version again
+    fn repeated(&self) -> Self {
+        match self {
+            Self::Prerelease { ident, .. } => Self::Prerelease {
+                bump: None,
+                ident: ident.clone(),
+            },
+            other => other.clone(),
+        }
+    }
+}
+
+/// Which file a bump reads the current version from
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BumpSource {
+    /// The VERSION file
+    VersionFile,
+    /// A build system's manifest in the current directory
+    BuildSystem(BuildSystem),
+}
+
+impl std::str::FromStr for BumpSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s == "version-file" {
+            return Ok(Self::VersionFile);
+        }
+        s.parse().map(Self::BuildSystem).map_err(|_| {
+            let names: Vec<&str> = BuildSystem::ALL.iter().map(BuildSystem::name).collect();
+            anyhow::anyhow!(
+                "Unknown source '{s}' (expected version-file or one of {})",
+                names.join(", ")
+            )
+        })
+    }
+}
+
+/// The version to bump from when it does not come from the VERSION file
+///
+/// `--from` gives it directly; `--source` names a detected build system to read it from.
+fn explicit_bump_base(
+    manager: &VersionManager,
+    args: &BumpArgs,
+) -> Result<Option<semver::Version>> {
+    if let Some(from) = &args.from {
+        return Ok(Some(from.clone()));
+    }
+    match &args.source {
+        BumpSource::VersionFile => Ok(None),
+        BumpSource::BuildSystem(system) => {
+            if !manager.detect_build_systems().contains(system) {
+                anyhow::bail!(
+                    "--source {}: no {} found in the current directory",
+                    system.name(),
+                    system.manifest_name()
+                );
+            }
+            manager
+                .read_build_system_version(system, None)
+                .with_context(|| format!("Failed to read {system:?} version"))
+                .map(Some)
+        }
+    }
+}
+
+/// Options controlling git tag creation
+#[derive(Args)]
+#[allow(clippy::struct_excessive_bools)]
+struct TagArgs {
+    /// Prefix prepended to the version when naming the tag (default: the prefix most
+    /// existing tags use, or `v`)
+    #[arg(long)]
+    tag_prefix: Option<String>,
+    /// Allow tagging a version that is not greater than the latest existing tag
+    #[arg(long)]
+    allow_retag: bool,
+    /// If the tag already exists, append or increment a numeric prerelease (1.2.3-1,
+    /// 1.2.3-2, ...) until a free tag is found, and write that version back to the files
+    #[arg(long, conflicts_with = "allow_retag")]
+    prerelease_bump_on_collision: bool,
+    /// Create an annotated tag with the message `Release <tag>`
+    #[arg(long)]
+    annotate: bool,
+    /// GPG-sign the tag (implies --annotate)
+    #[arg(long)]
+    sign: bool,
+}
+
+impl TagArgs {
+    const fn kind(&self) -> TagKind {
+        if self.sign {
+            TagKind::Signed
+        } else if self.annotate {
+            TagKind::Annotated
+        } else {
+            TagKind::Lightweight
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Show version information
-    Version,
+    Version {
+        /// Also show the build target, git commit and rustc version
+        #[arg(long, short)]
+        verbose: bool,
+    },
     /// Show license information
     License,
     /// Bump the major version (x.y.z -> (x+1).0.0)
-    Major {
+    Major(BumpArgs),
+    /// Bump the minor version (x.y.z -> x.(y+1).0)
+    Minor(BumpArgs),
+    /// Bump the patch version (x.y.z -> x.y.(z+1))
+    Patch(BumpArgs),
+    /// Pick major, minor or patch interactively, previewing each resulting version
+    Bump(BumpArgs),
+    /// Set a calendar version YYYY.MM.N from today's date (UTC)
+    ///
+    /// N increments within the same month and restarts at 0 in a new one.
+    Calver {
+        /// Use this date (YYYY-MM-DD) instead of today, e.g. for reproducible builds
+        #[arg(long, value_name = "DATE")]
+        date: Option<CalVerDate>,
+        #[command(flatten)]
+        args: BumpArgs,
+    },
+    /// Start a prerelease cycle on a bumped core version, or continue the current one
+    ///
+    /// `prerelease minor` takes 1.2.3 to 1.3.0-rc.1; `prerelease` alone then takes it to
+    /// 1.3.0-rc.2.
+    Prerelease {
+        /// Bump this component first and start a new cycle: major, minor or patch
+        bump_type: Option<BumpType>,
+        /// Prerelease identifier; switching to a later one (beta → rc) restarts at 1
+        #[arg(long, default_value = "rc")]
+        ident: String,
+        #[command(flatten)]
+        args: BumpArgs,
+    },
+    /// Show the current version
+    Show {
+        /// Also write `VERSION`, `VERSION_MAJOR`, ... as unquoted `KEY=value` lines to this
+        /// file, replacing it
+        #[arg(long, value_name = "PATH")]
+        env_file: Option<std::path::PathBuf>,
+    },
+    /// List every manifest cascade discovery finds, with its version and sync state
+    List,
+    /// Synchronize all version files to match the VERSION file
+    Sync {
         /// Update all manifests in subdirectories recursively
         #[arg(long)]
         cascade: bool,
@@ -38,21 +457,74 @@ enum Commands {
         /// Suppress output (only show errors)
         #[arg(long, short)]
         quiet: bool,
+        /// Re-verify all files are in sync after writing; fail if any are not
+        #[arg(long)]
+        verify_after: bool,
+        /// Adopt a build system's version and write it to VERSION and everything else
+        #[arg(long, conflicts_with = "dry_run")]
+        reverse: bool,
+        /// With --cascade, end with a summary: files scanned, updated, already in sync
+        /// and skipped, plus the updated paths
+        #[arg(long, requires = "cascade", conflicts_with_all = ["dry_run", "reverse"])]
+        summary: bool,
+        /// With --reverse, when the build systems disagree, list their versions and ask
+        /// which to adopt (needs a terminal)
+        #[arg(long, requires = "reverse", conflicts_with = "source")]
+        interactive: bool,
+        /// Build system to adopt with --reverse: cargo, pyproject, package-json, cmake,
+        /// terraform, nuspec, gleam, packages-props, setup-cfg, gemspec or info-plist
+        #[arg(long, requires = "reverse")]
+        source: Option<BuildSystem>,
+        /// Run this shell command in the project directory after writing, with the new
+        /// version in `$VERSIONEER_VERSION`; fail if it exits non-zero
+        #[arg(long, value_name = "COMMAND", conflicts_with = "dry_run")]
+        after_write_command: Option<String>,
+        /// Write a JSON report of the operation (versions, changed files) to this file,
+        /// replacing it; dry runs record `"applied": false`
+        #[arg(long, value_name = "PATH", conflicts_with = "reverse")]
+        report_file: Option<std::path::PathBuf>,
+        /// At the end, print the same report as one JSON line on stderr, leaving stdout
+        /// human-readable; dry runs print it with `"applied": false`
+        #[arg(long, conflicts_with = "reverse")]
+        summary_json_on_stderr: bool,
     },
-    /// Bump the minor version (x.y.z -> x.(y+1).0)
-    Minor {
-        /// Update all manifests in subdirectories recursively
+    /// Show which build systems are detected
+    Status {
+        /// Compare only major.minor.patch, ignoring prerelease and build differences
         #[arg(long)]
-        cascade: bool,
-        /// Preview changes without writing files (requires --cascade)
+        ignore_prerelease: bool,
+    },
+    /// Verify that all version files are synchronized
+    ///
+    /// Exit codes: 0 in sync, 3 version drift only, 4 a version could not be read,
+    /// 1 any other failure.
+    Verify {
+        /// Suppress the success message; errors are still reported
         #[arg(long)]
-        dry_run: bool,
-        /// Suppress output (only show errors)
-        #[arg(long, short)]
         quiet: bool,
+        /// Print nothing at all and rely on the exit code (for pre-commit hooks)
+        #[arg(long)]
+        check: bool,
+        /// Only check these build systems (repeatable): cargo, pyproject, package-json,
+        /// cmake, terraform, nuspec, gleam, packages-props, setup-cfg, gemspec or info-plist;
+        /// configured Python modules and JSON files are skipped
+        #[arg(long, value_name = "SYSTEM")]
+        only: Vec<BuildSystem>,
+        /// Compare only major.minor.patch, ignoring prerelease and build differences
+        #[arg(long)]
+        ignore_prerelease: bool,
+        /// Also fail unless the version tag pointing at HEAD names the VERSION file's
+        /// version (for publish pipelines that run on a tag checkout)
+        #[arg(long)]
+        against_tag: bool,
+        /// With --against-tag, check this tag name instead of the one at HEAD
+        #[arg(long, value_name = "NAME", requires = "against_tag")]
+        tag: Option<String>,
     },
-    /// Bump the patch version (x.y.z -> x.y.(z+1))
-    Patch {
+    /// Reset the version to a specific version or 0.0.0
+    Reset {
+        /// The version to reset to (default: 0.0.0)
+        version: Option<String>,
         /// Update all manifests in subdirectories recursively
         #[arg(long)]
         cascade: bool,
@@ -62,406 +534,1588 @@ enum Commands {
         /// Suppress output (only show errors)
         #[arg(long, short)]
         quiet: bool,
-    },
-    /// Show the current version
-    Show,
-    /// Synchronize all version files to match the VERSION file
-    Sync {
-        /// Update all manifests in subdirectories recursively
+        /// Re-verify all files are in sync after writing; fail if any are not
         #[arg(long)]
-        cascade: bool,
-        /// Preview changes without writing files (requires --cascade)
+        verify_after: bool,
+        /// Carry over the current version's prerelease (1.2.3-beta.4 → 2.0.0-beta.4)
         #[arg(long)]
-        dry_run: bool,
+        keep_prerelease: bool,
+        /// With --keep-prerelease, restart the prerelease counter at 1 (2.0.0-beta.1)
+        #[arg(long, requires = "keep_prerelease")]
+        reset_prerelease_counter: bool,
+        /// With --cascade, end with a summary: files scanned, updated, already in sync
+        /// and skipped, plus the updated paths
+        #[arg(long, requires = "cascade", conflicts_with = "dry_run")]
+        summary: bool,
+        /// Refuse targets below this version (overrides the config file)
+        #[arg(long, value_name = "VERSION")]
+        min_version: Option<semver::Version>,
+        /// Refuse targets above this version (overrides the config file)
+        #[arg(long, value_name = "VERSION")]
+        max_version: Option<semver::Version>,
+        /// Run this shell command in the project directory after writing, with the new
+        /// version in `$VERSIONEER_VERSION`; fail if it exits non-zero
+        #[arg(long, value_name = "COMMAND", conflicts_with = "dry_run")]
+        after_write_command: Option<String>,
+        /// Write a JSON report of the operation (versions, changed files) to this file,
+        /// replacing it; dry runs record `"applied": false`
+        #[arg(long, value_name = "PATH")]
+        report_file: Option<std::path::PathBuf>,
+        /// At the end, print the same report as one JSON line on stderr, leaving stdout
+        /// human-readable; dry runs print it with `"applied": false`
+        #[arg(long)]
+        summary_json_on_stderr: bool,
+    },
+    /// Compute the next release candidate version from VERSION + git tags
+    Rc,
+    /// Create a git tag for the current version, without bumping or writing files
+    ///
+    /// The version files must be in sync, as they are after a bump done elsewhere.
+    Tag {
+        #[command(flatten)]
+        tag_args: TagArgs,
+        /// Tag even if the build system files disagree with the VERSION file
+        #[arg(long)]
+        no_verify: bool,
         /// Suppress output (only show errors)
         #[arg(long, short)]
         quiet: bool,
     },
-    /// Show which build systems are detected
-    Status,
-    /// Verify that all version files are synchronized
-    Verify,
-    /// Reset the version to a specific version or 0.0.0
-    Reset {
-        /// The version to reset to (default: 0.0.0)
-        version: Option<String>,
+    /// Bump, update CHANGELOG.md, commit, and create an annotated tag in one step
+    ///
+    /// Any failure rolls the repository back, so a release commit never exists
+    /// without its tag.
+    Release {
+        /// Which version component to bump: major, minor or patch
+        bump_type: BumpType,
+        /// GPG-sign the release tag
+        #[arg(long)]
+        sign: bool,
         /// Update all manifests in subdirectories recursively
         #[arg(long)]
         cascade: bool,
-        /// Preview changes without writing files (requires --cascade)
+        /// Print the planned steps without changing anything
         #[arg(long)]
         dry_run: bool,
+        /// Prefix prepended to the version when naming the tag (default: the prefix most
+        /// existing tags use, or `v`)
+        #[arg(long)]
+        tag_prefix: Option<String>,
         /// Suppress output (only show errors)
         #[arg(long, short)]
         quiet: bool,
     },
-    /// Compute the next release candidate version from VERSION + git tags
-    Rc,
     /// Generate shell completion scripts
     Completions {
         /// Shell to generate completions for
         shell: clap_complete::Shell,
     },
     /// Check health and configuration
-    Doctor,
+    Doctor {
+        /// Repair what can be repaired: sync out-of-sync build systems to VERSION, or
+        /// create a missing VERSION file when the build systems agree
+        #[arg(long)]
+        fix: bool,
+        /// With --fix, repair without asking for confirmation (also set by
+        /// `VERSIONEER_ASSUME_YES`)
+        #[arg(long, short, visible_alias = "assume-yes", requires = "fix")]
+        yes: bool,
+        /// Treat a build system file committed after VERSION as an issue, not a warning
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Print the effective configuration and where each value came from
+    /// (default, .versioneer.toml, command line, environment, ...)
+    Config,
+    /// List every build system versioneer can read and update, the files each matches,
+    /// and whether it is auto-detected or must be configured
+    Systems,
+    /// Print the paths of the VERSION file and every manifest versioneer manages
+    Paths {
+        /// Include all manifests in subdirectories recursively
+        #[arg(long)]
+        cascade: bool,
+        /// Emit a JSON object instead of one path per line
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Output format for machine-consumable results
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable text
+    Human,
+    /// JSON on stdout
+    Json,
+    /// GitHub Actions step outputs appended to `$GITHUB_OUTPUT` (stdout if unset)
+    Github,
+    /// One compact JSON object per manifest, streamed as discovery finds them
+    /// (`list` and cascade dry-runs; other commands print JSON as for `json`)
+    Ndjson,
+}
+
+/// Replace `path` with the dotenv-style version lines (see [`env_file_lines`])
+///
+/// The lines go to a temporary file beside `path` that is then renamed over it, so a
+/// reader never sees a partly written file.
+fn write_env_file(path: &Path, version: &semver::Version) -> Result<()> {
+    replace_file(path, &env_file_lines(version), "env file")
+}
+
+/// Deliver a finished report: to `path` for `--report-file`, and as one JSON line on
+/// stderr for `--summary-json-on-stderr`
+fn emit_report(path: Option<&Path>, on_stderr: bool, report: &OperationReport) -> Result<()> {
+    if let Some(path) = path {
+        write_report_file(path, report)?;
+    }
+    if on_stderr {
+        eprintln!("{}", serde_json::to_string(report)?);
+    }
+    Ok(())
+}
+
+/// Write `report` as pretty-printed JSON to `path` for `--report-file`
+///
+/// Replaced atomically like the env file (see [`write_env_file`]).
+fn write_report_file(path: &Path, report: &OperationReport) -> Result<()> {
+    let mut json = serde_json::to_string_pretty(report)?;
+    json.push('\n');
+    replace_file(path, &json, "report file")
+}
+
+/// Write `contents` to a temporary file beside `path`, then rename it over `path`
+fn replace_file(path: &Path, contents: &str, kind: &str) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("Invalid {kind} path {}", path.display()))?;
+    let temp = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+    trace::write(&temp, contents)
+        .with_context(|| format!("Failed to write {kind} {}", temp.display()))?;
+    if let Err(err) = trace::rename(&temp, path) {
+        let _ = trace::remove_file(&temp);
+        return Err(err).with_context(|| format!("Failed to replace {kind} {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Append `version`, `previous` and `bumped` step outputs for GitHub Actions
+///
+/// Writes to the file named by `GITHUB_OUTPUT`, falling back to the reporter when unset.
+fn write_github_outputs(
+    reporter: &dyn Reporter,
+    version: &semver::Version,
+    previous: &semver::Version,
+    bumped: bool,
+) -> Result<()> {
+    use std::io::Write;
+
+    let lines = github_output_lines(&[
+        ("version", version.to_string()),
+        ("previous", previous.to_string()),
+        ("bumped", bumped.to_string()),
+    ]);
+
+    match env::var_os("GITHUB_OUTPUT").map(std::path::PathBuf::from) {
+        Some(path) => {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("Failed to open GITHUB_OUTPUT file {}", path.display()))?;
+            file.write_all(lines.as_bytes())
+                .context("Failed to write GITHUB_OUTPUT file")?;
+        }
+        None => reporter.data(lines.trim_end()),
+    }
+    Ok(())
+}
+
+/// Print a dry-run preview of the files that would be updated
+fn report_dry_run(
+    reporter: &dyn Reporter,
+    format: OutputFormat,
+    manager: &VersionManager,
+    headline: &str,
+    changes: &DryRunResult,
+) -> Result<()> {
+    match format {
+        OutputFormat::Human | OutputFormat::Github => {
+            reporter.success(headline);
+            reporter.info("\nFiles to update:");
+            for file in &changes.files_to_update {
+                reporter.info(&format!("  {}", file.display()));
+            }
+            if let Some(tag_name) = &changes.tag_name {
+                reporter.info(&format!("\nTag to create: {tag_name}"));
+            }
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let relative = changes.relative_to(&manager.base_path);
+            reporter.data(&reporter.json_style().render(&relative)?);
+        }
+    }
+    Ok(())
+}
+
+/// The tag a bump with `--tag` will create for `next_version`, or `None` without `--tag`
+///
+/// Fails if the tag would not advance past the latest one, unless retagging is allowed
+/// or the tag step may be skipped.
+fn plan_tag(
+    manager: &VersionManager,
+    args: &BumpArgs,
+    next_version: &semver::Version,
+    may_skip_tag: bool,
+) -> Result<Option<String>> {
+    if !args.tag {
+        return Ok(None);
+    }
+    let tag_prefix = manager.resolve_tag_prefix(args.tag_args.tag_prefix.as_deref());
+    let tag_version = if args.tag_args.prerelease_bump_on_collision {
+        manager.resolve_tag_collision(next_version, &tag_prefix)?
+    } else {
+        next_version.clone()
+    };
+    if !args.tag_args.allow_retag && !may_skip_tag && tag_version == *next_version {
+        manager.check_tag_advances(next_version, &tag_prefix)?;
+    }
+    Ok(Some(format!("{tag_prefix}{tag_version}")))
+}
+
+/// Print a cascade's end-of-run summary (paths are relative to the project root)
+fn report_cascade_summary(
+    reporter: &dyn Reporter,
+    format: OutputFormat,
+    manager: &VersionManager,
+    summary: &CascadeSummary,
+) -> Result<()> {
+    let summary = summary.relative_to(&manager.base_path);
+    match format {
+        OutputFormat::Human | OutputFormat::Github => {
+            reporter.info(&format!(
+                "\nSummary: {} scanned, {} updated, {} already in sync, {} skipped",
+                summary.scanned, summary.updated, summary.in_sync, summary.skipped
+            ));
+            for path in &summary.changed_paths {
+                reporter.info(&format!("  updated {}", path.display()));
+            }
+        }
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            reporter.data(&reporter.json_style().render(&summary)?);
+        }
+    }
+    Ok(())
+}
+
+/// Report one compact JSON object per manifest as discovery finds it
+///
+/// Each line is emitted immediately so consumers can start before the walk ends.
+fn stream_manifest_statuses(manager: &VersionManager, reporter: &dyn Reporter) -> Result<()> {
+    for status in manager.manifest_statuses()? {
+        let status = status?.relative_to(&manager.base_path);
+        reporter.data(&serde_json::to_string(&status)?);
+    }
+    Ok(())
+}
+
+/// Handle the `major`, `minor` and `patch` subcommands
+fn run_bump(
+    manager: &VersionManager,
+    reporter: &dyn Reporter,
+    format: OutputFormat,
+    bump: &Bump,
+    args: &BumpArgs,
+) -> Result<()> {
+    let label = bump.name();
+
+    check_cascade_options(
+        manager,
+        reporter,
+        format,
+        args.cascade,
+        args.dry_run,
+        args.silent(),
+    )?;
+
+    let base = explicit_bump_base(manager, args)?;
+    let previous_version = match &base {
+        Some(base) => base.clone(),
+        None => manager.read_version_file()?,
+    };
+    let next_version = apply_repeatedly(reporter, bump, &previous_version, args)?;
+    // Single semver bumps have dedicated write paths; anything else writes its computed
+    // target
+    let semver_bump = match bump {
+        Bump::Semver(bump_type) if base.is_none() && args.count == 1 => Some(*bump_type),
+        _ => None,
+    };
+
+    // Refuse up front so a failed tag check never leaves files bumped. A bump that
+    // lands on the current version may be skipped by --only-if-changed, so its tag is
+    // checked only once the files are known to have changed.
+    let unchanged = check_same_version(manager, label, args, &next_version)?;
+    let may_skip_tag = args.only_if_changed && unchanged;
+    let planned_tag = plan_tag(manager, args, &next_version, may_skip_tag)?;
+    let report = if args.reports() || args.show_diff_stat {
+        Some(manager.plan_report(label, &next_version, args.cascade)?)
+    } else {
+        None
+    };
+
+    if let Some(report) = report.clone().filter(|_| args.dry_run) {
+        let tag = planned_tag.clone();
+        args.emit_report(&OperationReport { tag, ..report })?;
+    }
+
+    if args.dry_run && format == OutputFormat::Ndjson {
+        if !args.silent() {
+            stream_manifest_statuses(manager, reporter)?;
+        }
+        return Ok(());
+    }
+
+    if args.dry_run {
+        let mut changes = semver_bump
+            .map_or_else(
+                || manager.reset_cascade_dry_run(&next_version.to_string()),
+                |bump_type| manager.bump_cascade_dry_run(bump_type),
+            )
+            .with_context(|| format!("Failed to preview {label} version bump"))?;
+        changes.tag_name = planned_tag;
+        if !args.silent() {
+            report_dry_run(
+                reporter,
+                format,
+                manager,
+                &format!("Would bump to version {}", changes.new_version),
+                &changes,
+            )?;
+        }
+        if format == OutputFormat::Github {
+            write_github_outputs(reporter, &changes.new_version, &previous_version, false)?;
+        }
+        return Ok(());
+    }
+
+    let (changed, summary) = write_bump(
+        manager,
+        reporter,
+        args,
+        semver_bump,
+        base.is_some(),
+        &next_version,
+    )
+    .with_context(|| format!("Failed to bump {label} version"))?;
+
+    let new_version = written_version(manager, &next_version)?;
+    report_bumped(
+        reporter,
+        bump,
+        args,
+        &previous_version,
+        &new_version,
+        changed,
+        changed_file_count(summary.as_ref(), report.as_ref()),
+    );
+    if format == OutputFormat::Github {
+        write_github_outputs(reporter, &new_version, &previous_version, true)?;
+    }
+    if let Some(path) = &args.env_file {
+        write_env_file(path, &new_version)?;
+    }
+    run_after_write_command(manager, args.after_write_command.as_deref(), &new_version)?;
+    if let Some(summary) = summary.filter(|_| args.summary) {
+        report_cascade_summary(reporter, format, manager, &summary)?;
+    }
+
+    let tagged = tag_after_bump(manager, reporter, args, &new_version, changed)?;
+    if let Some(report) = report {
+        args.emit_report(&report.applied(tagged.as_ref().map(|(name, _)| name.clone())))?;
+    }
+
+    if args.print_version_only {
+        // A tag collision may have moved the files to a prerelease
+        let version = tagged.map_or(new_version, |(_, version)| version);
+        reporter.data(&version.to_string());
+    }
+    Ok(())
+}
+
+/// Print the outcome of a written bump and, with `--show-diff-stat`, how many `files`
+/// changed, unless output is silenced
+fn report_bumped(
+    reporter: &dyn Reporter,
+    bump: &Bump,
+    args: &BumpArgs,
+    previous_version: &semver::Version,
+    new_version: &semver::Version,
+    changed: bool,
+    files: usize,
+) {
+    if args.silent() {
+        return;
+    }
+    if args.allow_same_version && !changed {
+        reporter.info(&format!(
+            "Already at version {new_version}; no files written"
+        ));
+    } else if matches!(bump, Bump::Prerelease { .. }) {
+        reporter.success(&format!("Prerelease {previous_version} → {new_version}"));
+    } else {
+        reporter.success(&format!("Bumped to version {new_version}"));
+    }
+    if args.show_diff_stat {
+        reporter.info(&diff_stat(files, previous_version, new_version));
+    }
+}
+
+/// Refuse a tagging bump that would change no file, unless the no-op is opted into
+///
+/// Returns whether the bump lands on what is already on disk. Tagging such a bump
+/// would only re-tag the current version, so it needs `--allow-same-version` (write
+/// nothing, tag anyway) or `--only-if-changed` (write nothing, skip the tag).
+fn check_same_version(
+    manager: &VersionManager,
+    label: &str,
+    args: &BumpArgs,
+    next_version: &semver::Version,
+) -> Result<bool> {
+    if !args.tag {
+        return Ok(false);
+    }
+    let unchanged = manager
+        .plan_report(label, next_version, args.cascade)?
+        .files
+        .is_empty();
+    if unchanged && !args.only_if_changed && !args.allow_same_version {
+        anyhow::bail!(
+            "Already at version {next_version}: the bump changes no file, so there is nothing new to tag; pass --allow-same-version to tag it anyway, or --only-if-changed to skip the tag"
+        );
+    }
+    Ok(unchanged)
+}
+
+/// How many files a bump rewrote: a cascade's own count, else the pre-write plan's
+fn changed_file_count(summary: Option<&CascadeSummary>, report: Option<&OperationReport>) -> usize {
+    summary.map_or_else(
+        || report.map_or(0, |report| report.files.len()),
+        |summary| summary.updated,
+    )
+}
+
+/// The `--show-diff-stat` line: how many files changed and the version transition
+fn diff_stat(files: usize, previous: &semver::Version, new: &semver::Version) -> String {
+    let noun = if files == 1 { "file" } else { "files" };
+    format!("{files} {noun} changed, version {previous} → {new}")
+}
+
+/// Handle `--tag` once a bump is written, returning the name of the tag created
+///
+/// With `--only-if-changed`, a bump that changed no file is not tagged.
+fn tag_after_bump(
+    manager: &VersionManager,
+    reporter: &dyn Reporter,
+    args: &BumpArgs,
+    new_version: &semver::Version,
+    changed: bool,
+) -> Result<Option<(String, semver::Version)>> {
+    if !args.tag {
+        return Ok(None);
+    }
+    if args.only_if_changed && !changed {
+        if !args.silent() {
+            reporter.info(&format!(
+                "No files changed; skipping tag for version {new_version}"
+            ));
+        }
+        return Ok(None);
+    }
+    run_tag(
+        manager,
+        reporter,
+        new_version,
+        &args.tag_args,
+        args.cascade,
+        args.silent(),
+    )
+    .map(Some)
+}
+
+/// Apply `bump` to `current` `--count` times, reporting each intermediate version
+///
+/// Every step is a full bump of the previous result, so `major --count 2` on `1.4.2`
+/// gives `3.0.0`.
+fn apply_repeatedly(
+    reporter: &dyn Reporter,
+    bump: &Bump,
+    current: &semver::Version,
+    args: &BumpArgs,
+) -> Result<semver::Version> {
+    let mut version = bump.apply(current)?;
+    let repeated = bump.repeated();
+    for step in 2..=args.count {
+        if !args.silent() {
+            reporter.info(&format!("Step {}/{}: {version}", step - 1, args.count));
+        }
+        version = repeated.apply(&version)?;
+    }
+    Ok(version)
+}
+
+/// Write a bump's result, returning whether any file changed and, for a cascade, its
+/// summary
+///
+/// `semver_bump` uses the dedicated bump path; otherwise `next_version` is written as
+/// is. An explicit base skips the pre-bump sync check: the files may hold placeholders
+/// or disagree, and the bump makes them all agree. With `--verify-after` the written
+/// files are checked and restored if they are not in sync.
+fn write_bump(
+    manager: &VersionManager,
+    reporter: &dyn Reporter,
+    args: &BumpArgs,
+    semver_bump: Option<BumpType>,
+    explicit_base: bool,
+    next_version: &semver::Version,
+) -> Result<(bool, Option<CascadeSummary>)> {
+    if semver_bump.is_none() && !explicit_base {
+        if args.cascade {
+            manager.verify_cascade_in_sync()?;
+        } else {
+            manager.verify_versions_in_sync()?;
+        }
+    }
+    let snapshot = snapshot_for_verify(manager, args.verify_after, args.cascade)?;
+    let (changed, summary) = match (args.cascade, semver_bump) {
+        (true, Some(bump_type)) => {
+            let summary = manager.bump_cascade_with_progress(
+                bump_type,
+                &mut cascade_progress(reporter, args.silent()),
+            )?;
+            (summary.changed(), Some(summary))
+        }
+        (false, Some(bump_type)) => (manager.bump_version(bump_type)?, None),
+        (true, None) => {
+            let summary = manager.reset_cascade_with_progress(
+                &next_version.to_string(),
+                &mut cascade_progress(reporter, args.silent()),
+            )?;
+            (summary.changed(), Some(summary))
+        }
+        (false, None) => (manager.reset_version(&next_version.to_string())?, None),
+    };
+    if snapshot.is_some() {
+        verify_after_write(manager, args.cascade, snapshot.as_ref())?;
+    }
+    Ok((changed, summary))
+}
+
+/// Run the `--after-write-command`, if any, with the version just written
+fn run_after_write_command(
+    manager: &VersionManager,
+    command: Option<&str>,
+    version: &semver::Version,
+) -> Result<()> {
+    let Some(command) = command else {
+        return Ok(());
+    };
+    manager.run_after_write_command(command, version)
+}
+
+/// The version a write landed on, read back from the version source
+///
+/// An environment variable source still holds the value from before the write, so
+/// `written` is used as is instead.
+fn written_version(manager: &VersionManager, written: &semver::Version) -> Result<semver::Version> {
+    if manager.config.version.source == VersionSource::Env {
+        Ok(written.clone())
+    } else {
+        manager.read_version_file()
+    }
+}
+
+/// Fail with `hint` under CI or unless stdin and stdout are a terminal, so scripts
+/// never block waiting for input
+fn require_terminal(hint: &str) -> Result<()> {
+    use std::io::IsTerminal;
+
+    if running_in_ci() || !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        anyhow::bail!("{hint}");
+    }
+    Ok(())
+}
+
+/// List `options` numbered from 1 and ask `question` until the answer picks one
+///
+/// Accepts an option's number, or any answer `by_name` maps to an index. Returns `None`
+/// at end of input.
+fn prompt_select(
+    options: &[String],
+    question: &str,
+    by_name: impl Fn(&str) -> Option<usize>,
+) -> Result<Option<usize>> {
+    use std::io::{BufRead, Write};
+
+    let mut stdout = std::io::stdout();
+    for (i, option) in options.iter().enumerate() {
+        writeln!(stdout, "  {}) {option}", i + 1)?;
+    }
+
+    let mut line = String::new();
+    loop {
+        write!(stdout, "{question} ")?;
+        stdout.flush()?;
+        line.clear();
+        if std::io::stdin().lock().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let choice = line.trim().to_lowercase();
+        let picked = choice
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .filter(|&i| i < options.len())
+            .or_else(|| by_name(&choice));
+        if picked.is_some() {
+            return Ok(picked);
+        }
+    }
+}
+
+/// Ask on the terminal which bump to apply to `current`
+///
+/// Accepts the bump name or its number in the list. Refuses to run without a terminal
+/// so scripts never block waiting for input.
+fn prompt_bump_type(current: &semver::Version) -> Result<BumpType> {
+    require_terminal(
+        "`versioneer bump` needs an interactive terminal; use `versioneer major`, `versioneer minor` or `versioneer patch` instead",
+    )?;
+
+    let options: Vec<String> = BumpType::ALL
+        .iter()
+        .map(|bump_type| {
+            format!(
+                "{:<5}  {current} → {}",
+                bump_type.name(),
+                bump_type.apply(current)
+            )
+        })
+        .collect();
+    let index = prompt_select(&options, "major / minor / patch?", |answer| {
+        let picked: BumpType = answer.parse().ok()?;
+        BumpType::ALL
+            .iter()
+            .position(|bump_type| *bump_type == picked)
+    })?
+    .context("No bump type selected")?;
+    Ok(BumpType::ALL[index])
+}
+
+/// Ask on the terminal which of the disagreeing build system versions to adopt
+///
+/// Accepts a build system name (`cargo`) or the number in the list. Refuses to run
+/// without a terminal, since adopting a version must never be a guess.
+fn prompt_adopt_version(
+    manager: &VersionManager,
+    declared: &[(std::path::PathBuf, BuildSystem, semver::Version)],
+) -> Result<semver::Version> {
+    require_terminal("--interactive needs a terminal; pass --source to choose a build system")?;
+
+    let labels: Vec<String> = declared
+        .iter()
+        .map(|(path, system, _)| {
+            let path = path.strip_prefix(&manager.base_path).unwrap_or(path);
+            format!("{} ({})", system.describe(path), path.display())
+        })
+        .collect();
+    let width = labels.iter().map(String::len).max().unwrap_or(0);
+    let options: Vec<String> = labels
+        .iter()
+        .zip(declared)
+        .map(|(label, (_, _, version))| format!("{label:<width$}  {version}"))
+        .collect();
+    let index = prompt_select(
+        &options,
+        "Build systems disagree; adopt which version?",
+        |answer| {
+            let mut matching = declared
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, system, _))| system.name() == answer);
+            match (matching.next(), matching.next()) {
+                (Some((i, _)), None) => Some(i),
+                _ => None,
+            }
+        },
+    )?
+    .context("No version selected")?;
+    Ok(declared[index].2.clone())
+}
+
+/// Adopt the build systems' version, asking which one wins when they disagree
+fn adopt_interactively(manager: &VersionManager) -> Result<semver::Version> {
+    let declared = manager.declared_versions()?;
+    let (_, _, first) = declared
+        .first()
+        .context("No build system files found to adopt a version from")?;
+    let version = if declared.iter().all(|(_, _, version)| version == first) {
+        first.clone()
+    } else {
+        prompt_adopt_version(manager, &declared)?
+    };
+    manager.adopt_version(&version)?;
+    Ok(version)
+}
+
+/// Ask a yes/no `question` on the terminal; anything but `y` or `yes` declines
+///
+/// Accepts without asking when `VERSIONEER_ASSUME_YES` is set, declines without asking
+/// under CI, and refuses to run without a terminal, so scripts never block waiting for
+/// input. Closed or unreadable stdin counts as no.
+fn confirm(question: &str) -> Result<bool> {
+    use std::io::{BufRead, Write};
+
+    if assume_yes() {
+        return Ok(true);
+    }
+    if running_in_ci() {
+        return Ok(false);
+    }
+    require_terminal(
+        "Confirmation needs an interactive terminal; pass --yes or set VERSIONEER_ASSUME_YES",
+    )?;
+
+    let mut stdout = std::io::stdout();
+    write!(stdout, "{question} [y/N] ")?;
+    stdout.flush()?;
+    let mut line = String::new();
+    let read = std::io::stdin().lock().read_line(&mut line);
+    match read {
+        Ok(0) | Err(_) => Ok(false),
+        Ok(_) => Ok(matches!(line.trim().to_lowercase().as_str(), "y" | "yes")),
+    }
 }
 
-#[allow(clippy::too_many_lines)]
-fn main() -> Result<()> {
+/// Exit code for `verify` when the only problem is version drift (fixable with `sync`)
+const EXIT_VERIFY_DRIFT: i32 = 3;
+
+/// Exit code for `verify` when the VERSION file or a build system file cannot be read
+const EXIT_VERIFY_UNREADABLE: i32 = 4;
+
+/// Run `verify`, returning its exit code
+///
+/// With `tag` (a tag name and the version it names), a VERSION file that disagrees
+/// with it counts as drift. Read failures take precedence over drift, since they need
+/// a human to look at them.
+fn run_verify(
+    manager: &VersionManager,
+    reporter: &dyn Reporter,
+    quiet: bool,
+    only: &[BuildSystem],
+    tag: Option<&(String, semver::Version)>,
+) -> i32 {
+    let mut issues = match manager.sync_issues() {
+        Ok(issues) if only.is_empty() => issues,
+        Ok(issues) => issues
+            .into_iter()
+            .filter(|issue| issue.system().is_some_and(|system| only.contains(system)))
+            .collect(),
+        Err(e) => {
+            reporter.failure(
+                VersioneerError::kind_of(&e),
+                &format!("{e:#}"),
+                &VersioneerError::details_of(&e),
+            );
+            return EXIT_VERIFY_UNREADABLE;
+        }
+    };
+    if let Some((name, version)) = tag {
+        match manager.tag_mismatch(name, version) {
+            Ok(mismatch) => issues.extend(mismatch),
+            Err(e) => {
+                reporter.failure(
+                    VersioneerError::kind_of(&e),
+                    &format!("{e:#}"),
+                    &VersioneerError::details_of(&e),
+                );
+                return EXIT_VERIFY_UNREADABLE;
+            }
+        }
+    }
+
+    if issues.is_empty() {
+        if let Some((name, _)) = tag.filter(|_| !quiet) {
+            reporter.success(&format!(
+                "All version files are synchronized and match tag {name}"
+            ));
+        } else if !quiet {
+            reporter.success("All version files are synchronized");
+        }
+        return 0;
+    }
+
+    let unreadable = issues.iter().any(SyncIssue::is_unreadable);
+    let err = VersioneerError::OutOfSync(issues);
+    reporter.failure(err.kind(), &err.to_string(), &err.details());
+
+    if unreadable {
+        EXIT_VERIFY_UNREADABLE
+    } else {
+        EXIT_VERIFY_DRIFT
+    }
+}
+
+/// Progress callback for cascade operations, silenced by `--quiet`
+fn cascade_progress(reporter: &dyn Reporter, quiet: bool) -> impl FnMut(usize, usize, &Path) + '_ {
+    move |done, total, path| {
+        if !quiet {
+            reporter.progress(done, total, path);
+        }
+    }
+}
+
+/// Refuse `--dry-run` without `--cascade`, then warn how many untracked manifests a
+/// cascade will leave out
+///
+/// The warning is for human output only, so JSON and NDJSON stay parseable.
+fn check_cascade_options(
+    manager: &VersionManager,
+    reporter: &dyn Reporter,
+    format: OutputFormat,
+    cascade: bool,
+    dry_run: bool,
+    quiet: bool,
+) -> Result<()> {
+    if dry_run && !cascade {
+        reporter.error("--dry-run requires --cascade");
+        std::process::exit(1);
+    }
+    if !cascade || quiet || format != OutputFormat::Human {
+        return Ok(());
+    }
+    let skipped = manager.untracked_manifests()?;
+    if !skipped.is_empty() {
+        reporter.warning(&format!(
+            "Skipped {} manifest(s) not tracked by git (pass --include-untracked to update them)",
+            skipped.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Snapshot the files a write may touch when `--verify-after` may need to undo it
+fn snapshot_for_verify(
+    manager: &VersionManager,
+    verify_after: bool,
+    cascade: bool,
+) -> Result<Option<WriteSnapshot>> {
+    if !verify_after {
+        return Ok(None);
+    }
+    manager
+        .snapshot_write_targets(cascade)
+        .map(Some)
+        .context("Failed to snapshot files before writing")
+}
+
+/// Re-check synchronization after a write phase so silent no-op updates fail loudly
+///
+/// On failure the files are put back from `snapshot`, taken before the write.
+fn verify_after_write(
+    manager: &VersionManager,
+    cascade: bool,
+    snapshot: Option<&WriteSnapshot>,
+) -> Result<()> {
+    let result = if cascade {
+        manager.verify_cascade_in_sync()
+    } else {
+        manager.verify_versions_in_sync()
+    };
+    let Err(e) = result else {
+        return Ok(());
+    };
+    let Some(snapshot) = snapshot else {
+        return Err(e.context("Verification after write failed"));
+    };
+    Err(match snapshot.restore() {
+        Ok(_) => e.context("Verification after write failed; the written files were restored"),
+        Err(restore_err) => e.context(format!(
+            "Verification after write failed, and restoring the written files failed too: {restore_err:#}"
+        )),
+    })
+}
+
+/// Create a git tag for `version` and report it
+///
+/// Returns the tag name and the version tagged, which differs from `version` when a
+/// collision moved it to a prerelease.
+fn run_tag(
+    manager: &VersionManager,
+    reporter: &dyn Reporter,
+    version: &semver::Version,
+    tag_args: &TagArgs,
+    cascade: bool,
+    quiet: bool,
+) -> Result<(String, semver::Version)> {
+    let tag_prefix = manager.resolve_tag_prefix(tag_args.tag_prefix.as_deref());
+    let mut allow_retag = tag_args.allow_retag;
+    let mut version = version.clone();
+    if tag_args.prerelease_bump_on_collision {
+        let free_version = manager
+            .resolve_tag_collision(&version, &tag_prefix)
+            .context("Failed to find a free tag")?;
+        if free_version != version {
+            // Keep the files consistent with the tag that is about to be created
+            if cascade {
+                manager
+                    .reset_cascade(&free_version.to_string())
+                    .map(|summary| summary.changed())
+            } else {
+                manager.reset_version(&free_version.to_string())
+            }
+            .context("Failed to write disambiguated version")?;
+            if !quiet {
+                reporter.warning(&format!(
+                    "Tag {tag_prefix}{version} exists; using version {free_version}"
+                ));
+            }
+            version = free_version;
+            // The name is known to be free; a suffixed prerelease sorts below the
+            // colliding tag, so the precedence check would always reject it
+            allow_retag = true;
+        }
+    }
+
+    let tag_name = manager
+        .create_tag(&version, &tag_prefix, allow_retag, tag_args.kind())
+        .context("Failed to create git tag")?;
+    if !quiet {
+        reporter.success(&format!("Created tag {tag_name}"));
+    }
+    Ok((tag_name, version))
+}
+
+/// Print the per-build-system status lines of a version report
+fn report_build_systems(
+    formatter: &OutputFormatter,
+    reporter: &dyn Reporter,
+    report: &VersionReport,
+) {
+    reporter.info(&format!("\n{}", formatter.build_systems_header()));
+    for (system, path, state) in &report.systems {
+        let name = system.describe(path);
+        match state {
+            ManifestVersion::Inherited => {
+                reporter.info(&format!("  {name}: inherited from workspace"));
+            }
+            ManifestVersion::VirtualWorkspace => {
+                reporter.info(&format!(
+                    "  {name}: virtual workspace has no package version"
+                ));
+            }
+            ManifestVersion::Placeholder => {
+                reporter.info(&format!(
+                    "  {name}: $version$ placeholder, filled in at pack time"
+                ));
+            }
+            ManifestVersion::Declared { version, in_sync } => {
+                let status = formatter.sync_status(*in_sync);
+                if *in_sync && report.ignore_prerelease && version.pre != report.version.pre {
+                    reporter.info(&format!(
+                        "  {name}: {version} {status} (core version matches {}; prerelease ignored)",
+                        report.version
+                    ));
+                } else {
+                    reporter.info(&format!("  {name}: {version} {status}"));
+                }
+            }
+            ManifestVersion::Unreadable(e) => {
+                reporter.error(&format!("  {name}: Error reading version: {e}"));
+            }
+        }
+    }
+    let modules = report
+        .modules
+        .iter()
+        .map(|(module, state)| (format!("{} ({})", module.path, module.variable), state));
+    let json_files = report
+        .json_files
+        .iter()
+        .map(|(file, state)| (format!("{} ({})", file.path, file.key), state));
+    for (label, state) in modules.chain(json_files) {
+        match state {
+            ManifestVersion::Declared { version, in_sync } => {
+                let status = formatter.sync_status(*in_sync);
+                reporter.info(&format!("  {label}: {version} {status}"));
+            }
+            ManifestVersion::Unreadable(e) => {
+                reporter.error(&format!("  {label}: Error reading version: {e}"));
+            }
+            ManifestVersion::Inherited
+            | ManifestVersion::VirtualWorkspace
+            | ManifestVersion::Placeholder => {}
+        }
+    }
+}
+
+/// Report `err` under `context`, classified so JSON output carries its kind
+fn report_failure(reporter: &dyn Reporter, context: &str, err: &anyhow::Error) {
+    reporter.failure(
+        VersioneerError::kind_of(err),
+        &format!("{context}: {err}"),
+        &VersioneerError::details_of(err),
+    );
+}
+
+fn main() {
     let cli = Cli::parse();
+    let json_errors = matches!(cli.format, OutputFormat::Json | OutputFormat::Ndjson);
+    let json_style = cli.json_style();
+
+    if let Err(err) = run(cli, json_errors) {
+        if json_errors {
+            eprintln!(
+                "{}",
+                error_envelope(
+                    VersioneerError::kind_of(&err),
+                    &err.to_string(),
+                    &VersioneerError::details_of(&err),
+                    json_style,
+                )
+            );
+        } else {
+            eprintln!("Error: {err:?}");
+        }
+        std::process::exit(1);
+    }
+}
 
+#[allow(clippy::too_many_lines)]
+fn run(cli: Cli, json_errors: bool) -> Result<()> {
+    if let Some(path) = &cli.trace_file {
+        trace::start(path)?;
+    }
     let current_dir = env::current_dir().context("Failed to get current directory")?;
-    let formatter = OutputFormatter::new();
-    let manager = VersionManager::with_version_file(current_dir, &cli.version_file);
+    let base_path = cli
+        .base_path
+        .as_ref()
+        .map_or_else(|| current_dir.clone(), |dir| current_dir.join(dir));
+    if !base_path.is_dir() {
+        anyhow::bail!("Base path {} is not a directory", base_path.display());
+    }
+    let base_path = if cli.git_root {
+        versioneer::git_root(&base_path).context("--git-root needs a git repository")?
+    } else {
+        base_path
+    };
+    let writer_reporter = WriterReporter::new(
+        OutputFormatter::with_color(cli.color),
+        std::io::stdout(),
+        std::io::stderr(),
+    )
+    .with_json_errors(json_errors)
+    .with_json_style(cli.json_style());
+    let formatter = writer_reporter.formatter();
+    let reporter: &dyn Reporter = &writer_reporter;
+    let mut config = Config::load(&base_path)?;
+    if cli.no_build_metadata {
+        config.build_metadata.strip = BuildSystem::ALL.to_vec();
+    }
+    if cli.v_prefix {
+        config.version.v_prefix = Some(true);
+    }
+    if cli.block_prerelease_downgrade {
+        config.tag.block_prerelease_downgrade = true;
+    }
+    if cli.no_package_json_reserialize {
+        config.json.exact = true;
+    }
+    if cli.strict_version {
+        config.version.strict = true;
+    }
+    config.ignore_systems.extend(cli.skip.iter().cloned());
+    if cli.manifest.len() != cli.manifest_format.len() {
+        anyhow::bail!("Each --manifest needs its own --manifest-format, in the same order");
+    }
+    config
+        .manifest_overrides
+        .extend(
+            cli.manifest
+                .iter()
+                .zip(&cli.manifest_format)
+                .map(|(path, format)| ManifestOverride {
+                    path: path.clone(),
+                    format: format.clone(),
+                }),
+        );
+    if matches!(
+        cli.command,
+        Some(
+            Commands::Status {
+                ignore_prerelease: true
+            } | Commands::Verify {
+                ignore_prerelease: true,
+                ..
+            }
+        )
+    ) {
+        config.verify.ignore_prerelease = true;
+    }
+    if let Some(Commands::Reset {
+        min_version,
+        max_version,
+        ..
+    }) = &cli.command
+    {
+        if min_version.is_some() {
+            config.reset.min_version.clone_from(min_version);
+        }
+        if max_version.is_some() {
+            config.reset.max_version.clone_from(max_version);
+        }
+    }
+    let manager = cli
+        .version_file
+        .as_deref()
+        .map_or_else(
+            || VersionManager::new(&base_path),
+            |version_file| VersionManager::with_version_file(&base_path, version_file),
+        )
+        .with_config(config)
+        .with_pyproject_source(cli.pyproject_source)
+        .with_allow_empty(cli.allow_empty)
+        .with_overwrite_placeholders(cli.overwrite_placeholders)
+        .with_bump_bundle_version(cli.bump_bundle_version)
+        .with_keep_going(cli.keep_going)
+        .with_detection_cache()
+        .with_discovery_options(DiscoveryOptions {
+            max_depth: cli.max_depth,
+            follow_symlinks: cli.follow_symlinks,
+            default_excludes: !cli.no_default_excludes,
+            untracked: if cli.abort_on_untracked_manifest {
+                UntrackedManifests::Abort
+            } else if cli.include_untracked {
+                UntrackedManifests::Include
+            } else {
+                UntrackedManifests::Skip
+            },
+        });
 
     match cli.command {
         None => {
             // No subcommand provided - show status if build system files exist, otherwise error
             let build_systems = manager.detect_build_systems();
-            if build_systems.is_empty() {
-                eprintln!("{}", formatter.error("No build system files (Cargo.toml or pyproject.toml) found in current directory."));
-                eprintln!("Versioneer requires at least one build system file to manage versions.");
+            if build_systems.is_empty() && !manager.allow_empty {
+                reporter.error(
+                    "No build system files (Cargo.toml or pyproject.toml) found in current directory. Versioneer requires at least one build system file to manage versions.",
+                );
                 std::process::exit(1);
             } else {
                 // Show status
-                let version = manager
-                    .read_version_file()
+                let report = manager
+                    .version_report()
                     .context("Failed to read VERSION file")?;
-                println!("{}", formatter.version(&version.to_string()));
-
-                println!("\n{}", formatter.build_systems_header());
-                for system in &build_systems {
-                    match manager.read_build_system_version(system) {
-                        Ok(sys_version) => {
-                            let status = formatter.sync_status(sys_version == version);
-                            println!("  {system:?}: {sys_version} {status}");
-                        }
-                        Err(e) => {
-                            eprintln!(
-                                "{}",
-                                formatter
-                                    .error(&format!("  {system:?}: Error reading version: {e}"))
-                            );
-                        }
-                    }
+                reporter.info(&formatter.version(&report.version.to_string()));
+                if report.systems.is_empty() {
+                    reporter.info("VERSION file only");
+                } else {
+                    report_build_systems(formatter, reporter, &report);
+                }
+                for (path, system) in manager.ignored_manifests() {
+                    reporter.info(&format!(
+                        "  {}: ignored (ignore_systems or --skip)",
+                        system.describe(&path)
+                    ));
                 }
             }
         }
         Some(command) => match command {
-            Commands::Version => {
-                println!("versioneer {}", env!("CARGO_PKG_VERSION"));
+            Commands::Version { verbose } => {
+                build_info::run_version(
+                    reporter,
+                    &build_info::BuildInfo::current(),
+                    verbose,
+                    matches!(cli.format, OutputFormat::Json | OutputFormat::Ndjson),
+                )?;
             }
             Commands::License => {
-                println!(
-                    "{}",
-                    workhelix_cli_common::license::display_license("versioneer", LicenseType::MIT)
-                );
+                reporter.data(&workhelix_cli_common::license::display_license(
+                    "versioneer",
+                    LicenseType::MIT,
+                ));
             }
-            Commands::Major {
-                cascade,
-                dry_run,
-                quiet,
-            } => {
-                if dry_run && !cascade {
-                    eprintln!("{}", formatter.error("--dry-run requires --cascade"));
-                    std::process::exit(1);
-                }
-
-                if dry_run {
-                    let changes = manager
-                        .bump_cascade_dry_run(BumpType::Major)
-                        .context("Failed to preview major version bump")?;
-                    if !quiet {
-                        println!(
-                            "{}",
-                            formatter
-                                .success(&format!("Would bump to version {}", changes.new_version))
-                        );
-                        println!("\nFiles to update:");
-                        for file in &changes.files_to_update {
-                            println!("  {}", file.display());
-                        }
-                    }
-                } else if cascade {
-                    manager
-                        .bump_cascade(BumpType::Major)
-                        .context("Failed to bump major version")?;
-                    if !quiet {
-                        let new_version = manager.read_version_file()?;
-                        println!(
-                            "{}",
-                            formatter.success(&format!("Bumped to version {new_version}"))
-                        );
-                    }
-                } else {
-                    manager
-                        .bump_version(BumpType::Major)
-                        .context("Failed to bump major version")?;
-                    if !quiet {
-                        let new_version = manager.read_version_file()?;
-                        println!(
-                            "{}",
-                            formatter.success(&format!("Bumped to version {new_version}"))
-                        );
-                    }
-                }
+            Commands::Major(args) => {
+                run_bump(
+                    &manager,
+                    reporter,
+                    cli.format,
+                    &Bump::Semver(BumpType::Major),
+                    &args,
+                )?;
             }
-            Commands::Minor {
-                cascade,
-                dry_run,
-                quiet,
+            Commands::Minor(args) => {
+                run_bump(
+                    &manager,
+                    reporter,
+                    cli.format,
+                    &Bump::Semver(BumpType::Minor),
+                    &args,
+                )?;
+            }
+            Commands::Patch(args) => {
+                run_bump(
+                    &manager,
+                    reporter,
+                    cli.format,
+                    &Bump::Semver(BumpType::Patch),
+                    &args,
+                )?;
+            }
+            Commands::Bump(args) => {
+                let current = match explicit_bump_base(&manager, &args)? {
+                    Some(base) => base,
+                    None => manager.read_version_file()?,
+                };
+                let bump_type = prompt_bump_type(&current)?;
+                run_bump(
+                    &manager,
+                    reporter,
+                    cli.format,
+                    &Bump::Semver(bump_type),
+                    &args,
+                )?;
+            }
+            Commands::Calver { date, args } => {
+                let date = match date {
+                    Some(date) => date,
+                    None => CalVerDate::today()?,
+                };
+                run_bump(&manager, reporter, cli.format, &Bump::CalVer(date), &args)?;
+            }
+            Commands::Prerelease {
+                bump_type,
+                ident,
+                args,
             } => {
-                if dry_run && !cascade {
-                    eprintln!("{}", formatter.error("--dry-run requires --cascade"));
-                    std::process::exit(1);
+                let bump = Bump::Prerelease {
+                    bump: bump_type,
+                    ident,
+                };
+                run_bump(&manager, reporter, cli.format, &bump, &args)?;
+            }
+            Commands::Show { env_file } => {
+                let version = manager
+                    .read_version_file()
+                    .context("Failed to read VERSION file")?;
+                reporter.data(&version.to_string());
+                if cli.format == OutputFormat::Github {
+                    write_github_outputs(reporter, &version, &version, false)?;
                 }
-
-                if dry_run {
-                    let changes = manager
-                        .bump_cascade_dry_run(BumpType::Minor)
-                        .context("Failed to preview minor version bump")?;
-                    if !quiet {
-                        println!(
-                            "{}",
-                            formatter
-                                .success(&format!("Would bump to version {}", changes.new_version))
-                        );
-                        println!("\nFiles to update:");
-                        for file in &changes.files_to_update {
-                            println!("  {}", file.display());
-                        }
-                    }
-                } else if cascade {
-                    manager
-                        .bump_cascade(BumpType::Minor)
-                        .context("Failed to bump minor version")?;
-                    if !quiet {
-                        let new_version = manager.read_version_file()?;
-                        println!(
-                            "{}",
-                            formatter.success(&format!("Bumped to version {new_version}"))
-                        );
-                    }
-                } else {
-                    manager
-                        .bump_version(BumpType::Minor)
-                        .context("Failed to bump minor version")?;
-                    if !quiet {
-                        let new_version = manager.read_version_file()?;
-                        println!(
-                            "{}",
-                            formatter.success(&format!("Bumped to version {new_version}"))
-                        );
-                    }
+                if let Some(path) = env_file {
+                    write_env_file(&path, &version)?;
                 }
             }
-            Commands::Patch {
+            Commands::Sync {
                 cascade,
                 dry_run,
                 quiet,
+                verify_after,
+                reverse,
+                interactive,
+                source,
+                summary,
+                after_write_command,
+                report_file,
+                summary_json_on_stderr,
             } => {
-                if dry_run && !cascade {
-                    eprintln!("{}", formatter.error("--dry-run requires --cascade"));
-                    std::process::exit(1);
-                }
+                check_cascade_options(&manager, reporter, cli.format, cascade, dry_run, quiet)?;
+                // --reverse conflicts with the reports, so VERSION is the target here
+                let report = if report_file.is_some() || summary_json_on_stderr {
+                    Some(manager.plan_report("sync", &manager.read_version_file()?, cascade)?)
+                } else {
+                    None
+                };
 
-                if dry_run {
-                    let changes = manager
-                        .bump_cascade_dry_run(BumpType::Patch)
-                        .context("Failed to preview patch version bump")?;
-                    if !quiet {
-                        println!(
-                            "{}",
-                            formatter
-                                .success(&format!("Would bump to version {}", changes.new_version))
-                        );
-                        println!("\nFiles to update:");
-                        for file in &changes.files_to_update {
-                            println!("  {}", file.display());
-                        }
+                if reverse {
+                    let snapshot = snapshot_for_verify(&manager, verify_after, cascade)?;
+                    let version = if interactive {
+                        adopt_interactively(&manager)
+                    } else {
+                        manager.adopt_build_system_version(source.as_ref())
                     }
-                } else if cascade {
-                    manager
-                        .bump_cascade(BumpType::Patch)
-                        .context("Failed to bump patch version")?;
+                    .context("Failed to adopt build system version")?;
+                    if cascade {
+                        manager
+                            .sync_cascade()
+                            .context("Failed to synchronize versions")?;
+                        verify_after_write(&manager, cascade, snapshot.as_ref())?;
+                    }
+                    run_after_write_command(
+                        &manager,
+                        after_write_command.as_deref(),
+                        &manager.read_version_file()?,
+                    )?;
                     if !quiet {
-                        let new_version = manager.read_version_file()?;
-                        println!(
-                            "{}",
-                            formatter.success(&format!("Bumped to version {new_version}"))
-                        );
+                        reporter.success(&format!("Adopted version {version} from build system"));
                     }
-                } else {
-                    manager
-                        .bump_version(BumpType::Patch)
-                        .context("Failed to bump patch version")?;
+                } else if dry_run && cli.format == OutputFormat::Ndjson {
                     if !quiet {
-                        let new_version = manager.read_version_file()?;
-                        println!(
-                            "{}",
-                            formatter.success(&format!("Bumped to version {new_version}"))
-                        );
+                        stream_manifest_statuses(&manager, reporter)?;
                     }
-                }
-            }
-            Commands::Show => {
-                let version = manager
-                    .read_version_file()
-                    .context("Failed to read VERSION file")?;
-                println!("{version}");
-            }
-            Commands::Sync {
-                cascade,
-                dry_run,
-                quiet,
-            } => {
-                if dry_run && !cascade {
-                    eprintln!("{}", formatter.error("--dry-run requires --cascade"));
-                    std::process::exit(1);
-                }
-
-                if dry_run {
+                } else if dry_run {
                     let changes = manager
                         .sync_cascade_dry_run()
                         .context("Failed to preview synchronization")?;
                     if !quiet {
-                        println!(
-                            "{}",
-                            formatter
-                                .success(&format!("Would sync to version {}", changes.new_version))
-                        );
-                        println!("\nFiles to update:");
-                        for file in &changes.files_to_update {
-                            println!("  {}", file.display());
-                        }
-                    }
-                } else if cascade {
-                    manager
-                        .sync_cascade()
-                        .context("Failed to synchronize versions")?;
-                    if !quiet {
-                        let version = manager.read_version_file()?;
-                        println!(
-                            "{}",
-                            formatter
-                                .success(&format!("Synchronized all files to version {version}"))
-                        );
+                        report_dry_run(
+                            reporter,
+                            cli.format,
+                            &manager,
+                            &format!("Would sync to version {}", changes.new_version),
+                            &changes,
+                        )?;
                     }
                 } else {
-                    manager
-                        .sync_versions()
-                        .context("Failed to synchronize versions")?;
-                    if !quiet {
+                    let snapshot = snapshot_for_verify(&manager, verify_after, cascade)?;
+                    let (changed, cascade_summary) = if cascade {
+                        let cascade_summary = manager
+                            .sync_cascade_with_progress(&mut cascade_progress(reporter, quiet))
+                            .context("Failed to synchronize versions")?;
+                        (cascade_summary.updated, Some(cascade_summary))
+                    } else {
+                        let changed = manager
+                            .sync_versions()
+                            .context("Failed to synchronize versions")?;
+                        (changed, None)
+                    };
+                    if verify_after {
+                        verify_after_write(&manager, cascade, snapshot.as_ref())?;
+                    }
+                    run_after_write_command(
+                        &manager,
+                        after_write_command.as_deref(),
+                        &manager.read_version_file()?,
+                    )?;
+                    if !quiet && changed == 0 {
+                        reporter.success("Already in sync, nothing to do");
+                    } else if !quiet {
                         let version = manager.read_version_file()?;
-                        println!(
-                            "{}",
-                            formatter
-                                .success(&format!("Synchronized all files to version {version}"))
-                        );
+                        reporter.success(&format!("Synchronized all files to version {version}"));
                     }
+                    if let Some(cascade_summary) = cascade_summary.filter(|_| summary) {
+                        report_cascade_summary(reporter, cli.format, &manager, &cascade_summary)?;
+                    }
+                }
+                if let Some(report) = report {
+                    emit_report(
+                        report_file.as_deref(),
+                        summary_json_on_stderr,
+                        &if dry_run {
+                            report
+                        } else {
+                            report.applied(None)
+                        },
+                    )?;
                 }
             }
-            Commands::Status => {
-                let version = manager
-                    .read_version_file()
+            Commands::List => match cli.format {
+                OutputFormat::Ndjson => stream_manifest_statuses(&manager, reporter)?,
+                OutputFormat::Json => {
+                    let statuses = manager
+                        .manifest_statuses()?
+                        .map(|status| status.map(|s| s.relative_to(&manager.base_path)))
+                        .collect::<Result<Vec<_>>>()?;
+                    reporter.data(&reporter.json_style().render(&statuses)?);
+                }
+                OutputFormat::Human | OutputFormat::Github => {
+                    for status in manager.manifest_statuses()? {
+                        let status = status?.relative_to(&manager.base_path);
+                        let version = status
+                            .version
+                            .as_ref()
+                            .map_or_else(|| "unreadable".to_string(), ToString::to_string);
+                        reporter.info(&format!(
+                            "{} ({}): {version} {}",
+                            status.path.display(),
+                            status.system.name(),
+                            formatter.sync_status(status.in_sync)
+                        ));
+                    }
+                }
+            },
+            Commands::Status { .. } => {
+                let report = manager
+                    .version_report()
                     .context("Failed to read VERSION file")?;
-                println!("{}", formatter.version(&version.to_string()));
+                reporter.info(&formatter.version(&report.version.to_string()));
+                if manager.version_file_outside_root() {
+                    reporter.info(&format!(
+                        "Version file {} is outside the managed root {}",
+                        manager.version_file_path().display(),
+                        manager.base_path.display()
+                    ));
+                }
 
-                let build_systems = manager.detect_build_systems();
-                if build_systems.is_empty() {
-                    println!("{}", formatter.warning("No build system files detected"));
-                } else {
-                    println!("\n{}", formatter.build_systems_header());
-                    for system in &build_systems {
-                        match manager.read_build_system_version(system) {
-                            Ok(sys_version) => {
-                                let status = formatter.sync_status(sys_version == version);
-                                println!("  {system:?}: {sys_version} {status}");
-                            }
-                            Err(e) => {
-                                eprintln!(
-                                    "{}",
-                                    formatter.error(&format!(
-                                        "  {system:?}: Error reading version: {e}"
-                                    ))
-                                );
-                            }
-                        }
+                if report.systems.is_empty() {
+                    if manager.allow_empty {
+                        reporter.info("VERSION file only");
+                    } else {
+                        reporter.warning("No build system files detected");
                     }
+                } else {
+                    report_build_systems(formatter, reporter, &report);
                 }
-            }
-            Commands::Verify => match manager.verify_versions_in_sync() {
-                Ok(()) => {
-                    println!(
-                        "{}",
-                        formatter.success("All version files are synchronized")
-                    );
+                for (path, system) in manager.ignored_manifests() {
+                    reporter.info(&format!(
+                        "  {}: ignored (ignore_systems or --skip)",
+                        system.describe(&path)
+                    ));
                 }
-                Err(e) => {
-                    eprintln!("{}", formatter.error(&e.to_string()));
-                    std::process::exit(1);
+            }
+            Commands::Verify {
+                quiet,
+                check,
+                only,
+                against_tag,
+                tag,
+                ..
+            } => {
+                let tag = against_tag
+                    .then(|| manager.tag_version(tag.as_deref()))
+                    .transpose()?;
+                let exit_code = if check {
+                    run_verify(&manager, &NullReporter, true, &only, tag.as_ref())
+                } else {
+                    run_verify(&manager, reporter, quiet, &only, tag.as_ref())
+                };
+                if exit_code != 0 {
+                    std::process::exit(exit_code);
                 }
-            },
+            }
             Commands::Reset {
                 version,
                 cascade,
                 dry_run,
                 quiet,
+                verify_after,
+                keep_prerelease,
+                reset_prerelease_counter,
+                summary,
+                after_write_command,
+                report_file,
+                summary_json_on_stderr,
+                ..
             } => {
-                if dry_run && !cascade {
-                    eprintln!("{}", formatter.error("--dry-run requires --cascade"));
-                    std::process::exit(1);
-                }
+                check_cascade_options(&manager, reporter, cli.format, cascade, dry_run, quiet)?;
 
-                let target_version = version.as_deref().unwrap_or("0.0.0");
+                let mut target_version = version.unwrap_or_else(|| "0.0.0".to_string());
+                if keep_prerelease {
+                    let mut target =
+                        semver::Version::parse(&target_version).with_context(|| {
+                            format!("Invalid semantic version format: '{target_version}'")
+                        })?;
+                    if !target.pre.is_empty() {
+                        reporter.error(&format!(
+                            "--keep-prerelease needs a target without a prerelease, got {target}"
+                        ));
+                        std::process::exit(1);
+                    }
+                    match manager.carried_prerelease(reset_prerelease_counter)? {
+                        Some(pre) => {
+                            target.pre = pre;
+                            target_version = target.to_string();
+                        }
+                        None => reporter.warning(
+                            "--keep-prerelease has no effect: the current version has no prerelease",
+                        ),
+                    }
+                }
+                let target_version = target_version.as_str();
+                // An unparsable target fails below with the usual message
+                let reporting = report_file.is_some() || summary_json_on_stderr;
+                let report = match semver::Version::parse(target_version) {
+                    Ok(target) if reporting => {
+                        Some(manager.plan_report("reset", &target, cascade)?)
+                    }
+                    _ => None,
+                };
+                if let Some(report) = report.clone().filter(|_| dry_run) {
+                    emit_report(report_file.as_deref(), summary_json_on_stderr, &report)?;
+                }
 
-                if dry_run {
+                if dry_run && cli.format == OutputFormat::Ndjson {
+                    let target = semver::Version::parse(target_version).with_context(|| {
+                        format!("Invalid semantic version format: '{target_version}'")
+                    })?;
+                    manager.check_version_bounds(&target)?;
+                    if !quiet {
+                        stream_manifest_statuses(&manager, reporter)?;
+                    }
+                } else if dry_run {
                     match manager.reset_cascade_dry_run(target_version) {
                         Ok(changes) => {
                             if !quiet {
-                                println!(
-                                    "{}",
-                                    formatter.success(&format!(
-                                        "Would reset to version {}",
-                                        changes.new_version
-                                    ))
-                                );
-                                println!("\nFiles to update:");
-                                for file in &changes.files_to_update {
-                                    println!("  {}", file.display());
-                                }
+                                report_dry_run(
+                                    reporter,
+                                    cli.format,
+                                    &manager,
+                                    &format!("Would reset to version {}", changes.new_version),
+                                    &changes,
+                                )?;
                             }
                         }
                         Err(e) => {
-                            eprintln!(
-                                "{}",
-                                formatter.error(&format!("Failed to preview reset: {e}"))
-                            );
+                            report_failure(reporter, "Failed to preview reset", &e);
                             std::process::exit(1);
                         }
                     }
                 } else {
+                    let snapshot = snapshot_for_verify(&manager, verify_after, cascade)?;
                     let result = if cascade {
-                        manager.reset_cascade(target_version)
+                        manager
+                            .reset_cascade_with_progress(
+                                target_version,
+                                &mut cascade_progress(reporter, quiet),
+                            )
+                            .map(Some)
                     } else {
-                        manager.reset_version(target_version)
-                    };
+                        manager.reset_version(target_version).map(|_| None)
+                    }
+                    .and_then(|cascade_summary| {
+                        if verify_after {
+                            verify_after_write(&manager, cascade, snapshot.as_ref())?;
+                        }
+                        run_after_write_command(
+                            &manager,
+                            after_write_command.as_deref(),
+                            &manager.read_version_file()?,
+                        )?;
+                        Ok(cascade_summary)
+                    });
 
                     match result {
-                        Ok(()) => {
+                        Ok(cascade_summary) => {
                             if !quiet {
-                                println!(
-                                    "{}",
-                                    formatter
-                                        .success(&format!("Version reset to {target_version}"))
-                                );
+                                reporter.success(&format!("Version reset to {target_version}"));
+                            }
+                            if let Some(cascade_summary) = cascade_summary.filter(|_| summary) {
+                                report_cascade_summary(
+                                    reporter,
+                                    cli.format,
+                                    &manager,
+                                    &cascade_summary,
+                                )?;
+                            }
+                            if let Some(report) = report {
+                                emit_report(
+                                    report_file.as_deref(),
+                                    summary_json_on_stderr,
+                                    &report.applied(None),
+                                )?;
                             }
                         }
                         Err(e) => {
-                            eprintln!(
-                                "{}",
-                                formatter.error(&format!("Failed to reset version: {e}"))
-                            );
+                            report_failure(reporter, "Failed to reset version", &e);
                             std::process::exit(1);
                         }
                     }
@@ -471,17 +2125,230 @@ fn main() -> Result<()> {
                 let rc_version = manager
                     .next_rc_version()
                     .context("Failed to compute RC version")?;
-                println!("{rc_version}");
+                reporter.data(&rc_version.to_string());
+                if cli.format == OutputFormat::Github {
+                    let version = manager.read_version_file()?;
+                    write_github_outputs(reporter, &rc_version, &version, false)?;
+                }
+            }
+            Commands::Tag {
+                tag_args,
+                no_verify,
+                quiet,
+            } => {
+                if !no_verify {
+                    manager
+                        .verify_versions_in_sync()
+                        .context("Refusing to tag; pass --no-verify to tag anyway")?;
+                }
+                let version = manager
+                    .read_version_file()
+                    .context("Failed to read VERSION file")?;
+                run_tag(&manager, reporter, &version, &tag_args, false, quiet)?;
+            }
+            Commands::Release {
+                bump_type,
+                sign,
+                cascade,
+                dry_run,
+                tag_prefix,
+                quiet,
+            } => {
+                let plan = manager
+                    .plan_release(
+                        bump_type,
+                        cascade,
+                        &manager.resolve_tag_prefix(tag_prefix.as_deref()),
+                        sign,
+                    )
+                    .context("Cannot release")?;
+                if dry_run {
+                    if !quiet {
+                        reporter.info("Release plan:");
+                        for step in plan.steps() {
+                            reporter.info(&format!("  {step}"));
+                        }
+                    }
+                } else {
+                    manager.release(&plan).context("Release failed")?;
+                    if !quiet {
+                        reporter.success(&format!(
+                            "Released {} (tag {})",
+                            plan.new_version, plan.tag_name
+                        ));
+                    }
+                }
             }
             Commands::Completions { shell } => {
-                workhelix_cli_common::completions::generate_completions::<Cli>(shell);
+                completions::run_completions::<Cli>(reporter, shell);
             }
-            Commands::Doctor => {
-                let exit_code = doctor::run_doctor(&manager);
+            Commands::Doctor { fix, yes, strict } => {
+                let exit_code = if !fix {
+                    doctor::run_doctor(&manager, reporter, strict)
+                } else if yes {
+                    doctor::run_doctor_fix(&manager, reporter, strict, &|_| Ok(true))
+                } else {
+                    doctor::run_doctor_fix(&manager, reporter, strict, &confirm)
+                };
                 std::process::exit(exit_code);
             }
+            Commands::Config => {
+                let file_keys = Config::explicit_keys(&manager.base_path)?;
+                let cli_keys: Vec<&str> = [
+                    (cli.no_build_metadata, "build_metadata.strip"),
+                    (cli.v_prefix, "version.v_prefix"),
+                    (
+                        cli.block_prerelease_downgrade,
+                        "tag.block_prerelease_downgrade",
+                    ),
+                    (cli.no_package_json_reserialize, "json.exact"),
+                    (cli.strict_version, "version.strict"),
+                    (!cli.skip.is_empty(), "ignore_systems"),
+                    (!cli.manifest.is_empty(), "manifest_overrides"),
+                    (cli.version_file.is_some(), "version_file"),
+                ]
+                .into_iter()
+                .filter_map(|(set, key)| set.then_some(key))
+                .collect();
+                let entries = effective_config::effective_config(
+                    &manager,
+                    &file_keys,
+                    &cli_keys,
+                    formatter.uses_color(),
+                );
+                effective_config::run_config(
+                    reporter,
+                    &entries,
+                    matches!(cli.format, OutputFormat::Json | OutputFormat::Ndjson),
+                )?;
+            }
+            Commands::Systems => {
+                systems::run_systems(
+                    reporter,
+                    &systems::supported_systems(&manager),
+                    matches!(cli.format, OutputFormat::Json | OutputFormat::Ndjson),
+                )?;
+            }
+            Commands::Paths { cascade, json } => {
+                let manifests = if cascade {
+                    manager.discover_manifests()?
+                } else {
+                    manager.detect_manifests()
+                };
+                let version_file = manager.version_file_path();
+
+                if json {
+                    let manifests: Vec<_> = manifests
+                        .iter()
+                        .map(|(path, system)| {
+                            serde_json::json!({
+                                "system": format!("{system:?}"),
+                                "path": path.display().to_string(),
+                            })
+                        })
+                        .collect();
+                    let output = serde_json::json!({
+                        "version_file": version_file.display().to_string(),
+                        "manifests": manifests,
+                    });
+                    reporter.data(&reporter.json_style().render(&output)?);
+                } else {
+                    reporter.data(&version_file.display().to_string());
+                    for (path, _) in &manifests {
+                        reporter.data(&path.display().to_string());
+                    }
+                }
+            }
         },
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn captured(run: impl FnOnce(&dyn Reporter)) -> (String, String) {
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        {
+            let reporter = WriterReporter::new(OutputFormatter::plain(), &mut out, &mut err);
+            run(&reporter);
+        }
+        (
+            String::from_utf8(out).unwrap(),
+            String::from_utf8(err).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_run_verify_reports_drift_on_error_stream() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"1.1.0\"\n",
+        )
+        .unwrap();
+        let manager = VersionManager::new(temp_dir.path());
+
+        let mut exit_code = 0;
+        let (out, err) = captured(|reporter| {
+            exit_code = run_verify(&manager, reporter, false, &[], None);
+        });
+
+        assert_eq!(exit_code, EXIT_VERIFY_DRIFT);
+        assert!(out.is_empty());
+        assert!(err.contains("Version files are not synchronized"));
+    }
+
+    #[test]
+    fn test_report_dry_run_json_is_relative() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = VersionManager::new(temp_dir.path());
+        let changes = DryRunResult {
+            new_version: semver::Version::new(1, 2, 3),
+            files_to_update: vec![temp_dir.path().join("VERSION")],
+            tag_name: None,
+        };
+
+        let (out, _) = captured(|reporter| {
+            report_dry_run(reporter, OutputFormat::Json, &manager, "", &changes).unwrap();
+        });
+
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(json["new_version"], "1.2.3");
+        assert_eq!(json["files_to_update"][0], "VERSION");
+    }
+
+    #[test]
+    fn test_diff_stat() {
+        let previous = semver::Version::new(1, 2, 3);
+        let new = semver::Version::new(1, 3, 0);
+        assert_eq!(
+            diff_stat(3, &previous, &new),
+            "3 files changed, version 1.2.3 → 1.3.0"
+        );
+        assert_eq!(
+            diff_stat(1, &previous, &new),
+            "1 file changed, version 1.2.3 → 1.3.0"
+        );
+    }
+
+    #[test]
+    fn test_bump_source_parses_version_file_and_build_systems() {
+        assert_eq!(
+            "version-file".parse::<BumpSource>().unwrap(),
+            BumpSource::VersionFile
+        );
+        assert_eq!(
+            "cargo".parse::<BumpSource>().unwrap(),
+            BumpSource::BuildSystem(BuildSystem::Cargo)
+        );
+        let err = "VERSION".parse::<BumpSource>().unwrap_err().to_string();
+        assert!(err.contains("expected version-file or one of cargo"));
+    }
+}
@@ -1,28 +1,295 @@
 //! Output formatting utilities for versioneer
 
-use console::{Emoji, style};
-use std::io::IsTerminal;
+use console::{Emoji, StyledObject, Term};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+
+/// Sink for user-facing messages emitted by versioneer commands
+///
+/// Embedders (TUIs, other CLIs) can supply their own implementation to capture
+/// or suppress messages instead of printing them to stdout/stderr.
+pub trait Reporter {
+    /// Report a successful outcome
+    fn success(&self, msg: &str);
+    /// Report an error
+    fn error(&self, msg: &str);
+    /// Report a warning
+    fn warning(&self, msg: &str);
+    /// Report a plain informational line
+    fn info(&self, msg: &str);
+    /// Emit machine-readable output (JSON, a bare version) meant for scripts
+    ///
+    /// Treated as [`Reporter::info`] by default.
+    fn data(&self, text: &str) {
+        self.info(text);
+    }
+    /// Report that `done` of `total` manifests have been processed, the last being `path`
+    ///
+    /// Does nothing by default.
+    fn progress(&self, _done: usize, _total: usize, _path: &Path) {}
+    /// Report a failure with a machine-readable `kind` and one line per detail
+    ///
+    /// Treated as [`Reporter::error`] with `msg` by default.
+    fn failure(&self, _kind: &str, msg: &str, _details: &[String]) {
+        self.error(msg);
+    }
+    /// How JSON passed to [`Reporter::data`] should be laid out
+    ///
+    /// [`JsonStyle::Compact`] by default.
+    fn json_style(&self) -> JsonStyle {
+        JsonStyle::Compact
+    }
+}
+
+/// Layout of JSON output, as chosen by `--json-compact` or `--json-pretty`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonStyle {
+    /// One line per document, for pipelines and log aggregators
+    #[default]
+    Compact,
+    /// Indented over several lines, for reading
+    Pretty,
+}
+
+impl JsonStyle {
+    /// Serialize `value` in this layout
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` cannot be serialized as JSON.
+    pub fn render<T: Serialize + ?Sized>(self, value: &T) -> serde_json::Result<String> {
+        match self {
+            Self::Compact => serde_json::to_string(value),
+            Self::Pretty => serde_json::to_string_pretty(value),
+        }
+    }
+}
+
+/// Render an error as `{"error":{"kind":...,"message":...,"details":[...]}}`
+#[must_use]
+pub fn error_envelope(kind: &str, message: &str, details: &[String], style: JsonStyle) -> String {
+    let envelope = serde_json::json!({
+        "error": {
+            "kind": kind,
+            "message": message,
+            "details": details,
+        }
+    });
+    match style {
+        JsonStyle::Compact => envelope.to_string(),
+        JsonStyle::Pretty => format!("{envelope:#}"),
+    }
+}
+
+/// Kind of message recorded by a [`CapturingReporter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportKind {
+    /// Emitted via [`Reporter::success`]
+    Success,
+    /// Emitted via [`Reporter::error`]
+    Error,
+    /// Emitted via [`Reporter::warning`]
+    Warning,
+    /// Emitted via [`Reporter::info`]
+    Info,
+}
+
+/// Reporter that discards every message
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullReporter;
+
+impl Reporter for NullReporter {
+    fn success(&self, _msg: &str) {}
+    fn error(&self, _msg: &str) {}
+    fn warning(&self, _msg: &str) {}
+    fn info(&self, _msg: &str) {}
+}
+
+/// Reporter that collects messages in memory for later inspection
+#[derive(Debug, Default)]
+pub struct CapturingReporter {
+    /// Messages recorded so far, in emission order
+    messages: RefCell<Vec<(ReportKind, String)>>,
+}
+
+impl CapturingReporter {
+    /// Create an empty capturing reporter
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return a copy of all recorded messages, in emission order
+    #[must_use]
+    pub fn messages(&self) -> Vec<(ReportKind, String)> {
+        self.messages.borrow().clone()
+    }
+
+    fn record(&self, kind: ReportKind, msg: &str) {
+        self.messages.borrow_mut().push((kind, msg.to_string()));
+    }
+}
+
+impl Reporter for CapturingReporter {
+    fn success(&self, msg: &str) {
+        self.record(ReportKind::Success, msg);
+    }
+
+    fn error(&self, msg: &str) {
+        self.record(ReportKind::Error, msg);
+    }
+
+    fn warning(&self, msg: &str) {
+        self.record(ReportKind::Warning, msg);
+    }
+
+    fn info(&self, msg: &str) {
+        self.record(ReportKind::Info, msg);
+    }
+}
+
+/// When to style output with colors and emojis, as chosen by `--color`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Defer to `CLICOLOR_FORCE`, then `NO_COLOR`, then whether stdout is a terminal
+    #[default]
+    Auto,
+    /// Always style, even when piped
+    Always,
+    /// Never style
+    Never,
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            other => {
+                anyhow::bail!("Unknown color choice '{other}' (expected auto, always or never)")
+            }
+        }
+    }
+}
+
+impl ColorChoice {
+    /// Decide whether to style output
+    ///
+    /// Precedence: an explicit choice, then `CLICOLOR_FORCE` (any value but `0` forces
+    /// color), then `NO_COLOR` (any non-empty value disables it), then `is_tty`.
+    #[must_use]
+    pub fn resolve(
+        self,
+        clicolor_force: Option<&str>,
+        no_color: Option<&str>,
+        is_tty: bool,
+    ) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto if clicolor_force.is_some_and(|v| v != "0") => true,
+            Self::Auto if no_color.is_some_and(|v| !v.is_empty()) => false,
+            Self::Auto => is_tty,
+        }
+    }
+}
+
+/// Environment variables CI services set; any of them marks a CI run
+const CI_VARIABLES: [&str; 3] = ["CI", "GITHUB_ACTIONS", "GITLAB_CI"];
+
+/// Whether versioneer is running under CI
+///
+/// True when `CI`, `GITHUB_ACTIONS` or `GITLAB_CI` is set to anything but an empty
+/// string, `0` or `false`. CI runs default to plain output and never prompt, even when
+/// the runner allocates a terminal.
+#[must_use]
+pub fn running_in_ci() -> bool {
+    ci_detected(|name| std::env::var(name).ok())
+}
+
+/// [`running_in_ci`] with each environment variable looked up through `var`
+fn ci_detected(var: impl Fn(&str) -> Option<String>) -> bool {
+    CI_VARIABLES
+        .iter()
+        .any(|name| var(name).is_some_and(|value| is_enabled(&value)))
+}
+
+/// Environment variable that answers yes to every confirmation, like `doctor --fix --yes`
+pub const ASSUME_YES_VARIABLE: &str = "VERSIONEER_ASSUME_YES";
+
+/// Whether confirmations should be answered yes without asking
+///
+/// True when `VERSIONEER_ASSUME_YES` is set, with the same rules as [`running_in_ci`].
+/// It takes precedence over CI, which otherwise declines.
+#[must_use]
+pub fn assume_yes() -> bool {
+    std::env::var(ASSUME_YES_VARIABLE).is_ok_and(|value| is_enabled(&value))
+}
+
+/// Whether an environment flag's `value` switches it on: anything but an empty
+/// string, `0` or `false`
+fn is_enabled(value: &str) -> bool {
+    !value.is_empty() && value != "0" && !value.eq_ignore_ascii_case("false")
+}
 
 /// Output formatter that strips colors and emojis for non-TTY output
 pub struct OutputFormatter {
-    /// Whether output is going to a TTY
+    /// Whether output is going to a TTY, which enables progress lines
     is_tty: bool,
+    /// Whether messages are styled with colors and emojis
+    color: bool,
 }
 
 impl OutputFormatter {
-    /// Create a new output formatter
+    /// Create a new output formatter, styled when stdout is a terminal outside CI
+    ///
+    /// `CLICOLOR_FORCE` and `NO_COLOR` are honored as for [`ColorChoice::Auto`].
     #[must_use]
     pub fn new() -> Self {
+        Self::with_color(ColorChoice::Auto)
+    }
+
+    /// Create an output formatter whose styling follows `choice` (see
+    /// [`ColorChoice::resolve`])
+    ///
+    /// Under CI ([`running_in_ci`]) stdout is treated as a pipe, so output is plain and
+    /// progress lines are off unless `--color` or `CLICOLOR_FORCE` asks for color.
+    #[must_use]
+    pub fn with_color(choice: ColorChoice) -> Self {
+        let is_tty = std::io::stdout().is_terminal() && !running_in_ci();
+        let color = choice.resolve(
+            std::env::var("CLICOLOR_FORCE").ok().as_deref(),
+            std::env::var("NO_COLOR").ok().as_deref(),
+            is_tty,
+        );
+        Self { is_tty, color }
+    }
+
+    /// Create a formatter that never adds colors or emojis, e.g. for captured output
+    #[must_use]
+    pub const fn plain() -> Self {
         Self {
-            is_tty: std::io::stdout().is_terminal(),
+            is_tty: false,
+            color: false,
         }
     }
 
+    /// Whether output is styled with colors and emojis
+    #[must_use]
+    pub const fn uses_color(&self) -> bool {
+        self.color
+    }
+
     /// Format a success message with checkmark
     #[must_use]
     pub fn success(&self, msg: &str) -> String {
-        if self.is_tty {
-            format!("{} {}", Emoji("✨", "✓"), style(msg).green())
+        if self.color {
+            format!("{} {}", Emoji("✨", "✓"), styled(msg).green())
         } else {
             format!("✓ {msg}")
         }
@@ -31,8 +298,8 @@ impl OutputFormatter {
     /// Format an error message with X mark
     #[must_use]
     pub fn error(&self, msg: &str) -> String {
-        if self.is_tty {
-            format!("{} {}", Emoji("❌", "✗"), style(msg).red())
+        if self.color {
+            format!("{} {}", Emoji("❌", "✗"), styled(msg).red())
         } else {
             format!("✗ {msg}")
         }
@@ -41,8 +308,8 @@ impl OutputFormatter {
     /// Format a warning message
     #[must_use]
     pub fn warning(&self, msg: &str) -> String {
-        if self.is_tty {
-            format!("{} {}", Emoji("⚠️", "!"), style(msg).yellow())
+        if self.color {
+            format!("{} {}", Emoji("⚠️", "!"), styled(msg).yellow())
         } else {
             format!("! {msg}")
         }
@@ -51,11 +318,11 @@ impl OutputFormatter {
     /// Format a version display
     #[must_use]
     pub fn version(&self, version: &str) -> String {
-        if self.is_tty {
+        if self.color {
             format!(
                 "{} Current version: {}",
                 Emoji("📦", ""),
-                style(version).cyan().bold()
+                styled(version).cyan().bold()
             )
         } else {
             format!("Current version: {version}")
@@ -65,21 +332,35 @@ impl OutputFormatter {
     /// Format build systems header
     #[must_use]
     pub fn build_systems_header(&self) -> String {
-        if self.is_tty {
+        if self.color {
             format!("{} Detected build systems:", Emoji("🔍", ""))
         } else {
             "Detected build systems:".to_string()
         }
     }
 
+    /// Format a cascade progress line
+    #[must_use]
+    pub fn progress(&self, done: usize, total: usize, path: &Path) -> String {
+        if self.color {
+            format!(
+                "{} [{done}/{total}] {}",
+                Emoji("⏳", ""),
+                styled(path.display()).dim()
+            )
+        } else {
+            format!("[{done}/{total}] {}", path.display())
+        }
+    }
+
     /// Format a sync status symbol
     #[must_use]
     pub fn sync_status(&self, in_sync: bool) -> String {
-        if self.is_tty {
+        if self.color {
             if in_sync {
-                format!("{}", style("✓").green().bold())
+                format!("{}", styled("✓").green().bold())
             } else {
-                format!("{}", style("✗").red().bold())
+                format!("{}", styled("✗").red().bold())
             }
         } else if in_sync {
             "✓".to_string()
@@ -89,19 +370,204 @@ impl OutputFormatter {
     }
 }
 
+/// Style `val`, regardless of console's own terminal detection
+///
+/// Callers only style when [`OutputFormatter::uses_color`] says so, which may force
+/// color into a pipe.
+fn styled<D>(val: D) -> StyledObject<D> {
+    console::style(val).force_styling(true)
+}
+
+/// Render `name=value` lines in the GitHub Actions step output format
+///
+/// Values containing newlines use the multiline `name<<DELIMITER` form.
+#[must_use]
+pub fn github_output_lines(outputs: &[(&str, String)]) -> String {
+    outputs
+        .iter()
+        .map(|(name, value)| {
+            if value.contains('\n') {
+                let delimiter = format!("VERSIONEER_{}_EOF", name.to_uppercase());
+                format!("{name}<<{delimiter}\n{value}\n{delimiter}\n")
+            } else {
+                format!("{name}={value}\n")
+            }
+        })
+        .collect()
+}
+
+/// Render `KEY=value` lines for a dotenv-style file describing `version`
+///
+/// Keys are `VERSION`, `VERSION_MAJOR`, `VERSION_MINOR`, `VERSION_PATCH`,
+/// `VERSION_PRERELEASE` and `VERSION_BUILD`; values are written unquoted, and the last
+/// two are empty when the version has no prerelease or build metadata.
+#[must_use]
+pub fn env_file_lines(version: &semver::Version) -> String {
+    [
+        ("VERSION", version.to_string()),
+        ("VERSION_MAJOR", version.major.to_string()),
+        ("VERSION_MINOR", version.minor.to_string()),
+        ("VERSION_PATCH", version.patch.to_string()),
+        ("VERSION_PRERELEASE", version.pre.to_string()),
+        ("VERSION_BUILD", version.build.to_string()),
+    ]
+    .iter()
+    .fold(String::new(), |mut lines, (key, value)| {
+        lines.push_str(key);
+        lines.push('=');
+        lines.push_str(value);
+        lines.push('\n');
+        lines
+    })
+}
+
 impl Default for OutputFormatter {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl Reporter for OutputFormatter {
+    fn success(&self, msg: &str) {
+        println!("{}", Self::success(self, msg));
+    }
+
+    fn error(&self, msg: &str) {
+        eprintln!("{}", Self::error(self, msg));
+    }
+
+    fn warning(&self, msg: &str) {
+        println!("{}", Self::warning(self, msg));
+    }
+
+    fn info(&self, msg: &str) {
+        println!("{msg}");
+    }
+
+    /// Rewrites a single stderr line on a TTY; silent otherwise to keep logs clean
+    fn progress(&self, done: usize, total: usize, path: &Path) {
+        if !self.is_tty {
+            return;
+        }
+        let term = Term::stderr();
+        let _ = term.clear_line();
+        if done < total {
+            let _ = term.write_str(&Self::progress(self, done, total, path));
+        }
+    }
+}
+
+/// Reporter that formats like [`OutputFormatter`] but writes to the given sinks
+///
+/// Success, warning, info and data lines go to `out`; errors and progress go to `err`.
+/// The binary passes stdout and stderr; tests can pass `Vec<u8>` buffers to capture
+/// output without spawning a process. Write failures (e.g. a closed pipe) are ignored.
+/// With [`WriterReporter::with_json_errors`], errors are written as
+/// [`error_envelope`] lines instead.
+pub struct WriterReporter<'a> {
+    formatter: OutputFormatter,
+    out: RefCell<Box<dyn Write + 'a>>,
+    err: RefCell<Box<dyn Write + 'a>>,
+    json_errors: bool,
+    json_style: JsonStyle,
+}
+
+impl<'a> WriterReporter<'a> {
+    /// Create a reporter writing regular output to `out` and errors to `err`
+    pub fn new(formatter: OutputFormatter, out: impl Write + 'a, err: impl Write + 'a) -> Self {
+        Self {
+            formatter,
+            out: RefCell::new(Box::new(out)),
+            err: RefCell::new(Box::new(err)),
+            json_errors: false,
+            json_style: JsonStyle::Compact,
+        }
+    }
+
+    /// Write errors as JSON envelopes (see [`error_envelope`]) instead of styled text
+    #[must_use]
+    pub const fn with_json_errors(mut self, json_errors: bool) -> Self {
+        self.json_errors = json_errors;
+        self
+    }
+
+    /// Lay out JSON data and error envelopes in `style`
+    #[must_use]
+    pub const fn with_json_style(mut self, style: JsonStyle) -> Self {
+        self.json_style = style;
+        self
+    }
+
+    /// The formatter used to style messages
+    #[must_use]
+    pub const fn formatter(&self) -> &OutputFormatter {
+        &self.formatter
+    }
+
+    fn write_line(sink: &RefCell<Box<dyn Write + 'a>>, line: &str) {
+        let mut sink = sink.borrow_mut();
+        let _ = writeln!(sink, "{line}");
+        let _ = sink.flush();
+    }
+}
+
+impl Reporter for WriterReporter<'_> {
+    fn success(&self, msg: &str) {
+        Self::write_line(&self.out, &self.formatter.success(msg));
+    }
+
+    fn error(&self, msg: &str) {
+        self.failure("Error", msg, &[]);
+    }
+
+    fn warning(&self, msg: &str) {
+        Self::write_line(&self.out, &self.formatter.warning(msg));
+    }
+
+    fn info(&self, msg: &str) {
+        Self::write_line(&self.out, msg);
+    }
+
+    fn failure(&self, kind: &str, msg: &str, details: &[String]) {
+        if self.json_errors {
+            Self::write_line(
+                &self.err,
+                &error_envelope(kind, msg.trim(), details, self.json_style),
+            );
+        } else {
+            Self::write_line(&self.err, &self.formatter.error(msg));
+        }
+    }
+
+    /// Rewrites a single line on a TTY; silent otherwise to keep logs clean
+    fn progress(&self, done: usize, total: usize, path: &Path) {
+        if !self.formatter.is_tty {
+            return;
+        }
+        let mut err = self.err.borrow_mut();
+        // Return to the start of the line and clear it
+        let _ = write!(err, "\r\x1b[2K");
+        if done < total {
+            let _ = write!(err, "{}", self.formatter.progress(done, total, path));
+        }
+        let _ = err.flush();
+    }
+
+    fn json_style(&self) -> JsonStyle {
+        self.json_style
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_non_tty_output() {
-        let formatter = OutputFormatter { is_tty: false };
+        let formatter = OutputFormatter {
+            is_tty: false,
+            color: false,
+        };
 
         assert_eq!(formatter.success("test"), "✓ test");
         assert_eq!(formatter.error("test"), "✗ test");
@@ -111,7 +577,10 @@ mod tests {
 
     #[test]
     fn test_sync_status() {
-        let formatter_no_tty = OutputFormatter { is_tty: false };
+        let formatter_no_tty = OutputFormatter {
+            is_tty: false,
+            color: false,
+        };
 
         assert_eq!(formatter_no_tty.sync_status(true), "✓");
         assert_eq!(formatter_no_tty.sync_status(false), "✗");
@@ -119,7 +588,10 @@ mod tests {
 
     #[test]
     fn test_build_systems_header() {
-        let formatter_no_tty = OutputFormatter { is_tty: false };
+        let formatter_no_tty = OutputFormatter {
+            is_tty: false,
+            color: false,
+        };
         assert_eq!(
             formatter_no_tty.build_systems_header(),
             "Detected build systems:"
@@ -134,10 +606,65 @@ mod tests {
         assert!(msg.contains("test"));
     }
 
+    #[test]
+    fn test_color_choice_precedence() {
+        // --color beats every environment variable and the terminal
+        assert!(ColorChoice::Always.resolve(None, Some("1"), false));
+        assert!(!ColorChoice::Never.resolve(Some("1"), None, true));
+        // CLICOLOR_FORCE beats NO_COLOR and the terminal
+        assert!(ColorChoice::Auto.resolve(Some("1"), Some("1"), false));
+        assert!(!ColorChoice::Auto.resolve(Some("0"), None, false));
+        // NO_COLOR beats the terminal; an empty value is ignored
+        assert!(!ColorChoice::Auto.resolve(None, Some("1"), true));
+        assert!(ColorChoice::Auto.resolve(None, Some(""), true));
+        // Otherwise the terminal decides
+        assert!(ColorChoice::Auto.resolve(None, None, true));
+        assert!(!ColorChoice::Auto.resolve(None, None, false));
+    }
+
+    #[test]
+    fn test_ci_detected_from_environment() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| (*value).to_string())
+            }
+        };
+        assert!(!ci_detected(env(&[])));
+        assert!(ci_detected(env(&[("CI", "true")])));
+        assert!(ci_detected(env(&[("GITHUB_ACTIONS", "true")])));
+        assert!(ci_detected(env(&[("GITLAB_CI", "1")])));
+        // Explicitly negative values do not count
+        assert!(!ci_detected(env(&[("CI", "false")])));
+        assert!(!ci_detected(env(&[("CI", "0"), ("GITLAB_CI", "")])));
+    }
+
+    #[test]
+    fn test_is_enabled() {
+        assert!(is_enabled("1"));
+        assert!(is_enabled("yes"));
+        assert!(!is_enabled(""));
+        assert!(!is_enabled("0"));
+        assert!(!is_enabled("FALSE"));
+    }
+
+    #[test]
+    fn test_forced_color_styles_without_tty() {
+        let formatter = OutputFormatter {
+            is_tty: false,
+            color: true,
+        };
+        assert!(formatter.success("done").contains("\x1b["));
+    }
+
     #[test]
     fn test_tty_output_contains_content() {
         // Test TTY mode still contains the message even if it adds formatting
-        let formatter_tty = OutputFormatter { is_tty: true };
+        let formatter_tty = OutputFormatter {
+            is_tty: true,
+            color: true,
+        };
 
         let success_msg = formatter_tty.success("success test");
         assert!(success_msg.contains("success test"));
@@ -154,7 +681,10 @@ mod tests {
 
     #[test]
     fn test_special_characters_in_messages() {
-        let formatter = OutputFormatter { is_tty: false };
+        let formatter = OutputFormatter {
+            is_tty: false,
+            color: false,
+        };
 
         // Test with special characters
         assert_eq!(formatter.success("test with 日本語"), "✓ test with 日本語");
@@ -167,7 +697,10 @@ mod tests {
 
     #[test]
     fn test_newlines_and_multiline() {
-        let formatter = OutputFormatter { is_tty: false };
+        let formatter = OutputFormatter {
+            is_tty: false,
+            color: false,
+        };
 
         // Test with newlines
         let msg_with_newline = formatter.success("line1\nline2");
@@ -177,7 +710,10 @@ mod tests {
 
     #[test]
     fn test_empty_messages() {
-        let formatter = OutputFormatter { is_tty: false };
+        let formatter = OutputFormatter {
+            is_tty: false,
+            color: false,
+        };
 
         assert_eq!(formatter.success(""), "✓ ");
         assert_eq!(formatter.error(""), "✗ ");
@@ -187,7 +723,10 @@ mod tests {
 
     #[test]
     fn test_long_messages() {
-        let formatter = OutputFormatter { is_tty: false };
+        let formatter = OutputFormatter {
+            is_tty: false,
+            color: false,
+        };
 
         let long_msg = "a".repeat(1000);
         let result = formatter.success(&long_msg);
@@ -198,7 +737,10 @@ mod tests {
 
     #[test]
     fn test_emoji_fallbacks_non_tty() {
-        let formatter_no_tty = OutputFormatter { is_tty: false };
+        let formatter_no_tty = OutputFormatter {
+            is_tty: false,
+            color: false,
+        };
 
         // Verify all emojis fall back to ASCII characters in non-TTY mode
         assert!(formatter_no_tty.success("test").starts_with('✓'));
@@ -219,7 +761,10 @@ mod tests {
     fn test_all_output_methods_with_both_modes() {
         // Test both TTY and non-TTY modes produce valid output
         for is_tty in [true, false] {
-            let formatter = OutputFormatter { is_tty };
+            let formatter = OutputFormatter {
+                is_tty,
+                color: is_tty,
+            };
 
             // All methods should produce non-empty output
             assert!(!formatter.success("msg").is_empty());
@@ -231,4 +776,134 @@ mod tests {
             assert!(!formatter.sync_status(false).is_empty());
         }
     }
+
+    #[test]
+    fn test_capturing_reporter_records_in_order() {
+        let reporter = CapturingReporter::new();
+        let sink: &dyn Reporter = &reporter;
+
+        sink.success("done");
+        sink.warning("careful");
+        sink.error("broken");
+        sink.info("plain");
+
+        assert_eq!(
+            reporter.messages(),
+            vec![
+                (ReportKind::Success, "done".to_string()),
+                (ReportKind::Warning, "careful".to_string()),
+                (ReportKind::Error, "broken".to_string()),
+                (ReportKind::Info, "plain".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_null_reporter_accepts_messages() {
+        let sink: &dyn Reporter = &NullReporter;
+        sink.success("ignored");
+        sink.error("ignored");
+        sink.warning("ignored");
+        sink.info("ignored");
+    }
+
+    #[test]
+    fn test_progress_line() {
+        let formatter = OutputFormatter {
+            is_tty: false,
+            color: false,
+        };
+        assert_eq!(
+            formatter.progress(3, 10, Path::new("crates/a/Cargo.toml")),
+            "[3/10] crates/a/Cargo.toml"
+        );
+    }
+
+    #[test]
+    fn test_github_output_lines() {
+        let rendered = github_output_lines(&[
+            ("version", "1.2.4".to_string()),
+            ("previous", "1.2.3".to_string()),
+            ("bumped", "true".to_string()),
+        ]);
+        assert_eq!(rendered, "version=1.2.4\nprevious=1.2.3\nbumped=true\n");
+    }
+
+    #[test]
+    fn test_env_file_lines() {
+        let version = semver::Version::parse("1.2.3-rc.1").unwrap();
+        assert_eq!(
+            env_file_lines(&version),
+            "VERSION=1.2.3-rc.1\nVERSION_MAJOR=1\nVERSION_MINOR=2\nVERSION_PATCH=3\nVERSION_PRERELEASE=rc.1\nVERSION_BUILD=\n"
+        );
+    }
+
+    #[test]
+    fn test_github_output_lines_multiline() {
+        let rendered = github_output_lines(&[("notes", "a\nb".to_string())]);
+        assert_eq!(
+            rendered,
+            "notes<<VERSIONEER_NOTES_EOF\na\nb\nVERSIONEER_NOTES_EOF\n"
+        );
+    }
+
+    #[test]
+    fn test_writer_reporter_captures_streams() {
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        {
+            let reporter = WriterReporter::new(OutputFormatter::plain(), &mut out, &mut err);
+            reporter.success("done");
+            reporter.info("line");
+            reporter.data("{}");
+            reporter.error("failed");
+            reporter.progress(1, 2, Path::new("a/Cargo.toml"));
+        }
+
+        assert_eq!(String::from_utf8(out).unwrap(), "✓ done\nline\n{}\n");
+        assert_eq!(String::from_utf8(err).unwrap(), "✗ failed\n");
+    }
+
+    #[test]
+    fn test_writer_reporter_json_errors() {
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        {
+            let reporter = WriterReporter::new(OutputFormatter::plain(), &mut out, &mut err)
+                .with_json_errors(true);
+            reporter.failure("OutOfSync", "drifted\n", &["cargo: 2.0.0".to_string()]);
+        }
+
+        assert!(out.is_empty());
+        let envelope: serde_json::Value = serde_json::from_slice(&err).unwrap();
+        assert_eq!(envelope["error"]["kind"], "OutOfSync");
+        assert_eq!(envelope["error"]["message"], "drifted");
+        assert_eq!(envelope["error"]["details"][0], "cargo: 2.0.0");
+    }
+
+    #[test]
+    fn test_json_style_controls_layout() {
+        let value = serde_json::json!({"version": "1.2.3"});
+        assert_eq!(
+            JsonStyle::Compact.render(&value).unwrap(),
+            r#"{"version":"1.2.3"}"#
+        );
+        assert_eq!(
+            JsonStyle::Pretty.render(&value).unwrap(),
+            "{\n  \"version\": \"1.2.3\"\n}"
+        );
+
+        let mut err = Vec::new();
+        {
+            let reporter = WriterReporter::new(OutputFormatter::plain(), std::io::sink(), &mut err)
+                .with_json_errors(true)
+                .with_json_style(JsonStyle::Pretty);
+            assert_eq!(reporter.json_style(), JsonStyle::Pretty);
+            reporter.error("failed");
+        }
+        let err = String::from_utf8(err).unwrap();
+        assert!(err.lines().count() > 1, "{err}");
+        let envelope: serde_json::Value = serde_json::from_str(&err).unwrap();
+        assert_eq!(envelope["error"]["message"], "failed");
+    }
 }
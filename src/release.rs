@@ -0,0 +1,406 @@
+//! One-shot release: bump, changelog, commit and annotated tag
+
+use crate::{BumpType, VersionManager, WriteSnapshot, trace};
+use anyhow::{Context, Result};
+use semver::Version;
+use std::path::PathBuf;
+
+/// Changelog filename updated during a release, if present
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+/// Heading that collects unreleased changes in a Keep a Changelog file
+const UNRELEASED_HEADING: &str = "## [Unreleased]";
+
+/// Everything a release will do, computed before any file is touched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleasePlan {
+    /// Version in the VERSION file before the release
+    pub current_version: Version,
+    /// Version being released
+    pub new_version: Version,
+    /// Name of the tag that will be created
+    pub tag_name: String,
+    /// Version files that will be rewritten
+    pub files_to_update: Vec<PathBuf>,
+    /// Changelog whose `[Unreleased]` section will be promoted, if any
+    pub changelog: Option<PathBuf>,
+    /// Whether every discovered manifest is updated, not just the top level
+    pub cascade: bool,
+    /// Whether the tag will be GPG-signed
+    pub sign: bool,
+}
+
+impl ReleasePlan {
+    /// Human-readable list of the steps this plan performs, in order
+    #[must_use]
+    pub fn steps(&self) -> Vec<String> {
+        let mut steps = vec![format!(
+            "Bump version {} -> {}",
+            self.current_version, self.new_version
+        )];
+        steps.extend(
+            self.files_to_update
+                .iter()
+                .map(|path| format!("  update {}", path.display())),
+        );
+        if let Some(changelog) = &self.changelog {
+            steps.push(format!(
+                "Move [Unreleased] entries in {} under [{}]",
+                changelog.display(),
+                self.new_version
+            ));
+        }
+        steps.push(format!("Commit \"{}\"", self.commit_message()));
+        steps.push(format!(
+            "Create {} tag {}",
+            if self.sign {
+                "signed annotated"
+            } else {
+                "annotated"
+            },
+            self.tag_name
+        ));
+        steps
+    }
+
+    fn commit_message(&self) -> String {
+        format!("Release {}", self.tag_name)
+    }
+}
+
+impl VersionManager {
+    /// Work out what [`Self::release`] would do without changing anything
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if versions are out of sync, the tag would not advance past the
+    /// latest existing tag, or discovery fails. Uncommitted changes are only refused by
+    /// [`Self::release`], so a dry run can preview a release from a dirty tree.
+    pub fn plan_release(
+        &self,
+        bump_type: BumpType,
+        cascade: bool,
+        tag_prefix: &str,
+        sign: bool,
+    ) -> Result<ReleasePlan> {
+        if cascade {
+            self.verify_cascade_in_sync()?;
+        } else {
+            self.verify_versions_in_sync()?;
+        }
+
+        let current_version = self.read_version_file()?;
+        let new_version = bump_type.apply(&current_version);
+        self.check_tag_advances(&new_version, tag_prefix)?;
+
+        let files_to_update = if cascade {
+            self.bump_cascade_dry_run(bump_type)?.files_to_update
+        } else {
+            std::iter::once(self.version_file_path())
+                .chain(
+                    self.detect_manifests()
+                        .into_iter()
+                        .filter(|(path, system)| !self.manifest_has_no_version(path, system))
+                        .map(|(path, _)| path),
+                )
+                .collect()
+        };
+
+        let changelog_path = self.base_path.join(CHANGELOG_FILE);
+        let changelog = trace::read_to_string(&changelog_path)
+            .is_ok_and(|content| content.contains(UNRELEASED_HEADING))
+            .then_some(changelog_path);
+
+        Ok(ReleasePlan {
+            current_version,
+            tag_name: format!("{tag_prefix}{new_version}"),
+            new_version,
+            files_to_update,
+            changelog,
+            cascade,
+            sign,
+        })
+    }
+
+    /// Carry out a release plan: write versions, update the changelog, commit, and tag
+    ///
+    /// Runs as a unit: if any step fails, the working tree is reset and any release
+    /// commit is dropped, so there is never a commit without its tag or a tag without
+    /// its commit. Only the files the release wrote are committed, including ones git
+    /// did not track yet, such as a new CHANGELOG.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the working tree has uncommitted changes or any step fails.
+    /// After a failed step the repository is rolled back, and the error says whether
+    /// the rollback succeeded.
+    pub fn release(&self, plan: &ReleasePlan) -> Result<()> {
+        let status = self.run_git(&["status", "--porcelain", "--untracked-files=no"])?;
+        if !status.trim().is_empty() {
+            anyhow::bail!(
+                "Working tree has uncommitted changes; commit or stash them before releasing"
+            );
+        }
+        let head_before = self.run_git(&["rev-parse", "HEAD"])?;
+        let head_before = head_before.trim();
+        let mut targets = self.write_targets(plan.cascade)?;
+        targets.extend(plan.changelog.clone());
+        let snapshot = WriteSnapshot::take(targets)?;
+
+        let committed = self.write_release_files(plan).and_then(|()| {
+            let written: Vec<String> = snapshot
+                .changed_paths()
+                .iter()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            let mut add = vec!["add", "--"];
+            add.extend(written.iter().map(String::as_str));
+            self.run_git(&add)?;
+            self.run_git(&["commit", "-m", &plan.commit_message()])
+                .context("Failed to commit release")
+        });
+        if let Err(e) = committed {
+            return Err(self.roll_back_release(e, head_before, &snapshot));
+        }
+
+        let mut tag_args = vec!["tag", "-a"];
+        if plan.sign {
+            tag_args.push("-s");
+        }
+        let message = plan.commit_message();
+        tag_args.extend([plan.tag_name.as_str(), "-m", message.as_str()]);
+        if let Err(e) = self.run_git(&tag_args) {
+            let e = e.context("Failed to create release tag");
+            return Err(self.roll_back_release(e, head_before, &snapshot));
+        }
+
+        Ok(())
+    }
+
+    fn write_release_files(&self, plan: &ReleasePlan) -> Result<()> {
+        let version = plan.new_version.to_string();
+        if plan.cascade {
+            self.reset_cascade(&version)?;
+        } else {
+            self.reset_version(&version)?;
+        }
+
+        if let Some(changelog) = &plan.changelog {
+            let content = trace::read_to_string(changelog)
+                .with_context(|| format!("Failed to read {}", changelog.display()))?;
+            let updated = promote_unreleased(&content, &plan.new_version, &today());
+            Self::write_if_changed(changelog, &content, &updated)
+                .with_context(|| format!("Failed to write {}", changelog.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Restore HEAD and the written files to how they were before the release started,
+    /// returning `error` annotated with whether that worked
+    ///
+    /// The release refuses to start with uncommitted tracked changes, so a hard reset
+    /// only discards what the release itself wrote. It also deletes files the release
+    /// staged that HEAD did not track, so `snapshot` then puts those back.
+    fn roll_back_release(
+        &self,
+        error: anyhow::Error,
+        head_before: &str,
+        snapshot: &WriteSnapshot,
+    ) -> anyhow::Error {
+        let rolled_back = self
+            .run_git(&["reset", "--hard", head_before])
+            .and_then(|_| snapshot.restore());
+        match rolled_back {
+            Ok(_) => error.context("Release was rolled back"),
+            Err(rollback_error) => error.context(format!(
+                "Rolling back the release failed too, so the repository may hold a partial release: {rollback_error:#}"
+            )),
+        }
+    }
+}
+
+/// Insert a heading for `version` below the `[Unreleased]` heading
+///
+/// Entries collected under `[Unreleased]` end up under the new version, leaving an
+/// empty `[Unreleased]` section for future changes.
+#[must_use]
+pub fn promote_unreleased(content: &str, version: &Version, date: &str) -> String {
+    content.replacen(
+        UNRELEASED_HEADING,
+        &format!("{UNRELEASED_HEADING}\n\n## [{version}] - {date}"),
+        1,
+    )
+}
+
+/// Today's UTC date as `YYYY-MM-DD`
+fn today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (year, month, day) = civil_from_days(secs / 86_400);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) Gregorian date
+const fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    // Howard Hinnant's days_from_civil inverse, restricted to dates after the epoch
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) -> Result<String> {
+        let output = Command::new("git").args(args).current_dir(dir).output()?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "git {:?} failed: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn release_repo() -> Result<TempDir> {
+        let temp_dir = TempDir::new()?;
+        let dir = temp_dir.path();
+        fs::write(dir.join("VERSION"), "1.2.3\n")?;
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"1.2.3\"\n",
+        )?;
+        fs::write(
+            dir.join(CHANGELOG_FILE),
+            "# Changelog\n\n## [Unreleased]\n\n- Added a thing\n",
+        )?;
+        git(dir, &["init"])?;
+        git(dir, &["config", "user.email", "test@test.com"])?;
+        git(dir, &["config", "user.name", "Test"])?;
+        git(dir, &["add", "."])?;
+        git(dir, &["commit", "-m", "init"])?;
+        Ok(temp_dir)
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn test_promote_unreleased() {
+        let updated = promote_unreleased(
+            "# Changelog\n\n## [Unreleased]\n\n- Fix\n",
+            &Version::new(1, 3, 0),
+            "2024-01-02",
+        );
+        assert_eq!(
+            updated,
+            "# Changelog\n\n## [Unreleased]\n\n## [1.3.0] - 2024-01-02\n\n- Fix\n"
+        );
+    }
+
+    #[test]
+    fn test_release_commits_and_tags() -> Result<()> {
+        let temp_dir = release_repo()?;
+        let dir = temp_dir.path();
+        let manager = VersionManager::new(dir);
+
+        let plan = manager.plan_release(BumpType::Minor, false, "v", false)?;
+        assert_eq!(plan.tag_name, "v1.3.0");
+        assert_eq!(plan.changelog, Some(dir.join(CHANGELOG_FILE)));
+
+        manager.release(&plan)?;
+
+        assert_eq!(manager.read_version_file()?, Version::new(1, 3, 0));
+        assert!(fs::read_to_string(dir.join(CHANGELOG_FILE))?.contains("## [1.3.0] - "));
+        assert_eq!(
+            git(dir, &["log", "-1", "--format=%s"])?.trim(),
+            "Release v1.3.0"
+        );
+        assert_eq!(git(dir, &["cat-file", "-t", "v1.3.0"])?.trim(), "tag");
+        assert!(git(dir, &["status", "--porcelain"])?.trim().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_release_rolls_back_when_tagging_fails() -> Result<()> {
+        let temp_dir = release_repo()?;
+        let dir = temp_dir.path();
+        let head = git(dir, &["rev-parse", "HEAD"])?;
+        // Signing with a key that does not exist makes `git tag -s` fail
+        git(dir, &["config", "user.signingkey", "0000000000000000"])?;
+        git(dir, &["config", "gpg.program", "false"])?;
+
+        let manager = VersionManager::new(dir);
+        let plan = manager.plan_release(BumpType::Patch, false, "v", true)?;
+        assert!(manager.release(&plan).is_err());
+
+        assert_eq!(git(dir, &["rev-parse", "HEAD"])?, head);
+        assert_eq!(manager.read_version_file()?, Version::new(1, 2, 3));
+        assert!(git(dir, &["tag", "-l"])?.trim().is_empty());
+        assert!(git(dir, &["status", "--porcelain"])?.trim().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_release_refuses_dirty_tree_but_plans_it() -> Result<()> {
+        let temp_dir = release_repo()?;
+        let dir = temp_dir.path();
+        fs::write(
+            dir.join(CHANGELOG_FILE),
+            "# Changelog\n\n## [Unreleased]\n\n- Uncommitted\n",
+        )?;
+
+        // A dry run only needs the plan, which a dirty tree does not block
+        let manager = VersionManager::new(dir);
+        let plan = manager.plan_release(BumpType::Patch, false, "v", false)?;
+        let err = manager.release(&plan).unwrap_err().to_string();
+        assert!(err.contains("uncommitted changes"), "{err}");
+        assert_eq!(manager.read_version_file()?, Version::new(1, 2, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_release_commits_untracked_changelog_and_restores_it_on_failure() -> Result<()> {
+        let temp_dir = release_repo()?;
+        let dir = temp_dir.path();
+        git(dir, &["rm", "--cached", "-q", CHANGELOG_FILE])?;
+        git(dir, &["commit", "-m", "untrack changelog"])?;
+        let changelog = "# Changelog\n\n## [Unreleased]\n\n- Added a thing\n";
+        fs::write(dir.join("notes.txt"), "not part of the release\n")?;
+
+        // A failed tag rolls back, leaving the untracked changelog as it was
+        git(dir, &["config", "user.signingkey", "0000000000000000"])?;
+        git(dir, &["config", "gpg.program", "false"])?;
+        let manager = VersionManager::new(dir);
+        let plan = manager.plan_release(BumpType::Patch, false, "v", true)?;
+        let err = format!("{:#}", manager.release(&plan).unwrap_err());
+        assert!(err.contains("Release was rolled back"), "{err}");
+        assert_eq!(fs::read_to_string(dir.join(CHANGELOG_FILE))?, changelog);
+        assert_eq!(manager.read_version_file()?, Version::new(1, 2, 3));
+
+        // A successful release commits the changelog it edited, and nothing else
+        let plan = manager.plan_release(BumpType::Patch, false, "v", false)?;
+        manager.release(&plan)?;
+        let committed = git(dir, &["show", "--name-only", "--format=", "HEAD"])?;
+        assert!(committed.contains(CHANGELOG_FILE), "{committed}");
+        assert!(!committed.contains("notes.txt"), "{committed}");
+        Ok(())
+    }
+}
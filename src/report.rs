@@ -0,0 +1,188 @@
+//! Structured record of what a mutating command did, for audit trails
+
+use crate::{BuildSystem, VersionManager};
+use anyhow::Result;
+use semver::Version;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One file an operation rewrote, or would rewrite on a dry run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileChange {
+    /// Path relative to the project root
+    pub path: PathBuf,
+    /// Version the file held before, `None` when it could not be read
+    pub old_version: Option<Version>,
+    /// Version written to the file
+    pub new_version: Version,
+}
+
+/// What a bump, sync or reset did
+///
+/// Built by [`VersionManager::plan_report`] before anything is written, then marked
+/// with [`Self::applied`] once the write succeeds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OperationReport {
+    /// Command that produced the report (e.g. `patch`, `sync`, `reset`)
+    pub operation: String,
+    /// Whether the changes were written; false for a dry run
+    pub applied: bool,
+    /// VERSION file version before the operation, `None` when it could not be read
+    pub previous_version: Option<Version>,
+    /// Version the operation wrote
+    pub new_version: Version,
+    /// Files whose version changed
+    pub files: Vec<FileChange>,
+    /// Git tag created (or planned on a dry run)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Seconds since the Unix epoch when the report was built
+    pub timestamp: u64,
+    /// Version of versioneer that produced the report
+    pub tool_version: String,
+}
+
+impl OperationReport {
+    /// Mark the report as written, recording the tag that was created, if any
+    #[must_use]
+    pub fn applied(mut self, tag: Option<String>) -> Self {
+        self.applied = true;
+        self.tag = tag;
+        self
+    }
+}
+
+impl VersionManager {
+    /// Snapshot every version file and describe writing `new_version` to them
+    ///
+    /// Call before the write: each file's current version is read now. Covers the
+    /// VERSION file, the top-level manifests (or every discovered manifest with
+    /// `cascade`), configured Python modules and JSON files. Files already holding
+    /// the version, and manifests with no version of their own, are left out. The
+    /// report starts unapplied; see [`OperationReport::applied`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if manifest discovery fails.
+    pub fn plan_report(
+        &self,
+        operation: &str,
+        new_version: &Version,
+        cascade: bool,
+    ) -> Result<OperationReport> {
+        let previous_version = self.read_version_file().ok();
+        let mut files = Vec::new();
+        let mut record = |path: &Path, old_version: Option<Version>, written: Version| {
+            if old_version.as_ref() != Some(&written) {
+                files.push(FileChange {
+                    path: path
+                        .strip_prefix(&self.base_path)
+                        .unwrap_or(path)
+                        .to_path_buf(),
+                    old_version,
+                    new_version: written,
+                });
+            }
+        };
+
+        record(
+            &self.version_file_path(),
+            previous_version.clone(),
+            new_version.clone(),
+        );
+        let manifests = if cascade {
+            self.discover_manifests()?
+        } else {
+            self.detect_manifests()
+        };
+        for (path, system) in manifests {
+            if self.manifest_has_no_version(&path, &system) {
+                continue;
+            }
+            record(
+                &path,
+                self.read_build_system_version(&system, Some(&path)).ok(),
+                self.written_version(&system, new_version),
+            );
+        }
+        for module in &self.config.python_modules {
+            record(
+                &self.base_path.join(&module.path),
+                self.read_python_module_version(module).ok(),
+                self.written_version(&BuildSystem::PyProject, new_version),
+            );
+        }
+        for file in &self.config.json_files {
+            record(
+                &self.base_path.join(&file.path),
+                self.read_json_file_version(file).ok(),
+                new_version.clone(),
+            );
+        }
+
+        Ok(OperationReport {
+            operation: operation.to_string(),
+            applied: false,
+            previous_version,
+            new_version: new_version.clone(),
+            files,
+            tag: None,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        })
+    }
+
+    /// The version that lands in a `system` file when `version` is written
+    fn written_version(&self, system: &BuildSystem, version: &Version) -> Version {
+        if self.strips_build_metadata(system) {
+            Self::without_build_metadata(version)
+        } else {
+            version.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_plan_report_lists_files_that_change() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let dir = temp_dir.path();
+        fs::write(dir.join("VERSION"), "1.0.0\n")?;
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+        )?;
+        fs::write(
+            dir.join("package.json"),
+            "{\n  \"name\": \"test\",\n  \"version\": \"1.1.0\"\n}\n",
+        )?;
+        let manager = VersionManager::new(dir);
+
+        let report = manager.plan_report("minor", &Version::parse("1.1.0")?, false)?;
+        assert_eq!(report.operation, "minor");
+        assert!(!report.applied);
+        assert_eq!(report.previous_version, Some(Version::parse("1.0.0")?));
+        // package.json already holds the target, so only two files change
+        let paths: Vec<_> = report.files.iter().map(|file| file.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("VERSION"), PathBuf::from("Cargo.toml")]
+        );
+        assert_eq!(report.files[1].old_version, Some(Version::parse("1.0.0")?));
+        assert_eq!(report.files[1].new_version, Version::parse("1.1.0")?);
+        assert_eq!(report.tool_version, env!("CARGO_PKG_VERSION"));
+
+        let applied = report.applied(Some("v1.1.0".to_string()));
+        assert!(applied.applied);
+        assert_eq!(applied.tag.as_deref(), Some("v1.1.0"));
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+//! Supported build systems and configurable targets for the `systems` command.
+
+use anyhow::Result;
+use serde::Serialize;
+use versioneer::output::Reporter;
+use versioneer::{BuildSystem, VersionManager};
+
+/// How versioneer finds the files of a system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Detection {
+    /// Found by its manifest filename in the project directory
+    Auto,
+    /// Only managed when listed in `.versioneer.toml`
+    Config,
+}
+
+impl Detection {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Auto => "auto-detected",
+            Self::Config => "configured",
+        }
+    }
+}
+
+/// One system versioneer can read and update
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SystemEntry {
+    /// Name used by `--source`, `--only` and JSON output
+    pub name: &'static str,
+    /// Filenames or globs the system matches; configured paths for configured targets
+    pub files: Vec<String>,
+    /// How the files are found
+    pub detection: Detection,
+    /// What the file is and where in it the version lives
+    pub description: &'static str,
+}
+
+/// Every build system followed by the targets that must be configured
+///
+/// Derived from [`BuildSystem::info`] and each system's format; the Terraform file and
+/// the configured targets' paths come from the manager's configuration.
+#[must_use]
+pub fn supported_systems(manager: &VersionManager) -> Vec<SystemEntry> {
+    let mut entries: Vec<SystemEntry> = BuildSystem::ALL
+        .iter()
+        .map(|system| {
+            let info = system.info();
+            let files = if *system == BuildSystem::Terraform {
+                vec![manager.config.terraform.file.clone()]
+            } else {
+                info.format
+                    .filenames()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect()
+            };
+            SystemEntry {
+                name: info.name,
+                files,
+                detection: Detection::Auto,
+                description: info.description,
+            }
+        })
+        .collect();
+    entries.push(SystemEntry {
+        name: "python-module",
+        files: manager
+            .config
+            .python_modules
+            .iter()
+            .map(|module| module.path.clone())
+            .collect(),
+        detection: Detection::Config,
+        description: "Python source file, a top-level version assignment ([[python_modules]])",
+    });
+    entries.push(SystemEntry {
+        name: "json-file",
+        files: manager
+            .config
+            .json_files
+            .iter()
+            .map(|file| file.path.clone())
+            .collect(),
+        detection: Detection::Config,
+        description: "Any JSON file, a possibly nested string field ([[json_files]])",
+    });
+    entries
+}
+
+/// Report `entries` as aligned text lines, or as a JSON array
+///
+/// # Errors
+///
+/// Returns an error if JSON serialization fails.
+pub fn run_systems(reporter: &dyn Reporter, entries: &[SystemEntry], json: bool) -> Result<()> {
+    if json {
+        reporter.data(&reporter.json_style().render(entries)?);
+        return Ok(());
+    }
+    let files: Vec<String> = entries
+        .iter()
+        .map(|e| {
+            if e.files.is_empty() {
+                "(none configured)".to_string()
+            } else {
+                e.files.join(", ")
+            }
+        })
+        .collect();
+    let name_width = entries.iter().map(|e| e.name.len()).max().unwrap_or(0);
+    let file_width = files.iter().map(String::len).max().unwrap_or(0);
+    for (e, files) in entries.iter().zip(&files) {
+        reporter.data(&format!(
+            "{:<name_width$}  {files:<file_width$}  {:<13}  {}",
+            e.name,
+            e.detection.label(),
+            e.description
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+    use versioneer::config::{CONFIG_FILE, Config};
+
+    #[test]
+    fn test_supported_systems_follow_registry_and_config() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            "[terraform]\nfile = \"versions.tf\"\n\n[[python_modules]]\npath = \"src/pkg/__init__.py\"\n",
+        )?;
+        let manager =
+            VersionManager::new(temp_dir.path()).with_config(Config::load(temp_dir.path())?);
+
+        let entries = supported_systems(&manager);
+
+        assert_eq!(entries.len(), BuildSystem::ALL.len() + 2);
+        for (entry, system) in entries.iter().zip(BuildSystem::ALL.iter()) {
+            assert_eq!(entry.name, system.name());
+            assert_eq!(entry.detection, Detection::Auto);
+        }
+        let file_of = |name: &str| {
+            entries
+                .iter()
+                .find(|e| e.name == name)
+                .map(|e| e.files.clone())
+                .unwrap()
+        };
+        assert_eq!(file_of("cargo"), ["Cargo.toml"]);
+        assert_eq!(file_of("terraform"), ["versions.tf"]);
+        assert_eq!(file_of("python-module"), ["src/pkg/__init__.py"]);
+        assert!(file_of("json-file").is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+//! Diagnostic log of every file versioneer reads or writes, for `--trace-file`
+//!
+//! Once [`start`] has opened a trace, the wrappers here record each operation as one
+//! NDJSON line: the action, the path and the outcome. File contents are never logged.
+//! Without a trace they behave exactly like their `std::fs` counterparts.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+static TRACE: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// One traced file operation
+#[derive(Debug, Serialize)]
+struct Operation<'a> {
+    /// `read`, `write`, `skip`, `rename` or `remove`
+    action: &'a str,
+    path: &'a Path,
+    /// `ok`, `missing`, `error`, or for a skip why nothing was written
+    outcome: &'a str,
+    /// The I/O error message when the outcome is `error`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Start recording file operations to `path`, replacing it
+///
+/// Only the first call takes effect.
+///
+/// # Errors
+///
+/// Returns an error if the trace file cannot be created.
+pub fn start(path: &Path) -> Result<()> {
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create trace file {}", path.display()))?;
+    let _ = TRACE.set(Mutex::new(file));
+    Ok(())
+}
+
+/// Append one operation to the trace, if one is open
+///
+/// Tracing is best effort: a failure to write the trace never fails the operation.
+fn record(action: &str, path: &Path, outcome: &str, error: Option<String>) {
+    let Some(trace) = TRACE.get() else {
+        return;
+    };
+    let operation = Operation {
+        action,
+        path,
+        outcome,
+        error,
+    };
+    if let (Ok(mut file), Ok(line)) = (trace.lock(), serde_json::to_string(&operation)) {
+        let _ = writeln!(file, "{line}");
+    }
+}
+
+/// Record the result of `action` on `path` and pass it through
+fn traced<T>(action: &str, path: &Path, result: io::Result<T>) -> io::Result<T> {
+    match &result {
+        Ok(_) => record(action, path, "ok", None),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            record(action, path, "missing", None);
+        }
+        Err(err) => record(action, path, "error", Some(err.to_string())),
+    }
+    result
+}
+
+/// [`std::fs::read_to_string`], traced as a `read`
+///
+/// # Errors
+///
+/// Returns the error from [`std::fs::read_to_string`].
+pub fn read_to_string<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let path = path.as_ref();
+    traced("read", path, std::fs::read_to_string(path))
+}
+
+/// [`std::fs::read`], traced as a `read`
+///
+/// # Errors
+///
+/// Returns the error from [`std::fs::read`].
+pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+    let path = path.as_ref();
+    traced("read", path, std::fs::read(path))
+}
+
+/// [`std::fs::write`], traced as a `write`
+///
+/// # Errors
+///
+/// Returns the error from [`std::fs::write`].
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    let path = path.as_ref();
+    traced("write", path, std::fs::write(path, contents))
+}
+
+/// [`std::fs::rename`], traced as a `rename` of the destination
+///
+/// # Errors
+///
+/// Returns the error from [`std::fs::rename`].
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> io::Result<()> {
+    let to = to.as_ref();
+    traced("rename", to, std::fs::rename(from, to))
+}
+
+/// [`std::fs::remove_file`], traced as a `remove`
+///
+/// # Errors
+///
+/// Returns the error from [`std::fs::remove_file`].
+pub fn remove_file<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let path = path.as_ref();
+    traced("remove", path, std::fs::remove_file(path))
+}
+
+/// Record that `path` was deliberately not written, and why (e.g. `unchanged`)
+pub fn skip(path: &Path, reason: &str) {
+    record("skip", path, reason, None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_serializes_without_contents() -> Result<()> {
+        let operation = Operation {
+            action: "read",
+            path: Path::new("/project/VERSION"),
+            outcome: "ok",
+            error: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&operation)?,
+            r#"{"action":"read","path":"/project/VERSION","outcome":"ok"}"#
+        );
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 //! Integration tests for the versioneer CLI
 
 use std::fs;
+use std::path::Path;
 use std::process::Command;
 use tempfile::TempDir;
 
@@ -10,6 +11,24 @@ fn bin_path() -> std::path::PathBuf {
         .join("target/debug/versioneer")
 }
 
+/// Run git in `dir`, returning its stdout
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("Failed to run git");
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// Initialize a git repository in `dir` with a committer identity and an empty commit
+fn init_git_repo(dir: &Path) {
+    git(dir, &["init"]);
+    git(dir, &["config", "user.email", "test@test.com"]);
+    git(dir, &["config", "user.name", "Test"]);
+    git(dir, &["commit", "--allow-empty", "-m", "init"]);
+}
+
 #[test]
 fn test_version_command() {
     let output = Command::new(bin_path())
@@ -57,6 +76,38 @@ fn test_verify_command_in_sync() {
     assert!(String::from_utf8_lossy(&output.stdout).contains("synchronized"));
 }
 
+#[test]
+fn test_color_forced_when_piped() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    let verify = |args: &[&str], clicolor_force: Option<&str>| {
+        let mut command = Command::new(bin_path());
+        command
+            .args(args)
+            .arg("verify")
+            .env_remove("NO_COLOR")
+            .env_remove("CLICOLOR_FORCE")
+            .current_dir(temp_dir.path());
+        if let Some(value) = clicolor_force {
+            command.env("CLICOLOR_FORCE", value);
+        }
+        let output = command.output().expect("Failed to execute verify command");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).contains('\x1b')
+    };
+
+    assert!(!verify(&[], None));
+    assert!(verify(&["--color", "always"], None));
+    assert!(verify(&[], Some("1")));
+    assert!(!verify(&["--color", "never"], Some("1")));
+}
+
 #[test]
 fn test_verify_command_out_of_sync() {
     let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -73,7 +124,158 @@ fn test_verify_command_out_of_sync() {
         .output()
         .expect("Failed to execute verify command");
 
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn test_ignored_systems_are_left_alone() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    let package_json = "{\n  \"name\": \"web\",\n  \"version\": \"9.9.9\"\n}\n";
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    let run = |args: &[&str]| {
+        Command::new(bin_path())
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute versioneer")
+    };
+
+    assert_eq!(run(&["verify"]).status.code(), Some(3));
+    assert!(run(&["verify", "--skip", "package-json"]).status.success());
+
+    fs::write(
+        temp_dir.path().join(".versioneer.toml"),
+        "ignore_systems = [\"package-json\"]\n",
+    )
+    .unwrap();
+    assert!(run(&["verify"]).status.success());
+    let output = run(&["status"]);
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains("PackageJson: ignored (ignore_systems or --skip)")
+    );
+    assert!(run(&["patch"]).status.success());
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("package.json")).unwrap(),
+        package_json
+    );
+}
+
+#[test]
+fn test_json_format_reports_errors_as_envelope() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"2.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["--format", "json", "verify"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute verify command");
+    assert_eq!(output.status.code(), Some(3));
+    let envelope: serde_json::Value =
+        serde_json::from_slice(&output.stderr).expect("stderr should be a JSON envelope");
+    assert_eq!(envelope["error"]["kind"], "OutOfSync");
+    assert_eq!(envelope["error"]["details"].as_array().unwrap().len(), 1);
+
+    let output = Command::new(bin_path())
+        .args(["--format", "json", "reset", "not-a-version"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute reset command");
     assert!(!output.status.success());
+    let envelope: serde_json::Value =
+        serde_json::from_slice(&output.stderr).expect("stderr should be a JSON envelope");
+    assert_eq!(envelope["error"]["kind"], "InvalidVersion");
+}
+
+#[test]
+fn test_json_format_types_unreadable_version_on_verify() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "not-a-version\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["--format", "json", "verify"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute verify command");
+    assert_eq!(output.status.code(), Some(4));
+    let envelope: serde_json::Value =
+        serde_json::from_slice(&output.stderr).expect("stderr should be a JSON envelope");
+    assert_eq!(envelope["error"]["kind"], "InvalidVersion");
+}
+
+#[test]
+fn test_ignore_prerelease_matches_core_version() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.2.0-rc.3\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.2.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .arg("verify")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute verify command");
+    assert_eq!(output.status.code(), Some(3));
+
+    let output = Command::new(bin_path())
+        .args(["verify", "--ignore-prerelease"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute verify command");
+    assert!(output.status.success());
+
+    let output = Command::new(bin_path())
+        .args(["status", "--ignore-prerelease"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute status command");
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains("core version matches 1.2.0-rc.3; prerelease ignored")
+    );
+}
+
+#[test]
+fn test_verify_command_unreadable_manifest_exit_code() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"2.0.0\"\n",
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("package.json"), "not json").unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["verify", "--quiet"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute verify command");
+
+    assert_eq!(output.status.code(), Some(4));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to read PackageJson"));
 }
 
 #[test]
@@ -122,6 +324,88 @@ fn test_sync_command() {
     assert!(cargo_content.contains(r#"version = "3.1.4""#));
 }
 
+#[test]
+fn test_sync_then_verify_pep440_prerelease() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let pyproject = "[project]\nname = \"test\"\nversion = \"1.0.0\"\n";
+    fs::write(temp_dir.path().join("pyproject.toml"), pyproject).unwrap();
+    let run = |command: &str| {
+        Command::new(bin_path())
+            .arg(command)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute versioneer")
+    };
+
+    // Without a number PEP 440 would read the prerelease back as alpha.0
+    fs::write(temp_dir.path().join("VERSION"), "1.2.3-alpha\n").unwrap();
+    let output = run("sync");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("has no number"));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("pyproject.toml")).unwrap(),
+        pyproject
+    );
+
+    fs::write(temp_dir.path().join("VERSION"), "1.2.3-alpha.1\n").unwrap();
+    assert!(run("sync").status.success());
+    assert!(
+        fs::read_to_string(temp_dir.path().join("pyproject.toml"))
+            .unwrap()
+            .contains(r#"version = "1.2.3a1""#)
+    );
+    let output = run("verify");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_sync_cascade_summary() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "3.1.4\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    fs::create_dir(temp_dir.path().join("web")).unwrap();
+    fs::write(
+        temp_dir.path().join("web/package.json"),
+        r#"{"name": "web", "version": "3.1.4"}"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["sync", "--cascade", "--summary"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute sync command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Summary: 2 scanned, 1 updated, 1 already in sync, 0 skipped"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("  updated Cargo.toml"), "{stdout}");
+
+    let output = Command::new(bin_path())
+        .args(["--format", "json", "sync", "--cascade", "--summary"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute sync command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(r#"{"scanned":2,"updated":0,"in_sync":2,"skipped":0,"changed_paths":[]}"#),
+        "{stdout}"
+    );
+}
+
 #[test]
 fn test_patch_command() {
     let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -145,6 +429,42 @@ fn test_patch_command() {
     assert_eq!(version_content.trim(), "1.0.1");
 }
 
+#[test]
+fn test_patch_print_version_only() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["patch", "--print-version-only"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1.0.1\n");
+    assert!(
+        fs::read_to_string(temp_dir.path().join("Cargo.toml"))
+            .unwrap()
+            .contains("version = \"1.0.1\"")
+    );
+
+    fs::write(temp_dir.path().join("VERSION"), "not a version\n").unwrap();
+    let output = Command::new(bin_path())
+        .args(["patch", "--print-version-only"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(!output.stderr.is_empty());
+}
+
 #[test]
 fn test_minor_command() {
     let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -224,6 +544,104 @@ fn test_doctor_command() {
     assert!(stdout.contains("health check"));
 }
 
+#[test]
+fn test_doctor_fix_declines_under_ci() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    let cargo_toml = temp_dir.path().join("Cargo.toml");
+    fs::write(
+        &cargo_toml,
+        "[package]\nname = \"test\"\nversion = \"2.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["doctor", "--fix"])
+        .env("GITLAB_CI", "true")
+        .env_remove("VERSIONEER_ASSUME_YES")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute doctor command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout.contains("Skipped repair"), "stdout: {stdout}");
+    assert!(
+        fs::read_to_string(&cargo_toml)
+            .unwrap()
+            .contains("version = \"2.0.0\"")
+    );
+
+    // VERSIONEER_ASSUME_YES answers yes even under CI
+    let output = Command::new(bin_path())
+        .args(["doctor", "--fix"])
+        .env("GITLAB_CI", "true")
+        .env("VERSIONEER_ASSUME_YES", "1")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute doctor command");
+    assert!(output.status.success());
+    assert!(
+        fs::read_to_string(&cargo_toml)
+            .unwrap()
+            .contains("version = \"1.0.0\"")
+    );
+}
+
+#[test]
+fn test_doctor_warns_when_manifest_committed_after_version() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let commit_at = |date: &str, message: &str| {
+        git(temp_dir.path(), &["add", "."]);
+        Command::new("git")
+            .args(["commit", "-m", message])
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to run git");
+    };
+    let doctor = |extra: &[&str]| {
+        Command::new(bin_path())
+            .arg("doctor")
+            .args(extra)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute doctor command")
+    };
+    init_git_repo(temp_dir.path());
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    let cargo_toml = temp_dir.path().join("Cargo.toml");
+    fs::write(
+        &cargo_toml,
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    commit_at("2020-01-01T00:00:00Z", "release 1.0.0");
+
+    // Committed together, neither is newer
+    let output = doctor(&[]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout: {stdout}");
+    assert!(stdout.contains("No build system file was committed after VERSION"));
+
+    fs::write(
+        &cargo_toml,
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\nedition = \"2024\"\n",
+    )
+    .unwrap();
+    commit_at("2020-01-02T00:00:00Z", "edit manifest");
+
+    // Advisory by default
+    let output = doctor(&[]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout: {stdout}");
+    assert!(stdout.contains("Cargo.toml was committed after VERSION"));
+
+    // An issue with --strict
+    let output = doctor(&["--strict"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
 #[test]
 fn test_no_subcommand_with_build_files() {
     let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -258,3 +676,1731 @@ fn test_no_subcommand_no_build_files() {
     let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(stderr.contains("No build system files"));
 }
+
+#[test]
+fn test_tag_command_rejects_existing_version() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    init_git_repo(temp_dir.path());
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+
+    let output = Command::new(bin_path())
+        .arg("tag")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute tag command");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("v1.0.0"));
+
+    let output = Command::new(bin_path())
+        .arg("tag")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute tag command");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--allow-retag"));
+
+    let output = Command::new(bin_path())
+        .args(["tag", "--allow-retag"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute tag command");
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_verify_against_tag() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let verify = |extra: &[&str]| {
+        Command::new(bin_path())
+            .args(["verify", "--against-tag"])
+            .args(extra)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute verify command")
+    };
+    init_git_repo(temp_dir.path());
+    fs::write(temp_dir.path().join("VERSION"), "1.2.2\n").unwrap();
+
+    let output = verify(&[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No version tag points at HEAD"));
+
+    git(temp_dir.path(), &["tag", "v1.2.3"]);
+    let output = verify(&[]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("Tag v1.2.3 is version 1.2.3 but VERSION file has 1.2.2")
+    );
+
+    fs::write(temp_dir.path().join("VERSION"), "1.2.3\n").unwrap();
+    let output = verify(&[]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("match tag v1.2.3"));
+
+    // An explicit tag need not exist and may use another prefix
+    assert_eq!(verify(&["--tag", "release-1.2.4"]).status.code(), Some(3));
+    assert!(verify(&["--tag", "release-1.2.3"]).status.success());
+}
+
+#[test]
+fn test_tag_command_verifies_sync_and_annotates() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    init_git_repo(temp_dir.path());
+    fs::write(temp_dir.path().join("VERSION"), "1.1.0\n").unwrap();
+    let cargo_toml = temp_dir.path().join("Cargo.toml");
+    fs::write(
+        &cargo_toml,
+        "[package]\nname = \"demo\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["tag", "--annotate"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute tag command");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--no-verify"));
+    assert!(git(temp_dir.path(), &["tag", "-l"]).is_empty());
+
+    fs::write(
+        &cargo_toml,
+        "[package]\nname = \"demo\"\nversion = \"1.1.0\"\n",
+    )
+    .unwrap();
+    let output = Command::new(bin_path())
+        .args(["tag", "--annotate"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute tag command");
+    assert!(output.status.success());
+    assert_eq!(
+        git(temp_dir.path(), &["cat-file", "-t", "v1.1.0"]).trim(),
+        "tag"
+    );
+
+    fs::write(temp_dir.path().join("VERSION"), "1.2.0\n").unwrap();
+    let output = Command::new(bin_path())
+        .args(["tag", "--no-verify"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute tag command");
+    assert!(output.status.success());
+    assert_eq!(
+        git(temp_dir.path(), &["cat-file", "-t", "v1.2.0"]).trim(),
+        "commit"
+    );
+}
+
+#[test]
+fn test_paths_command() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("version.txt"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    fs::create_dir(temp_dir.path().join("web")).unwrap();
+    fs::write(
+        temp_dir.path().join("web/package.json"),
+        r#"{"name": "web", "version": "1.0.0"}"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["--version-file", "version.txt", "paths"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute paths command");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("version.txt"));
+    assert!(lines[1].ends_with("Cargo.toml"));
+
+    let output = Command::new(bin_path())
+        .args(["paths", "--cascade", "--json"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute paths command");
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["manifests"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_env_file_written_by_bump_and_show() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.2.3\n").unwrap();
+    let env_file = temp_dir.path().join("version.env");
+    fs::write(&env_file, "STALE=1\n").unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["--allow-empty", "minor", "--env-file", "version.env"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute minor command");
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(&env_file).unwrap(),
+        "VERSION=1.3.0\nVERSION_MAJOR=1\nVERSION_MINOR=3\nVERSION_PATCH=0\nVERSION_PRERELEASE=\nVERSION_BUILD=\n"
+    );
+
+    let output = Command::new(bin_path())
+        .args(["--allow-empty", "show", "--env-file", "version.env"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute show command");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "1.3.0");
+    assert!(
+        fs::read_to_string(&env_file)
+            .unwrap()
+            .starts_with("VERSION=1.3.0\n")
+    );
+    assert!(!temp_dir.path().join(".version.env.tmp").exists());
+}
+
+#[test]
+fn test_bump_count_applies_sequential_bumps() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0-rc.1\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0-rc.1\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["patch", "--count", "3"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(output.status.success());
+    let printed = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(printed.contains("Step 1/3: 1.0.1"));
+    assert!(printed.contains("Step 2/3: 1.0.2"));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+        "1.0.3\n"
+    );
+    assert!(
+        fs::read_to_string(temp_dir.path().join("Cargo.toml"))
+            .unwrap()
+            .contains("version = \"1.0.3\"")
+    );
+
+    let output = Command::new(bin_path())
+        .args(["patch", "--count", "0"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_prerelease_command_starts_and_continues_cycle() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.2.3\n").unwrap();
+    let cargo_toml = temp_dir.path().join("Cargo.toml");
+    fs::write(
+        &cargo_toml,
+        "[package]\nname = \"test\"\nversion = \"1.2.3\"\n",
+    )
+    .unwrap();
+    let prerelease = |args: &[&str]| {
+        Command::new(bin_path())
+            .arg("prerelease")
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute prerelease command")
+    };
+
+    // A release has no cycle to continue
+    assert!(!prerelease(&[]).status.success());
+
+    let output = prerelease(&["minor"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1.2.3 → 1.3.0-rc.1"));
+    assert!(
+        fs::read_to_string(&cargo_toml)
+            .unwrap()
+            .contains("version = \"1.3.0-rc.1\"")
+    );
+
+    let output = prerelease(&["--print-version-only"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1.3.0-rc.2\n");
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+        "1.3.0-rc.2\n"
+    );
+}
+
+#[test]
+fn test_bump_writes_discovered_version_file() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION.txt"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["patch", "--quiet"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION.txt")).unwrap(),
+        "1.0.1\n"
+    );
+    assert!(!temp_dir.path().join("VERSION").exists());
+}
+
+#[test]
+fn test_git_root_manages_the_whole_repository() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let subdir = temp_dir.path().join("crates/app");
+    fs::create_dir_all(&subdir).unwrap();
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    // Outside a repository the flag is an error rather than a silent no-op
+    let output = Command::new(bin_path())
+        .args(["show", "--git-root"])
+        .current_dir(&subdir)
+        .output()
+        .expect("Failed to execute show command");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not inside a git repository"));
+
+    git(temp_dir.path(), &["init"]);
+    let output = Command::new(bin_path())
+        .args(["patch", "--git-root"])
+        .current_dir(&subdir)
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+        "1.0.1\n"
+    );
+    assert!(
+        fs::read_to_string(temp_dir.path().join("Cargo.toml"))
+            .unwrap()
+            .contains("version = \"1.0.1\"")
+    );
+    assert!(!subdir.join("VERSION").exists());
+}
+
+#[test]
+fn test_base_path_with_version_file_elsewhere() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let project = temp_dir.path().join("project");
+    let shared = temp_dir.path().join("shared");
+    fs::create_dir(&project).unwrap();
+    fs::create_dir(&shared).unwrap();
+    let version_path = shared.join("VERSION");
+    fs::write(&version_path, "1.0.0\n").unwrap();
+    fs::write(
+        project.join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"2.0.0\"\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        Command::new(bin_path())
+            .args(["-C", "project", "--version-file"])
+            .arg(&version_path)
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute versioneer")
+    };
+
+    // verify reads the outside VERSION file and checks the manifests under -C
+    let output = run(&["verify"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2.0.0"));
+
+    assert!(run(&["sync", "--quiet"]).status.success());
+    assert!(run(&["verify"]).status.success());
+    let output = run(&["status"]);
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("outside the managed root"),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    assert!(!project.join("VERSION").exists());
+}
+
+#[test]
+fn test_show_diff_stat_summarizes_bump() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.2.3\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.2.3\"\n",
+    )
+    .unwrap();
+    fs::create_dir(temp_dir.path().join("web")).unwrap();
+    fs::write(
+        temp_dir.path().join("web/package.json"),
+        "{\n  \"name\": \"web\",\n  \"version\": \"1.2.3\"\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["minor", "--show-diff-stat"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute minor command");
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("2 files changed, version 1.2.3 → 1.3.0")
+    );
+
+    // A cascade counts every manifest it rewrote
+    let output = Command::new(bin_path())
+        .args(["patch", "--cascade", "--show-diff-stat"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("3 files changed, version 1.3.0 → 1.3.1")
+    );
+
+    let output = Command::new(bin_path())
+        .args(["patch", "--cascade", "--quiet", "--show-diff-stat"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_trace_file_records_file_operations() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let trace_dir = TempDir::new().expect("Failed to create temp dir");
+    let trace_path = trace_dir.path().join("trace.ndjson");
+    fs::write(temp_dir.path().join("VERSION"), "1.4.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"secret-name\"\nversion = \"1.3.0\"\n",
+    )
+    .unwrap();
+    let traced = |args: &[&str]| {
+        let output = Command::new(bin_path())
+            .args(args)
+            .arg("--trace-file")
+            .arg(&trace_path)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute versioneer");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let trace = fs::read_to_string(&trace_path).unwrap();
+        assert!(!trace.contains("secret-name"), "{trace}");
+        trace
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).expect("NDJSON line"))
+            .collect::<Vec<_>>()
+    };
+    let has = |operations: &[serde_json::Value], action: &str, file: &str, outcome: &str| {
+        operations.iter().any(|operation| {
+            operation["action"] == action
+                && operation["outcome"] == outcome
+                && operation["path"]
+                    .as_str()
+                    .is_some_and(|path| path.ends_with(file))
+        })
+    };
+
+    // Read-only commands are traced too
+    let operations = traced(&["status"]);
+    assert!(has(&operations, "read", "VERSION", "ok"));
+    assert!(has(&operations, "read", "Cargo.toml", "ok"));
+    assert!(
+        !operations
+            .iter()
+            .any(|operation| operation["action"] == "write")
+    );
+
+    let operations = traced(&["sync", "--quiet"]);
+    assert!(has(&operations, "write", "Cargo.toml", "ok"));
+    let operations = traced(&["sync", "--quiet"]);
+    assert!(has(&operations, "skip", "Cargo.toml", "unchanged"));
+}
+
+#[test]
+fn test_second_sync_writes_nothing() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.4.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.3.0\"\n",
+    )
+    .unwrap();
+    fs::create_dir(temp_dir.path().join("web")).unwrap();
+    fs::write(
+        temp_dir.path().join("web/package.json"),
+        "{\n  \"name\": \"web\",\n  \"version\": \"1.3.0\"\n}\n",
+    )
+    .unwrap();
+    let sync = || {
+        let output = Command::new(bin_path())
+            .args([
+                "sync",
+                "--cascade",
+                "--quiet",
+                "--summary",
+                "--format",
+                "json",
+            ])
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute sync command");
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("summary should be JSON")
+    };
+
+    assert_eq!(sync()["updated"], 2);
+
+    // Backdate every file so any rewrite would show up as a new mtime
+    let old = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+    let files = ["VERSION", "Cargo.toml", "web/package.json"];
+    for file in files {
+        fs::File::options()
+            .write(true)
+            .open(temp_dir.path().join(file))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+    }
+    let summary = sync();
+    assert_eq!(summary["updated"], 0);
+    assert_eq!(summary["in_sync"], 2);
+    for file in files {
+        let modified = fs::metadata(temp_dir.path().join(file))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(modified, old, "{file} was rewritten");
+    }
+}
+
+#[test]
+fn test_summary_json_on_stderr_keeps_stdout_human() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(bin_path())
+            .args(args)
+            .arg("--summary-json-on-stderr")
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute versioneer");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        let summary: serde_json::Value =
+            serde_json::from_str(stderr.lines().last().expect("a summary line"))
+                .expect("summary should be one JSON line");
+        (
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+            summary,
+        )
+    };
+
+    let (stdout, summary) = run(&["minor", "--cascade", "--dry-run"]);
+    assert!(stdout.contains("Would bump to version 1.1.0"), "{stdout}");
+    assert_eq!(summary["applied"], false);
+
+    let (stdout, summary) = run(&["minor"]);
+    assert!(stdout.contains("Bumped to version 1.1.0"), "{stdout}");
+    assert!(!stdout.contains("\"applied\""), "{stdout}");
+    assert_eq!(summary["operation"], "minor");
+    assert_eq!(summary["applied"], true);
+    assert_eq!(summary["new_version"], "1.1.0");
+    assert_eq!(summary["files"].as_array().map(Vec::len), Some(2));
+}
+
+#[test]
+fn test_report_file_records_operation() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    let read_report = || -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(temp_dir.path().join("report.json")).unwrap())
+            .expect("report should be JSON")
+    };
+
+    let output = Command::new(bin_path())
+        .args([
+            "minor",
+            "--cascade",
+            "--dry-run",
+            "--report-file",
+            "report.json",
+        ])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute minor command");
+    assert!(output.status.success());
+    let report = read_report();
+    assert_eq!(report["operation"], "minor");
+    assert_eq!(report["applied"], false);
+    assert_eq!(report["new_version"], "1.1.0");
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+        "1.0.0\n"
+    );
+
+    let output = Command::new(bin_path())
+        .args(["patch", "--quiet", "--report-file", "report.json"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(output.status.success());
+    let report = read_report();
+    assert_eq!(report["applied"], true);
+    assert_eq!(report["previous_version"], "1.0.0");
+    assert_eq!(report["new_version"], "1.0.1");
+    assert_eq!(report["files"].as_array().unwrap().len(), 2);
+    assert_eq!(report["files"][1]["path"], "Cargo.toml");
+    assert_eq!(report["files"][1]["old_version"], "1.0.0");
+    assert_eq!(report["files"][1]["new_version"], "1.0.1");
+    assert!(report["timestamp"].as_u64().unwrap() > 0);
+
+    let output = Command::new(bin_path())
+        .args(["sync", "--report-file", "report.json"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute sync command");
+    assert!(output.status.success());
+    let report = read_report();
+    assert_eq!(report["operation"], "sync");
+    assert!(report["files"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_after_write_command_runs_with_new_version() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args([
+            "patch",
+            "--print-version-only",
+            "--after-write-command",
+            "echo \"$VERSIONEER_VERSION\" > stamp.txt; echo noise",
+        ])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(output.status.success());
+    // The command's output stays off stdout
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1.0.1\n");
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("stamp.txt")).unwrap(),
+        "1.0.1\n"
+    );
+
+    let output = Command::new(bin_path())
+        .args(["reset", "2.0.0", "--after-write-command", "exit 3"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute reset command");
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("After-write command `exit 3` failed")
+    );
+
+    let output = Command::new(bin_path())
+        .args([
+            "sync",
+            "--cascade",
+            "--dry-run",
+            "--after-write-command",
+            "touch ran.txt",
+        ])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute sync command");
+    assert!(!output.status.success());
+    assert!(!temp_dir.path().join("ran.txt").exists());
+}
+
+#[test]
+fn test_systems_command_lists_formats() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    let output = Command::new(bin_path())
+        .args(["systems", "--format", "json"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute systems command");
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let systems = json.as_array().unwrap();
+    let gemspec = systems.iter().find(|s| s["name"] == "gemspec").unwrap();
+    assert_eq!(gemspec["files"][0], "*.gemspec");
+    assert_eq!(gemspec["detection"], "auto");
+    let modules = systems
+        .iter()
+        .find(|s| s["name"] == "python-module")
+        .unwrap();
+    assert_eq!(modules["detection"], "config");
+
+    let output = Command::new(bin_path())
+        .arg("systems")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute systems command");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.lines().next().unwrap().starts_with("cargo"));
+    assert!(stdout.contains("Directory.Packages.props"));
+}
+
+#[test]
+fn test_cascade_dry_run_json_format() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::create_dir(temp_dir.path().join("sub")).unwrap();
+    fs::write(
+        temp_dir.path().join("sub/Cargo.toml"),
+        "[package]\nname = \"sub\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["major", "--cascade", "--dry-run", "--format", "json"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute major command");
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["new_version"], "2.0.0");
+    assert_eq!(
+        json["files_to_update"],
+        serde_json::json!(["VERSION", "sub/Cargo.toml"])
+    );
+    assert!(json.get("tag_name").is_none());
+
+    // With --tag, the preview names the tag, using the prefix existing tags use
+    init_git_repo(temp_dir.path());
+    git(temp_dir.path(), &["tag", "release-1.0.0"]);
+
+    let output = Command::new(bin_path())
+        .args([
+            "major",
+            "--cascade",
+            "--dry-run",
+            "--tag",
+            "--format",
+            "json",
+        ])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute major command");
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["tag_name"], "release-2.0.0");
+
+    let output = Command::new(bin_path())
+        .args(["major", "--cascade", "--dry-run", "--tag"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute major command");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Tag to create: release-2.0.0"));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+        "1.0.0\n"
+    );
+    assert!(!git(temp_dir.path(), &["tag", "-l"]).contains("2.0.0"));
+}
+
+#[test]
+fn test_list_and_cascade_dry_run_ndjson_format() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"root\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    fs::create_dir(temp_dir.path().join("sub")).unwrap();
+    fs::write(
+        temp_dir.path().join("sub/package.json"),
+        r#"{"name": "sub", "version": "0.9.0"}"#,
+    )
+    .unwrap();
+
+    for args in [
+        vec!["list", "--format", "ndjson"],
+        vec!["patch", "--cascade", "--dry-run", "--format", "ndjson"],
+    ] {
+        let output = Command::new(bin_path())
+            .args(&args)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute versioneer");
+
+        assert!(output.status.success(), "{args:?}");
+        let mut lines: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        lines.sort_by_key(|line| line["path"].as_str().unwrap().to_string());
+        assert_eq!(
+            lines,
+            vec![
+                serde_json::json!({"path": "Cargo.toml", "system": "cargo", "version": "1.0.0", "in_sync": true}),
+                serde_json::json!({"path": "sub/package.json", "system": "package-json", "version": "0.9.0", "in_sync": false}),
+            ],
+            "{args:?}"
+        );
+    }
+}
+
+#[test]
+fn test_no_subcommand_no_build_files_allow_empty() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+
+    let output = Command::new(bin_path())
+        .arg("--allow-empty")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute versioneer");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1.0.0"));
+    assert!(stdout.contains("VERSION file only"));
+}
+
+#[test]
+fn test_patch_github_format_writes_step_outputs() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    let github_output = temp_dir.path().join("github_output");
+    fs::write(&github_output, "existing=1\n").unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["patch", "--format", "github"])
+        .env("GITHUB_OUTPUT", &github_output)
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(&github_output).unwrap(),
+        "existing=1\nversion=1.0.1\nprevious=1.0.0\nbumped=true\n"
+    );
+}
+
+#[test]
+fn test_show_github_format_falls_back_to_stdout() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.2.3\n").unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["show", "--format", "github"])
+        .env_remove("GITHUB_OUTPUT")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute show command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("version=1.2.3\nprevious=1.2.3\nbumped=false\n"));
+}
+
+#[test]
+fn test_sync_verify_after_reaches_version_after_array() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "2.0.0\n").unwrap();
+    // The [tool.commitizen] version follows an array, which a pattern-based
+    // update could not see past
+    fs::write(
+        temp_dir.path().join("pyproject.toml"),
+        "[project]\nname = \"test\"\nversion = \"1.0.0\"\n\n[tool.commitizen]\nversion_files = [\"x\"]\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["sync", "--verify-after"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute sync command");
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("pyproject.toml")).unwrap(),
+        "[project]\nname = \"test\"\nversion = \"2.0.0\"\n\n[tool.commitizen]\nversion_files = [\"x\"]\nversion = \"2.0.0\"\n"
+    );
+}
+
+#[test]
+fn test_bump_without_terminal_points_to_explicit_subcommands() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.2.3\n").unwrap();
+
+    let output = Command::new(bin_path())
+        .arg("bump")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute bump command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("interactive terminal"), "{stderr}");
+    assert!(stderr.contains("versioneer major"), "{stderr}");
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+        "1.2.3\n"
+    );
+}
+
+#[test]
+fn test_minor_from_explicit_version_ignores_placeholder() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "@VERSION@\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"0.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["minor", "--from", "1.0.0", "--verify-after"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute minor command");
+
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+        "1.1.0\n"
+    );
+    assert!(
+        fs::read_to_string(temp_dir.path().join("Cargo.toml"))
+            .unwrap()
+            .contains("version = \"1.1.0\"")
+    );
+
+    let output = Command::new(bin_path())
+        .args(["minor", "--from", "not-semver"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute minor command");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_minor_from_named_source_makes_all_files_agree() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.4.0\"\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{"name": "test", "version": "1.3.0"}"#,
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["minor", "--source", "gleam"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute minor command");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no gleam.toml found"));
+
+    let output = Command::new(bin_path())
+        .args(["minor", "--source", "cargo", "--verify-after"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute minor command");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+        "1.5.0\n"
+    );
+    assert!(
+        fs::read_to_string(temp_dir.path().join("package.json"))
+            .unwrap()
+            .contains("\"version\": \"1.5.0\"")
+    );
+}
+
+#[test]
+fn test_calver_derives_version_from_date() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "2024.5.7\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"2024.5.7\"\n",
+    )
+    .unwrap();
+
+    for expected in ["2024.6.0", "2024.6.1"] {
+        let output = Command::new(bin_path())
+            .args(["calver", "--date", "2024-06-15"])
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute calver command");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+            format!("{expected}\n")
+        );
+        assert!(
+            fs::read_to_string(temp_dir.path().join("Cargo.toml"))
+                .unwrap()
+                .contains(&format!("version = \"{expected}\""))
+        );
+    }
+
+    let output = Command::new(bin_path())
+        .args(["calver", "--date", "2024-05-01"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute calver command");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("would not advance"));
+}
+
+#[test]
+fn test_sync_reverse_adopts_named_build_system() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "0.1.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.4.0\"\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        "{\n  \"name\": \"test\",\n  \"version\": \"1.3.0\"\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["sync", "--reverse"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute sync command");
+    assert!(!output.status.success());
+
+    // Without a terminal there is no one to ask, so --interactive never guesses
+    let output = Command::new(bin_path())
+        .args(["sync", "--reverse", "--interactive"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute sync command");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("needs a terminal"));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+        "0.1.0\n"
+    );
+
+    let output = Command::new(bin_path())
+        .args(["sync", "--reverse", "--source", "cargo"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute sync command");
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+        "1.4.0\n"
+    );
+    assert!(
+        fs::read_to_string(temp_dir.path().join("package.json"))
+            .unwrap()
+            .contains("\"version\": \"1.4.0\"")
+    );
+}
+
+#[test]
+fn test_tag_prerelease_bump_on_collision() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    init_git_repo(temp_dir.path());
+    git(temp_dir.path(), &["tag", "v1.0.0"]);
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["tag", "--prerelease-bump-on-collision"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute tag command");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("v1.0.0-1"));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+        "1.0.0-1\n"
+    );
+    assert!(
+        fs::read_to_string(temp_dir.path().join("Cargo.toml"))
+            .unwrap()
+            .contains("version = \"1.0.0-1\"")
+    );
+    let tags = git(temp_dir.path(), &["tag", "-l"]);
+    assert!(tags.contains("v1.0.0-1"));
+}
+
+#[test]
+fn test_only_if_changed_skips_tag_when_nothing_changed() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    init_git_repo(temp_dir.path());
+    git(temp_dir.path(), &["tag", "v1.0.0"]);
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    // Re-running a bump that already landed: files are at the target and tagged
+    let output = Command::new(bin_path())
+        .args(["major", "--from", "0.5.0", "--tag", "--only-if-changed"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute major command");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No files changed; skipping tag"));
+    let tags = git(temp_dir.path(), &["tag", "-l"]);
+    assert_eq!(tags, "v1.0.0\n");
+
+    // Without the flag the duplicate tag is still refused
+    let output = Command::new(bin_path())
+        .args(["major", "--from", "0.5.0", "--tag"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute major command");
+    assert!(!output.status.success());
+
+    let output = Command::new(bin_path())
+        .args(["patch", "--tag", "--only-if-changed"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(output.status.success());
+    let tags = git(temp_dir.path(), &["tag", "-l"]);
+    assert!(tags.contains("v1.0.1"));
+}
+
+#[test]
+fn test_allow_same_version_tags_without_writing() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    init_git_repo(temp_dir.path());
+    git(temp_dir.path(), &["tag", "v1.0.0"]);
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    let major = |extra: &[&str]| {
+        Command::new(bin_path())
+            .args(["major", "--from", "0.5.0", "--tag"])
+            .args(extra)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute major command")
+    };
+
+    // A tagging bump that writes nothing is refused, naming the way out
+    let output = major(&[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Already at version 1.0.0"), "{stderr}");
+    assert!(stderr.contains("--allow-same-version"), "{stderr}");
+
+    // Opting in skips the write, but the existing tag is still a collision
+    let output = major(&["--allow-same-version"]);
+    assert!(!output.status.success());
+
+    git(temp_dir.path(), &["tag", "-d", "v1.0.0"]);
+    let output = major(&["--allow-same-version"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Already at version 1.0.0; no files written"),
+        "{stdout}"
+    );
+    let tags = git(temp_dir.path(), &["tag", "-l"]);
+    assert_eq!(tags, "v1.0.0\n");
+}
+
+#[test]
+fn test_release_dry_run_prints_plan_without_changes() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    init_git_repo(temp_dir.path());
+    git(temp_dir.path(), &["add", "."]);
+    git(temp_dir.path(), &["commit", "-m", "init"]);
+
+    let output = Command::new(bin_path())
+        .args(["release", "minor", "--sign", "--dry-run"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute release command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Bump version 1.0.0 -> 1.1.0"));
+    assert!(stdout.contains("Create signed annotated tag v1.1.0"));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("VERSION")).unwrap(),
+        "1.0.0\n"
+    );
+
+    // Uncommitted changes block the release itself, not the preview
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\nedition = \"2024\"\n",
+    )
+    .unwrap();
+    let release = |extra: &[&str]| {
+        Command::new(bin_path())
+            .args(["release", "minor"])
+            .args(extra)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute release command")
+    };
+    assert!(release(&["--dry-run"]).status.success());
+    let output = release(&[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("uncommitted changes"));
+}
+
+#[test]
+fn test_env_version_source_syncs_manifests() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(
+        temp_dir.path().join(".versioneer.toml"),
+        "[version]\nsource = \"env\"\nenv_var = \"APP_VERSION\"\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"0.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["sync", "--verify-after"])
+        .env("APP_VERSION", "4.5.6")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute sync command");
+
+    assert!(output.status.success());
+    assert!(
+        fs::read_to_string(temp_dir.path().join("Cargo.toml"))
+            .unwrap()
+            .contains("version = \"4.5.6\"")
+    );
+    assert!(!temp_dir.path().join("VERSION").exists());
+
+    let output = Command::new(bin_path())
+        .arg("show")
+        .env("APP_VERSION", "not-a-version")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute show command");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_env_version_source_bump_reports_and_tags_new_version() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(
+        temp_dir.path().join(".versioneer.toml"),
+        "[version]\nsource = \"env\"\nenv_var = \"APP_VERSION\"\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    init_git_repo(temp_dir.path());
+    let env_file = temp_dir.path().join("version.env");
+
+    // The variable still holds 1.0.0 after the write; everything reports 1.0.1
+    let output = Command::new(bin_path())
+        .args(["patch", "--tag", "--env-file"])
+        .arg(&env_file)
+        .env("APP_VERSION", "1.0.0")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Bumped to version 1.0.1"), "{stdout}");
+    assert!(
+        fs::read_to_string(temp_dir.path().join("Cargo.toml"))
+            .unwrap()
+            .contains("version = \"1.0.1\"")
+    );
+    assert!(fs::read_to_string(&env_file).unwrap().contains("1.0.1"));
+    assert_eq!(git(temp_dir.path(), &["tag", "-l"]), "v1.0.1\n");
+
+    let output = Command::new(bin_path())
+        .args(["patch", "--print-version-only"])
+        .env("APP_VERSION", "1.0.1")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "1.0.2");
+}
+
+#[test]
+fn test_verify_after_failure_restores_files() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    // An environment variable cannot be rewritten, so the version source silently
+    // keeps 1.0.0 while the manifest moves on
+    fs::write(
+        temp_dir.path().join(".versioneer.toml"),
+        "[version]\nsource = \"env\"\nenv_var = \"APP_VERSION\"\n",
+    )
+    .unwrap();
+    let cargo = "[package]\nname = \"test\"\nversion = \"1.0.0\"\n";
+    fs::write(temp_dir.path().join("Cargo.toml"), cargo).unwrap();
+
+    for args in [
+        &["reset", "2.0.0", "--verify-after"][..],
+        &["minor", "--verify-after"],
+    ] {
+        let output = Command::new(bin_path())
+            .args(args)
+            .env("APP_VERSION", "1.0.0")
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute versioneer");
+        assert!(!output.status.success(), "{args:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("the written files were restored"),
+            "{stderr}"
+        );
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("Cargo.toml")).unwrap(),
+            cargo
+        );
+    }
+}
+
+#[test]
+fn test_verify_check_is_silent_and_honors_only() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        "{\n  \"name\": \"test\",\n  \"version\": \"2.0.0\"\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["verify", "--check"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute verify command");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+
+    let output = Command::new(bin_path())
+        .args(["verify", "--check", "--only", "cargo"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute verify command");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_manifest_format_manages_non_standard_filenames() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml.tpl"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["patch", "--manifest", "Cargo.toml.tpl"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(!output.status.success());
+
+    let output = Command::new(bin_path())
+        .args([
+            "patch",
+            "--manifest",
+            "Cargo.toml.tpl",
+            "--manifest-format",
+            "json5",
+        ])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("package-json"));
+
+    let output = Command::new(bin_path())
+        .args([
+            "patch",
+            "--manifest",
+            "Cargo.toml.tpl",
+            "--manifest-format",
+            "cargo",
+        ])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        fs::read_to_string(temp_dir.path().join("Cargo.toml.tpl"))
+            .unwrap()
+            .contains("version = \"1.0.1\"")
+    );
+
+    // The same pairing in .versioneer.toml keeps the file managed without flags
+    fs::write(
+        temp_dir.path().join(".versioneer.toml"),
+        "[[manifest_overrides]]\npath = \"Cargo.toml.tpl\"\nformat = \"cargo\"\n",
+    )
+    .unwrap();
+    let output = Command::new(bin_path())
+        .arg("patch")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(output.status.success());
+    assert!(
+        fs::read_to_string(temp_dir.path().join("Cargo.toml.tpl"))
+            .unwrap()
+            .contains("version = \"1.0.2\"")
+    );
+}
+
+#[test]
+fn test_json_output_is_compact_unless_pretty_is_asked_for() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["--format", "json", "config"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute config command");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+
+    let output = Command::new(bin_path())
+        .args(["--format", "json", "--json-pretty", "config"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute config command");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.lines().count() > 1, "{stdout}");
+    let _: serde_json::Value = serde_json::from_str(&stdout).expect("pretty output is JSON");
+
+    let output = Command::new(bin_path())
+        .args([
+            "--format",
+            "json",
+            "--json-pretty",
+            "reset",
+            "not-a-version",
+        ])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute reset command");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.lines().count() > 1, "{stderr}");
+    let envelope: serde_json::Value = serde_json::from_str(&stderr).expect("envelope is JSON");
+    assert_eq!(envelope["error"]["kind"], "InvalidVersion");
+
+    // NDJSON stays one object per line
+    let output = Command::new(bin_path())
+        .args(["--format", "ndjson", "--json-pretty", "list"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute list command");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 1);
+
+    let output = Command::new(bin_path())
+        .args(["--json-pretty", "--json-compact", "config"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute config command");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_keep_going_updates_the_files_it_can() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        "{\n  \"name\": \"test\",\n  \"version\": \"0.9.0\"\n}\n",
+    )
+    .unwrap();
+    fs::create_dir(temp_dir.path().join("broken")).unwrap();
+    fs::write(temp_dir.path().join("broken/package.json"), "{ not json").unwrap();
+
+    let output = Command::new(bin_path())
+        .args(["sync", "--cascade"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute sync command");
+    assert!(!output.status.success());
+    assert!(
+        fs::read_to_string(temp_dir.path().join("package.json"))
+            .unwrap()
+            .contains("0.9.0")
+    );
+
+    let output = Command::new(bin_path())
+        .args(["sync", "--cascade", "--keep-going", "--format", "json"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute sync command");
+    assert!(!output.status.success());
+    let envelope: serde_json::Value =
+        serde_json::from_slice(&output.stderr).expect("stderr should be a JSON envelope");
+    assert_eq!(envelope["error"]["kind"], "PartialCascade");
+    assert_eq!(envelope["error"]["details"].as_array().unwrap().len(), 1);
+    assert!(
+        fs::read_to_string(temp_dir.path().join("package.json"))
+            .unwrap()
+            .contains("1.0.0")
+    );
+}
+
+#[test]
+fn test_two_nuspec_files_in_flat_mode() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let nuspec = |version: &str| {
+        format!(
+            "<?xml version=\"1.0\"?>\n<package>\n  <metadata>\n    <id>Pkg</id>\n    <version>{version}</version>\n  </metadata>\n</package>\n"
+        )
+    };
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(temp_dir.path().join("A.nuspec"), nuspec("1.0.0")).unwrap();
+    fs::write(temp_dir.path().join("B.nuspec"), nuspec("1.0.0")).unwrap();
+
+    let output = Command::new(bin_path())
+        .arg("patch")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute patch command");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    for name in ["A.nuspec", "B.nuspec"] {
+        assert!(
+            fs::read_to_string(temp_dir.path().join(name))
+                .unwrap()
+                .contains("<version>1.0.1</version>"),
+            "{name}"
+        );
+    }
+
+    // Drift in one file is reported by its name
+    fs::write(temp_dir.path().join("B.nuspec"), nuspec("0.9.0")).unwrap();
+    let output = Command::new(bin_path())
+        .arg("verify")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute verify command");
+    assert_eq!(output.status.code(), Some(3));
+    let report = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(report.contains("B.nuspec"), "{report}");
+    assert!(!report.contains("A.nuspec"), "{report}");
+}
+
+#[test]
+fn test_placeholder_nuspec_is_left_alone() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let placeholder = "<?xml version=\"1.0\"?>\n<package>\n  <metadata>\n    <id>Pkg</id>\n    <version>$version$</version>\n  </metadata>\n</package>\n";
+    fs::write(temp_dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"test\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("x.nuspec"), placeholder).unwrap();
+
+    let output = Command::new(bin_path())
+        .arg("verify")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute verify command");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    let output = Command::new(bin_path())
+        .arg("status")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute status command");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("placeholder"));
+
+    for args in [["patch", "--cascade"].as_slice(), ["patch"].as_slice()] {
+        let output = Command::new(bin_path())
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute patch command");
+        assert!(
+            output.status.success(),
+            "{args:?}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("x.nuspec")).unwrap(),
+            placeholder
+        );
+    }
+    assert!(
+        fs::read_to_string(temp_dir.path().join("Cargo.toml"))
+            .unwrap()
+            .contains("version = \"1.0.2\"")
+    );
+
+    // Asking to overwrite it makes the file managed again
+    let output = Command::new(bin_path())
+        .args(["sync", "--overwrite-placeholders"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute sync command");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        fs::read_to_string(temp_dir.path().join("x.nuspec"))
+            .unwrap()
+            .contains("<version>1.0.2</version>")
+    );
+}
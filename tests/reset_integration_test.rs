@@ -120,3 +120,66 @@ fn test_reset_command_invalid_version() {
         fs::read_to_string(temp_path.join("VERSION")).expect("Failed to read VERSION file");
     assert_eq!(version_content.trim(), "1.0.0");
 }
+
+#[test]
+fn test_reset_command_keep_prerelease() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let temp_path = temp_dir.path();
+    fs::write(temp_path.join("VERSION"), "1.2.3-beta.4").expect("Failed to write VERSION file");
+
+    let binary_path = std::env::current_dir()
+        .expect("Failed to get current directory")
+        .join("target/debug/versioneer");
+    let reset = |args: &[&str]| {
+        Command::new(&binary_path)
+            .args(["--allow-empty", "reset", "2.0.0"])
+            .args(args)
+            .current_dir(temp_path)
+            .output()
+            .expect("Failed to execute reset command")
+    };
+    let read_version = || {
+        fs::read_to_string(temp_path.join("VERSION"))
+            .expect("Failed to read VERSION file")
+            .trim()
+            .to_string()
+    };
+
+    let output = reset(&["--keep-prerelease"]);
+    assert!(output.status.success());
+    assert_eq!(read_version(), "2.0.0-beta.4");
+
+    let output = reset(&["--keep-prerelease", "--reset-prerelease-counter"]);
+    assert!(output.status.success());
+    assert_eq!(read_version(), "2.0.0-beta.1");
+
+    fs::write(temp_path.join("VERSION"), "1.2.3").expect("Failed to write VERSION file");
+    let output = reset(&["--keep-prerelease"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("no prerelease"));
+    assert_eq!(read_version(), "2.0.0");
+}
+
+#[test]
+fn test_reset_command_max_version() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let temp_path = temp_dir.path();
+    fs::write(temp_path.join("VERSION"), "1.0.0").expect("Failed to write VERSION file");
+
+    let binary_path = std::env::current_dir()
+        .expect("Failed to get current directory")
+        .join("target/debug/versioneer");
+    let output = Command::new(&binary_path)
+        .args(["--allow-empty", "reset", "10.0.0", "--max-version", "9.0.0"])
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute reset command");
+
+    assert!(!output.status.success(), "Reset command should have failed");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("above the maximum allowed version 9.0.0")
+    );
+    let version_content =
+        fs::read_to_string(temp_path.join("VERSION")).expect("Failed to read VERSION file");
+    assert_eq!(version_content.trim(), "1.0.0");
+}